@@ -55,6 +55,18 @@ export class NotificationBell implements OnInit {
         return 'pi pi-play';
       case 'new_crew_joined':
         return 'pi pi-user-plus';
+      case 'waitlist_promoted':
+        return 'pi pi-arrow-circle-up';
+      case 'join_request':
+        return 'pi pi-inbox';
+      case 'application_accepted':
+        return 'pi pi-check-circle';
+      case 'application_rejected':
+        return 'pi pi-times-circle';
+      case 'mission_invitation':
+        return 'pi pi-envelope';
+      case 'invitation_declined':
+        return 'pi pi-times-circle';
       case 'kicked_from_mission':
         return 'pi pi-user-minus';
       case 'mission_completed':
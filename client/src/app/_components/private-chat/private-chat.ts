@@ -13,7 +13,6 @@ import { FormsModule } from '@angular/forms';
 import { ChatService, PrivateMessage } from '../../_services/chat-service';
 import { FriendshipService } from '../../_services/friendship-service';
 import { WebsocketService } from '../../_services/websocket-service';
-import { getUserIdFromToken } from '../../_helpers/util';
 import { Subscription } from 'rxjs';
 
 // PrimeNG
@@ -48,7 +47,6 @@ export class PrivateChat implements OnInit, OnDestroy {
   // Data
   recentChats: any[] = [];
   friends: any[] = [];
-  onlineNonFriends: any[] = [];
   friendIds = new Set<number>();
   onlineUserIds = signal<number[]>([]);
 
@@ -62,8 +60,7 @@ export class PrivateChat implements OnInit, OnDestroy {
 
   ngOnInit() {
     const passportJson = localStorage.getItem('passport');
-    const token = passportJson ? JSON.parse(passportJson).token : '';
-    this.currentUserId = getUserIdFromToken(token) || 0;
+    this.currentUserId = passportJson ? JSON.parse(passportJson).id : 0;
 
     setTimeout(() => {
       this.loadAll();
@@ -140,11 +137,6 @@ export class PrivateChat implements OnInit, OnDestroy {
         is_online: onlineIds.includes(f.id),
       }));
 
-      // Online users who are NOT friends and NOT self
-      this.onlineNonFriends = onlineUsers.filter(
-        (u: any) => !this.friendIds.has(u.id) && u.id !== this.currentUserId,
-      );
-
       this._cdr.markForCheck();
     } catch (e) {
       console.error('Failed to load friends/online', e);
@@ -177,20 +169,16 @@ export class PrivateChat implements OnInit, OnDestroy {
     });
   }
 
-  // Search across friends + online non-friends
+  // Search across friends
   onSearchChange() {
     if (!this.searchQuery.trim()) {
       this.searchResults = [];
       return;
     }
     const q = this.searchQuery.toLowerCase();
-    const allPeople = [...this.friends, ...this.onlineNonFriends];
-    const seen = new Set<number>();
-    this.searchResults = allPeople.filter((p) => {
-      if (seen.has(p.id)) return false;
-      seen.add(p.id);
-      return p.display_name.toLowerCase().includes(q) || p.id.toString().includes(q);
-    });
+    this.searchResults = this.friends.filter(
+      (p) => p.display_name.toLowerCase().includes(q) || p.id.toString().includes(q),
+    );
   }
 
   toggleChat() {
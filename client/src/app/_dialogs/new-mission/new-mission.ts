@@ -42,6 +42,12 @@ export class NewMission {
     { label: 'Other', value: 'Other' },
   ];
 
+  joinPolicies = [
+    { label: 'Anyone can join', value: 'open' },
+    { label: 'Chief approves requests', value: 'approval_required' },
+    { label: 'Invite only', value: 'invite_only' },
+  ];
+
   addMission: any = {
     name: this._config.data?.name || '',
     description: this._config.data?.description || '',
@@ -49,6 +55,8 @@ export class NewMission {
     scheduled_at: this._config.data?.scheduled_at ? new Date(this._config.data.scheduled_at) : null,
     location: this._config.data?.location || '',
     category: this._config.data?.category || 'Other',
+    join_policy: this._config.data?.join_policy || 'open',
+    is_private: this._config.data?.is_private || false,
   };
 
   onSubmit() {
@@ -64,6 +72,8 @@ export class NewMission {
       scheduled_at: addMission.scheduled_at ? new Date(addMission.scheduled_at) : undefined,
       location: addMission.location?.trim() || undefined,
       category: addMission.category || 'Other',
+      join_policy: addMission.join_policy || 'open',
+      is_private: !!addMission.is_private,
     };
   }
 }
@@ -5,7 +5,8 @@ import { PassportService } from '../_services/passport-service';
 export const authGuard: CanActivateFn = (route, state) => {
   const passport = inject(PassportService);
   const router = inject(Router);
-  if (passport.data()?.token) return true;
+  // No token in the passport in cookie mode, so go by the sign-in state
+  if (passport.isSignin()) return true;
   router.navigate(['/not-found']);
   return false;
 };
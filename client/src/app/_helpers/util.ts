@@ -1,3 +1,4 @@
+import { ApiError } from '../_models/api-error';
 import { Brawler } from '../_models/brawler';
 import { Passport } from '../_models/passport';
 
@@ -32,3 +33,14 @@ export function getUserIdFromToken(token: string | undefined): number | null {
     return null;
   }
 }
+
+/**
+ * Extract a human readable message from an HttpErrorResponse
+ * Server errors come back as { code, message, details }
+ */
+export function getErrorMessage(error: any): string {
+  const body = error?.error;
+  if (!body) return '';
+  if (typeof body === 'string') return body;
+  return (body as ApiError).message ?? '';
+}
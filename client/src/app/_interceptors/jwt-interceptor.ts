@@ -1,17 +1,41 @@
-import { HttpInterceptorFn } from '@angular/common/http';
+import { HttpErrorResponse, HttpInterceptorFn } from '@angular/common/http';
 import { PassportService } from '../_services/passport-service';
 import { inject } from '@angular/core';
+import { catchError, from, switchMap, throwError } from 'rxjs';
+import { Router } from '@angular/router';
 
 export const jwtInterceptor: HttpInterceptorFn = (req, next) => {
   const _passport = inject(PassportService);
+  const _router = inject(Router);
   const token = _passport.data()?.token;
-  if (token) {
-    const Authorization = `Bearer ${token}`;
-    req = req.clone({
-      setHeaders: {
-        Authorization,
-      },
-    });
-  }
-  return next(req);
+  const cookieMode = _passport.cookieMode();
+  if (!token && !cookieMode) return next(req);
+
+  // In cookie mode the browser sends the token; state changes echo the CSRF cookie
+  const withToken = (token: string) => {
+    if (token) {
+      return req.clone({ setHeaders: { Authorization: `Bearer ${token}` } });
+    }
+    const csrf = _passport.csrfToken();
+    if (!csrf || ['GET', 'HEAD', 'OPTIONS'].includes(req.method)) return req;
+    return req.clone({ setHeaders: { [PassportService.CSRF_HEADER]: csrf } });
+  };
+
+  // Access tokens are short-lived: on a 401, refresh once and retry
+  return next(withToken(token ?? '')).pipe(
+    catchError((error) => {
+      if (!(error instanceof HttpErrorResponse) || error.status !== 401) {
+        return throwError(() => error);
+      }
+      return from(_passport.refresh()).pipe(
+        switchMap((fresh) => {
+          if (fresh === null) {
+            _router.navigate(['/login']);
+            return throwError(() => error);
+          }
+          return next(withToken(fresh));
+        }),
+      );
+    }),
+  );
 };
@@ -1,3 +1,5 @@
+import { JoinPolicy } from './mission';
+
 export interface AddMission {
   name: string;
   description?: string;
@@ -5,4 +7,6 @@ export interface AddMission {
   scheduled_at?: Date;
   location?: string;
   category?: string;
+  join_policy?: JoinPolicy;
+  is_private?: boolean;
 }
@@ -0,0 +1,5 @@
+export interface ApiError {
+  code: string;
+  message: string;
+  details?: unknown;
+}
@@ -0,0 +1,9 @@
+export interface MissionApplication {
+  id: number;
+  mission_id: number;
+  brawler_id: number;
+  brawler_display_name: string;
+  brawler_avatar_url: string;
+  status: 'pending' | 'accepted' | 'rejected';
+  created_at: Date;
+}
@@ -0,0 +1,22 @@
+export interface InviteLink {
+  id: number;
+  mission_id: number;
+  token: string;
+  max_uses?: number | null;
+  uses: number;
+  expires_at: Date;
+  revoked_at?: Date | null;
+  created_at: Date;
+}
+
+export interface MissionInvitation {
+  id: number;
+  mission_id: number;
+  mission_name: string;
+  inviter_id: number;
+  inviter_display_name: string;
+  invitee_id: number;
+  invitee_display_name: string;
+  status: 'pending' | 'accepted' | 'declined' | 'revoked';
+  created_at: Date;
+}
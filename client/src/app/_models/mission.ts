@@ -1,3 +1,5 @@
+export type JoinPolicy = 'open' | 'approval_required' | 'invite_only';
+
 export interface Mission {
   id: number;
   name: string;
@@ -14,4 +16,6 @@ export interface Mission {
   location?: string;
   deleted_at?: Date | null;
   category?: string;
+  join_policy?: JoinPolicy;
+  is_private?: boolean;
 }
@@ -1,6 +1,7 @@
 export interface Passport {
   id: number;
-  token: string; //jwt_model
+  token?: string; // short-lived JWT; absent in cookie mode, where it is an HttpOnly cookie
+  refresh_token?: string; // traded at /authentication/refresh for a new pair
   display_name: string;
   avatar_url?: string;
   bio?: string;
@@ -8,8 +9,12 @@ export interface Passport {
   contact_email?: string;
   instagram?: string;
   facebook?: string;
+  role?: Role; // absent on passports saved before roles existed
 }
 
+/** Site-wide rank; moderators and admins can act as any mission's chief */
+export type Role = 'user' | 'moderator' | 'admin';
+
 export interface RegisterModel {
   username: string;
   password: string;
@@ -20,3 +25,46 @@ export interface LoginModel {
   username: string;
   password: string;
 }
+
+/** Login answer when the account has two-factor authentication on */
+export interface MfaChallenge {
+  mfa_required: true;
+  mfa_token: string; // posted with the code to /authentication/mfa/verify
+  expires_in: number; // seconds
+}
+
+export interface MfaStatus {
+  enabled: boolean;
+  recovery_codes_left: number;
+}
+
+/** A new authenticator secret; `otpauth_uri` is what the QR code encodes */
+export interface MfaEnrollment {
+  secret: string;
+  otpauth_uri: string;
+}
+
+/** One signed-in device, from GET /authentication/sessions */
+export interface LoginSession {
+  id: number;
+  user_agent?: string;
+  ip?: string;
+  created_at: string;
+  last_used_at: string;
+  current: boolean; // the session this browser is using
+}
+
+/** Where to send the browser to sign in at a provider, from /oauth/authorize or /oauth/link */
+export interface OAuthAuthorize {
+  authorize_url: string;
+  state: string; // comes back on the callback; checked against the one kept here
+}
+
+/** An outside account a brawler can sign in with, from GET /oauth/identities */
+export interface LinkedIdentity {
+  provider: string;
+  email?: string;
+  display_name?: string;
+  created_at: string;
+  last_login_at?: string;
+}
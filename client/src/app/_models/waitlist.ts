@@ -0,0 +1,5 @@
+export interface WaitlistPosition {
+  mission_id: number;
+  position: number;
+  total: number;
+}
@@ -3,21 +3,26 @@ import { environment } from '../../environments/environment';
 import { HttpClient } from '@angular/common/http';
 import { firstValueFrom } from 'rxjs';
 import { Mission } from '../_models/mission';
+import { WaitlistPosition } from '../_models/waitlist';
 
 @Injectable({
   providedIn: 'root',
 })
 export class CrewService {
   private _base_url = environment.baseUrl + '/api/crew';
+  private _waitlist_url = environment.baseUrl + '/api/waitlist';
   private _http = inject(HttpClient);
 
   /**
-   * Join a mission
+   * Join a mission. Resolves to `true` when the mission needs the chief's approval
+   * and a join request was sent instead.
    */
-  async join(missionId: number): Promise<string> {
+  async join(missionId: number): Promise<boolean> {
     const url = `${this._base_url}/join/${missionId}`;
-    const resp = await firstValueFrom(this._http.post(url, {}, { responseType: 'text' }));
-    return resp;
+    const resp = await firstValueFrom(
+      this._http.post(url, {}, { responseType: 'text', observe: 'response' }),
+    );
+    return resp.status === 202;
   }
 
   /**
@@ -37,4 +42,28 @@ export class CrewService {
     const resp = await firstValueFrom(this._http.get<Mission[]>(url));
     return resp;
   }
+
+  /**
+   * Wait for a slot in a full mission
+   */
+  async joinWaitlist(missionId: number): Promise<WaitlistPosition> {
+    const url = `${this._waitlist_url}/${missionId}`;
+    return await firstValueFrom(this._http.post<WaitlistPosition>(url, {}));
+  }
+
+  /**
+   * Leave the waitlist of a mission
+   */
+  async leaveWaitlist(missionId: number): Promise<void> {
+    const url = `${this._waitlist_url}/${missionId}`;
+    await firstValueFrom(this._http.delete(url));
+  }
+
+  /**
+   * Get current place in a mission's waitlist
+   */
+  async getWaitlistPosition(missionId: number): Promise<WaitlistPosition> {
+    const url = `${this._waitlist_url}/${missionId}`;
+    return await firstValueFrom(this._http.get<WaitlistPosition>(url));
+  }
 }
@@ -2,6 +2,7 @@ import { inject, Injectable } from '@angular/core';
 import { NavigationExtras, Router } from '@angular/router';
 import { Observable, throwError } from 'rxjs';
 import { ToastService } from './toast-service';
+import { getErrorMessage } from '../_helpers/util';
 
 @Injectable({
   providedIn: 'root',
@@ -12,18 +13,23 @@ export class ErrorService {
 
   handleError(error: any): Observable<never> {
     if (error) {
+      const message = getErrorMessage(error);
       switch (error.status) {
         case 400:
           console.log(error);
-          if (error.error === 'Record not found') this._toast.error('Invalid username or password');
-          else if (error.error !== '') this._toast.error(error.error);
+          if (message !== '') this._toast.error(message);
           else this._toast.error('Bad Request');
           break;
         case 404:
           this._router.navigate(['/not-found']);
           break;
         case 401:
-          this._toast.error('Unauthorized');
+          this._toast.error(message || 'Unauthorized');
+          break;
+        case 403:
+        case 409:
+        case 429:
+          this._toast.error(message || 'Something went wrong, please try again later');
           break;
         case 500:
         case 501:
@@ -38,7 +44,7 @@ export class ErrorService {
         case 510:
         case 511:
           const navExtra: NavigationExtras = {
-            state: { error: error.error },
+            state: { error: message },
           };
           this._router.navigate(['/server-error'], navExtra);
           break;
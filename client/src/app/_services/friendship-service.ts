@@ -35,6 +35,7 @@ export class FriendshipService {
     return firstValueFrom(this._http.get<any[]>(url));
   }
 
+  /** Online friends only; the server doesn't share anyone else's presence */
   async getOnlineUsers(): Promise<any[]> {
     const url = `${this._base_url}/online`;
     return firstValueFrom(this._http.get<any[]>(url));
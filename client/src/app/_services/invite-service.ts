@@ -0,0 +1,84 @@
+import { Injectable, inject } from '@angular/core';
+import { environment } from '../../environments/environment';
+import { HttpClient } from '@angular/common/http';
+import { firstValueFrom } from 'rxjs';
+import { Mission } from '../_models/mission';
+import { InviteLink, MissionInvitation } from '../_models/mission-invite';
+
+@Injectable({
+  providedIn: 'root',
+})
+export class InviteService {
+  private _base_url = environment.baseUrl + '/api/invites';
+  private _crew_url = environment.baseUrl + '/api/crew';
+  private _http = inject(HttpClient);
+
+  /**
+   * Create a shareable invite link (chief only)
+   */
+  async createLink(
+    missionId: number,
+    maxUses?: number,
+    expiresInHours?: number,
+  ): Promise<InviteLink> {
+    const url = `${this._base_url}/mission/${missionId}/links`;
+    const body = { max_uses: maxUses ?? null, expires_in_hours: expiresInHours ?? null };
+    return await firstValueFrom(this._http.post<InviteLink>(url, body));
+  }
+
+  async getLinks(missionId: number): Promise<InviteLink[]> {
+    const url = `${this._base_url}/mission/${missionId}/links`;
+    return await firstValueFrom(this._http.get<InviteLink[]>(url));
+  }
+
+  async revokeLink(missionId: number, linkId: number): Promise<void> {
+    const url = `${this._base_url}/mission/${missionId}/links/${linkId}`;
+    await firstValueFrom(this._http.delete(url));
+  }
+
+  /**
+   * Join a mission through an invite link token
+   */
+  async joinByInvite(token: string): Promise<Mission> {
+    const url = `${this._crew_url}/join-by-invite/${encodeURIComponent(token)}`;
+    return await firstValueFrom(this._http.post<Mission>(url, {}));
+  }
+
+  /**
+   * Invite a friend directly (chief only)
+   */
+  async inviteFriend(missionId: number, brawlerId: number): Promise<number> {
+    const url = `${this._base_url}/mission/${missionId}`;
+    const resp = await firstValueFrom(
+      this._http.post<{ invitation_id: number }>(url, { brawler_id: brawlerId }),
+    );
+    return resp.invitation_id;
+  }
+
+  async getMissionInvitations(missionId: number): Promise<MissionInvitation[]> {
+    const url = `${this._base_url}/mission/${missionId}`;
+    return await firstValueFrom(this._http.get<MissionInvitation[]>(url));
+  }
+
+  async revokeInvitation(missionId: number, invitationId: number): Promise<void> {
+    const url = `${this._base_url}/mission/${missionId}/${invitationId}`;
+    await firstValueFrom(this._http.delete(url));
+  }
+
+  /**
+   * Pending invitations sent to me
+   */
+  async getMyInvitations(): Promise<MissionInvitation[]> {
+    return await firstValueFrom(this._http.get<MissionInvitation[]>(this._base_url));
+  }
+
+  async accept(invitationId: number): Promise<Mission> {
+    const url = `${this._base_url}/${invitationId}/accept`;
+    return await firstValueFrom(this._http.patch<Mission>(url, {}));
+  }
+
+  async decline(invitationId: number): Promise<void> {
+    const url = `${this._base_url}/${invitationId}/decline`;
+    await firstValueFrom(this._http.patch(url, {}));
+  }
+}
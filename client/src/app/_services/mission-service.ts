@@ -6,6 +6,7 @@ import { firstValueFrom } from 'rxjs';
 import { AddMission } from '../_models/add-mission';
 import { Mission } from '../_models/mission';
 import { MissionComment } from '../_models/mission-comment';
+import { MissionApplication } from '../_models/mission-application';
 
 @Injectable({
   providedIn: 'root',
@@ -103,6 +104,21 @@ export class MissionService {
     return await firstValueFrom(this._http.get<any[]>(url));
   }
 
+  async getApplications(missionId: number): Promise<MissionApplication[]> {
+    const url = `${this._base_url}/mission-management/${missionId}/applications`;
+    return await firstValueFrom(this._http.get<MissionApplication[]>(url));
+  }
+
+  async acceptApplication(missionId: number, applicationId: number): Promise<void> {
+    const url = `${this._base_url}/mission-management/${missionId}/applications/${applicationId}/accept`;
+    await firstValueFrom(this._http.patch(url, {}));
+  }
+
+  async rejectApplication(missionId: number, applicationId: number): Promise<void> {
+    const url = `${this._base_url}/mission-management/${missionId}/applications/${applicationId}/reject`;
+    await firstValueFrom(this._http.patch(url, {}));
+  }
+
   async getComments(missionId: number): Promise<MissionComment[]> {
     const url = `${this._base_url}/comment/${missionId}`;
     return await firstValueFrom(this._http.get<MissionComment[]>(url));
@@ -1,9 +1,19 @@
 import { inject, Injectable, signal } from '@angular/core';
-import { HttpClient, HttpErrorResponse } from '@angular/common/http';
+import { HttpBackend, HttpClient, HttpErrorResponse, HttpHeaders } from '@angular/common/http';
 import { environment } from '../../environments/environment';
-import { LoginModel, Passport, RegisterModel } from '../_models/passport';
+import {
+  LoginModel,
+  LinkedIdentity,
+  LoginSession,
+  MfaChallenge,
+  MfaEnrollment,
+  MfaStatus,
+  OAuthAuthorize,
+  Passport,
+  RegisterModel,
+} from '../_models/passport';
 import { firstValueFrom } from 'rxjs';
-import { getAvatarUrl } from '../_helpers/util';
+import { getAvatarUrl, getErrorMessage } from '../_helpers/util';
 // import { environment } from '../../environments/environment.development';
 @Injectable({
   providedIn: 'root',
@@ -12,10 +22,20 @@ export class PassportService {
   private _key = 'passport';
   private _base_url = environment.baseUrl + '/api';
   private _http = inject(HttpClient);
+  // Skips the interceptors, so a failed refresh isn't itself retried or toasted
+  private _rawHttp = new HttpClient(inject(HttpBackend));
+  private _refreshing?: Promise<string | null>;
+
+  static readonly CSRF_COOKIE = 'vibe_csrf';
+  /** The sign-in started at a provider, kept until its callback comes back */
+  static readonly OAUTH_STATE = 'oauth_state';
+  static readonly CSRF_HEADER = 'X-CSRF-Token';
 
   data = signal<undefined | Passport>(undefined);
   avatar = signal<string>('');
   isSignin = signal<boolean>(false);
+  /** Set while a login waits for its two-factor code */
+  mfaChallenge = signal<undefined | MfaChallenge>(undefined);
 
   saveAvatarImgUrl(url: string) {
     let passport = this.data();
@@ -28,6 +48,8 @@ export class PassportService {
   }
 
   updatePassport(passport: Passport) {
+    // Profile updates hand out a new access token but keep the current login
+    passport.refresh_token ??= this.data()?.refresh_token;
     this.data.set(passport);
     this.avatar.set(getAvatarUrl(passport));
     this.savePassportToLocalStorage();
@@ -60,7 +82,236 @@ export class PassportService {
     this.loadPassportFromLocalStorage();
   }
 
+  /**
+   * True when the server runs in cookie mode (`AUTH_COOKIES`): the tokens live in
+   * HttpOnly cookies and the passport only carries the profile.
+   */
+  cookieMode(): boolean {
+    return this.isSignin() && !this.data()?.token;
+  }
+
+  /** The readable CSRF cookie, echoed in a header on state-changing requests */
+  csrfToken(): string | null {
+    const prefix = PassportService.CSRF_COOKIE + '=';
+    const cookie = document.cookie.split('; ').find((c) => c.startsWith(prefix));
+    return cookie ? decodeURIComponent(cookie.slice(prefix.length)) : null;
+  }
+
+  private csrfHeaders(): HttpHeaders {
+    const token = this.csrfToken();
+    return token ? new HttpHeaders({ [PassportService.CSRF_HEADER]: token }) : new HttpHeaders();
+  }
+
+  /**
+   * Trades the refresh token for a new access token. Concurrent callers share one
+   * request, since a refresh token only works once. Resolves to null when the
+   * login is over.
+   */
+  refresh(): Promise<string | null> {
+    this._refreshing ??= this.doRefresh().finally(() => (this._refreshing = undefined));
+    return this._refreshing;
+  }
+
+  private async doRefresh(): Promise<string | null> {
+    const cookieMode = this.cookieMode();
+    const refresh_token = this.data()?.refresh_token;
+    if (!refresh_token && !cookieMode) return null;
+
+    try {
+      const api_url = this._base_url + '/authentication/refresh';
+      // In cookie mode the refresh token is sent as a cookie, with the CSRF header
+      const request = cookieMode
+        ? this._rawHttp.post<Passport>(api_url, null, { headers: this.csrfHeaders() })
+        : this._rawHttp.post<Passport>(api_url, { refresh_token });
+      const passport = await firstValueFrom(request);
+      this.updatePassport(passport);
+      return passport.token ?? '';
+    } catch {
+      this.destroy();
+      return null;
+    }
+  }
+
+  /**
+   * The access token, refreshed first if it is about to expire (for sockets, which
+   * can't retry). Empty in cookie mode, where the cookie goes along by itself.
+   */
+  async freshToken(): Promise<string | null> {
+    if (this.cookieMode()) return '';
+    const token = this.data()?.token;
+    if (!token) return null;
+
+    try {
+      const { exp } = JSON.parse(atob(token.split('.')[1].replace(/-/g, '+').replace(/_/g, '/')));
+      if (exp * 1000 - Date.now() > 30_000) return token;
+    } catch {
+      return token;
+    }
+    return this.refresh();
+  }
+
+  /** Ends this login on the server too, so the refresh token can't be used again */
+  async logout() {
+    const cookieMode = this.cookieMode();
+    const refresh_token = this.data()?.refresh_token;
+    const headers = this.csrfHeaders();
+    this.destroy();
+    if (!refresh_token && !cookieMode) return;
+
+    try {
+      const api_url = this._base_url + '/authentication/logout';
+      const body = cookieMode ? null : { refresh_token };
+      await firstValueFrom(this._rawHttp.post(api_url, body, { headers }));
+    } catch (error) {
+      console.error('Failed to log out on the server', error);
+    }
+  }
+
+  /** Ends every login of this account, this device included */
+  async logoutAll() {
+    const api_url = this._base_url + '/authentication/logout-all';
+    await firstValueFrom(this._http.post(api_url, {}));
+    this.destroy();
+  }
+
+  /** Devices this account is signed in on, most recently used first */
+  async listSessions(): Promise<LoginSession[]> {
+    const api_url = this._base_url + '/authentication/sessions';
+    return await firstValueFrom(this._http.get<LoginSession[]>(api_url));
+  }
+
+  /** Signs another device out; revoking the current session signs this one out too */
+  async revokeSession(session: LoginSession) {
+    const api_url = this._base_url + '/authentication/sessions/' + session.id;
+    await firstValueFrom(this._http.delete(api_url));
+    if (session.current) this.destroy();
+  }
+
+  /** Mails a reset link if the account has a contact email; the reply never says whether it does */
+  async requestPasswordReset(username: string): Promise<string | null> {
+    const api_url = this._base_url + '/authentication/password-reset';
+    try {
+      await firstValueFrom(this._http.post(api_url, { username }));
+      return null;
+    } catch (error: any) {
+      return getErrorMessage(error);
+    }
+  }
+
+  /** Sets a new password with the token from a reset mail. Every device is signed out. */
+  async resetPassword(token: string, newPassword: string): Promise<string | null> {
+    const api_url = this._base_url + '/authentication/password-reset/confirm';
+    try {
+      await firstValueFrom(this._http.post(api_url, { token, new_password: newPassword }));
+      this.destroy();
+      return null;
+    } catch (error: any) {
+      return getErrorMessage(error);
+    }
+  }
+
+  /** Finishes a login that answered with `mfaChallenge`, with an app or recovery code */
+  async verifyMfa(code: string): Promise<string | null> {
+    const challenge = this.mfaChallenge();
+    if (!challenge) return 'Login again';
+    const api_url = this._base_url + '/authentication/mfa/verify';
+    return await this.fetchPassport(api_url, { mfa_token: challenge.mfa_token, code });
+  }
+
+  async mfaStatus(): Promise<MfaStatus> {
+    const api_url = this._base_url + '/authentication/mfa';
+    return await firstValueFrom(this._http.get<MfaStatus>(api_url));
+  }
+
+  /** Starts setting up an authenticator; 2FA is on once `confirmMfa` gets a code from it */
+  async enrollMfa(): Promise<MfaEnrollment> {
+    const api_url = this._base_url + '/authentication/mfa/enroll';
+    return await firstValueFrom(this._http.post<MfaEnrollment>(api_url, {}));
+  }
+
+  /** Turns 2FA on; resolves to the recovery codes, which can't be fetched again */
+  async confirmMfa(code: string): Promise<string[]> {
+    const api_url = this._base_url + '/authentication/mfa/confirm';
+    const result = this._http.post<{ recovery_codes: string[] }>(api_url, { code });
+    return (await firstValueFrom(result)).recovery_codes;
+  }
+
+  async regenerateRecoveryCodes(code: string): Promise<string[]> {
+    const api_url = this._base_url + '/authentication/mfa/recovery-codes';
+    const result = this._http.post<{ recovery_codes: string[] }>(api_url, { code });
+    return (await firstValueFrom(result)).recovery_codes;
+  }
+
+  async disableMfa(password: string, code: string): Promise<string | null> {
+    const api_url = this._base_url + '/authentication/mfa/disable';
+    try {
+      await firstValueFrom(this._http.post(api_url, { password, code }));
+      return null;
+    } catch (error: any) {
+      return getErrorMessage(error);
+    }
+  }
+
+  /** Providers the server offers sign-in with, e.g. `discord` */
+  async oauthProviders(): Promise<string[]> {
+    const api_url = this._base_url + '/oauth/providers';
+    try {
+      return await firstValueFrom(this._http.get<string[]>(api_url));
+    } catch {
+      return [];
+    }
+  }
+
+  /**
+   * Leaves for the provider's sign-in page. With `link` the account gets linked to
+   * the signed-in brawler instead of logging in.
+   */
+  async startOAuth(provider: string, link = false) {
+    const path = link ? '/oauth/link/' : '/oauth/authorize/';
+    const api_url = this._base_url + path + encodeURIComponent(provider);
+    const { authorize_url, state } = await firstValueFrom(this._http.get<OAuthAuthorize>(api_url));
+    sessionStorage.setItem(PassportService.OAUTH_STATE, JSON.stringify({ state, link }));
+    window.location.href = authorize_url;
+  }
+
+  /** Finishes what `startOAuth` began, with the query the provider sent back */
+  async completeOAuth(code: string, state: string): Promise<string | null> {
+    const started = sessionStorage.getItem(PassportService.OAUTH_STATE);
+    sessionStorage.removeItem(PassportService.OAUTH_STATE);
+    const { state: expected, link } = started ? JSON.parse(started) : { state: null, link: false };
+    // Only callbacks of a sign-in this browser started
+    if (!expected || expected !== state) return 'Sign-in expired, please try again';
+
+    if (!link) {
+      return await this.fetchPassport(this._base_url + '/oauth/callback', { code, state });
+    }
+    try {
+      const api_url = this._base_url + '/oauth/identities';
+      await firstValueFrom(this._http.post(api_url, { code, state }));
+      return null;
+    } catch (error: any) {
+      return getErrorMessage(error);
+    }
+  }
+
+  async linkedIdentities(): Promise<LinkedIdentity[]> {
+    const api_url = this._base_url + '/oauth/identities';
+    return await firstValueFrom(this._http.get<LinkedIdentity[]>(api_url));
+  }
+
+  /** The password is only asked for when it is the last linked provider */
+  async unlinkIdentity(provider: string, password?: string): Promise<string | null> {
+    const api_url = this._base_url + '/oauth/identities/' + encodeURIComponent(provider);
+    try {
+      await firstValueFrom(this._http.delete(api_url, { body: password ? { password } : null }));
+      return null;
+    } catch (error: any) {
+      return getErrorMessage(error);
+    }
+  }
+
   destroy() {
+    this.mfaChallenge.set(undefined);
     this.data.set(undefined);
     this.avatar.set('');
     this.isSignin.set(false);
@@ -79,11 +330,20 @@ export class PassportService {
 
   private async fetchPassport(
     api_url: string,
-    model: LoginModel | RegisterModel,
+    model:
+      | LoginModel
+      | RegisterModel
+      | { mfa_token: string; code: string }
+      | { code: string; state: string },
   ): Promise<string | null> {
     try {
-      const result = this._http.post<Passport>(api_url, model);
+      const result = this._http.post<Passport | MfaChallenge>(api_url, model);
       const passport = await firstValueFrom(result);
+      if ('mfa_required' in passport) {
+        this.mfaChallenge.set(passport);
+        return null;
+      }
+      this.mfaChallenge.set(undefined);
       this.data.set(passport);
       this.avatar.set(getAvatarUrl(passport)); // เพื่อให้ รูป avatar เปลี่ยนทันที ที่ login ไม่ต้อง รีเฟรชหน้าเว็บ
       this.savePassportToLocalStorage();
@@ -91,7 +351,7 @@ export class PassportService {
     } catch (error: any) {
       // console.error(error)
       // console.log(error.error);
-      return error.error;
+      return getErrorMessage(error);
     }
   }
 }
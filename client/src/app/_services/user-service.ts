@@ -3,6 +3,7 @@ import { environment } from '../../environments/environment';
 import { HttpClient } from '@angular/common/http';
 import { PassportService } from './passport-service';
 import { fileToBase64 } from '../_helpers/file';
+import { getErrorMessage } from '../_helpers/util';
 import { firstValueFrom } from 'rxjs';
 import { CloudinaryImage } from '../_models/cloudinary-image';
 import { Passport } from '../_models/passport';
@@ -25,7 +26,7 @@ export class UserService {
       const cloudinaryImg = await firstValueFrom(this._http.post<CloudinaryImage>(url, uploadImg));
       this._passport.saveAvatarImgUrl(cloudinaryImg.url);
     } catch (error: any) {
-      return error.error as string;
+      return getErrorMessage(error);
     }
     return null;
   }
@@ -51,7 +52,22 @@ export class UserService {
       const passport = await firstValueFrom(this._http.patch<Passport>(url, body));
       this._passport.updatePassport(passport);
     } catch (error: any) {
-      return error.error as string;
+      return getErrorMessage(error);
+    }
+    return null;
+  }
+
+  /** Other devices are signed out; this one stays signed in */
+  async changePassword(currentPassword: string, newPassword: string): Promise<string | null> {
+    const url = this._base_url + '/password';
+    const body = {
+      current_password: currentPassword,
+      new_password: newPassword,
+    };
+    try {
+      await firstValueFrom(this._http.patch(url, body));
+    } catch (error: any) {
+      return getErrorMessage(error);
     }
     return null;
   }
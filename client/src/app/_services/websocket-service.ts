@@ -1,14 +1,44 @@
 import { Injectable, inject, NgZone } from '@angular/core';
 import { Subject, Observable } from 'rxjs';
 import { environment } from '../../environments/environment';
-
+import { PassportService } from './passport-service';
+
+/** Must match `PROTOCOL_VERSION` in server/src/infrastructure/websocket/protocol.rs */
+export const WS_PROTOCOL_VERSION = 1;
+
+const RECONNECT_MIN_DELAY_MS = 1000;
+const RECONNECT_MAX_DELAY_MS = 30000;
+/** Upgrades that fail before opening, after which notifications come over SSE instead */
+const WS_FAILURES_BEFORE_SSE = 2;
+/** The server closed the socket because this login was signed out elsewhere */
+const SESSION_REVOKED_CLOSE_CODE = 4401;
+
+/** Replies to room requests; they carry no `room` but belong to the mission view */
+const ROOM_REPLY_TYPES = ['chat_ack', 'error', 'subscribed', 'unsubscribed'];
+
+/**
+ * One socket (`/api/ws/global`) carries both the user's notifications and the
+ * mission room being viewed, which is followed with `subscribe`/`unsubscribe`.
+ * Behind proxies that block WebSockets, notifications fall back to `/api/events`
+ * and room requests to REST.
+ */
 @Injectable({
   providedIn: 'root',
 })
 export class WebsocketService {
   private _ngZone = inject(NgZone);
+  private _passport = inject(PassportService);
   private socket?: WebSocket;
-  private notificationSocket?: WebSocket;
+  private events?: EventSource;
+  private failedUpgrades = 0;
+
+  // Last `seq` seen on each stream, sent back as `since` to replay what was missed
+  private notificationSeq?: number;
+  private roomSeq?: number;
+  private roomId?: number;
+  private roomSubscribed = false;
+  private retryDelay = RECONNECT_MIN_DELAY_MS;
+  private retryTimer?: ReturnType<typeof setTimeout>;
 
   private messageSubject = new Subject<any>();
   private notificationSubject = new Subject<any>();
@@ -23,87 +53,189 @@ export class WebsocketService {
   public messages$: Observable<any> = this.messageSubject.asObservable();
   public notifications$: Observable<any> = this.notificationSubject.asObservable();
 
+  private get isOpen(): boolean {
+    return this.socket?.readyState === WebSocket.OPEN;
+  }
+
+  private send(type: string, data?: any) {
+    // Requests without a payload (typing_start/typing_stop) must leave out `data`
+    this.socket!.send(JSON.stringify({ version: WS_PROTOCOL_VERSION, type, data }));
+  }
+
+  /** Follow a mission room on the shared socket */
   connect(missionId: number): void {
+    this.roomId = missionId;
+    this.roomSeq = undefined;
+    this.roomSubscribed = false;
+    if (this.isOpen) {
+      this.send('subscribe', { mission_id: missionId });
+    }
+  }
+
+  async connectNotifications(): Promise<void> {
+    // Reconnects can come long after login, when the access token has expired
+    const token = await this._passport.freshToken();
+    // Signed out while the token was being refreshed
+    if (token === null || !this._passport.isSignin()) return;
+
+    if (this.failedUpgrades >= WS_FAILURES_BEFORE_SSE) {
+      this.connectEvents(token);
+      return;
+    }
+
     const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
-    const url = `${protocol}//${this.host}/api/ws/mission/${missionId}`;
+    const since = this.notificationSeq === undefined ? '' : `&since=${this.notificationSeq}`;
+    // Cookie mode authenticates the handshake with the access cookie instead
+    const auth = token ? `token=${token}` : '';
+    const url = `${protocol}//${this.host}/api/ws/global?${auth}${since}`;
+
+    if (this.socket) {
+      const previous = this.socket;
+      this.socket = undefined;
+      previous.close();
+    }
 
-    console.log('[WebSocket] Connecting to mission:', url);
-    this.socket = new WebSocket(url);
+    console.log('[WebSocket] Connecting');
+    const socket = new WebSocket(url);
+    this.socket = socket;
+    let opened = false;
 
-    this.socket.onmessage = (event) => {
+    socket.onmessage = (event) => {
       this._ngZone.run(() => {
         try {
-          const data = JSON.parse(event.data);
-          this.messageSubject.next(data);
+          this.route(JSON.parse(event.data));
         } catch (e) {
           console.error('[WebSocket] Failed to parse message:', e);
         }
       });
     };
 
-    this.socket.onopen = () => console.log('[WebSocket] Mission connected');
-    this.socket.onclose = () => console.log('[WebSocket] Mission closed');
+    socket.onopen = () => {
+      console.log('[WebSocket] Connected');
+      opened = true;
+      this.failedUpgrades = 0;
+      this.retryDelay = RECONNECT_MIN_DELAY_MS;
+      // Pick the room back up where it left off after a reconnect
+      if (this.roomId !== undefined) {
+        this.send('subscribe', { mission_id: this.roomId, since: this.roomSeq });
+      }
+    };
+    socket.onclose = (event) => {
+      console.log('[WebSocket] Connection closed', event.code);
+      this.roomSubscribed = false;
+      if (this.socket !== socket) return;
+
+      if (event.code === SESSION_REVOKED_CLOSE_CODE) {
+        this.socket = undefined;
+        this._passport.destroy();
+        return;
+      }
+      if (!opened) {
+        this.failedUpgrades++;
+        // The access cookie can't be read to check its expiry; refresh it instead
+        if (this._passport.cookieMode()) this._passport.refresh();
+      }
+      this.retryTimer = setTimeout(() => this.connectNotifications(), this.retryDelay);
+      this.retryDelay = Math.min(this.retryDelay * 2, RECONNECT_MAX_DELAY_MS);
+    };
   }
 
-  connectNotifications(): void {
-    const passportJson = localStorage.getItem('passport');
-    if (!passportJson) return;
-
-    let token = '';
-    try {
-      token = JSON.parse(passportJson).token;
-    } catch (e) {
-      console.error('Failed to parse passport for token', e);
-      return;
-    }
+  /** Notifications only; EventSource reconnects by itself and resumes with Last-Event-ID */
+  private connectEvents(token: string) {
+    const since = this.notificationSeq === undefined ? '' : `&since=${this.notificationSeq}`;
+    const auth = token ? `token=${token}` : '';
+    const url = `${window.location.protocol}//${this.host}/api/events?${auth}${since}`;
 
-    if (!token) return;
+    this.events?.close();
+    console.log('[SSE] Connecting');
+    const events = new EventSource(url);
+    this.events = events;
 
-    const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
-    const url = `${protocol}//${this.host}/api/ws/global?token=${token}`;
-
-    if (this.notificationSocket) {
-      this.notificationSocket.close();
-    }
-
-    console.log('[WebSocket] Connecting to Notifications');
-    this.notificationSocket = new WebSocket(url);
-
-    this.notificationSocket.onmessage = (event) => {
+    events.onmessage = (event) => {
       this._ngZone.run(() => {
         try {
-          const data = JSON.parse(event.data);
-          console.log('[Notification] Received:', data);
-          this.notificationSubject.next(data);
+          this.route(JSON.parse(event.data));
         } catch (e) {
-          console.error('[Notification] Failed to parse:', e);
+          console.error('[SSE] Failed to parse message:', e);
         }
       });
     };
+    events.onerror = () => {
+      if (events.readyState === EventSource.CLOSED) {
+        console.error('[SSE] Connection refused');
+      }
+    };
+  }
 
-    this.notificationSocket.onopen = () => console.log('[Notification] Connected successfully');
-    this.notificationSocket.onclose = () => console.log('[Notification] Connection closed');
+  /** Room frames go to `messages$`, everything else to `notifications$` */
+  private route(data: any) {
+    if (data.room !== undefined) {
+      if (data.room !== this.roomId) return;
+      if (data.seq !== undefined) {
+        this.roomSeq = data.seq;
+      }
+      // resync_required is passed on too: subscribers should reload over REST
+      this.messageSubject.next(data);
+      return;
+    }
+
+    if (ROOM_REPLY_TYPES.includes(data.type)) {
+      if (data.type === 'subscribed' && data.data.mission_id === this.roomId) {
+        this.roomSubscribed = true;
+      } else if (data.type === 'unsubscribed' && data.data.mission_id === this.roomId) {
+        // Left, kicked or deleted; the matching room event has already arrived
+        this.roomSubscribed = false;
+        if (data.data.reason) {
+          this.roomId = undefined;
+        }
+      }
+      this.messageSubject.next(data);
+      return;
+    }
+
+    console.log('[Notification] Received:', data);
+    if (data.seq !== undefined) {
+      this.notificationSeq = data.seq;
+    }
+    this.notificationSubject.next(data);
   }
 
+  /** Stop following the mission room; the socket stays open for notifications */
   disconnect(): void {
-    if (this.socket) {
-      this.socket.close();
-      this.socket = undefined;
+    if (this.roomId !== undefined && this.isOpen) {
+      this.send('unsubscribe', { mission_id: this.roomId });
     }
+    this.roomId = undefined;
+    this.roomSeq = undefined;
+    this.roomSubscribed = false;
   }
 
   disconnectNotifications(): void {
-    if (this.notificationSocket) {
-      this.notificationSocket.close();
-      this.notificationSocket = undefined;
+    clearTimeout(this.retryTimer);
+    this.notificationSeq = undefined;
+    this.failedUpgrades = 0;
+    this.events?.close();
+    this.events = undefined;
+    this.roomSubscribed = false;
+    if (this.socket) {
+      // Cleared first so onclose knows not to reconnect
+      const socket = this.socket;
+      this.socket = undefined;
+      socket.close();
     }
   }
 
-  sendMessage(type: string, data: any): void {
-    if (this.socket && this.socket.readyState === WebSocket.OPEN) {
-      this.socket.send(JSON.stringify({ type, data }));
-    } else {
-      console.error('[WebSocket] Cannot send message, socket not open');
+  get isConnected(): boolean {
+    return this.isOpen && this.roomSubscribed;
+  }
+
+  /** Sends a request to the followed room. Returns false when it can't, so callers can fall back to REST. */
+  sendMessage(type: string, data?: any): boolean {
+    if (this.isConnected) {
+      this.send(type, { ...data, mission_id: this.roomId });
+      return true;
     }
+    console.error('[WebSocket] Cannot send message, room not subscribed');
+    return false;
   }
 }
@@ -10,10 +10,14 @@ import { MissionManager } from './missions/mission-manager/mission-manager';
 import { MyCrew } from './my-crew/my-crew';
 import { Dashboard } from './dashboard/dashboard';
 import { Network } from './network/network';
+import { ResetPassword } from './reset-password/reset-password';
+import { OAuthCallback } from './oauth-callback/oauth-callback';
 
 export const routes: Routes = [
   { path: '', component: Home },
   { path: 'login', component: Login },
+  { path: 'reset-password', component: ResetPassword },
+  { path: 'oauth/callback', component: OAuthCallback },
   { path: 'network', component: Network, canActivate: [authGuard] },
   {
     path: 'profile',
@@ -51,6 +55,12 @@ export const routes: Routes = [
       import('./missions/mission-detail/mission-detail').then((m) => m.MissionDetail),
     canActivate: [authGuard],
   },
+  {
+    path: 'invite/:token',
+    loadComponent: () =>
+      import('./missions/join-invite/join-invite').then((m) => m.JoinInvite),
+    canActivate: [authGuard],
+  },
   {
     path: 'dashboard',
     component: Dashboard,
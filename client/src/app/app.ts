@@ -1,4 +1,4 @@
-import { Component, effect, inject, signal } from '@angular/core';
+import { Component, effect, inject, signal, untracked } from '@angular/core';
 import { Router, RouterOutlet } from '@angular/router';
 import { Navbar } from './navbar/navbar';
 import { PassportService } from './_services/passport-service';
@@ -35,7 +35,8 @@ export class App {
     // Auto-connect/disconnect notifications based on login state
     effect(() => {
       if (this._passport.isSignin()) {
-        this._ws.connectNotifications();
+        // Untracked: token refreshes update the passport and must not reconnect the socket
+        untracked(() => this._ws.connectNotifications());
         this._notifService.getNotifications();
       } else {
         this._ws.disconnectNotifications();
@@ -84,6 +85,24 @@ export class App {
       case 'new_crew_joined':
         this._toast.success(`New crew joined mission: ${data.mission_name}`);
         break;
+      case 'waitlist_promoted':
+        this._toast.success(`A slot opened up! You're now crew of: ${data.mission_name}`);
+        break;
+      case 'join_request':
+        this._toast.info(`Someone asked to join: ${data.mission_name}`);
+        break;
+      case 'application_accepted':
+        this._toast.success(`You're in! Your request to join ${data.mission_name} was accepted`);
+        break;
+      case 'application_rejected':
+        this._toast.warning(`Your request to join ${data.mission_name} was declined`);
+        break;
+      case 'mission_invitation':
+        this._toast.info(`${data.inviter_display_name} invited you to join: ${data.mission_name}`);
+        break;
+      case 'invitation_declined':
+        this._toast.info(`Your invitation to ${data.mission_name} was declined`);
+        break;
       case 'mission_started':
         this._toast.info(`Mission "${data.mission_name}" has started! Time to fight!`);
         break;
@@ -112,6 +131,9 @@ export class App {
       case 'agent_offline':
         // Silently handle or minor log
         break;
+      case 'resync_required':
+        // Too much was missed while disconnected; the bell reloads from the API
+        break;
       default:
         console.warn('Unknown notification type:', type);
     }
@@ -7,7 +7,7 @@ import {
   Validators,
 } from '@angular/forms';
 import { passwordMatchValidator, PasswordValidator } from '../_helpers/password-validator';
-import { Router } from '@angular/router';
+import { Router, RouterLink } from '@angular/router';
 import { PassportService } from '../_services/passport-service';
 import { ButtonModule } from 'primeng/button';
 import { CommonModule } from '@angular/common';
@@ -15,7 +15,7 @@ import { CommonModule } from '@angular/common';
 @Component({
   selector: 'app-login',
   standalone: true,
-  imports: [FormsModule, ReactiveFormsModule, ButtonModule, CommonModule],
+  imports: [FormsModule, ReactiveFormsModule, ButtonModule, CommonModule, RouterLink],
   templateUrl: './login.html',
   styleUrl: './login.scss',
 })
@@ -28,6 +28,7 @@ export class Login {
 
   mode: 'login' | ' register' = 'login';
   form: FormGroup;
+  mfaCode = new FormControl('', [Validators.required]);
   errorMsg = {
     username: signal(''),
     password: signal(''),
@@ -37,10 +38,13 @@ export class Login {
   };
 
   private _router = inject(Router);
-  private _passport = inject(PassportService);
+  protected _passport = inject(PassportService);
+  /** Outside accounts the server lets people sign in with */
+  providers = signal<string[]>([]);
 
   constructor() {
     if (this._passport.data()) this._router.navigate(['/']);
+    this._passport.oauthProviders().then((providers) => this.providers.set(providers));
 
     this.form = new FormGroup({
       username: new FormControl(null, [
@@ -130,9 +134,33 @@ export class Login {
     } else {
       errMsg = await this._passport.register(this.form.value);
     }
+    if (errMsg) this.errorMsg.server.set(errMsg);
+    // With two-factor authentication on, the code is asked for next
+    else if (!this._passport.mfaChallenge()) this._router.navigate(['/']);
+  }
+
+  async signInWith(provider: string) {
+    this.errorMsg.server.set('');
+    try {
+      await this._passport.startOAuth(provider);
+    } catch {
+      this.errorMsg.server.set(`Sign-in with ${provider} is not available`);
+    }
+  }
+
+  async onSubmitMfa() {
+    this.errorMsg.server.set('');
+    const errMsg = await this._passport.verifyMfa(this.mfaCode.value ?? '');
     if (!errMsg) this._router.navigate(['/']);
     else {
       this.errorMsg.server.set(errMsg);
+      this.mfaCode.reset();
     }
   }
+
+  cancelMfa() {
+    this._passport.mfaChallenge.set(undefined);
+    this.mfaCode.reset();
+    this.errorMsg.server.set('');
+  }
 }
@@ -0,0 +1,33 @@
+import { Component, inject, OnInit } from '@angular/core';
+import { ActivatedRoute, Router } from '@angular/router';
+import { InviteService } from '../../_services/invite-service';
+import { ToastService } from '../../_services/toast-service';
+import { getErrorMessage } from '../../_helpers/util';
+
+/**
+ * Landing page for shared invite links: joins right away and opens the room.
+ */
+@Component({
+  selector: 'app-join-invite',
+  standalone: true,
+  imports: [],
+  templateUrl: './join-invite.html',
+})
+export class JoinInvite implements OnInit {
+  private _route = inject(ActivatedRoute);
+  private _router = inject(Router);
+  private _inviteService = inject(InviteService);
+  private _toast = inject(ToastService);
+
+  async ngOnInit() {
+    const token = this._route.snapshot.paramMap.get('token') ?? '';
+    try {
+      const mission = await this._inviteService.joinByInvite(token);
+      this._toast.success(`Joined the room: ${mission.name}`);
+      this._router.navigate(['/missions', mission.id]);
+    } catch (e: any) {
+      this._toast.error('Failed to join: ' + (getErrorMessage(e) || e.message));
+      this._router.navigate(['/missions']);
+    }
+  }
+}
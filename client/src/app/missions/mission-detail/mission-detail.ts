@@ -11,6 +11,7 @@ import { MissionComment } from '../../_models/mission-comment';
 import { FormsModule } from '@angular/forms';
 import { Subscription } from 'rxjs';
 import { distinctUntilChanged, map } from 'rxjs/operators';
+import { getErrorMessage } from '../../_helpers/util';
 
 // PrimeNG
 import { ButtonModule } from 'primeng/button';
@@ -42,6 +43,11 @@ export class MissionDetail implements OnInit, OnDestroy {
   isKicked = false;
   countdownValue = 0;
   isCountdownActive = false;
+  typingIds = new Set<number>();
+
+  private _pendingComment?: { clientId: string; content: string };
+  private _typingStopTimer?: ReturnType<typeof setTimeout>;
+  private _isTyping = false;
 
   private _wsSubscription?: Subscription;
   private _notificationSubscription?: Subscription;
@@ -51,6 +57,12 @@ export class MissionDetail implements OnInit, OnDestroy {
     return this.mission?.chief_id === this._passportService.data()?.id;
   }
 
+  /** The chief, or a moderator, who may kick crew and clear the chat */
+  get canModerate(): boolean {
+    const role = this._passportService.data()?.role;
+    return this.isChief || role === 'moderator' || role === 'admin';
+  }
+
   get currentUserId(): number | undefined {
     return this._passportService.data()?.id;
   }
@@ -92,9 +104,19 @@ export class MissionDetail implements OnInit, OnDestroy {
     this._routeSubscription?.unsubscribe();
     this._wsSubscription?.unsubscribe();
     this._notificationSubscription?.unsubscribe();
+    clearTimeout(this._typingStopTimer);
     this._wsService.disconnect();
   }
 
+  get typingNames(): string[] {
+    const chief = this.mission
+      ? [{ id: this.mission.chief_id, display_name: this.mission.chief_display_name }]
+      : [];
+    return [...this.crew, ...chief]
+      .filter((member) => this.typingIds.has(member.id))
+      .map((member) => member.display_name);
+  }
+
   private handleWsMessage(msg: any) {
     console.log('[MissionDetail] WS Message:', msg);
     if (msg.type === 'new_comment') {
@@ -103,10 +125,42 @@ export class MissionDetail implements OnInit, OnDestroy {
       const exists = this.comments.some((c) => c.id === newComment.id);
       if (!exists) {
         this.comments = [...this.comments, newComment];
+        this.typingIds.delete(newComment.brawler_id);
+        if (newComment.brawler_id !== this.currentUserId) {
+          this._wsService.sendMessage('read_up_to', { comment_id: newComment.id });
+        }
         setTimeout(() => {
           this._cdr.detectChanges();
         }, 0);
       }
+    } else if (msg.type === 'chat_ack') {
+      if (this._pendingComment?.clientId === msg.data.client_id) {
+        this._pendingComment = undefined;
+        this.sendingComment = false;
+        this._cdr.detectChanges();
+      }
+    } else if (msg.type === 'error' && msg.data.request === 'chat_message') {
+      if (this._pendingComment?.clientId === msg.data.client_id) {
+        this.newCommentContent = this._pendingComment.content; // Restore content if failed
+        this._pendingComment = undefined;
+        this.sendingComment = false;
+      }
+      this._toast.error(msg.data.message || 'Failed to send message');
+      this._cdr.detectChanges();
+    } else if (msg.type === 'typing_start' || msg.type === 'typing_stop') {
+      if (msg.data.brawler_id !== this.currentUserId) {
+        if (msg.type === 'typing_start') {
+          this.typingIds.add(msg.data.brawler_id);
+        } else {
+          this.typingIds.delete(msg.data.brawler_id);
+        }
+        this._cdr.detectChanges();
+      }
+    } else if (msg.type === 'resync_required') {
+      // Missed more than the server kept while disconnected
+      if (this.mission) {
+        this.loadMission(this.mission.id);
+      }
     } else if (msg.type === 'clear_chat') {
       this.comments = [];
       this._cdr.detectChanges();
@@ -195,6 +249,14 @@ export class MissionDetail implements OnInit, OnDestroy {
     this.sendingComment = true;
     const content = this.newCommentContent.trim();
     this.newCommentContent = '';
+    this.stopTyping();
+
+    // Prefer the room socket; the ack (or error) frame settles sendingComment
+    const clientId = `${Date.now()}-${Math.random().toString(36).slice(2)}`;
+    if (this._wsService.sendMessage('chat_message', { content, client_id: clientId })) {
+      this._pendingComment = { clientId, content };
+      return;
+    }
 
     try {
       await this._missionService.addComment(this.mission.id, content);
@@ -211,6 +273,22 @@ export class MissionDetail implements OnInit, OnDestroy {
     }
   }
 
+  onCommentInput() {
+    if (!this._isTyping) {
+      this._isTyping = this._wsService.sendMessage('typing_start');
+    }
+    clearTimeout(this._typingStopTimer);
+    this._typingStopTimer = setTimeout(() => this.stopTyping(), 3000);
+  }
+
+  private stopTyping() {
+    clearTimeout(this._typingStopTimer);
+    if (this._isTyping) {
+      this._isTyping = false;
+      this._wsService.sendMessage('typing_stop');
+    }
+  }
+
   async onStart() {
     if (!this.mission || this.isCountdownActive) return;
 
@@ -231,7 +309,7 @@ export class MissionDetail implements OnInit, OnDestroy {
             this._toast.success('MISSION STARTED! GO BERSERK!');
             await this.loadMission(this.mission!.id);
           } catch (e: any) {
-            this._toast.error('Failed to start: ' + (e.error || e.message));
+            this._toast.error('Failed to start: ' + (getErrorMessage(e) || e.message));
           }
         }, 0);
       }
@@ -245,7 +323,7 @@ export class MissionDetail implements OnInit, OnDestroy {
       this._toast.success('MISSION COMPLETED! Well done, Crew!');
       await this.loadMission(this.mission.id);
     } catch (e: any) {
-      this._toast.error('Failed to complete: ' + (e.error || e.message));
+      this._toast.error('Failed to complete: ' + (getErrorMessage(e) || e.message));
     }
   }
 
@@ -256,7 +334,7 @@ export class MissionDetail implements OnInit, OnDestroy {
       this._toast.warning('MISSION FAILED. Regroup and try again.');
       await this.loadMission(this.mission.id);
     } catch (e: any) {
-      this._toast.error('Failed to fail: ' + (e.error || e.message));
+      this._toast.error('Failed to fail: ' + (getErrorMessage(e) || e.message));
     }
   }
 
@@ -270,7 +348,7 @@ export class MissionDetail implements OnInit, OnDestroy {
       this.crew = await this._missionService.getCrew(this.mission.id);
       this.mission.crew_count--;
     } catch (e: any) {
-      this._toast.error('Failed to kick: ' + (e.error || e.message));
+      this._toast.error('Failed to kick: ' + (getErrorMessage(e) || e.message));
     }
   }
 
@@ -280,7 +358,7 @@ export class MissionDetail implements OnInit, OnDestroy {
       await this._missionService.clearComments(this.mission.id);
       this.comments = [];
     } catch (e: any) {
-      this._toast.error('Failed to clear chat: ' + (e.error || e.message));
+      this._toast.error('Failed to clear chat: ' + (getErrorMessage(e) || e.message));
     }
   }
 
@@ -292,7 +370,7 @@ export class MissionDetail implements OnInit, OnDestroy {
       await this._crewService.leave(this.mission.id);
       this._router.navigate(['/my-crew']);
     } catch (e: any) {
-      this._toast.error('Failed to leave: ' + (e.error || e.message));
+      this._toast.error('Failed to leave: ' + (getErrorMessage(e) || e.message));
     }
   }
 }
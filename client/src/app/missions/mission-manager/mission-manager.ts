@@ -16,6 +16,7 @@ import { ButtonModule } from 'primeng/button';
 import { CardModule } from 'primeng/card';
 import { TagModule } from 'primeng/tag';
 import { TooltipModule } from 'primeng/tooltip';
+import { getErrorMessage } from '../../_helpers/util';
 
 @Component({
   selector: 'app-mission-manager',
@@ -91,7 +92,7 @@ export class MissionManager implements OnDestroy {
           this._toast.success('Mission created successfully!');
           this.loadMyMission();
         } catch (e: any) {
-          this._toast.error('Failed to create mission: ' + (e.error || e.message));
+          this._toast.error('Failed to create mission: ' + (getErrorMessage(e) || e.message));
         }
       }
     });
@@ -112,7 +113,7 @@ export class MissionManager implements OnDestroy {
           this._toast.success('Mission updated successfully!');
           this.loadMyMission();
         } catch (e: any) {
-          this._toast.error('Failed to update mission: ' + (e.error || e.message));
+          this._toast.error('Failed to update mission: ' + (getErrorMessage(e) || e.message));
         }
       }
     });
@@ -125,7 +126,7 @@ export class MissionManager implements OnDestroy {
       this._toast.success('Mission deleted.');
       this.loadMyMission();
     } catch (e: any) {
-      this._toast.error('Deletion failed: ' + (e.error || e.message));
+      this._toast.error('Deletion failed: ' + (getErrorMessage(e) || e.message));
     }
   }
 
@@ -7,7 +7,7 @@ import { PassportService } from '../_services/passport-service';
 import { BehaviorSubject, Subscription, firstValueFrom } from 'rxjs';
 import { CommonModule } from '@angular/common';
 import { CrewService } from '../_services/crew-service';
-import { getUserIdFromToken } from '../_helpers/util';
+import { getErrorMessage } from '../_helpers/util';
 import { ToastService } from '../_services/toast-service';
 import { WebsocketService } from '../_services/websocket-service';
 import { ActivatedRoute } from '@angular/router';
@@ -129,8 +129,7 @@ export class Missions implements OnDestroy {
     const filter: MissionFilter = { status: '', category: '' };
     const passport = this._passportService.data();
     if (passport) {
-      const userId = getUserIdFromToken(passport.token);
-      if (userId) filter.exclude_user_id = userId.toString();
+      filter.exclude_user_id = passport.id.toString();
     }
 
     this._allMissions = await this._mission.getByFilter(filter);
@@ -189,12 +188,26 @@ export class Missions implements OnDestroy {
   async onJoin(mission: Mission) {
     if (!confirm(`Do you want to join "${mission.name}"?`)) return;
     try {
-      await this._crewService.join(mission.id);
-      this._toast.success('Joined the room!');
+      const pending = await this._crewService.join(mission.id);
       this.showPreview = false;
+      if (pending) {
+        this._toast.info('Join request sent to the chief');
+        return;
+      }
+      this._toast.success('Joined the room!');
       this._router.navigate(['/missions', mission.id]);
     } catch (e: any) {
-      this._toast.error('Failed to join: ' + (e.error || e.message));
+      this._toast.error('Failed to join: ' + (getErrorMessage(e) || e.message));
+    }
+  }
+
+  async onJoinWaitlist(mission: Mission) {
+    try {
+      const waitlist = await this._crewService.joinWaitlist(mission.id);
+      this._toast.success(`You're #${waitlist.position} on the waitlist`);
+      this.showPreview = false;
+    } catch (e: any) {
+      this._toast.error('Failed to join waitlist: ' + (getErrorMessage(e) || e.message));
     }
   }
 
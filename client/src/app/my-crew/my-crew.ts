@@ -8,6 +8,7 @@ import { MissionService } from '../_services/mission-service';
 import { BehaviorSubject, Subscription } from 'rxjs';
 import { ToastService } from '../_services/toast-service';
 import { WebsocketService } from '../_services/websocket-service';
+import { getErrorMessage } from '../_helpers/util';
 
 @Component({
   selector: 'app-my-crew',
@@ -80,7 +81,7 @@ export class MyCrew implements OnDestroy {
       this.loadMyJoinedMissions();
     } catch (e: any) {
       console.error('Failed to leave mission', e);
-      this._toast.error('Failed to leave mission: ' + (e.error || e.message));
+      this._toast.error('Failed to leave mission: ' + (getErrorMessage(e) || e.message));
     }
   }
 
@@ -55,7 +55,7 @@ export class Navbar {
 
   logout() {
     if (confirm('Do you want to disconnect from the network?')) {
-      this._passport.destroy();
+      this._passport.logout();
       this._router.navigate(['/login']);
     }
   }
@@ -0,0 +1,38 @@
+import { Component, inject, OnInit, signal } from '@angular/core';
+import { ActivatedRoute, Router, RouterLink } from '@angular/router';
+import { PassportService } from '../_services/passport-service';
+
+/** Where providers send the browser back after signing in (`?code=&state=`) */
+@Component({
+  selector: 'app-oauth-callback',
+  standalone: true,
+  imports: [RouterLink],
+  templateUrl: './oauth-callback.html',
+  styleUrl: '../login/login.scss',
+})
+export class OAuthCallback implements OnInit {
+  private _route = inject(ActivatedRoute);
+  private _router = inject(Router);
+  private _passport = inject(PassportService);
+
+  serverError = signal('');
+
+  async ngOnInit() {
+    const query = this._route.snapshot.queryParamMap;
+    const code = query.get('code');
+    const state = query.get('state');
+    if (!code || !state) {
+      // The provider sends `error` when the user said no
+      const error = query.get('error_description') ?? query.get('error');
+      this.serverError.set(error ?? 'Sign-in was cancelled');
+      return;
+    }
+
+    const linking = this._passport.isSignin();
+    const errMsg = await this._passport.completeOAuth(code, state);
+    if (errMsg) this.serverError.set(errMsg);
+    // With two-factor authentication on, the login page asks for the code
+    else if (this._passport.mfaChallenge()) this._router.navigate(['/login']);
+    else this._router.navigate([linking ? '/profile' : '/']);
+  }
+}
@@ -0,0 +1,53 @@
+import { Component, inject, signal } from '@angular/core';
+import { FormControl, FormGroup, ReactiveFormsModule, Validators } from '@angular/forms';
+import { ActivatedRoute, Router, RouterLink } from '@angular/router';
+import { ButtonModule } from 'primeng/button';
+import { passwordMatchValidator } from '../_helpers/password-validator';
+import { PassportService } from '../_services/passport-service';
+
+/** Asks for a reset mail, or sets the new password when opened from one (`?token=`) */
+@Component({
+  selector: 'app-reset-password',
+  standalone: true,
+  imports: [ReactiveFormsModule, ButtonModule, RouterLink],
+  templateUrl: './reset-password.html',
+  styleUrl: '../login/login.scss',
+})
+export class ResetPassword {
+  private _route = inject(ActivatedRoute);
+  private _router = inject(Router);
+  private _passport = inject(PassportService);
+
+  readonly passwordMinLength = 8;
+  token = this._route.snapshot.queryParamMap.get('token');
+  sent = signal(false);
+  serverError = signal('');
+
+  requestForm = new FormGroup({
+    username: new FormControl('', [Validators.required]),
+  });
+  resetForm = new FormGroup(
+    {
+      password: new FormControl('', [
+        Validators.required,
+        Validators.minLength(this.passwordMinLength),
+      ]),
+      cf_password: new FormControl('', [Validators.required]),
+    },
+    { validators: passwordMatchValidator('password', 'cf_password') },
+  );
+
+  async onRequest() {
+    this.serverError.set('');
+    const errMsg = await this._passport.requestPasswordReset(this.requestForm.value.username!);
+    if (errMsg) this.serverError.set(errMsg);
+    else this.sent.set(true);
+  }
+
+  async onReset() {
+    this.serverError.set('');
+    const errMsg = await this._passport.resetPassword(this.token!, this.resetForm.value.password!);
+    if (errMsg) this.serverError.set(errMsg);
+    else this._router.navigate(['/login']);
+  }
+}
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+
+use crate::{
+    domain::{
+        entities::access_tokens::AddAccessTokenEntity,
+        errors::{AppError, AppResult},
+        repositories::access_tokens::AccessTokenRepository,
+        value_objects::access_token_model::{
+            ACCESS_TOKEN_PREFIX, AccessTokenModel, CreateAccessTokenModel, CreatedAccessTokenModel,
+        },
+    },
+    infrastructure::secure_token,
+};
+
+pub const MAX_NAME_LENGTH: usize = 64;
+pub const MAX_EXPIRY_DAYS: i64 = 365;
+/// Active tokens one brawler can have at a time
+pub const MAX_ACTIVE_TOKENS: usize = 20;
+
+/// Personal access tokens for scripts and bots. Checking them on requests is
+/// `auth`'s job.
+pub struct AccessTokenUseCase {
+    access_tokens: Arc<dyn AccessTokenRepository>,
+}
+
+impl AccessTokenUseCase {
+    pub fn new(access_tokens: Arc<dyn AccessTokenRepository>) -> Self {
+        Self { access_tokens }
+    }
+
+    pub async fn create(
+        &self,
+        brawler_id: i32,
+        model: CreateAccessTokenModel,
+    ) -> AppResult<CreatedAccessTokenModel> {
+        let name = model.name.trim().to_string();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+            return Err(AppError::Validation(format!(
+                "Token names must be 1 to {} characters",
+                MAX_NAME_LENGTH
+            )));
+        }
+        let mut scopes = Vec::new();
+        for scope in model.scopes {
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+        if scopes.is_empty() {
+            return Err(AppError::Validation(
+                "A token needs at least one scope".to_string(),
+            ));
+        }
+        let expires_at = match model.expires_in_days {
+            Some(days) if !(1..=MAX_EXPIRY_DAYS).contains(&days) => {
+                return Err(AppError::Validation(format!(
+                    "Tokens expire after 1 to {} days",
+                    MAX_EXPIRY_DAYS
+                )));
+            }
+            Some(days) => Some((Utc::now() + Duration::days(days)).naive_utc()),
+            None => None,
+        };
+        if self.access_tokens.list_active(brawler_id).await?.len() >= MAX_ACTIVE_TOKENS {
+            return Err(AppError::Conflict(format!(
+                "You already have {} active tokens; revoke one first",
+                MAX_ACTIVE_TOKENS
+            )));
+        }
+
+        let token = format!("{}{}", ACCESS_TOKEN_PREFIX, secure_token::generate()?);
+        let created = self
+            .access_tokens
+            .create(AddAccessTokenEntity {
+                brawler_id,
+                name,
+                token_hash: secure_token::hash(&token),
+                scopes: scopes.iter().map(ToString::to_string).collect(),
+                expires_at,
+            })
+            .await?;
+        Ok(CreatedAccessTokenModel {
+            token,
+            access_token: AccessTokenModel::from(created),
+        })
+    }
+
+    pub async fn list(&self, brawler_id: i32) -> AppResult<Vec<AccessTokenModel>> {
+        let tokens = self.access_tokens.list_active(brawler_id).await?;
+        Ok(tokens.into_iter().map(AccessTokenModel::from).collect())
+    }
+
+    /// Takes effect on the token's next request
+    pub async fn revoke(&self, brawler_id: i32, token_id: i32) -> AppResult<()> {
+        self.access_tokens.revoke(brawler_id, token_id).await?;
+        Ok(())
+    }
+}
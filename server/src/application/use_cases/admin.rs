@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use crate::{
+    domain::{
+        errors::{AppError, AppResult},
+        repositories::{brawlers::BrawlerRepository, sessions::SessionRepository},
+        value_objects::brawler_role::{BrawlerRole, BrawlerRoleModel, RoleFilter, SetRoleModel},
+    },
+    infrastructure::websocket::manager::ConnectionManager,
+};
+
+pub struct AdminUseCase<T>
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    brawler_repository: Arc<T>,
+    sessions: Arc<dyn SessionRepository>,
+    manager: Arc<ConnectionManager>,
+}
+
+impl<T> AdminUseCase<T>
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    pub fn new(
+        brawler_repository: Arc<T>,
+        sessions: Arc<dyn SessionRepository>,
+        manager: Arc<ConnectionManager>,
+    ) -> Self {
+        Self {
+            brawler_repository,
+            sessions,
+            manager,
+        }
+    }
+
+    /// Brawlers with the given role, or every moderator and admin
+    pub async fn list(&self, filter: RoleFilter) -> AppResult<Vec<BrawlerRoleModel>> {
+        let roles = match filter.role {
+            Some(role) => vec![role],
+            None => vec![BrawlerRole::Moderator, BrawlerRole::Admin],
+        };
+        let brawlers = self.brawler_repository.find_by_roles(roles).await?;
+        Ok(brawlers.into_iter().map(BrawlerRoleModel::from).collect())
+    }
+
+    /// Promotes or demotes `brawler_id`. A promotion reaches their token on its
+    /// next refresh. A demotion signs them out everywhere, so the old role in
+    /// their tokens can't be used any longer.
+    pub async fn set_role(
+        &self,
+        admin_id: i32,
+        brawler_id: i32,
+        model: SetRoleModel,
+    ) -> AppResult<BrawlerRoleModel> {
+        // Also keeps the last admin from demoting themselves
+        if admin_id == brawler_id {
+            return Err(AppError::Forbidden(
+                "You can't change your own role".to_string(),
+            ));
+        }
+
+        let mut brawler = self.brawler_repository.find_by_id(brawler_id).await?;
+        let previous = brawler.role();
+        if previous == model.role {
+            return Ok(BrawlerRoleModel::from(brawler));
+        }
+
+        self.brawler_repository
+            .set_role(brawler_id, model.role)
+            .await?;
+        tracing::info!(
+            "Admin {} changed brawler {}'s role from {} to {}",
+            admin_id,
+            brawler_id,
+            previous,
+            model.role
+        );
+
+        if model.role < previous {
+            let session_ids = self.sessions.revoke_all_for_brawler(brawler_id).await?;
+            for session_id in session_ids {
+                self.manager.revoke_session(session_id).await;
+            }
+        }
+
+        brawler.role = model.role.to_string();
+        Ok(BrawlerRoleModel::from(brawler))
+    }
+}
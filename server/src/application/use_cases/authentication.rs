@@ -1,49 +1,322 @@
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 
-use anyhow::Result;
+use chrono::{Duration, Utc};
 
 use crate::{
-    domain::repositories::brawlers::BrawlerRepository,
+    application::use_cases::{login_throttle::LoginThrottle, mfa::redeem_code},
+    config::config_loader::{get_jwt_env, get_mfa_env},
+    domain::{
+        entities::{
+            brawlers::BrawlerEntity,
+            sessions::{AddSessionEntity, RefreshRotation},
+        },
+        errors::{AppError, AppResult},
+        repositories::{
+            brawlers::BrawlerRepository, sessions::SessionRepository, totp::TotpRepository,
+        },
+        value_objects::{
+            mfa_model::{MfaChallengeModel, MfaLoginModel},
+            session_model::{SessionClient, SessionModel},
+        },
+    },
     infrastructure::{
         argon2,
-        jwt::{authentication_model::LoginModel, jwt_model::Passport},
+        jwt::{
+            authentication_model::{LoginModel, RefreshTokenModel},
+            jwt_model::{MfaPendingClaims, Passport},
+            verify_mfa_pending_token,
+        },
+        secure_token,
+        websocket::manager::ConnectionManager,
     },
 };
+
+/// Starts a new login session for `passport`'s brawler and signs its first
+/// access and refresh tokens
+pub async fn start_session(
+    sessions: &dyn SessionRepository,
+    client: SessionClient,
+    passport: Passport,
+) -> AppResult<Passport> {
+    let token = secure_token::generate()?;
+    let session_id = sessions
+        .start(
+            AddSessionEntity {
+                brawler_id: passport.id,
+                user_agent: client.user_agent,
+                ip: client.ip,
+            },
+            secure_token::hash(&token),
+            refresh_expiry()?,
+        )
+        .await?;
+
+    let mut passport = passport.with_session(session_id)?;
+    passport.refresh_token = Some(token);
+    Ok(passport)
+}
+
+fn refresh_expiry() -> AppResult<chrono::NaiveDateTime> {
+    let jwt_env = get_jwt_env()?;
+    Ok((Utc::now() + Duration::days(jwt_env.ttl)).naive_utc())
+}
+
+fn invalid_credentials() -> AppError {
+    AppError::Unauthorized("Invalid credentials".to_string())
+}
+
+/// Checked against when the username doesn't exist, so that answer takes as long
+/// as a wrong password
+static DUMMY_PASSWORD_HASH: LazyLock<String> =
+    LazyLock::new(|| argon2::hash("not-a-password".to_string()).unwrap_or_default());
+
+fn invalid_refresh_token() -> AppError {
+    AppError::Unauthorized("Invalid or expired refresh token".to_string())
+}
+
+fn invalid_mfa_login() -> AppError {
+    AppError::Unauthorized("Invalid or expired two-factor login".to_string())
+}
+
+pub fn passport_of(user: BrawlerEntity) -> Passport {
+    let role = user.role();
+    Passport::new(
+        user.id,
+        user.display_name,
+        user.avatar_url,
+        user.bio,
+        user.discord_id,
+        user.contact_email,
+        user.instagram,
+        user.facebook,
+        role,
+    )
+}
+
+/// The challenge a login has to answer before it gets a passport, when the
+/// brawler has two-factor authentication on
+pub async fn mfa_challenge(
+    totp_repository: &dyn TotpRepository,
+    brawler_id: i32,
+) -> AppResult<Option<MfaChallengeModel>> {
+    let enabled = totp_repository
+        .find(brawler_id)
+        .await?
+        .is_some_and(|enrollment| enrollment.is_enabled());
+    if !enabled {
+        return Ok(None);
+    }
+    let ttl_minutes = get_mfa_env()?.pending_ttl_minutes;
+    Ok(Some(MfaChallengeModel {
+        mfa_required: true,
+        mfa_token: MfaPendingClaims::sign(brawler_id, ttl_minutes)?,
+        expires_in: ttl_minutes * 60,
+    }))
+}
+
+/// What a correct password, or a sign-in through a provider, gets: a signed-in
+/// passport, or with two-factor authentication on, a challenge to answer at
+/// `/mfa/verify`
+pub enum LoginOutcome {
+    Passport(Passport),
+    MfaRequired(MfaChallengeModel),
+}
+
 pub struct AuthenticationUseCase<T>
 where
     T: BrawlerRepository + Send + Sync,
 {
     brawler_repository: Arc<T>,
+    sessions: Arc<dyn SessionRepository>,
+    manager: Arc<ConnectionManager>,
+    throttle: LoginThrottle,
+    totp_repository: Arc<dyn TotpRepository>,
 }
 impl<T> AuthenticationUseCase<T>
 where
     T: BrawlerRepository + Sync + Send,
 {
-    pub fn new(brawler_repository: Arc<T>) -> Self {
-        Self { brawler_repository }
+    pub fn new(
+        brawler_repository: Arc<T>,
+        sessions: Arc<dyn SessionRepository>,
+        manager: Arc<ConnectionManager>,
+        throttle: LoginThrottle,
+        totp_repository: Arc<dyn TotpRepository>,
+    ) -> Self {
+        Self {
+            brawler_repository,
+            sessions,
+            manager,
+            throttle,
+            totp_repository,
+        }
     }
 
-    pub async fn login(&self, login_model: LoginModel) -> Result<Passport> {
-        let username = login_model.username.clone();
+    pub async fn login(
+        &self,
+        login_model: LoginModel,
+        client: SessionClient,
+    ) -> AppResult<LoginOutcome> {
+        let username = login_model.username;
+        let ip = client.ip.clone();
+        self.throttle.check(&username, ip.as_deref()).await?;
+
+        let user = match self
+            .brawler_repository
+            .find_by_username(username.clone())
+            .await
+        {
+            Ok(user) => Some(user),
+            Err(e) => match AppError::from(e) {
+                AppError::NotFound(_) => None,
+                other => return Err(other),
+            },
+        };
 
-        //find this user in database
-        let user = self.brawler_repository.find_by_username(username).await?;
-        let hashed_password = user.password;
+        // Same answer, and the same argon2 work, whether the username or the
+        // password is wrong
+        let hashed_password = user
+            .as_ref()
+            .map_or_else(|| DUMMY_PASSWORD_HASH.clone(), |user| user.password.clone());
+        let verified = argon2::verify(login_model.password, hashed_password).unwrap_or(false);
+        let user = match user {
+            Some(user) if verified => user,
+            user => {
+                self.throttle
+                    .record_failure(&username, ip.as_deref(), user.map(|user| user.id))
+                    .await?;
+                return Err(invalid_credentials());
+            }
+        };
 
-        if !argon2::verify(login_model.password, hashed_password)? {
-            return Err(anyhow::anyhow!("Invalid Password !!"));
+        if let Some(challenge) = mfa_challenge(self.totp_repository.as_ref(), user.id).await? {
+            // The username's failures stay counted until the code is right too,
+            // so the password can't be used to reset them between code guesses
+            return Ok(LoginOutcome::MfaRequired(challenge));
         }
+        self.throttle.record_success(&username).await?;
+
+        let passport = start_session(self.sessions.as_ref(), client, passport_of(user)).await?;
+        Ok(LoginOutcome::Passport(passport))
+    }
+
+    /// Second step of a login with two-factor authentication: an authenticator
+    /// or recovery code for the pending token `login` handed out. Wrong codes
+    /// count against the same limits as wrong passwords.
+    pub async fn verify_mfa(
+        &self,
+        model: MfaLoginModel,
+        client: SessionClient,
+    ) -> AppResult<Passport> {
+        let claims = verify_mfa_pending_token(get_jwt_env()?.secret, model.mfa_token)
+            .map_err(|_| invalid_mfa_login())?;
+        let brawler_id: i32 = claims.sub.parse().map_err(|_| invalid_mfa_login())?;
+        let user = self.brawler_repository.find_by_id(brawler_id).await?;
+        let ip = client.ip.clone();
+        self.throttle.check(&user.username, ip.as_deref()).await?;
+
+        // 2FA may have been turned off since the password was checked
+        let enrollment = self
+            .totp_repository
+            .find(brawler_id)
+            .await?
+            .ok_or_else(invalid_mfa_login)?;
+        if !redeem_code(self.totp_repository.as_ref(), &enrollment, &model.code).await? {
+            self.throttle
+                .record_failure(&user.username, ip.as_deref(), Some(brawler_id))
+                .await?;
+            return Err(AppError::Unauthorized(
+                "Invalid two-factor code".to_string(),
+            ));
+        }
+        self.throttle.record_success(&user.username).await?;
+
+        start_session(self.sessions.as_ref(), client, passport_of(user)).await
+    }
+
+    /// Trades a refresh token for a new access token and the next refresh token.
+    /// Presenting a token that was already traded revokes that whole session.
+    pub async fn refresh(
+        &self,
+        model: RefreshTokenModel,
+        client: SessionClient,
+    ) -> AppResult<Passport> {
+        let next_token = secure_token::generate()?;
+        let rotation = self
+            .sessions
+            .rotate(
+                secure_token::hash(&model.refresh_token),
+                secure_token::hash(&next_token),
+                refresh_expiry()?,
+                client,
+            )
+            .await?;
+
+        let (brawler_id, session_id) = match rotation {
+            RefreshRotation::Rotated {
+                brawler_id,
+                session_id,
+            } => (brawler_id, session_id),
+            RefreshRotation::Reused {
+                brawler_id,
+                session_id,
+            } => {
+                tracing::warn!(
+                    "Refresh token reused for brawler {}; revoked session {}",
+                    brawler_id,
+                    session_id
+                );
+                self.manager.revoke_session(session_id).await;
+                return Err(invalid_refresh_token());
+            }
+            RefreshRotation::Rejected => return Err(invalid_refresh_token()),
+        };
 
-        let passport = Passport::new(
-            user.id,
-            user.display_name,
-            user.avatar_url,
-            user.bio,
-            user.discord_id,
-            user.contact_email,
-            user.instagram,
-            user.facebook,
-        )?;
+        let user = self.brawler_repository.find_by_id(brawler_id).await?;
+        let mut passport = passport_of(user).with_session(session_id)?;
+        passport.refresh_token = Some(next_token);
         Ok(passport)
     }
+
+    /// Ends the session the refresh token belongs to. Unknown tokens are ignored,
+    /// so logging out twice is fine.
+    pub async fn logout(&self, model: RefreshTokenModel) -> AppResult<()> {
+        if let Some(session_id) = self
+            .sessions
+            .revoke_by_token(secure_token::hash(&model.refresh_token))
+            .await?
+        {
+            self.manager.revoke_session(session_id).await;
+        }
+        Ok(())
+    }
+
+    /// Ends every session of the brawler, on every device
+    pub async fn logout_all(&self, brawler_id: i32) -> AppResult<()> {
+        let session_ids = self.sessions.revoke_all_for_brawler(brawler_id).await?;
+        for session_id in session_ids {
+            self.manager.revoke_session(session_id).await;
+        }
+        Ok(())
+    }
+
+    /// The brawler's signed-in devices, most recently used first
+    pub async fn list_sessions(
+        &self,
+        brawler_id: i32,
+        current_session_id: i32,
+    ) -> AppResult<Vec<SessionModel>> {
+        let sessions = self.sessions.list_active(brawler_id).await?;
+        Ok(sessions
+            .into_iter()
+            .map(|session| SessionModel::from_entity(session, current_session_id))
+            .collect())
+    }
+
+    /// Signs one device out: its tokens stop working and its sockets close
+    pub async fn revoke_session(&self, brawler_id: i32, session_id: i32) -> AppResult<()> {
+        self.sessions.revoke(brawler_id, session_id).await?;
+        self.manager.revoke_session(session_id).await;
+        Ok(())
+    }
 }
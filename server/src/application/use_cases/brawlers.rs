@@ -1,16 +1,24 @@
 use crate::{
+    application::use_cases::authentication::start_session,
     domain::{
-        repositories::brawlers::BrawlerRepository,
+        errors::{AppError, AppResult},
+        repositories::{brawlers::BrawlerRepository, sessions::SessionRepository},
         value_objects::{
             base64_img::Base64Img,
             brawler_model::{RegisterBrawlerModel, UpdateBrawlerModel},
             mission_model::MissionModel,
+            password_model::{ChangePasswordModel, validate_new_password},
+            session_model::SessionClient,
             uploaded_img::UploadedImg,
         },
     },
-    infrastructure::{argon2::hash, cloudinary::UploadImageOptions, jwt::jwt_model::Passport},
+    infrastructure::{
+        argon2::{self, hash},
+        cloudinary::UploadImageOptions,
+        jwt::jwt_model::Passport,
+        websocket::manager::ConnectionManager,
+    },
 };
-use anyhow::Result;
 use std::sync::Arc;
 
 pub struct BrawlersUseCase<T>
@@ -18,42 +26,64 @@ where
     T: BrawlerRepository + Send + Sync,
 {
     brawler_repository: Arc<T>,
+    sessions: Arc<dyn SessionRepository>,
+    manager: Arc<ConnectionManager>,
 }
 
 impl<T> BrawlersUseCase<T>
 where
     T: BrawlerRepository + Send + Sync,
 {
-    pub fn new(brawler_repository: Arc<T>) -> Self {
-        Self { brawler_repository }
+    pub fn new(
+        brawler_repository: Arc<T>,
+        sessions: Arc<dyn SessionRepository>,
+        manager: Arc<ConnectionManager>,
+    ) -> Self {
+        Self {
+            brawler_repository,
+            sessions,
+            manager,
+        }
     }
     pub async fn register(
         &self,
         mut register_brawler_model: RegisterBrawlerModel,
-    ) -> Result<Passport> {
+        client: SessionClient,
+    ) -> AppResult<Passport> {
         let hashed_password = hash(register_brawler_model.password.clone())?;
 
         register_brawler_model.password = hashed_password;
 
         let register_entity = register_brawler_model.to_entity();
 
-        let passport = self.brawler_repository.register(register_entity).await?;
+        let passport = self
+            .brawler_repository
+            .register(register_entity)
+            .await
+            .map_err(|e| match AppError::from(e) {
+                AppError::Conflict(_) => {
+                    AppError::Conflict("Username is already taken".to_string())
+                }
+                other => other,
+            })?;
 
-        Ok(passport)
+        // Signing up logs the brawler in
+        start_session(self.sessions.as_ref(), client, passport).await
     }
 
     pub async fn upload_base64img(
         &self,
         user_id: i32,
         base64string: String,
-    ) -> Result<UploadedImg> {
+    ) -> AppResult<UploadedImg> {
         let opt = UploadImageOptions {
             folder: Some("avatar".to_string()),
             public_id: Some(user_id.to_string()),
             transformation: Some("c_scale,w_256".to_string()),
         };
 
-        let base64img = Base64Img::new(base64string)?;
+        let base64img =
+            Base64Img::new(base64string).map_err(|e| AppError::Validation(e.to_string()))?;
 
         let uploaded = self
             .brawler_repository
@@ -63,24 +93,59 @@ where
         Ok(uploaded)
     }
 
-    pub async fn get_my_missions(&self, brawler_id: i32) -> Result<Vec<MissionModel>> {
-        self.brawler_repository.get_missions(brawler_id).await
+    pub async fn get_my_missions(&self, brawler_id: i32) -> AppResult<Vec<MissionModel>> {
+        Ok(self.brawler_repository.get_missions(brawler_id).await?)
     }
 
     pub async fn get_brawler_by_id(
         &self,
         brawler_id: i32,
-    ) -> Result<crate::domain::entities::brawlers::BrawlerEntity> {
-        self.brawler_repository.find_by_id(brawler_id).await
+    ) -> AppResult<crate::domain::entities::brawlers::BrawlerEntity> {
+        Ok(self.brawler_repository.find_by_id(brawler_id).await?)
     }
 
+    /// Returns the updated passport, re-signed for the caller's session
     pub async fn update_profile(
         &self,
         brawler_id: i32,
+        session_id: i32,
         model: UpdateBrawlerModel,
-    ) -> Result<Passport> {
-        self.brawler_repository
+    ) -> AppResult<Passport> {
+        let passport = self
+            .brawler_repository
             .update_profile(brawler_id, model)
-            .await
+            .await?;
+        Ok(passport.with_session(session_id)?)
+    }
+
+    /// Replaces the password after checking the current one. Other devices are
+    /// signed out; the session making the change stays.
+    pub async fn change_password(
+        &self,
+        brawler_id: i32,
+        session_id: i32,
+        model: ChangePasswordModel,
+    ) -> AppResult<()> {
+        let brawler = self.brawler_repository.find_by_id(brawler_id).await?;
+        // Not 401: the caller is signed in, and the client would try to refresh
+        if !argon2::verify(model.current_password, brawler.password)? {
+            return Err(AppError::Forbidden(
+                "Current password is incorrect".to_string(),
+            ));
+        }
+        validate_new_password(&model.new_password)?;
+
+        self.brawler_repository
+            .update_password(brawler_id, hash(model.new_password)?)
+            .await?;
+
+        let session_ids = self
+            .sessions
+            .revoke_all_except(brawler_id, session_id)
+            .await?;
+        for session_id in session_ids {
+            self.manager.revoke_session(session_id).await;
+        }
+        Ok(())
     }
 }
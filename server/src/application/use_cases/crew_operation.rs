@@ -1,90 +1,126 @@
-use crate::domain::{
-    entities::crew_memberships::CrewMemberShips,
-    repositories::{
-        crew_operation::CrewOperationRepository, mission_viewing::MissionViewingRepository,
+use crate::{
+    application::use_cases::mission_waitlist::promote_next,
+    domain::{
+        entities::crew_memberships::CrewMemberShips,
+        errors::{AppError, AppResult},
+        repositories::{
+            crew_operation::CrewOperationRepository, mission_viewing::MissionViewingRepository,
+            transaction_provider::TransactionProvider,
+        },
+        value_objects::{
+            join_policy::JoinPolicy, mission_application_model::JoinOutcome,
+            mission_model::MissionModel, mission_statuses::MissionStatuses,
+        },
     },
-    value_objects::{mission_model::MissionModel, mission_statuses::MissionStatuses},
 };
-use anyhow::Result;
 use std::sync::Arc;
 
-pub struct CrewOperationUseCase<T1, T2>
+pub struct CrewOperationUseCase<T1, T2, T3>
 where
     T1: CrewOperationRepository + Send + Sync,
     T2: MissionViewingRepository + Send + Sync,
+    T3: TransactionProvider + Send + Sync,
 {
     crew_operation_repository: Arc<T1>,
     mission_viewing_repository: Arc<T2>,
+    transaction_provider: Arc<T3>,
 }
 
-impl<T1, T2> CrewOperationUseCase<T1, T2>
+impl<T1, T2, T3> CrewOperationUseCase<T1, T2, T3>
 where
     T1: CrewOperationRepository + Send + Sync + 'static,
     T2: MissionViewingRepository + Send + Sync,
+    T3: TransactionProvider + Send + Sync,
 {
-    pub fn new(crew_operation_repository: Arc<T1>, mission_viewing_repository: Arc<T2>) -> Self {
+    pub fn new(
+        crew_operation_repository: Arc<T1>,
+        mission_viewing_repository: Arc<T2>,
+        transaction_provider: Arc<T3>,
+    ) -> Self {
         Self {
             crew_operation_repository,
             mission_viewing_repository,
+            transaction_provider,
         }
     }
 
-    pub async fn join(&self, mission_id: i32, brawler_id: i32) -> Result<()> {
+    pub async fn join(&self, mission_id: i32, brawler_id: i32) -> AppResult<JoinOutcome> {
         let mission = self.mission_viewing_repository.get_one(mission_id).await?;
 
         if mission.chief_id == brawler_id {
-            return Err(anyhow::anyhow!(
-                "The Chief can not join in his own mission as a crew member!!"
+            return Err(AppError::Forbidden(
+                "The Chief can not join in his own mission as a crew member!!".to_string(),
             ));
         }
 
-        let crew_count = self
-            .mission_viewing_repository
-            .crew_counting(mission_id)
-            .await?;
+        let crew_member_ships = CrewMemberShips {
+            mission_id,
+            brawler_id,
+        };
 
-        let mission_status_condition = mission.status == MissionStatuses::Open.to_string();
-        if !mission_status_condition {
-            return Err(anyhow::anyhow!("Mission is not joinable in current status"));
+        if mission.is_private || mission.join_policy == JoinPolicy::InviteOnly.to_string() {
+            return Err(AppError::Forbidden(
+                "This mission is invite only".to_string(),
+            ));
         }
-        let crew_count_condition = (crew_count as i32) < mission.max_crew;
-        if !crew_count_condition {
-            return Err(anyhow::anyhow!("Mission is full"));
+
+        if mission.join_policy == JoinPolicy::ApprovalRequired.to_string() {
+            let application_id = self
+                .crew_operation_repository
+                .apply(crew_member_ships)
+                .await?;
+            return Ok(JoinOutcome::Applied(application_id));
         }
 
+        // Status and capacity are checked by the repository while it holds a lock
+        // on the mission row, so two brawlers can't both take the last slot.
         self.crew_operation_repository
-            .join(CrewMemberShips {
-                mission_id,
-                brawler_id,
-            })
+            .join(crew_member_ships)
             .await?;
 
-        Ok(())
+        Ok(JoinOutcome::Joined)
     }
 
-    pub async fn leave(&self, mission_id: i32, brawler_id: i32) -> Result<()> {
+    /// Returns the brawler promoted from the waitlist into the freed slot, if any.
+    pub async fn leave(&self, mission_id: i32, brawler_id: i32) -> AppResult<Option<i32>> {
         let mission = self.mission_viewing_repository.get_one(mission_id).await?;
 
         let leaving_condition = mission.status == MissionStatuses::Open.to_string()
             || mission.status == MissionStatuses::Failed.to_string()
             || mission.deleted_at.is_some(); // Allow leaving if mission is deleted
         if !leaving_condition {
-            return Err(anyhow::anyhow!("Mission is not leavable at this state"));
+            return Err(AppError::Conflict(
+                "Mission is not leavable at this state".to_string(),
+            ));
         }
-        self.crew_operation_repository
-            .leave(CrewMemberShips {
-                mission_id,
-                brawler_id,
-            })
-            .await?;
 
-        Ok(())
+        // One transaction, so the freed slot goes to the waitlist before anyone
+        // can join directly
+        let is_deleted = mission.deleted_at.is_some();
+        let chief_id = mission.chief_id;
+        let mission_name = mission.name;
+        self.transaction_provider
+            .transaction(move |uow| {
+                uow.remove_crew_member(mission_id, brawler_id)?;
+                if !is_deleted {
+                    return promote_next(uow, mission_id, &mission_name);
+                }
+
+                // The last one out of a deleted mission cleans it up for good
+                if uow.crew_ids(mission_id)?.is_empty() {
+                    uow.delete_mission_comments(mission_id)?;
+                    uow.delete_mission(mission_id, chief_id)?;
+                }
+                Ok(None)
+            })
+            .await
     }
 
     /// ดึงรายการภารกิจที่ผู้ใช้เข้าร่วมอยู่ (เป็น crew member)
-    pub async fn get_my_joined_missions(&self, brawler_id: i32) -> Result<Vec<MissionModel>> {
-        self.crew_operation_repository
+    pub async fn get_my_joined_missions(&self, brawler_id: i32) -> AppResult<Vec<MissionModel>> {
+        Ok(self
+            .crew_operation_repository
             .get_my_joined_missions(brawler_id)
-            .await
+            .await?)
     }
 }
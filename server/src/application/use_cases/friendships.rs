@@ -1,12 +1,11 @@
 use crate::application::use_cases::notifications::NotificationUseCase;
 use crate::domain::{
     entities::friendships::{FriendshipEntity, NewFriendshipEntity, PendingRequestDto},
+    errors::{AppError, AppResult},
     repositories::{brawlers::BrawlerRepository, friendship_repository::FriendshipRepository},
 };
-use crate::infrastructure::websocket::handler::WSMessage;
 use crate::infrastructure::websocket::manager::ConnectionManager;
-use anyhow::{Result, anyhow};
-use serde_json::json;
+use crate::infrastructure::websocket::protocol::{FriendNotification, ServerEvent};
 use std::sync::Arc;
 
 pub struct FriendshipUseCase {
@@ -35,18 +34,22 @@ impl FriendshipUseCase {
         &self,
         requester_id: i32,
         receiver_id: i32,
-    ) -> Result<FriendshipEntity> {
+    ) -> AppResult<FriendshipEntity> {
         if requester_id == receiver_id {
-            return Err(anyhow!("Cannot add yourself as friend"));
+            return Err(AppError::Validation(
+                "Cannot add yourself as friend".to_string(),
+            ));
         }
 
-        if let Some(_) = self
+        if self
             .repo
             .find_by_users(requester_id, receiver_id)
-            .await
-            .map_err(|e| anyhow!(e))?
+            .await?
+            .is_some()
         {
-            return Err(anyhow!("Friendship already exists or pending"));
+            return Err(AppError::Conflict(
+                "Friendship already exists or pending".to_string(),
+            ));
         }
 
         // Fetch requester name
@@ -61,11 +64,7 @@ impl FriendshipUseCase {
             status: "pending".to_string(),
         };
 
-        let entity = self
-            .repo
-            .create(new_friendship)
-            .await
-            .map_err(|e| anyhow!(e))?;
+        let entity = self.repo.create(new_friendship).await?;
 
         // Notify receiver
         let content = format!("User {} sent you a friend request", requester_name);
@@ -77,38 +76,35 @@ impl FriendshipUseCase {
         self.ws_manager
             .notify_user(
                 receiver_id,
-                WSMessage {
-                    msg_type: "notification".to_string(),
-                    data: json!({
-                        "type": "friend_request",
-                        "requester_id": requester_id,
-                        "requester_name": requester_name,
-                        "content": content
-                    }),
-                },
+                ServerEvent::Notification(FriendNotification::FriendRequest {
+                    requester_id,
+                    requester_name,
+                    content,
+                }),
             )
             .await;
 
         Ok(entity)
     }
 
-    pub async fn accept_request(&self, user_id: i32, request_id: i32) -> Result<FriendshipEntity> {
+    pub async fn accept_request(
+        &self,
+        user_id: i32,
+        request_id: i32,
+    ) -> AppResult<FriendshipEntity> {
         let friendship = self
             .repo
             .find_by_id(request_id)
-            .await
-            .map_err(|e| anyhow!(e))?
-            .ok_or_else(|| anyhow!("Friend request not found"))?;
+            .await?
+            .ok_or_else(|| AppError::NotFound("Friend request not found".to_string()))?;
 
         if friendship.receiver_id != user_id {
-            return Err(anyhow!("You are not the receiver of this request"));
+            return Err(AppError::Forbidden(
+                "You are not the receiver of this request".to_string(),
+            ));
         }
 
-        let updated = self
-            .repo
-            .update_status(request_id, "accepted")
-            .await
-            .map_err(|e| anyhow!(e))?;
+        let updated = self.repo.update_status(request_id, "accepted").await?;
 
         // Fetch current user name
         let user_name = match self.brawler_repo.find_by_id(user_id).await {
@@ -131,77 +127,60 @@ impl FriendshipUseCase {
         self.ws_manager
             .notify_user(
                 friendship.requester_id,
-                WSMessage {
-                    msg_type: "notification".to_string(),
-                    data: json!({
-                        "type": "friend_accepted",
-                        "friend_id": user_id,
-                        "content": content
-                    }),
-                },
+                ServerEvent::Notification(FriendNotification::FriendAccepted {
+                    friend_id: user_id,
+                    content,
+                }),
             )
             .await;
 
         Ok(updated)
     }
 
-    pub async fn reject_request(&self, user_id: i32, request_id: i32) -> Result<()> {
+    pub async fn reject_request(&self, user_id: i32, request_id: i32) -> AppResult<()> {
         let friendship = self
             .repo
             .find_by_id(request_id)
-            .await
-            .map_err(|e| anyhow!(e))?
-            .ok_or_else(|| anyhow!("Friend request not found"))?;
+            .await?
+            .ok_or_else(|| AppError::NotFound("Friend request not found".to_string()))?;
 
         if friendship.receiver_id != user_id {
-            return Err(anyhow!("You are not the receiver of this request"));
+            return Err(AppError::Forbidden(
+                "You are not the receiver of this request".to_string(),
+            ));
         }
 
-        self.repo.delete(request_id).await.map_err(|e| anyhow!(e))?;
+        self.repo.delete(request_id).await?;
 
         Ok(())
     }
 
-    pub async fn remove_friend(&self, user_id: i32, friend_id: i32) -> Result<()> {
+    pub async fn remove_friend(&self, user_id: i32, friend_id: i32) -> AppResult<()> {
         let friendship = self
             .repo
             .find_by_users(user_id, friend_id)
-            .await
-            .map_err(|e| anyhow!(e))?
-            .ok_or_else(|| anyhow!("Friendship not found"))?;
+            .await?
+            .ok_or_else(|| AppError::NotFound("Friendship not found".to_string()))?;
 
-        self.repo
-            .delete(friendship.id)
-            .await
-            .map_err(|e| anyhow!(e))?;
+        self.repo.delete(friendship.id).await?;
 
         Ok(())
     }
 
-    pub async fn list_pending(&self, user_id: i32) -> Result<Vec<PendingRequestDto>> {
-        self.repo
-            .list_pending_requests(user_id)
-            .await
-            .map_err(|e| anyhow!(e))
+    pub async fn list_pending(&self, user_id: i32) -> AppResult<Vec<PendingRequestDto>> {
+        self.repo.list_pending_requests(user_id).await
     }
 
-    pub async fn list_friends(&self, user_id: i32) -> Result<Vec<i32>> {
-        self.repo
-            .list_friends(user_id)
-            .await
-            .map_err(|e| anyhow!(e))
+    pub async fn list_friends(&self, user_id: i32) -> AppResult<Vec<i32>> {
+        self.repo.list_friends(user_id).await
     }
 
     pub async fn get_friendship_status(
         &self,
         user1_id: i32,
         user2_id: i32,
-    ) -> Result<Option<String>> {
-        let f = self
-            .repo
-            .find_by_users(user1_id, user2_id)
-            .await
-            .map_err(|e| anyhow!(e))?;
+    ) -> AppResult<Option<String>> {
+        let f = self.repo.find_by_users(user1_id, user2_id).await?;
         Ok(f.map(|e| e.status))
     }
 }
@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+
+use crate::{
+    config::{config_loader::get_login_throttle_env, config_model::LoginThrottleEnv},
+    domain::{
+        entities::login_throttles::{AddLoginLockoutEntity, LoginThrottleEntity},
+        errors::{AppError, AppResult},
+        repositories::login_throttles::LoginThrottleRepository,
+        value_objects::login_throttle_model::{ThrottleKey, ThrottleScope},
+    },
+};
+
+/// Delay the `failures`-th failure in a row imposes before the next attempt
+pub fn backoff_delay(env: &LoginThrottleEnv, failures: i32) -> Duration {
+    let doublings = (failures - 1).clamp(0, 30) as u32;
+    let seconds = env
+        .backoff_base_seconds
+        .saturating_mul(1 << doublings)
+        .min(env.backoff_max_seconds);
+    Duration::seconds(seconds)
+}
+
+/// When the key may be tried again: after its lockout, or after the backoff of
+/// its recent failures
+fn retry_at(
+    env: &LoginThrottleEnv,
+    row: &LoginThrottleEntity,
+    since: NaiveDateTime,
+) -> Option<NaiveDateTime> {
+    let backoff = (row.failures > 0 && row.last_failed_at >= since)
+        .then(|| row.last_failed_at + backoff_delay(env, row.failures));
+    row.locked_until.max(backoff)
+}
+
+/// Brute-force protection for login, counting failures per username and per IP
+pub struct LoginThrottle {
+    repository: Arc<dyn LoginThrottleRepository>,
+}
+
+impl LoginThrottle {
+    pub fn new(repository: Arc<dyn LoginThrottleRepository>) -> Self {
+        Self { repository }
+    }
+
+    fn keys(username: &str, ip: Option<&str>) -> Vec<ThrottleKey> {
+        let mut keys = vec![ThrottleKey::username(username)];
+        keys.extend(ip.map(ThrottleKey::ip));
+        keys
+    }
+
+    /// Refuses the attempt while its username or IP is locked out or backing off.
+    /// Refused attempts aren't counted as failures.
+    pub async fn check(&self, username: &str, ip: Option<&str>) -> AppResult<()> {
+        let env = get_login_throttle_env()?;
+        let now = Utc::now().naive_utc();
+        let since = now - Duration::minutes(env.window_minutes);
+
+        let rows = self.repository.find(Self::keys(username, ip)).await?;
+        match rows
+            .iter()
+            .filter_map(|row| retry_at(&env, row, since))
+            .max()
+        {
+            Some(retry_at) if retry_at > now => {
+                // Round up, so retrying after the advertised wait works
+                let seconds = ((retry_at - now).num_milliseconds() + 999) / 1000;
+                Err(AppError::TooManyRequests(format!(
+                    "Too many failed login attempts, try again in {} seconds",
+                    seconds
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Counts a failed attempt, locking out the username or IP once it reaches
+    /// its limit
+    pub async fn record_failure(
+        &self,
+        username: &str,
+        ip: Option<&str>,
+        brawler_id: Option<i32>,
+    ) -> AppResult<()> {
+        let env = get_login_throttle_env()?;
+        let now = Utc::now().naive_utc();
+        let since = now - Duration::minutes(env.window_minutes);
+
+        for key in Self::keys(username, ip) {
+            let failures = self.repository.record_failure(key.clone(), since).await?;
+            let limit = match key.scope {
+                ThrottleScope::Username => env.max_failures,
+                ThrottleScope::Ip => env.ip_max_failures,
+            };
+            if failures < limit {
+                continue;
+            }
+
+            tracing::warn!(
+                "Locking out login {} {:?} after {} failures",
+                key.scope.as_str(),
+                key.subject,
+                failures
+            );
+            self.repository
+                .lock(AddLoginLockoutEntity {
+                    scope: key.scope.as_str().to_string(),
+                    subject: key.subject,
+                    brawler_id: match key.scope {
+                        ThrottleScope::Username => brawler_id,
+                        ThrottleScope::Ip => None,
+                    },
+                    ip: ip.map(str::to_string),
+                    failures,
+                    locked_until: now + Duration::minutes(env.lockout_minutes),
+                })
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// A successful login clears the username's count. The IP's is kept, so one
+    /// valid account can't be used to reset it between guesses at others.
+    pub async fn record_success(&self, username: &str) -> AppResult<()> {
+        Ok(self
+            .repository
+            .clear(ThrottleKey::username(username))
+            .await?)
+    }
+}
@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+
+use crate::{
+    config::config_loader::get_mfa_env,
+    domain::{
+        entities::totp::BrawlerTotpEntity,
+        errors::{AppError, AppResult},
+        repositories::{brawlers::BrawlerRepository, totp::TotpRepository},
+        value_objects::mfa_model::{
+            DisableMfaModel, MfaCodeModel, MfaEnrollmentModel, MfaStatusModel, RecoveryCodesModel,
+        },
+    },
+    infrastructure::{argon2, secure_token, totp},
+};
+
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+fn invalid_code() -> AppError {
+    AppError::Forbidden("Invalid two-factor code".to_string())
+}
+
+fn not_enabled() -> AppError {
+    AppError::NotFound("Two-factor authentication is not enabled".to_string())
+}
+
+/// Fresh recovery codes, and the hashes that get stored for them
+fn new_recovery_codes() -> AppResult<(Vec<String>, Vec<String>)> {
+    let codes = totp::generate_recovery_codes(RECOVERY_CODE_COUNT)?;
+    let hashes = codes
+        .iter()
+        .filter_map(|code| totp::normalize_recovery_code(code))
+        .map(|code| secure_token::hash(&code))
+        .collect();
+    Ok((codes, hashes))
+}
+
+/// Uses up `code` as either the current authenticator code or an unused recovery
+/// code. False when it is neither, including an authenticator code already used.
+pub async fn redeem_code(
+    totp_repository: &dyn TotpRepository,
+    enrollment: &BrawlerTotpEntity,
+    code: &str,
+) -> AppResult<bool> {
+    if !enrollment.is_enabled() {
+        return Ok(false);
+    }
+    if let Some(step) = totp::verify(&enrollment.secret, code, Utc::now().timestamp())? {
+        return Ok(totp_repository
+            .accept_step(enrollment.brawler_id, step)
+            .await?);
+    }
+    match totp::normalize_recovery_code(code) {
+        Some(code) => Ok(totp_repository
+            .use_recovery_code(enrollment.brawler_id, secure_token::hash(&code))
+            .await?),
+        None => Ok(false),
+    }
+}
+
+/// Turning TOTP two-factor authentication on and off, and its recovery codes.
+/// The login half lives in `AuthenticationUseCase`.
+pub struct MfaUseCase<T>
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    brawler_repository: Arc<T>,
+    totp_repository: Arc<dyn TotpRepository>,
+}
+
+impl<T> MfaUseCase<T>
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    pub fn new(brawler_repository: Arc<T>, totp_repository: Arc<dyn TotpRepository>) -> Self {
+        Self {
+            brawler_repository,
+            totp_repository,
+        }
+    }
+
+    async fn enabled(&self, brawler_id: i32) -> AppResult<BrawlerTotpEntity> {
+        self.totp_repository
+            .find(brawler_id)
+            .await?
+            .filter(BrawlerTotpEntity::is_enabled)
+            .ok_or_else(not_enabled)
+    }
+
+    pub async fn status(&self, brawler_id: i32) -> AppResult<MfaStatusModel> {
+        let enabled = self
+            .totp_repository
+            .find(brawler_id)
+            .await?
+            .is_some_and(|enrollment| enrollment.is_enabled());
+        let recovery_codes_left = if enabled {
+            self.totp_repository
+                .remaining_recovery_codes(brawler_id)
+                .await?
+        } else {
+            0
+        };
+        Ok(MfaStatusModel {
+            enabled,
+            recovery_codes_left,
+        })
+    }
+
+    /// Starts over with a new secret; 2FA is only on once `confirm` gets a code
+    /// from it
+    pub async fn enroll(&self, brawler_id: i32) -> AppResult<MfaEnrollmentModel> {
+        let brawler = self.brawler_repository.find_by_id(brawler_id).await?;
+        let secret = totp::generate_secret()?;
+        self.totp_repository
+            .begin_enrollment(brawler_id, secret.clone())
+            .await?;
+
+        let issuer = get_mfa_env()?.issuer;
+        Ok(MfaEnrollmentModel {
+            otpauth_uri: totp::otpauth_uri(&issuer, &brawler.username, &secret),
+            secret,
+        })
+    }
+
+    /// Turns 2FA on once the authenticator shows it has the secret, and hands out
+    /// the recovery codes
+    pub async fn confirm(
+        &self,
+        brawler_id: i32,
+        model: MfaCodeModel,
+    ) -> AppResult<RecoveryCodesModel> {
+        let enrollment = match self.totp_repository.find(brawler_id).await? {
+            Some(enrollment) if enrollment.is_enabled() => {
+                return Err(AppError::Conflict(
+                    "Two-factor authentication is already enabled".to_string(),
+                ));
+            }
+            Some(enrollment) => enrollment,
+            None => {
+                return Err(AppError::NotFound(
+                    "No two-factor enrollment to confirm".to_string(),
+                ));
+            }
+        };
+
+        let step = totp::verify(&enrollment.secret, &model.code, Utc::now().timestamp())?
+            .ok_or_else(|| AppError::Validation("Invalid two-factor code".to_string()))?;
+        let (recovery_codes, hashes) = new_recovery_codes()?;
+        self.totp_repository
+            .confirm(brawler_id, step, hashes)
+            .await?;
+        Ok(RecoveryCodesModel { recovery_codes })
+    }
+
+    /// Replaces every recovery code, used or not
+    pub async fn regenerate_recovery_codes(
+        &self,
+        brawler_id: i32,
+        model: MfaCodeModel,
+    ) -> AppResult<RecoveryCodesModel> {
+        let enrollment = self.enabled(brawler_id).await?;
+        if !redeem_code(self.totp_repository.as_ref(), &enrollment, &model.code).await? {
+            return Err(invalid_code());
+        }
+
+        let (recovery_codes, hashes) = new_recovery_codes()?;
+        self.totp_repository
+            .replace_recovery_codes(brawler_id, hashes)
+            .await?;
+        Ok(RecoveryCodesModel { recovery_codes })
+    }
+
+    /// Needs both the password and a code, so a session left open can't turn it off
+    pub async fn disable(&self, brawler_id: i32, model: DisableMfaModel) -> AppResult<()> {
+        let brawler = self.brawler_repository.find_by_id(brawler_id).await?;
+        if !argon2::verify(model.password, brawler.password)? {
+            return Err(AppError::Forbidden("Password is incorrect".to_string()));
+        }
+        let enrollment = self.enabled(brawler_id).await?;
+        if !redeem_code(self.totp_repository.as_ref(), &enrollment, &model.code).await? {
+            return Err(invalid_code());
+        }
+
+        self.totp_repository.disable(brawler_id).await?;
+        Ok(())
+    }
+}
@@ -1,10 +1,10 @@
 use crate::domain::{
+    errors::{AppError, AppResult},
     repositories::{
         mission_comment::MissionCommentRepository, mission_viewing::MissionViewingRepository,
     },
-    value_objects::mission_comment_model::MissionCommentModel,
+    value_objects::{brawler_role::BrawlerRole, mission_comment_model::MissionCommentModel},
 };
-use anyhow::Result;
 use std::sync::Arc;
 
 pub struct MissionCommentUseCase<T1, T2>
@@ -33,13 +33,13 @@ where
         mission_id: i32,
         brawler_id: i32,
         content: &str,
-    ) -> Result<MissionCommentModel> {
+    ) -> AppResult<MissionCommentModel> {
         // 1. Check if user is chief or member
         let mission = self.mission_viewing_repository.get_one(mission_id).await?;
 
         if mission.deleted_at.is_some() {
-            return Err(anyhow::anyhow!(
-                "This mission has been removed. Chat is disabled."
+            return Err(AppError::Conflict(
+                "This mission has been removed. Chat is disabled.".to_string(),
             ));
         }
 
@@ -49,23 +49,38 @@ where
         let is_member = crew.iter().any(|m| m.id == brawler_id);
 
         if !is_chief && !is_member {
-            return Err(anyhow::anyhow!(
-                "You are not authorized to post in this mission's chat."
+            return Err(AppError::Forbidden(
+                "You are not authorized to post in this mission's chat.".to_string(),
             ));
         }
 
-        self.repository.add(mission_id, brawler_id, content).await
+        Ok(self.repository.add(mission_id, brawler_id, content).await?)
     }
 
-    pub async fn get_comments(&self, mission_id: i32) -> Result<Vec<MissionCommentModel>> {
-        self.repository.get_by_mission_id(mission_id).await
+    pub async fn get_comments(&self, mission_id: i32) -> AppResult<Vec<MissionCommentModel>> {
+        Ok(self.repository.get_by_mission_id(mission_id).await?)
     }
 
-    pub async fn clear_comments(&self, mission_id: i32, brawler_id: i32) -> Result<()> {
+    /// The chief's, or any moderator's, to do
+    pub async fn clear_comments(
+        &self,
+        mission_id: i32,
+        brawler_id: i32,
+        role: BrawlerRole,
+    ) -> AppResult<()> {
         let mission = self.mission_viewing_repository.get_one(mission_id).await?;
         if mission.chief_id != brawler_id {
-            return Err(anyhow::anyhow!("Only the chief can clear the chat!"));
+            if !role.can_moderate() {
+                return Err(AppError::Forbidden(
+                    "Only the chief can clear the chat!".to_string(),
+                ));
+            }
+            tracing::info!(
+                "Moderator {} cleared the chat of mission {}",
+                brawler_id,
+                mission_id
+            );
         }
-        self.repository.clear_by_mission_id(mission_id).await
+        Ok(self.repository.clear_by_mission_id(mission_id).await?)
     }
 }
@@ -0,0 +1,282 @@
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+
+use crate::{
+    config::config_loader::get_invite_env,
+    domain::{
+        entities::mission_invites::{
+            AddMissionInvitationEntity, AddMissionInviteLinkEntity, MissionInviteLinkEntity,
+        },
+        errors::{AppError, AppResult},
+        repositories::{
+            friendship_repository::FriendshipRepository, mission_invites::MissionInviteRepository,
+            mission_viewing::MissionViewingRepository,
+        },
+        value_objects::{
+            mission_invite_model::{
+                CreateInviteLinkModel, InviteLinkModel, MissionInvitationModel,
+            },
+            mission_model::MissionModel,
+            mission_statuses::MissionStatuses,
+        },
+    },
+    infrastructure::jwt::{generate_token, jwt_model::InviteClaims, verify_invite_token},
+};
+
+/// Longest a chief can make an invite link last
+const MAX_LINK_TTL_HOURS: i64 = 24 * 30;
+
+pub struct MissionInviteUseCase<T1, T2, T3>
+where
+    T1: MissionInviteRepository + Send + Sync,
+    T2: MissionViewingRepository + Send + Sync,
+    T3: FriendshipRepository + Send + Sync,
+{
+    mission_invite_repository: Arc<T1>,
+    mission_viewing_repository: Arc<T2>,
+    friendship_repository: Arc<T3>,
+}
+
+impl<T1, T2, T3> MissionInviteUseCase<T1, T2, T3>
+where
+    T1: MissionInviteRepository + Send + Sync,
+    T2: MissionViewingRepository + Send + Sync,
+    T3: FriendshipRepository + Send + Sync,
+{
+    pub fn new(
+        mission_invite_repository: Arc<T1>,
+        mission_viewing_repository: Arc<T2>,
+        friendship_repository: Arc<T3>,
+    ) -> Self {
+        Self {
+            mission_invite_repository,
+            mission_viewing_repository,
+            friendship_repository,
+        }
+    }
+
+    async fn ensure_chief(&self, mission_id: i32, chief_id: i32) -> AppResult<MissionModel> {
+        let mission = self.mission_viewing_repository.get_one(mission_id).await?;
+        if mission.chief_id != chief_id {
+            return Err(AppError::Forbidden(
+                "You are not the chief of this mission!".to_string(),
+            ));
+        }
+        Ok(mission)
+    }
+
+    /// Signs the link row. The claims are all derived from the row, so listing the
+    /// links later hands back the same token.
+    fn to_link_model(link: MissionInviteLinkEntity) -> AppResult<InviteLinkModel> {
+        let invite_env = get_invite_env()?;
+        let claims = InviteClaims {
+            invite_id: link.id,
+            mission_id: link.mission_id,
+            exp: link.expires_at.and_utc().timestamp() as usize,
+            iat: link.created_at.and_utc().timestamp() as usize,
+        };
+        let token = generate_token(invite_env.secret, &claims)?;
+
+        Ok(InviteLinkModel {
+            id: link.id,
+            mission_id: link.mission_id,
+            token,
+            max_uses: link.max_uses,
+            uses: link.uses,
+            expires_at: link.expires_at,
+            revoked_at: link.revoked_at,
+            created_at: link.created_at,
+        })
+    }
+
+    pub async fn create_link(
+        &self,
+        mission_id: i32,
+        chief_id: i32,
+        create_link_model: CreateInviteLinkModel,
+    ) -> AppResult<InviteLinkModel> {
+        let mission = self.ensure_chief(mission_id, chief_id).await?;
+        if mission.deleted_at.is_some() {
+            return Err(AppError::NotFound("Mission not found".to_string()));
+        }
+
+        if create_link_model
+            .max_uses
+            .is_some_and(|max_uses| max_uses < 1)
+        {
+            return Err(AppError::Validation(
+                "max_uses must be at least 1".to_string(),
+            ));
+        }
+
+        let ttl_hours = match create_link_model.expires_in_hours {
+            Some(hours) if !(1..=MAX_LINK_TTL_HOURS).contains(&hours) => {
+                return Err(AppError::Validation(format!(
+                    "expires_in_hours must be between 1 and {}",
+                    MAX_LINK_TTL_HOURS
+                )));
+            }
+            Some(hours) => hours,
+            None => get_invite_env()?.ttl_hours,
+        };
+
+        let link = self
+            .mission_invite_repository
+            .create_link(AddMissionInviteLinkEntity {
+                mission_id,
+                created_by: chief_id,
+                max_uses: create_link_model.max_uses,
+                expires_at: (Utc::now() + Duration::hours(ttl_hours)).naive_utc(),
+            })
+            .await?;
+
+        Self::to_link_model(link)
+    }
+
+    pub async fn links(&self, mission_id: i32, chief_id: i32) -> AppResult<Vec<InviteLinkModel>> {
+        self.ensure_chief(mission_id, chief_id).await?;
+
+        self.mission_invite_repository
+            .links(mission_id)
+            .await?
+            .into_iter()
+            .map(Self::to_link_model)
+            .collect()
+    }
+
+    pub async fn revoke_link(&self, mission_id: i32, link_id: i32, chief_id: i32) -> AppResult<()> {
+        self.ensure_chief(mission_id, chief_id).await?;
+
+        self.mission_invite_repository
+            .revoke_link(mission_id, link_id)
+            .await?;
+        Ok(())
+    }
+
+    /// Invite links skip the mission's join policy and privacy, but not its capacity.
+    pub async fn join_by_invite(&self, token: String, brawler_id: i32) -> AppResult<MissionModel> {
+        let invite_env = get_invite_env()?;
+        let claims = verify_invite_token(invite_env.secret, token).map_err(|_| {
+            AppError::Forbidden("Invite link is invalid or has expired".to_string())
+        })?;
+
+        let mission = self
+            .mission_viewing_repository
+            .get_one(claims.mission_id)
+            .await?;
+
+        if mission.chief_id == brawler_id {
+            return Err(AppError::Forbidden(
+                "The Chief can not join in his own mission as a crew member!!".to_string(),
+            ));
+        }
+
+        self.mission_invite_repository
+            .join_by_link(claims.invite_id, brawler_id)
+            .await?;
+
+        Ok(mission)
+    }
+
+    /// Returns the mission and the new invitation id.
+    pub async fn invite(
+        &self,
+        mission_id: i32,
+        chief_id: i32,
+        invitee_id: i32,
+    ) -> AppResult<(MissionModel, i32)> {
+        let mission = self.ensure_chief(mission_id, chief_id).await?;
+
+        if mission.deleted_at.is_some() || mission.status != MissionStatuses::Open.to_string() {
+            return Err(AppError::Conflict(
+                "Mission is not joinable in current status".to_string(),
+            ));
+        }
+
+        if invitee_id == chief_id {
+            return Err(AppError::Validation(
+                "You can not invite yourself".to_string(),
+            ));
+        }
+
+        let friends = self.friendship_repository.list_friends(chief_id).await?;
+        if !friends.contains(&invitee_id) {
+            return Err(AppError::Forbidden(
+                "You can only invite your friends".to_string(),
+            ));
+        }
+
+        let invitation_id = self
+            .mission_invite_repository
+            .invite(AddMissionInvitationEntity {
+                mission_id,
+                inviter_id: chief_id,
+                invitee_id,
+            })
+            .await?;
+
+        Ok((mission, invitation_id))
+    }
+
+    pub async fn mission_invitations(
+        &self,
+        mission_id: i32,
+        chief_id: i32,
+    ) -> AppResult<Vec<MissionInvitationModel>> {
+        self.ensure_chief(mission_id, chief_id).await?;
+
+        Ok(self
+            .mission_invite_repository
+            .pending_for_mission(mission_id)
+            .await?)
+    }
+
+    pub async fn my_invitations(&self, brawler_id: i32) -> AppResult<Vec<MissionInvitationModel>> {
+        Ok(self
+            .mission_invite_repository
+            .pending_for_brawler(brawler_id)
+            .await?)
+    }
+
+    pub async fn accept(&self, invitation_id: i32, brawler_id: i32) -> AppResult<MissionModel> {
+        let invitation = self
+            .mission_invite_repository
+            .accept(invitation_id, brawler_id)
+            .await?;
+
+        Ok(self
+            .mission_viewing_repository
+            .get_one(invitation.mission_id)
+            .await?)
+    }
+
+    pub async fn decline(&self, invitation_id: i32, brawler_id: i32) -> AppResult<MissionModel> {
+        let invitation = self
+            .mission_invite_repository
+            .decline(invitation_id, brawler_id)
+            .await?;
+
+        Ok(self
+            .mission_viewing_repository
+            .get_one(invitation.mission_id)
+            .await?)
+    }
+
+    /// Returns the mission and the brawler whose invitation was withdrawn.
+    pub async fn revoke(
+        &self,
+        mission_id: i32,
+        invitation_id: i32,
+        chief_id: i32,
+    ) -> AppResult<(MissionModel, i32)> {
+        let mission = self.ensure_chief(mission_id, chief_id).await?;
+
+        let invitation = self
+            .mission_invite_repository
+            .revoke(mission_id, invitation_id)
+            .await?;
+
+        Ok((mission, invitation.invitee_id))
+    }
+}
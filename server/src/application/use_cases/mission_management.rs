@@ -1,49 +1,61 @@
 use std::sync::Arc;
 
 use crate::domain::{
+    entities::notifications::AddNotificationEntity,
+    errors::{AppError, AppResult},
     repositories::{
         mission_management::MissionManagementRepository, mission_viewing::MissionViewingRepository,
+        transaction_provider::TransactionProvider,
+    },
+    value_objects::{
+        brawler_role::BrawlerRole,
+        mission_application_model::MissionApplicationModel,
+        mission_model::{AddMissionModel, EditMissionModel, MissionModel},
     },
-    value_objects::mission_model::{AddMissionModel, EditMissionModel},
 };
 
-pub struct MissionManagementUseCase<T1, T2>
+pub struct MissionManagementUseCase<T1, T2, T3>
 where
     T1: MissionManagementRepository + Send + Sync,
     T2: MissionViewingRepository + Send + Sync,
+    T3: TransactionProvider + Send + Sync,
 {
     pub mission_management_repository: Arc<T1>,
     pub mission_viewing_repository: Arc<T2>,
+    pub transaction_provider: Arc<T3>,
 }
 
-use anyhow::Result;
-
-impl<T1, T2> MissionManagementUseCase<T1, T2>
+impl<T1, T2, T3> MissionManagementUseCase<T1, T2, T3>
 where
     T1: MissionManagementRepository + Send + Sync,
     T2: MissionViewingRepository + Send + Sync,
+    T3: TransactionProvider + Send + Sync,
 {
     pub fn new(
         mission_management_repository: Arc<T1>,
         mission_viewing_repository: Arc<T2>,
+        transaction_provider: Arc<T3>,
     ) -> Self {
         Self {
             mission_management_repository,
             mission_viewing_repository,
+            transaction_provider,
         }
     }
 
-    pub async fn add(&self, chief_id: i32, add_mission_model: AddMissionModel) -> Result<i32> {
+    pub async fn add(&self, chief_id: i32, add_mission_model: AddMissionModel) -> AppResult<i32> {
         if add_mission_model.name.trim().is_empty() || add_mission_model.name.trim().len() < 3 {
-            return Err(anyhow::anyhow!(
-                "Mission name must be least 4 characters long"
+            return Err(AppError::Validation(
+                "Mission name must be least 4 characters long".to_string(),
             ));
         }
 
-        if let Some(scheduled_at) = add_mission_model.scheduled_at {
-            if scheduled_at < chrono::Utc::now() {
-                return Err(anyhow::anyhow!("Scheduled time cannot be in the past!"));
-            }
+        if let Some(scheduled_at) = add_mission_model.scheduled_at
+            && scheduled_at < chrono::Utc::now()
+        {
+            return Err(AppError::Validation(
+                "Scheduled time cannot be in the past!".to_string(),
+            ));
         }
 
         let insert_mission_entity = add_mission_model.to_entity(chief_id);
@@ -61,23 +73,32 @@ where
         mission_id: i32,
         chief_id: i32,
         mut edit_mission_model: EditMissionModel,
-    ) -> Result<i32> {
+    ) -> AppResult<i32> {
         if let Some(name) = edit_mission_model.name {
             if name.trim().is_empty() {
                 edit_mission_model.name = None;
             } else if name.trim().len() < 3 {
-                return Err(anyhow::anyhow!(
-                    "Mission name must be least 4 characters long"
+                return Err(AppError::Validation(
+                    "Mission name must be least 4 characters long".to_string(),
                 ));
             } else {
                 edit_mission_model.name = Some(name.trim().to_string())
             }
         }
 
-        if let Some(scheduled_at) = edit_mission_model.scheduled_at {
-            if scheduled_at < chrono::Utc::now() {
-                return Err(anyhow::anyhow!("Scheduled time cannot be in the past!"));
-            }
+        if let Some(scheduled_at) = edit_mission_model.scheduled_at
+            && scheduled_at < chrono::Utc::now()
+        {
+            return Err(AppError::Validation(
+                "Scheduled time cannot be in the past!".to_string(),
+            ));
+        }
+
+        let mission = self.mission_viewing_repository.get_one(mission_id).await?;
+        if mission.chief_id != chief_id {
+            return Err(AppError::Forbidden(
+                "You are not the chief of this mission!".to_string(),
+            ));
         }
 
         let crew_count = self
@@ -85,8 +106,8 @@ where
             .crew_counting(mission_id)
             .await?;
         if crew_count > 0 {
-            return Err(anyhow::anyhow!(
-                "Mission has been taken by brawler for now!"
+            return Err(AppError::Conflict(
+                "Mission has been taken by brawler for now!".to_string(),
             ));
         }
 
@@ -100,22 +121,117 @@ where
         Ok(result)
     }
 
-    pub async fn remove(&self, mission_id: i32, chief_id: i32) -> Result<()> {
+    /// Moderators may remove any mission; it is still removed as its chief's
+    pub async fn remove(
+        &self,
+        mission_id: i32,
+        brawler_id: i32,
+        role: BrawlerRole,
+    ) -> AppResult<()> {
         let mission = self.mission_viewing_repository.get_one(mission_id).await?;
 
-        if mission.chief_id != chief_id {
-            return Err(anyhow::anyhow!("You are not the chief of this mission!"));
-        }
+        let removed_by = if mission.chief_id == brawler_id {
+            "the chief"
+        } else if role.can_moderate() {
+            tracing::info!("Moderator {} removed mission {}", brawler_id, mission_id);
+            "a moderator"
+        } else {
+            return Err(AppError::Forbidden(
+                "You are not the chief of this mission!".to_string(),
+            ));
+        };
+        let chief_id = mission.chief_id;
 
         if mission.status == "InProgress" {
-            return Err(anyhow::anyhow!(
-                "Cannot delete a mission while it is in progress!"
+            return Err(AppError::Conflict(
+                "Cannot delete a mission while it is in progress!".to_string(),
+            ));
+        }
+
+        let mission_name = mission.name;
+        self.transaction_provider
+            .transaction(move |uow| {
+                let crew = uow.crew_ids(mission_id)?;
+
+                if crew.is_empty() {
+                    // HARD DELETE: No one to notify, just clean up
+                    uow.delete_mission_comments(mission_id)?;
+                    uow.delete_mission(mission_id, chief_id)?;
+                    return Ok(());
+                }
+
+                // SOFT DELETE: Keep it so crew members can see Status: REMOVED and leave
+                uow.soft_delete_mission(mission_id, chief_id)?;
+                for brawler_id in crew.into_iter().filter(|id| *id != chief_id) {
+                    uow.add_notification(AddNotificationEntity {
+                        brawler_id,
+                        type_: "mission_deleted".to_string(),
+                        content: format!(
+                            "Mission '{}' has been removed by {}.",
+                            mission_name, removed_by
+                        ),
+                        related_id: Some(mission_id),
+                    })?;
+                }
+                Ok(())
+            })
+            .await
+    }
+
+    async fn ensure_chief(&self, mission_id: i32, chief_id: i32) -> AppResult<MissionModel> {
+        let mission = self.mission_viewing_repository.get_one(mission_id).await?;
+        if mission.chief_id != chief_id {
+            return Err(AppError::Forbidden(
+                "You are not the chief of this mission!".to_string(),
             ));
         }
+        Ok(mission)
+    }
+
+    pub async fn applications(
+        &self,
+        mission_id: i32,
+        chief_id: i32,
+    ) -> AppResult<Vec<MissionApplicationModel>> {
+        self.ensure_chief(mission_id, chief_id).await?;
+
+        Ok(self
+            .mission_management_repository
+            .pending_applications(mission_id)
+            .await?)
+    }
+
+    /// Returns the mission and the brawler who became crew.
+    pub async fn accept_application(
+        &self,
+        mission_id: i32,
+        application_id: i32,
+        chief_id: i32,
+    ) -> AppResult<(MissionModel, i32)> {
+        let mission = self.ensure_chief(mission_id, chief_id).await?;
 
-        self.mission_management_repository
-            .remove(mission_id, chief_id)
+        let brawler_id = self
+            .mission_management_repository
+            .accept_application(mission_id, application_id)
             .await?;
-        Ok(())
+
+        Ok((mission, brawler_id))
+    }
+
+    /// Returns the mission and the rejected brawler.
+    pub async fn reject_application(
+        &self,
+        mission_id: i32,
+        application_id: i32,
+        chief_id: i32,
+    ) -> AppResult<(MissionModel, i32)> {
+        let mission = self.ensure_chief(mission_id, chief_id).await?;
+
+        let brawler_id = self
+            .mission_management_repository
+            .reject_application(mission_id, application_id)
+            .await?;
+
+        Ok((mission, brawler_id))
     }
 }
@@ -1,37 +1,80 @@
 use std::sync::Arc;
 
-use anyhow::Result;
-
-use crate::domain::{
-    repositories::{
-        mission_operation::MissionOperationRepository, mission_viewing::MissionViewingRepository,
+use crate::{
+    application::use_cases::mission_waitlist::promote_next,
+    domain::{
+        entities::notifications::AddNotificationEntity,
+        errors::{AppError, AppResult},
+        repositories::{
+            mission_viewing::MissionViewingRepository, transaction_provider::TransactionProvider,
+        },
+        value_objects::{brawler_role::BrawlerRole, mission_statuses::MissionStatuses},
     },
-    value_objects::mission_statuses::MissionStatuses,
 };
 pub struct MissionOperationUseCase<T1, T2>
 where
-    T1: MissionOperationRepository + Send + Sync,
-    T2: MissionViewingRepository + Send + Sync,
+    T1: MissionViewingRepository + Send + Sync,
+    T2: TransactionProvider + Send + Sync,
 {
-    mission_operation_repository: Arc<T1>,
-    mission_viewing_repository: Arc<T2>,
+    mission_viewing_repository: Arc<T1>,
+    transaction_provider: Arc<T2>,
 }
 
 impl<T1, T2> MissionOperationUseCase<T1, T2>
 where
-    T1: MissionOperationRepository + Send + Sync,
-    T2: MissionViewingRepository + Send + Sync,
+    T1: MissionViewingRepository + Send + Sync,
+    T2: TransactionProvider + Send + Sync,
 {
-    pub fn new(mission_operation_repository: Arc<T1>, mission_viewing_repository: Arc<T2>) -> Self {
+    pub fn new(mission_viewing_repository: Arc<T1>, transaction_provider: Arc<T2>) -> Self {
         Self {
-            mission_operation_repository,
             mission_viewing_repository,
+            transaction_provider,
         }
     }
 
-    pub async fn in_progress(&self, mission_id: i32, chief_id: i32) -> Result<i32> {
+    /// Moves the mission to `status` and stores a notification for every crew
+    /// member (and the chief, when `notify_chief` is set) in the same transaction.
+    async fn change_status(
+        &self,
+        mission_id: i32,
+        chief_id: i32,
+        status: MissionStatuses,
+        notification_type: &'static str,
+        content: String,
+        notify_chief: bool,
+    ) -> AppResult<i32> {
+        self.transaction_provider
+            .transaction(move |uow| {
+                let result = uow.set_mission_status(mission_id, chief_id, status)?;
+
+                let mut recipients = uow.crew_ids(mission_id)?;
+                recipients.retain(|id| *id != chief_id);
+                if notify_chief {
+                    recipients.insert(0, chief_id);
+                }
+                for brawler_id in recipients {
+                    uow.add_notification(AddNotificationEntity {
+                        brawler_id,
+                        type_: notification_type.to_string(),
+                        content: content.clone(),
+                        related_id: Some(mission_id),
+                    })?;
+                }
+
+                Ok(result)
+            })
+            .await
+    }
+
+    pub async fn in_progress(&self, mission_id: i32, chief_id: i32) -> AppResult<i32> {
         let mission = self.mission_viewing_repository.get_one(mission_id).await?;
 
+        if mission.chief_id != chief_id {
+            return Err(AppError::Forbidden(
+                "You are not the chief of this mission!".to_string(),
+            ));
+        }
+
         let crew_count = self
             .mission_viewing_repository
             .crew_counting(mission_id)
@@ -40,68 +83,114 @@ where
         let is_status_open_or_fail = mission.status == MissionStatuses::Open.to_string()
             || mission.status == MissionStatuses::Failed.to_string();
 
-        let update_condition = is_status_open_or_fail
-            && crew_count > 0
-            && (crew_count as i32) <= mission.max_crew
-            && mission.chief_id == chief_id;
+        let update_condition =
+            is_status_open_or_fail && crew_count > 0 && (crew_count as i32) <= mission.max_crew;
         if !update_condition {
-            return Err(anyhow::anyhow!("Invalid condition to change stages!"));
+            return Err(AppError::Conflict(
+                "Invalid condition to change stages!".to_string(),
+            ));
         }
 
-        let result = self
-            .mission_operation_repository
-            .to_progress(mission_id, chief_id)
-            .await?;
-        Ok(result)
+        self.change_status(
+            mission_id,
+            chief_id,
+            MissionStatuses::InProgress,
+            "mission_started",
+            format!("Mission '{}' has started!", mission.name),
+            false,
+        )
+        .await
     }
-    pub async fn to_completed(&self, mission_id: i32, chief_id: i32) -> Result<i32> {
+    pub async fn to_completed(&self, mission_id: i32, chief_id: i32) -> AppResult<i32> {
         let mission = self.mission_viewing_repository.get_one(mission_id).await?;
 
-        let update_condition = mission.status == MissionStatuses::InProgress.to_string()
-            && mission.chief_id == chief_id;
-        if !update_condition {
-            return Err(anyhow::anyhow!("Invalid condition to change stages!"));
+        if mission.chief_id != chief_id {
+            return Err(AppError::Forbidden(
+                "You are not the chief of this mission!".to_string(),
+            ));
         }
-        let result = self
-            .mission_operation_repository
-            .to_completed(mission_id, chief_id)
-            .await?;
 
-        Ok(result)
+        let update_condition = mission.status == MissionStatuses::InProgress.to_string();
+        if !update_condition {
+            return Err(AppError::Conflict(
+                "Invalid condition to change stages!".to_string(),
+            ));
+        }
+        self.change_status(
+            mission_id,
+            chief_id,
+            MissionStatuses::Completed,
+            "mission_completed",
+            format!("Mission '{}' has been COMPLETED!", mission.name),
+            true,
+        )
+        .await
     }
-    pub async fn to_failed(&self, mission_id: i32, chief_id: i32) -> Result<i32> {
+    pub async fn to_failed(&self, mission_id: i32, chief_id: i32) -> AppResult<i32> {
         let mission = self.mission_viewing_repository.get_one(mission_id).await?;
 
-        let update_condition = mission.status == MissionStatuses::InProgress.to_string()
-            && mission.chief_id == chief_id;
-        if !update_condition {
-            return Err(anyhow::anyhow!("Invalid condition to change stages!"));
+        if mission.chief_id != chief_id {
+            return Err(AppError::Forbidden(
+                "You are not the chief of this mission!".to_string(),
+            ));
         }
-        let result = self
-            .mission_operation_repository
-            .to_failed(mission_id, chief_id)
-            .await?;
 
-        Ok(result)
+        let update_condition = mission.status == MissionStatuses::InProgress.to_string();
+        if !update_condition {
+            return Err(AppError::Conflict(
+                "Invalid condition to change stages!".to_string(),
+            ));
+        }
+        self.change_status(
+            mission_id,
+            chief_id,
+            MissionStatuses::Failed,
+            "mission_failed",
+            format!("Mission '{}' has FAILED.", mission.name),
+            true,
+        )
+        .await
     }
 
-    pub async fn kick(&self, mission_id: i32, brawler_id: i32, chief_id: i32) -> Result<()> {
+    /// Returns the brawler promoted from the waitlist into the freed slot, if any.
+    /// Moderators may kick from any mission.
+    pub async fn kick(
+        &self,
+        mission_id: i32,
+        brawler_id: i32,
+        chief_id: i32,
+        role: BrawlerRole,
+    ) -> AppResult<Option<i32>> {
         let mission = self.mission_viewing_repository.get_one(mission_id).await?;
 
         if mission.chief_id != chief_id {
-            return Err(anyhow::anyhow!("Only the chief can kick members!"));
+            if !role.can_moderate() {
+                return Err(AppError::Forbidden(
+                    "Only the chief can kick members!".to_string(),
+                ));
+            }
+            tracing::info!(
+                "Moderator {} kicked brawler {} from mission {}",
+                chief_id,
+                brawler_id,
+                mission_id
+            );
         }
 
         if mission.status != MissionStatuses::Open.to_string() {
-            return Err(anyhow::anyhow!(
-                "Can only kick members before the mission starts!"
+            return Err(AppError::Conflict(
+                "Can only kick members before the mission starts!".to_string(),
             ));
         }
 
-        self.mission_operation_repository
-            .kick(mission_id, brawler_id)
-            .await?;
-
-        Ok(())
+        // One transaction, so the freed slot goes to the waitlist before anyone
+        // can join directly
+        let mission_name = mission.name;
+        self.transaction_provider
+            .transaction(move |uow| {
+                uow.remove_crew_member(mission_id, brawler_id)?;
+                promote_next(uow, mission_id, &mission_name)
+            })
+            .await
     }
 }
@@ -1,8 +1,7 @@
 use std::sync::Arc;
 
-use anyhow::Result;
-
 use crate::domain::{
+    errors::{AppError, AppResult},
     repositories::mission_viewing::MissionViewingRepository,
     value_objects::{
         brawler_model::BrawlerModel, mission_filter::MissionFilter, mission_model::MissionModel,
@@ -24,15 +23,38 @@ where
         Self { repository }
     }
 
-    pub async fn get_one(&self, mission_id: i32) -> Result<MissionModel> {
-        self.repository.get_one(mission_id).await
+    pub async fn get_one(&self, mission_id: i32) -> AppResult<MissionModel> {
+        Ok(self.repository.get_one(mission_id).await?)
+    }
+
+    pub async fn get_crew(&self, mission_id: i32) -> AppResult<Vec<BrawlerModel>> {
+        Ok(self.repository.get_crew(mission_id).await?)
     }
 
-    pub async fn get_crew(&self, mission_id: i32) -> Result<Vec<BrawlerModel>> {
-        self.repository.get_crew(mission_id).await
+    pub async fn get_all(&self, mission_filter: &MissionFilter) -> AppResult<Vec<MissionModel>> {
+        Ok(self.repository.get_all(mission_filter).await?)
     }
 
-    pub async fn get_all(&self, mission_filter: &MissionFilter) -> Result<Vec<MissionModel>> {
-        self.repository.get_all(mission_filter).await
+    /// Only the chief and crew may follow a mission's room.
+    pub async fn ensure_room_member(
+        &self,
+        mission_id: i32,
+        brawler_id: i32,
+    ) -> AppResult<MissionModel> {
+        let mission = self.repository.get_one(mission_id).await?;
+        if mission.deleted_at.is_some() {
+            return Err(AppError::NotFound("Mission not found".to_string()));
+        }
+        if mission.chief_id == brawler_id {
+            return Ok(mission);
+        }
+
+        let crew = self.repository.get_crew(mission_id).await?;
+        if !crew.iter().any(|member| member.id == brawler_id) {
+            return Err(AppError::Forbidden(
+                "You are not a member of this mission".to_string(),
+            ));
+        }
+        Ok(mission)
     }
 }
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use crate::domain::{
+    entities::{mission_waitlist::AddMissionWaitlistEntity, notifications::AddNotificationEntity},
+    errors::{AppError, AppResult},
+    repositories::{
+        mission_viewing::MissionViewingRepository, mission_waitlist::MissionWaitlistRepository,
+        transaction_provider::UnitOfWork,
+    },
+    value_objects::{join_policy::JoinPolicy, mission_waitlist_model::WaitlistPositionModel},
+};
+
+/// Fills a freed slot from the waitlist and notifies the promoted brawler.
+///
+/// Meant to run inside the same transaction that freed the slot.
+pub fn promote_next(
+    uow: &mut dyn UnitOfWork,
+    mission_id: i32,
+    mission_name: &str,
+) -> AppResult<Option<i32>> {
+    let Some(brawler_id) = uow.promote_from_waitlist(mission_id)? else {
+        return Ok(None);
+    };
+
+    uow.add_notification(AddNotificationEntity {
+        brawler_id,
+        type_: "waitlist_promoted".to_string(),
+        content: format!("A slot opened up! You joined the crew of: {}", mission_name),
+        related_id: Some(mission_id),
+    })?;
+
+    Ok(Some(brawler_id))
+}
+
+pub struct MissionWaitlistUseCase<T1, T2>
+where
+    T1: MissionWaitlistRepository + Send + Sync,
+    T2: MissionViewingRepository + Send + Sync,
+{
+    mission_waitlist_repository: Arc<T1>,
+    mission_viewing_repository: Arc<T2>,
+}
+
+impl<T1, T2> MissionWaitlistUseCase<T1, T2>
+where
+    T1: MissionWaitlistRepository + Send + Sync,
+    T2: MissionViewingRepository + Send + Sync,
+{
+    pub fn new(mission_waitlist_repository: Arc<T1>, mission_viewing_repository: Arc<T2>) -> Self {
+        Self {
+            mission_waitlist_repository,
+            mission_viewing_repository,
+        }
+    }
+
+    pub async fn join(&self, mission_id: i32, brawler_id: i32) -> AppResult<WaitlistPositionModel> {
+        let mission = self.mission_viewing_repository.get_one(mission_id).await?;
+
+        if mission.chief_id == brawler_id {
+            return Err(AppError::Forbidden(
+                "The Chief can not wait for a slot in his own mission!!".to_string(),
+            ));
+        }
+
+        if mission.is_private || mission.join_policy != JoinPolicy::Open.to_string() {
+            return Err(AppError::Forbidden(
+                "Only open missions have a waitlist".to_string(),
+            ));
+        }
+
+        let crew = self.mission_viewing_repository.get_crew(mission_id).await?;
+        if crew.iter().any(|member| member.id == brawler_id) {
+            return Err(AppError::Conflict(
+                "You are already a crew member of this mission".to_string(),
+            ));
+        }
+
+        self.mission_waitlist_repository
+            .join(AddMissionWaitlistEntity {
+                mission_id,
+                brawler_id,
+            })
+            .await?;
+
+        self.position(mission_id, brawler_id).await
+    }
+
+    pub async fn leave(&self, mission_id: i32, brawler_id: i32) -> AppResult<()> {
+        let removed = self
+            .mission_waitlist_repository
+            .leave(mission_id, brawler_id)
+            .await?;
+
+        if removed == 0 {
+            return Err(AppError::NotFound(
+                "You are not on the waitlist for this mission".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn position(
+        &self,
+        mission_id: i32,
+        brawler_id: i32,
+    ) -> AppResult<WaitlistPositionModel> {
+        self.mission_waitlist_repository
+            .position(mission_id, brawler_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound("You are not on the waitlist for this mission".to_string())
+            })
+    }
+}
@@ -1,9 +1,18 @@
+pub mod access_tokens;
+pub mod admin;
 pub mod authentication;
 pub mod brawlers;
 pub mod crew_operation;
 pub mod friendships;
+pub mod login_throttle;
+pub mod mfa;
 pub mod mission_comment;
+pub mod mission_invites;
 pub mod mission_management;
 pub mod mission_operation;
 pub mod mission_viewing;
+pub mod mission_waitlist;
 pub mod notifications;
+pub mod oauth;
+pub mod password_reset;
+pub mod presence;
@@ -0,0 +1,302 @@
+use std::sync::Arc;
+
+use aws_lc_rs::constant_time::verify_slices_are_equal;
+use chrono::{Duration, Utc};
+
+use crate::{
+    application::use_cases::authentication::{
+        LoginOutcome, mfa_challenge, passport_of, start_session,
+    },
+    config::config_loader::get_jwt_env,
+    domain::{
+        entities::brawlers::RegisterExternalBrawlerEntity,
+        errors::{AppError, AppResult},
+        repositories::{
+            brawler_identities::BrawlerIdentityRepository, brawlers::BrawlerRepository,
+            sessions::SessionRepository, totp::TotpRepository,
+        },
+        value_objects::{
+            oauth_model::{
+                AuthorizeModel, BrawlerIdentityModel, DISCORD, ExternalIdentity,
+                OAuthCallbackModel, UnlinkIdentityModel,
+            },
+            session_model::SessionClient,
+        },
+    },
+    infrastructure::{
+        argon2,
+        jwt::{jwt_model::OAuthStateClaims, verify_oauth_state},
+        oauth::{self, IdentityProvider, ProviderProfile},
+        secure_token,
+    },
+};
+
+/// minutes between leaving for the provider and coming back
+pub const STATE_TTL_MINUTES: i64 = 10;
+/// Tries at a free username for a new brawler before giving up
+const USERNAME_ATTEMPTS: usize = 5;
+
+fn invalid_state() -> AppError {
+    AppError::Validation("Invalid or expired sign-in, please try again".to_string())
+}
+
+/// A username from the provider's handle, or the email's local part: lowercase
+/// letters, digits, `_` and `.`
+fn base_username(profile: &ProviderProfile) -> String {
+    let source = profile
+        .username
+        .as_deref()
+        .or_else(|| profile.email.as_deref()?.split('@').next())
+        .unwrap_or_default();
+    let username: String = source
+        .chars()
+        .map(|c| c.to_ascii_lowercase())
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '.')
+        .take(24)
+        .collect();
+    if username.is_empty() {
+        "brawler".to_string()
+    } else {
+        username
+    }
+}
+
+/// Sign-in and account linking through outside OAuth2/OIDC providers
+pub struct OAuthUseCase<T>
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    brawler_repository: Arc<T>,
+    identities: Arc<dyn BrawlerIdentityRepository>,
+    sessions: Arc<dyn SessionRepository>,
+    totp_repository: Arc<dyn TotpRepository>,
+    providers: Vec<Arc<dyn IdentityProvider>>,
+}
+
+impl<T> OAuthUseCase<T>
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    pub fn new(
+        brawler_repository: Arc<T>,
+        identities: Arc<dyn BrawlerIdentityRepository>,
+        sessions: Arc<dyn SessionRepository>,
+        totp_repository: Arc<dyn TotpRepository>,
+        providers: Vec<Arc<dyn IdentityProvider>>,
+    ) -> Self {
+        Self {
+            brawler_repository,
+            identities,
+            sessions,
+            totp_repository,
+            providers,
+        }
+    }
+
+    fn provider(&self, name: &str) -> AppResult<&Arc<dyn IdentityProvider>> {
+        self.providers
+            .iter()
+            .find(|provider| provider.name() == name)
+            .ok_or_else(|| AppError::NotFound(format!("Sign-in with {name} is not available")))
+    }
+
+    pub fn providers(&self) -> Vec<String> {
+        self.providers
+            .iter()
+            .map(|provider| provider.name().to_string())
+            .collect()
+    }
+
+    /// Starts a sign-in at `provider`. With `link_brawler_id` the callback links
+    /// the account to that brawler instead of logging in. Also returns the
+    /// state's nonce, which the browser has to hand back with the callback.
+    pub fn authorize(
+        &self,
+        provider: &str,
+        link_brawler_id: Option<i32>,
+    ) -> AppResult<(AuthorizeModel, String)> {
+        let provider = self.provider(provider)?;
+        let nonce = secure_token::generate()?;
+        let state = OAuthStateClaims {
+            provider: provider.name().to_string(),
+            link_brawler_id,
+            nonce: nonce.clone(),
+            exp: (Utc::now() + Duration::minutes(STATE_TTL_MINUTES)).timestamp() as usize,
+            iat: Utc::now().timestamp() as usize,
+        }
+        .sign()?;
+        let code_verifier = oauth::code_verifier(&get_jwt_env()?.secret, &nonce);
+
+        let model = AuthorizeModel {
+            authorize_url: provider.authorize_url(&state, &oauth::code_challenge(&code_verifier)),
+            state,
+        };
+        Ok((model, nonce))
+    }
+
+    /// Checks the callback belongs to a sign-in we started, in this browser and
+    /// for the same purpose, and asks the provider who signed in. Without the
+    /// browser check, someone could get a victim signed in to their account by
+    /// sending them their own callback link.
+    async fn callback_profile(
+        &self,
+        model: OAuthCallbackModel,
+        browser_nonce: Option<String>,
+        link_brawler_id: Option<i32>,
+    ) -> AppResult<(OAuthStateClaims, ProviderProfile)> {
+        let secret = get_jwt_env()?.secret;
+        let claims =
+            verify_oauth_state(secret.clone(), model.state).map_err(|_| invalid_state())?;
+        let same_browser = browser_nonce.is_some_and(|nonce| {
+            verify_slices_are_equal(nonce.as_bytes(), claims.nonce.as_bytes()).is_ok()
+        });
+        if !same_browser || claims.link_brawler_id != link_brawler_id {
+            return Err(invalid_state());
+        }
+        let provider = self.provider(&claims.provider)?;
+
+        let code_verifier = oauth::code_verifier(&secret, &claims.nonce);
+        let profile = provider
+            .exchange(&model.code, &code_verifier)
+            .await
+            .map_err(|e| {
+                tracing::warn!("Sign-in with {} failed: {:#}", provider.name(), e);
+                AppError::Validation(format!("Couldn't sign in with {}", provider.name()))
+            })?;
+        Ok((claims, profile))
+    }
+
+    /// Logs in the brawler the provider account is linked to, or signs up a new
+    /// one from its profile
+    pub async fn login(
+        &self,
+        model: OAuthCallbackModel,
+        browser_nonce: Option<String>,
+        client: SessionClient,
+    ) -> AppResult<LoginOutcome> {
+        let (claims, profile) = self.callback_profile(model, browser_nonce, None).await?;
+
+        let brawler_id = match self
+            .identities
+            .find(claims.provider.clone(), profile.subject.clone())
+            .await?
+        {
+            Some(identity) => {
+                self.identities.touch_login(identity.id).await?;
+                identity.brawler_id
+            }
+            None => self.register(claims.provider, profile).await?,
+        };
+
+        let user = self.brawler_repository.find_by_id(brawler_id).await?;
+        if let Some(challenge) = mfa_challenge(self.totp_repository.as_ref(), brawler_id).await? {
+            return Ok(LoginOutcome::MfaRequired(challenge));
+        }
+        let passport = start_session(self.sessions.as_ref(), client, passport_of(user)).await?;
+        Ok(LoginOutcome::Passport(passport))
+    }
+
+    /// The new brawler gets a random password; they can set one through a
+    /// password reset if they want to log in without the provider
+    async fn register(&self, provider: String, profile: ProviderProfile) -> AppResult<i32> {
+        let base = base_username(&profile);
+        let display_name: String = profile
+            .display_name
+            .clone()
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| base.clone())
+            .chars()
+            .take(50)
+            .collect();
+        let identity = ExternalIdentity {
+            provider: provider.clone(),
+            subject: profile.subject.clone(),
+            email: profile.email.clone(),
+            display_name: profile.display_name.clone(),
+        };
+
+        let mut username = base.clone();
+        for _ in 0..USERNAME_ATTEMPTS {
+            let brawler = RegisterExternalBrawlerEntity {
+                username: username.clone(),
+                password: argon2::hash(secure_token::generate()?)?,
+                display_name: display_name.clone(),
+                avatar_url: profile.avatar_url.clone().filter(|url| url.len() <= 512),
+                contact_email: profile.email.clone().filter(|_| profile.email_verified),
+                discord_id: (provider == DISCORD).then(|| profile.subject.clone()),
+            };
+            match self.identities.register(brawler, identity.clone()).await {
+                Ok(brawler_id) => return Ok(brawler_id),
+                Err(e) => match AppError::from(e) {
+                    AppError::Conflict(_) => {
+                        let suffix = secure_token::hash(&secure_token::generate()?);
+                        username = format!("{base}_{}", &suffix[..6]);
+                    }
+                    other => return Err(other),
+                },
+            }
+        }
+        Err(AppError::Conflict(
+            "Couldn't find a free username, please try again".to_string(),
+        ))
+    }
+
+    /// Finishes a sign-in started with `authorize(.., Some(brawler_id))`
+    pub async fn link(
+        &self,
+        brawler_id: i32,
+        model: OAuthCallbackModel,
+        browser_nonce: Option<String>,
+    ) -> AppResult<BrawlerIdentityModel> {
+        let (claims, profile) = self
+            .callback_profile(model, browser_nonce, Some(brawler_id))
+            .await?;
+
+        let identity = self
+            .identities
+            .link(
+                brawler_id,
+                ExternalIdentity {
+                    provider: claims.provider,
+                    subject: profile.subject,
+                    email: profile.email,
+                    display_name: profile.display_name,
+                },
+            )
+            .await?;
+        Ok(BrawlerIdentityModel::from(identity))
+    }
+
+    pub async fn identities(&self, brawler_id: i32) -> AppResult<Vec<BrawlerIdentityModel>> {
+        let identities = self.identities.list(brawler_id).await?;
+        Ok(identities
+            .into_iter()
+            .map(BrawlerIdentityModel::from)
+            .collect())
+    }
+
+    /// The last provider only goes with the password, so the brawler still has a
+    /// way to log in afterwards
+    pub async fn unlink(
+        &self,
+        brawler_id: i32,
+        provider: String,
+        model: UnlinkIdentityModel,
+    ) -> AppResult<()> {
+        let identities = self.identities.list(brawler_id).await?;
+        if identities.len() == 1 && identities[0].provider == provider {
+            let password = model.password.ok_or_else(|| {
+                AppError::Validation(
+                    "Enter your password to unlink your last sign-in provider".to_string(),
+                )
+            })?;
+            let brawler = self.brawler_repository.find_by_id(brawler_id).await?;
+            if !argon2::verify(password, brawler.password)? {
+                return Err(AppError::Forbidden("Password is incorrect".to_string()));
+            }
+        }
+
+        self.identities.unlink(brawler_id, provider).await?;
+        Ok(())
+    }
+}
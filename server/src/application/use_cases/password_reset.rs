@@ -0,0 +1,132 @@
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+
+use crate::{
+    config::config_loader::get_password_reset_env,
+    domain::{
+        entities::password_resets::AddPasswordResetEntity,
+        errors::{AppError, AppResult},
+        repositories::{
+            brawlers::BrawlerRepository, password_resets::PasswordResetRepository,
+            sessions::SessionRepository,
+        },
+        value_objects::password_model::{
+            PasswordResetModel, PasswordResetRequestModel, validate_new_password,
+        },
+    },
+    infrastructure::{
+        argon2,
+        mailer::{Mail, Mailer},
+        secure_token,
+        websocket::manager::ConnectionManager,
+    },
+};
+
+pub struct PasswordResetUseCase<T>
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    brawler_repository: Arc<T>,
+    password_resets: Arc<dyn PasswordResetRepository>,
+    sessions: Arc<dyn SessionRepository>,
+    mailer: Arc<dyn Mailer>,
+    manager: Arc<ConnectionManager>,
+}
+
+impl<T> PasswordResetUseCase<T>
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    pub fn new(
+        brawler_repository: Arc<T>,
+        password_resets: Arc<dyn PasswordResetRepository>,
+        sessions: Arc<dyn SessionRepository>,
+        mailer: Arc<dyn Mailer>,
+        manager: Arc<ConnectionManager>,
+    ) -> Self {
+        Self {
+            brawler_repository,
+            password_resets,
+            sessions,
+            mailer,
+            manager,
+        }
+    }
+
+    /// Mails a reset link to the brawler's contact email. Succeeds the same way
+    /// whether or not the username exists, so it can't be used to probe accounts.
+    pub async fn request_reset(&self, model: PasswordResetRequestModel) -> AppResult<()> {
+        let brawler = match self
+            .brawler_repository
+            .find_by_username(model.username)
+            .await
+        {
+            Ok(brawler) => brawler,
+            Err(e) => {
+                return match AppError::from(e) {
+                    AppError::NotFound(_) => Ok(()),
+                    other => Err(other),
+                };
+            }
+        };
+        let Some(email) = brawler.contact_email.filter(|email| !email.is_empty()) else {
+            tracing::info!(
+                "Password reset asked for brawler {} who has no contact email",
+                brawler.id
+            );
+            return Ok(());
+        };
+
+        let env = get_password_reset_env()?;
+        let token = secure_token::generate()?;
+        self.password_resets
+            .issue(AddPasswordResetEntity {
+                brawler_id: brawler.id,
+                token_hash: secure_token::hash(&token),
+                expires_at: (Utc::now() + Duration::minutes(env.ttl_minutes)).naive_utc(),
+            })
+            .await?;
+
+        let mail = Mail {
+            to: email,
+            subject: "Reset your Vibe Assemble password".to_string(),
+            body: format!(
+                "Hi {},\n\n\
+                 Someone asked to reset the password of your Vibe Assemble account.\n\
+                 Open this link within {} minutes to choose a new one:\n\n\
+                 {}/reset-password?token={}\n\n\
+                 If it wasn't you, ignore this mail; your password stays as it is.\n",
+                brawler.display_name, env.ttl_minutes, env.app_url, token
+            ),
+        };
+        // Failing here would tell the caller the account has an email on file
+        if let Err(e) = self.mailer.send(&mail).await {
+            tracing::error!(
+                "Failed to mail password reset to brawler {}: {:?}",
+                brawler.id,
+                e
+            );
+        }
+        Ok(())
+    }
+
+    /// Sets a new password with a mailed token, then signs the brawler out
+    /// everywhere
+    pub async fn reset_password(&self, model: PasswordResetModel) -> AppResult<()> {
+        validate_new_password(&model.new_password)?;
+        let password_hash = argon2::hash(model.new_password)?;
+
+        let brawler_id = self
+            .password_resets
+            .consume(secure_token::hash(&model.token), password_hash)
+            .await?
+            .ok_or_else(|| AppError::Validation("Invalid or expired reset token".to_string()))?;
+
+        let session_ids = self.sessions.revoke_all_for_brawler(brawler_id).await?;
+        for session_id in session_ids {
+            self.manager.revoke_session(session_id).await;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use crate::domain::{
+    entities::brawlers::BrawlerEntity,
+    errors::AppResult,
+    repositories::{brawlers::BrawlerRepository, friendship_repository::FriendshipRepository},
+};
+use crate::infrastructure::websocket::manager::ConnectionManager;
+use crate::infrastructure::websocket::protocol::{AgentPresence, ServerEvent};
+use std::sync::Arc;
+
+/// Online/offline tracking across a user's tabs and devices. Only the first socket
+/// to open and the last to close change anything, and only friends hear about it.
+pub struct PresenceUseCase {
+    friendship_repo: Arc<dyn FriendshipRepository>,
+    brawler_repo: Arc<dyn BrawlerRepository + Send + Sync>,
+    ws_manager: Arc<ConnectionManager>,
+}
+
+impl PresenceUseCase {
+    pub fn new(
+        friendship_repo: Arc<dyn FriendshipRepository>,
+        brawler_repo: Arc<dyn BrawlerRepository + Send + Sync>,
+        ws_manager: Arc<ConnectionManager>,
+    ) -> Self {
+        Self {
+            friendship_repo,
+            brawler_repo,
+            ws_manager,
+        }
+    }
+
+    pub async fn session_started(&self, user_id: i32) -> AppResult<()> {
+        if !self.ws_manager.session_started(user_id).await {
+            return Ok(());
+        }
+
+        self.brawler_repo.touch_last_seen(user_id).await?;
+        self.notify_friends(
+            user_id,
+            ServerEvent::AgentOnline(AgentPresence {
+                user_id,
+                last_seen_at: None,
+            }),
+        )
+        .await
+    }
+
+    pub async fn session_ended(&self, user_id: i32) -> AppResult<()> {
+        if !self.ws_manager.session_ended(user_id).await {
+            return Ok(());
+        }
+
+        let last_seen_at = self.brawler_repo.touch_last_seen(user_id).await?;
+        self.notify_friends(
+            user_id,
+            ServerEvent::AgentOffline(AgentPresence {
+                user_id,
+                last_seen_at: Some(last_seen_at),
+            }),
+        )
+        .await
+    }
+
+    /// The user's friends that have at least one socket open
+    pub async fn online_friends(&self, user_id: i32) -> AppResult<Vec<BrawlerEntity>> {
+        let friend_ids = self.friendship_repo.list_friends(user_id).await?;
+        let online_ids = self.ws_manager.get_online_users().await;
+        let online_friend_ids = friend_ids
+            .into_iter()
+            .filter(|id| online_ids.contains(id))
+            .collect::<Vec<_>>();
+
+        if online_friend_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(self.brawler_repo.find_many(online_friend_ids).await?)
+    }
+
+    async fn notify_friends(&self, user_id: i32, event: ServerEvent) -> AppResult<()> {
+        for friend_id in self.friendship_repo.list_friends(user_id).await? {
+            self.ws_manager.notify_user(friend_id, event.clone()).await;
+        }
+        Ok(())
+    }
+}
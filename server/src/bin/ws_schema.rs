@@ -0,0 +1,25 @@
+//! Writes the JSON Schemas for the WebSocket protocol into `schemas/`.
+//!
+//! cargo run --bin ws_schema
+
+use std::{fs, path::Path};
+
+use schemars::Schema;
+use server::infrastructure::websocket::protocol::{client_frame_schema, server_frame_schema};
+
+fn write(dir: &Path, name: &str, schema: Schema) -> std::io::Result<()> {
+    let path = dir.join(name);
+    let json = serde_json::to_string_pretty(&schema).expect("schema serializes");
+    fs::write(&path, json + "\n")?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
+
+fn main() -> std::io::Result<()> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas");
+    fs::create_dir_all(&dir)?;
+
+    write(&dir, "ws-server-frame.schema.json", server_frame_schema())?;
+    write(&dir, "ws-client-frame.schema.json", client_frame_schema())?;
+    Ok(())
+}
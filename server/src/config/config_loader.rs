@@ -3,7 +3,11 @@ use std::env;
 use anyhow::Result;
 
 use crate::config::{
-    config_model::{CloudinaryEnv, Database, DotEnvyConfig, JwtEnv, Server},
+    config_model::{
+        AuthCookieEnv, BroadcastBackendKind, CloudinaryEnv, Database, DotEnvyConfig, InviteEnv,
+        JwtEnv, LoginThrottleEnv, MailerEnv, MailerKind, MfaEnv, OAuthEnv, OAuthProviderEnv,
+        OAuthProviderKind, PasswordResetEnv, ProxyEnv, Server, SmtpEnv, SmtpSecurity, WebSocket,
+    },
     stage::Stage,
 };
 
@@ -29,8 +33,25 @@ pub fn load() -> Result<DotEnvyConfig> {
         url: std::env::var("DATABASE_URL")
             .expect("DATABASE_URL is valid")
             .parse()?,
+        max_connections: env_or("DATABASE_MAX_CONNECTIONS", 15)?,
+        min_idle: env_or("DATABASE_MIN_IDLE", 0)?,
+        connection_timeout: env_or("DATABASE_CONNECTION_TIMEOUT", 10)?,
+        idle_timeout: env_or("DATABASE_IDLE_TIMEOUT", 300)?,
     };
 
+    let websocket = WebSocket {
+        heartbeat_interval: env_or("WS_HEARTBEAT_INTERVAL", 30)?,
+        heartbeat_timeout: env_or("WS_HEARTBEAT_TIMEOUT", 90)?,
+        broadcast_backend: BroadcastBackendKind::try_form(
+            &env::var("WS_BROADCAST_BACKEND").unwrap_or_default(),
+        )?,
+    };
+    if websocket.heartbeat_interval == 0
+        || websocket.heartbeat_timeout <= websocket.heartbeat_interval
+    {
+        anyhow::bail!("WS_HEARTBEAT_TIMEOUT must be longer than a non-zero WS_HEARTBEAT_INTERVAL");
+    }
+
     let secret = std::env::var("JWT_USER_SECRET")
         .expect("SECRET is valid")
         .parse()?;
@@ -38,12 +59,25 @@ pub fn load() -> Result<DotEnvyConfig> {
     let config = DotEnvyConfig {
         server,
         database,
+        websocket,
         secret,
     };
 
     Ok(config)
 }
 
+/// Read an optional variable, falling back to `default` when it is not set
+fn env_or<T>(key: &str, default: T) -> Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    match env::var(key) {
+        Ok(value) if !value.is_empty() => Ok(value.parse()?),
+        _ => Ok(default),
+    }
+}
+
 pub fn get_stage() -> Stage {
     dotenvy::dotenv().ok();
 
@@ -56,6 +90,135 @@ pub fn get_jwt_env() -> Result<JwtEnv> {
     Ok(JwtEnv {
         secret: env::var("JWT_USER_SECRET")?,
         ttl: env::var("JWT_TTL")?.parse::<i64>()?,
+        access_ttl_minutes: env_or("JWT_ACCESS_TTL_MINUTES", 15)?,
+    })
+}
+
+pub fn get_auth_cookie_env() -> Result<AuthCookieEnv> {
+    dotenvy::dotenv().ok();
+    Ok(AuthCookieEnv {
+        enabled: env_or("AUTH_COOKIES", false)?,
+        secure: env_or("AUTH_COOKIE_SECURE", true)?,
+    })
+}
+
+/// `TRUSTED_PROXIES` is a comma-separated list of IP addresses
+pub fn get_proxy_env() -> Result<ProxyEnv> {
+    dotenvy::dotenv().ok();
+    let trusted_proxies = env::var("TRUSTED_PROXIES")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+        .map(|ip| {
+            ip.parse()
+                .map_err(|_| anyhow::anyhow!("TRUSTED_PROXIES has an invalid IP address: {ip}"))
+        })
+        .collect::<Result<_>>()?;
+    Ok(ProxyEnv { trusted_proxies })
+}
+
+pub fn get_login_throttle_env() -> Result<LoginThrottleEnv> {
+    dotenvy::dotenv().ok();
+    Ok(LoginThrottleEnv {
+        max_failures: env_or("LOGIN_MAX_FAILURES", 5)?,
+        ip_max_failures: env_or("LOGIN_IP_MAX_FAILURES", 20)?,
+        backoff_base_seconds: env_or("LOGIN_BACKOFF_BASE_SECONDS", 1)?,
+        backoff_max_seconds: env_or("LOGIN_BACKOFF_MAX_SECONDS", 60)?,
+        lockout_minutes: env_or("LOGIN_LOCKOUT_MINUTES", 15)?,
+        window_minutes: env_or("LOGIN_FAILURE_WINDOW_MINUTES", 15)?,
+    })
+}
+
+pub fn get_mfa_env() -> Result<MfaEnv> {
+    dotenvy::dotenv().ok();
+    Ok(MfaEnv {
+        issuer: env_or("MFA_ISSUER", "Vibe Assemble".to_string())?,
+        pending_ttl_minutes: env_or("MFA_PENDING_TTL_MINUTES", 5)?,
+    })
+}
+
+/// Discord has fixed endpoints; the generic OIDC provider needs them all set
+pub fn get_oauth_env() -> Result<OAuthEnv> {
+    dotenvy::dotenv().ok();
+    let optional = |key: &str| env::var(key).ok().filter(|value| !value.is_empty());
+    let app_url: String = env_or("APP_URL", "http://localhost:4200".to_string())?;
+    let mut providers = Vec::new();
+
+    if let Some(client_id) = optional("OAUTH_DISCORD_CLIENT_ID") {
+        providers.push(OAuthProviderEnv {
+            name: "discord".to_string(),
+            kind: OAuthProviderKind::Discord,
+            client_id,
+            client_secret: env::var("OAUTH_DISCORD_CLIENT_SECRET")?,
+            authorize_url: "https://discord.com/oauth2/authorize".to_string(),
+            token_url: "https://discord.com/api/oauth2/token".to_string(),
+            userinfo_url: "https://discord.com/api/users/@me".to_string(),
+            scopes: "identify email".to_string(),
+        });
+    }
+    if let Some(client_id) = optional("OAUTH_OIDC_CLIENT_ID") {
+        providers.push(OAuthProviderEnv {
+            name: env_or("OAUTH_OIDC_NAME", "oidc".to_string())?,
+            kind: OAuthProviderKind::Oidc,
+            client_id,
+            client_secret: env::var("OAUTH_OIDC_CLIENT_SECRET")?,
+            authorize_url: env::var("OAUTH_OIDC_AUTHORIZE_URL")?,
+            token_url: env::var("OAUTH_OIDC_TOKEN_URL")?,
+            userinfo_url: env::var("OAUTH_OIDC_USERINFO_URL")?,
+            scopes: env_or("OAUTH_OIDC_SCOPES", "openid profile email".to_string())?,
+        });
+    }
+
+    Ok(OAuthEnv {
+        redirect_uri: env_or(
+            "OAUTH_REDIRECT_URI",
+            format!("{}/oauth/callback", app_url.trim_end_matches('/')),
+        )?,
+        providers,
+    })
+}
+
+pub fn get_password_reset_env() -> Result<PasswordResetEnv> {
+    dotenvy::dotenv().ok();
+    let app_url: String = env_or("APP_URL", "http://localhost:4200".to_string())?;
+    Ok(PasswordResetEnv {
+        ttl_minutes: env_or("PASSWORD_RESET_TTL_MINUTES", 60)?,
+        app_url: app_url.trim_end_matches('/').to_string(),
+    })
+}
+
+pub fn get_mailer_env() -> Result<MailerEnv> {
+    dotenvy::dotenv().ok();
+    let optional = |key: &str| env::var(key).ok().filter(|value| !value.is_empty());
+    Ok(MailerEnv {
+        kind: MailerKind::try_form(&env::var("MAILER").unwrap_or_default())?,
+        from: env_or(
+            "MAIL_FROM",
+            "Vibe Assemble <no-reply@localhost>".to_string(),
+        )?,
+        file: optional("MAILER_FILE"),
+        smtp: SmtpEnv {
+            host: env_or("SMTP_HOST", "localhost".to_string())?,
+            port: env_or("SMTP_PORT", 587)?,
+            username: optional("SMTP_USERNAME"),
+            password: optional("SMTP_PASSWORD"),
+            security: SmtpSecurity::try_form(&env::var("SMTP_SECURITY").unwrap_or_default())?,
+        },
+    })
+}
+
+/// Invite links are signed with their own secret when one is set, so rotating it
+/// kills outstanding links without logging everyone out.
+pub fn get_invite_env() -> Result<InviteEnv> {
+    dotenvy::dotenv().ok();
+    let secret = match env::var("JWT_INVITE_SECRET") {
+        Ok(secret) if !secret.is_empty() => secret,
+        _ => env::var("JWT_USER_SECRET")?,
+    };
+    Ok(InviteEnv {
+        secret,
+        ttl_hours: env_or("INVITE_TTL_HOURS", 72)?,
     })
 }
 
@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 #[derive(Debug, Clone)]
 pub struct Server {
     pub port: u16,
@@ -8,12 +10,200 @@ pub struct Server {
 #[derive(Debug, Clone)]
 pub struct Database {
     pub url: String,
+    pub max_connections: u32,
+    pub min_idle: u32,
+    /// seconds to wait for a free connection before giving up
+    pub connection_timeout: u64,
+    /// seconds an unused connection may stay open
+    pub idle_timeout: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct WebSocket {
+    /// seconds between server pings
+    pub heartbeat_interval: u64,
+    /// seconds of silence after which a socket is dropped
+    pub heartbeat_timeout: u64,
+    pub broadcast_backend: BroadcastBackendKind,
+}
+
+/// Where fan-outs go beyond this instance's own sockets
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BroadcastBackendKind {
+    /// Single instance, nothing is relayed
+    #[default]
+    Memory,
+    /// Relayed to every instance on the same database via LISTEN/NOTIFY
+    Postgres,
+}
+
+impl BroadcastBackendKind {
+    pub fn try_form(kind: &str) -> anyhow::Result<Self> {
+        match kind {
+            "" | "memory" => Ok(Self::Memory),
+            "postgres" => Ok(Self::Postgres),
+            _ => Err(anyhow::anyhow!(
+                "WS_BROADCAST_BACKEND must be `memory` or `postgres`"
+            )),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct JwtEnv {
     pub secret: String,
+    /// days a login lasts, i.e. how long an unused refresh token stays valid
     pub ttl: i64,
+    /// minutes an access token stays valid
+    pub access_ttl_minutes: i64,
+}
+
+/// Cookie session mode: tokens travel in HttpOnly cookies instead of response bodies
+#[derive(Debug, Clone)]
+pub struct AuthCookieEnv {
+    pub enabled: bool,
+    /// `Secure` attribute; only turn off for plain-http local development
+    pub secure: bool,
+}
+
+/// Reverse proxies whose `X-Forwarded-For` header is believed
+#[derive(Debug, Clone, Default)]
+pub struct ProxyEnv {
+    /// peer addresses of those proxies; none are trusted when empty
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+/// Brute-force limits on login. Each failure delays the next attempt on the same
+/// username or IP exponentially; enough of them lock it out for a while.
+#[derive(Debug, Clone)]
+pub struct LoginThrottleEnv {
+    /// failures on one username before it is locked out
+    pub max_failures: i32,
+    /// failures from one IP, across usernames, before it is locked out
+    pub ip_max_failures: i32,
+    /// seconds the first failure delays the next attempt; doubles per failure
+    pub backoff_base_seconds: i64,
+    /// cap on that delay, in seconds
+    pub backoff_max_seconds: i64,
+    pub lockout_minutes: i64,
+    /// minutes without failures after which the count starts over
+    pub window_minutes: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct MfaEnv {
+    /// Name authenticator apps list the account under
+    pub issuer: String,
+    /// minutes between a correct password and the code that completes the login
+    pub pending_ttl_minutes: i64,
+}
+
+/// Sign-in through outside accounts. A provider is offered once its client id is
+/// set.
+#[derive(Debug, Clone)]
+pub struct OAuthEnv {
+    /// Where providers send the browser back, the client's `/oauth/callback`
+    pub redirect_uri: String,
+    pub providers: Vec<OAuthProviderEnv>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OAuthProviderEnv {
+    /// Stored with each linked identity, so renaming it unlinks everyone
+    pub name: String,
+    pub kind: OAuthProviderKind,
+    pub client_id: String,
+    pub client_secret: String,
+    pub authorize_url: String,
+    pub token_url: String,
+    pub userinfo_url: String,
+    /// space separated
+    pub scopes: String,
+}
+
+/// How a provider's userinfo response reads
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OAuthProviderKind {
+    Discord,
+    /// Standard OpenID Connect claims
+    Oidc,
+}
+
+#[derive(Debug, Clone)]
+pub struct PasswordResetEnv {
+    /// minutes a reset link stays valid
+    pub ttl_minutes: i64,
+    /// Where the client is served; reset links point at `{app_url}/reset-password`
+    pub app_url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MailerEnv {
+    pub kind: MailerKind,
+    /// `From` address of every mail
+    pub from: String,
+    /// Log mailer only: append mails to this file instead of the log
+    pub file: Option<String>,
+    pub smtp: SmtpEnv,
+}
+
+/// How outgoing mail is delivered
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum MailerKind {
+    /// Written to the log or a file, for local development
+    #[default]
+    Log,
+    Smtp,
+}
+
+impl MailerKind {
+    pub fn try_form(kind: &str) -> anyhow::Result<Self> {
+        match kind {
+            "" | "log" => Ok(Self::Log),
+            "smtp" => Ok(Self::Smtp),
+            _ => Err(anyhow::anyhow!("MAILER must be `log` or `smtp`")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpEnv {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub security: SmtpSecurity,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS, usually port 587
+    #[default]
+    StartTls,
+    /// TLS from the first byte, usually port 465
+    Tls,
+    /// No encryption, for local catch-all servers only
+    None,
+}
+
+impl SmtpSecurity {
+    pub fn try_form(security: &str) -> anyhow::Result<Self> {
+        match security {
+            "" | "starttls" => Ok(Self::StartTls),
+            "tls" => Ok(Self::Tls),
+            "none" => Ok(Self::None),
+            _ => Err(anyhow::anyhow!(
+                "SMTP_SECURITY must be `starttls`, `tls` or `none`"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InviteEnv {
+    pub secret: String,
+    /// hours an invite link stays valid when the chief doesn't pick an expiry
+    pub ttl_hours: i64,
 }
 
 #[derive(Debug, Clone)]
@@ -27,6 +217,7 @@ pub struct CloudinaryEnv {
 pub struct DotEnvyConfig {
     pub server: Server,
     pub database: Database,
+    pub websocket: WebSocket,
     pub secret: String,
     // pub max_crew_per_mission: u32,
 }
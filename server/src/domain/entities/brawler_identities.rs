@@ -0,0 +1,28 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::infrastructure::database::schema::brawler_identities;
+
+#[derive(Debug, Clone, Identifiable, Selectable, Queryable)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = brawler_identities)]
+pub struct BrawlerIdentityEntity {
+    pub id: i32,
+    pub brawler_id: i32,
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub display_name: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_login_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = brawler_identities)]
+pub struct AddBrawlerIdentityEntity {
+    pub brawler_id: i32,
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub display_name: Option<String>,
+}
@@ -1,4 +1,6 @@
-use crate::infrastructure::database::schema::brawlers;
+use crate::{
+    domain::value_objects::brawler_role::BrawlerRole, infrastructure::database::schema::brawlers,
+};
 use chrono::NaiveDateTime;
 use diesel::{Selectable, prelude::*};
 use serde::Serialize;
@@ -20,6 +22,16 @@ pub struct BrawlerEntity {
     pub contact_email: Option<String>,
     pub instagram: Option<String>,
     pub facebook: Option<String>,
+    /// Last time the brawler's presence changed; `None` until they first connect
+    pub last_seen_at: Option<NaiveDateTime>,
+    pub role: String,
+}
+
+impl BrawlerEntity {
+    /// The column is constrained to known roles; anything else counts as a plain user
+    pub fn role(&self) -> BrawlerRole {
+        self.role.parse().unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone, Insertable)]
@@ -29,3 +41,15 @@ pub struct RegisterBrawlerEntity {
     pub password: String,
     pub display_name: String,
 }
+
+/// A brawler signing up through an outside provider, filled in from its profile
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = brawlers)]
+pub struct RegisterExternalBrawlerEntity {
+    pub username: String,
+    pub password: String,
+    pub display_name: String,
+    pub avatar_url: Option<String>,
+    pub contact_email: Option<String>,
+    pub discord_id: Option<String>,
+}
@@ -0,0 +1,26 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::infrastructure::database::schema::{login_lockouts, login_throttles};
+
+#[derive(Debug, Clone, Selectable, Queryable)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = login_throttles)]
+pub struct LoginThrottleEntity {
+    pub scope: String,
+    pub subject: String,
+    pub failures: i32,
+    pub last_failed_at: NaiveDateTime,
+    pub locked_until: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = login_lockouts)]
+pub struct AddLoginLockoutEntity {
+    pub scope: String,
+    pub subject: String,
+    pub brawler_id: Option<i32>,
+    pub ip: Option<String>,
+    pub failures: i32,
+    pub locked_until: NaiveDateTime,
+}
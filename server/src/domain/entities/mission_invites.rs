@@ -0,0 +1,48 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::infrastructure::database::schema::{mission_invitations, mission_invite_links};
+
+#[derive(Debug, Clone, Identifiable, Selectable, Queryable)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = mission_invite_links)]
+pub struct MissionInviteLinkEntity {
+    pub id: i32,
+    pub mission_id: i32,
+    pub created_by: i32,
+    pub max_uses: Option<i32>,
+    pub uses: i32,
+    pub expires_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = mission_invite_links)]
+pub struct AddMissionInviteLinkEntity {
+    pub mission_id: i32,
+    pub created_by: i32,
+    pub max_uses: Option<i32>,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Identifiable, Selectable, Queryable)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = mission_invitations)]
+pub struct MissionInvitationEntity {
+    pub id: i32,
+    pub mission_id: i32,
+    pub inviter_id: i32,
+    pub invitee_id: i32,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = mission_invitations)]
+pub struct AddMissionInvitationEntity {
+    pub mission_id: i32,
+    pub inviter_id: i32,
+    pub invitee_id: i32,
+}
@@ -21,6 +21,8 @@ pub struct MissionEntity {
     pub scheduled_at: Option<NaiveDateTime>,
     pub location: Option<String>,
     pub category: String,
+    pub join_policy: String,
+    pub is_private: bool,
 }
 
 impl MissionEntity {
@@ -41,6 +43,8 @@ impl MissionEntity {
             location: self.location.clone(),
             deleted_at: self.deleted_at,
             category: self.category.clone(),
+            join_policy: self.join_policy.clone(),
+            is_private: self.is_private,
         }
     }
 }
@@ -56,6 +60,8 @@ pub struct AddMissionEntity {
     pub scheduled_at: Option<NaiveDateTime>,
     pub location: Option<String>,
     pub category: String,
+    pub join_policy: String,
+    pub is_private: bool,
 }
 
 #[derive(Debug, Clone, AsChangeset)]
@@ -68,4 +74,6 @@ pub struct EditMissionEntity {
     pub scheduled_at: Option<NaiveDateTime>,
     pub location: Option<String>,
     pub category: Option<String>,
+    pub join_policy: Option<String>,
+    pub is_private: Option<bool>,
 }
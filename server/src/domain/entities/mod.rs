@@ -1,6 +1,14 @@
+pub mod access_tokens;
+pub mod brawler_identities;
 pub mod brawlers;
 pub mod crew_memberships;
 pub mod friendships;
+pub mod login_throttles;
+pub mod mission_invites;
+pub mod mission_waitlist;
 pub mod missions;
 pub mod notifications;
+pub mod password_resets;
 pub mod private_messages;
+pub mod sessions;
+pub mod totp;
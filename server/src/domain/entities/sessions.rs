@@ -0,0 +1,59 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::infrastructure::database::schema::{refresh_tokens, sessions};
+
+#[derive(Debug, Clone, Identifiable, Selectable, Queryable)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = sessions)]
+pub struct SessionEntity {
+    pub id: i32,
+    pub brawler_id: i32,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = sessions)]
+pub struct AddSessionEntity {
+    pub brawler_id: i32,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+}
+
+#[derive(Debug, Clone, Identifiable, Selectable, Queryable)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = refresh_tokens)]
+pub struct RefreshTokenEntity {
+    pub id: i32,
+    pub brawler_id: i32,
+    pub session_id: i32,
+    pub token_hash: String,
+    pub expires_at: NaiveDateTime,
+    pub used_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = refresh_tokens)]
+pub struct AddRefreshTokenEntity {
+    pub brawler_id: i32,
+    pub session_id: i32,
+    pub token_hash: String,
+    pub expires_at: NaiveDateTime,
+}
+
+/// What became of a refresh token presented for rotation
+#[derive(Debug, Clone, PartialEq)]
+pub enum RefreshRotation {
+    /// The token was current; its successor has been stored
+    Rotated { brawler_id: i32, session_id: i32 },
+    /// The token had already been rotated, so someone else holds a copy. Its
+    /// session has been revoked.
+    Reused { brawler_id: i32, session_id: i32 },
+    /// Unknown, expired or its session is revoked
+    Rejected,
+}
@@ -0,0 +1,22 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::infrastructure::database::schema::brawler_totp;
+
+#[derive(Debug, Clone, Selectable, Queryable)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = brawler_totp)]
+pub struct BrawlerTotpEntity {
+    pub brawler_id: i32,
+    pub secret: String,
+    pub confirmed_at: Option<NaiveDateTime>,
+    pub last_used_step: Option<i64>,
+    pub created_at: NaiveDateTime,
+}
+
+impl BrawlerTotpEntity {
+    /// Enrolled and confirmed with a first code
+    pub fn is_enabled(&self) -> bool {
+        self.confirmed_at.is_some()
+    }
+}
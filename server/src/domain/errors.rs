@@ -0,0 +1,92 @@
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use thiserror::Error;
+
+pub type AppResult<T> = std::result::Result<T, AppError>;
+
+/// Error returned by use cases and repositories.
+///
+/// Each variant maps to one HTTP status and one stable `code` in the JSON error body,
+/// so the client can tell "Mission is full" apart from a database outage.
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Unauthorized(String),
+    #[error("{0}")]
+    Forbidden(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    Validation(String),
+    #[error("{0}")]
+    TooManyRequests(String),
+    #[error(transparent)]
+    Internal(anyhow::Error),
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Unauthorized(_) => "UNAUTHORIZED",
+            AppError::Forbidden(_) => "FORBIDDEN",
+            AppError::Conflict(_) => "CONFLICT",
+            AppError::Validation(_) => "VALIDATION_ERROR",
+            AppError::TooManyRequests(_) => "TOO_MANY_REQUESTS",
+            AppError::Internal(_) => "INTERNAL_ERROR",
+        }
+    }
+}
+
+impl From<DieselError> for AppError {
+    fn from(err: DieselError) -> Self {
+        match err {
+            DieselError::NotFound => AppError::NotFound("Record not found".to_string()),
+            // Postgres' own message names tables and constraints; it goes to the log only
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, info) => {
+                tracing::warn!(
+                    "Unique violation on {:?}: {}",
+                    info.constraint_name(),
+                    info.message()
+                );
+                AppError::Conflict("That already exists".to_string())
+            }
+            DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, info) => {
+                tracing::warn!(
+                    "Foreign key violation on {:?}: {}",
+                    info.constraint_name(),
+                    info.message()
+                );
+                AppError::Validation("Refers to something that doesn't exist".to_string())
+            }
+            other => AppError::Internal(other.into()),
+        }
+    }
+}
+
+impl From<diesel::r2d2::PoolError> for AppError {
+    fn from(err: diesel::r2d2::PoolError) -> Self {
+        AppError::Internal(err.into())
+    }
+}
+
+impl From<tokio::task::JoinError> for AppError {
+    fn from(err: tokio::task::JoinError) -> Self {
+        AppError::Internal(err.into())
+    }
+}
+
+impl From<anyhow::Error> for AppError {
+    fn from(err: anyhow::Error) -> Self {
+        // Repositories still speak anyhow; recover the typed cause when there is one.
+        let err = match err.downcast::<AppError>() {
+            Ok(app_error) => return app_error,
+            Err(err) => err,
+        };
+        match err.downcast::<DieselError>() {
+            Ok(diesel_error) => diesel_error.into(),
+            Err(err) => AppError::Internal(err),
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::domain::entities::access_tokens::{AccessTokenEntity, AddAccessTokenEntity};
+
+#[async_trait]
+pub trait AccessTokenRepository: Send + Sync {
+    async fn create(&self, token: AddAccessTokenEntity) -> Result<AccessTokenEntity>;
+    /// Neither revoked nor expired, newest first
+    async fn list_active(&self, brawler_id: i32) -> Result<Vec<AccessTokenEntity>>;
+    /// NotFound unless it is the brawler's and still active
+    async fn revoke(&self, brawler_id: i32, token_id: i32) -> Result<()>;
+    /// The active token with that hash, with its last use bumped to now
+    async fn authenticate(&self, token_hash: String) -> Result<Option<AccessTokenEntity>>;
+}
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::domain::{
+    entities::{
+        brawler_identities::BrawlerIdentityEntity, brawlers::RegisterExternalBrawlerEntity,
+    },
+    value_objects::oauth_model::ExternalIdentity,
+};
+
+#[async_trait]
+pub trait BrawlerIdentityRepository: Send + Sync {
+    async fn find(
+        &self,
+        provider: String,
+        subject: String,
+    ) -> Result<Option<BrawlerIdentityEntity>>;
+    async fn list(&self, brawler_id: i32) -> Result<Vec<BrawlerIdentityEntity>>;
+    /// Creates the brawler and links the identity to it in one go. Conflict when
+    /// the username or the identity is taken.
+    async fn register(
+        &self,
+        brawler: RegisterExternalBrawlerEntity,
+        identity: ExternalIdentity,
+    ) -> Result<i32>;
+    /// Conflict when the identity belongs to someone else or the brawler already
+    /// has one from that provider. A Discord identity also fills `discord_id`.
+    async fn link(
+        &self,
+        brawler_id: i32,
+        identity: ExternalIdentity,
+    ) -> Result<BrawlerIdentityEntity>;
+    /// NotFound when nothing from that provider is linked. Clears `discord_id`
+    /// when it came from the identity.
+    async fn unlink(&self, brawler_id: i32, provider: String) -> Result<()>;
+    async fn touch_login(&self, identity_id: i32) -> Result<()>;
+}
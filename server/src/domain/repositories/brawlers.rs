@@ -2,14 +2,15 @@ use crate::{
     domain::{
         entities::brawlers::{BrawlerEntity, RegisterBrawlerEntity},
         value_objects::{
-            base64_img::Base64Img, brawler_model::UpdateBrawlerModel, mission_model::MissionModel,
-            uploaded_img::UploadedImg,
+            base64_img::Base64Img, brawler_model::UpdateBrawlerModel, brawler_role::BrawlerRole,
+            mission_model::MissionModel, uploaded_img::UploadedImg,
         },
     },
     infrastructure::{cloudinary::UploadImageOptions, jwt::jwt_model::Passport},
 };
 use anyhow::Result;
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
 
 #[async_trait]
 pub trait BrawlerRepository {
@@ -26,4 +27,9 @@ pub trait BrawlerRepository {
     async fn crew_counting(&self, mission_id: i32) -> Result<u32>;
     async fn get_missions(&self, brawler_id: i32) -> Result<Vec<MissionModel>>;
     async fn update_profile(&self, brawler_id: i32, model: UpdateBrawlerModel) -> Result<Passport>;
+    async fn touch_last_seen(&self, brawler_id: i32) -> Result<NaiveDateTime>;
+    async fn update_password(&self, brawler_id: i32, password_hash: String) -> Result<()>;
+    /// Brawlers holding any of `roles`, by username
+    async fn find_by_roles(&self, roles: Vec<BrawlerRole>) -> Result<Vec<BrawlerEntity>>;
+    async fn set_role(&self, brawler_id: i32, role: BrawlerRole) -> Result<()>;
 }
@@ -7,8 +7,10 @@ use crate::domain::{
 
 #[async_trait]
 pub trait CrewOperationRepository {
+    /// Adds the membership only if the mission is open and still has a free slot.
     async fn join(&self, crew_member_ships: CrewMemberShips) -> Result<()>;
-    async fn leave(&self, crew_member_ships: CrewMemberShips) -> Result<()>;
+    /// Files a pending application for a mission that needs the chief's approval.
+    async fn apply(&self, crew_member_ships: CrewMemberShips) -> Result<i32>;
     /// ดึงภารกิจที่ brawler เข้าร่วมอยู่ (เป็น crew member)
     async fn get_my_joined_missions(&self, brawler_id: i32) -> Result<Vec<MissionModel>>;
 }
@@ -0,0 +1,22 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+
+use crate::domain::{
+    entities::login_throttles::{AddLoginLockoutEntity, LoginThrottleEntity},
+    value_objects::login_throttle_model::ThrottleKey,
+};
+
+#[async_trait]
+pub trait LoginThrottleRepository: Send + Sync {
+    /// Rows for whichever of `keys` have recent failures
+    async fn find(&self, keys: Vec<ThrottleKey>) -> Result<Vec<LoginThrottleEntity>>;
+    /// Counts a failed login against `key` and returns its failures so far.
+    /// Failures before `since` are forgotten first.
+    async fn record_failure(&self, key: ThrottleKey, since: NaiveDateTime) -> Result<i32>;
+    /// Locks the lockout's key until `locked_until`, restarting its count, and
+    /// records the lockout in the audit table
+    async fn lock(&self, lockout: AddLoginLockoutEntity) -> Result<()>;
+    /// Forgets the failures counted against `key`
+    async fn clear(&self, key: ThrottleKey) -> Result<()>;
+}
@@ -0,0 +1,32 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::domain::{
+    entities::mission_invites::{
+        AddMissionInvitationEntity, AddMissionInviteLinkEntity, MissionInvitationEntity,
+        MissionInviteLinkEntity,
+    },
+    value_objects::mission_invite_model::MissionInvitationModel,
+};
+
+#[async_trait]
+pub trait MissionInviteRepository {
+    async fn create_link(
+        &self,
+        add_link_entity: AddMissionInviteLinkEntity,
+    ) -> Result<MissionInviteLinkEntity>;
+    async fn links(&self, mission_id: i32) -> Result<Vec<MissionInviteLinkEntity>>;
+    async fn revoke_link(&self, mission_id: i32, link_id: i32) -> Result<()>;
+    /// Makes the brawler crew and counts one use of the link, provided the link is
+    /// not revoked, expired or used up. Capacity rules are the same as a normal join.
+    async fn join_by_link(&self, link_id: i32, brawler_id: i32) -> Result<()>;
+
+    async fn invite(&self, add_invitation_entity: AddMissionInvitationEntity) -> Result<i32>;
+    async fn pending_for_mission(&self, mission_id: i32) -> Result<Vec<MissionInvitationModel>>;
+    async fn pending_for_brawler(&self, brawler_id: i32) -> Result<Vec<MissionInvitationModel>>;
+    /// Makes the invitee crew and marks the invitation accepted.
+    async fn accept(&self, invitation_id: i32, invitee_id: i32) -> Result<MissionInvitationEntity>;
+    async fn decline(&self, invitation_id: i32, invitee_id: i32)
+    -> Result<MissionInvitationEntity>;
+    async fn revoke(&self, mission_id: i32, invitation_id: i32) -> Result<MissionInvitationEntity>;
+}
@@ -1,11 +1,18 @@
 use anyhow::Result;
 use async_trait::async_trait;
 
-use crate::domain::entities::missions::{AddMissionEntity, EditMissionEntity};
+use crate::domain::{
+    entities::missions::{AddMissionEntity, EditMissionEntity},
+    value_objects::mission_application_model::MissionApplicationModel,
+};
 
 #[async_trait]
 pub trait MissionManagementRepository {
     async fn add(&self, add_mission_entity: AddMissionEntity) -> Result<i32>;
     async fn edit(&self, mission_id: i32, edit_mission_entity: EditMissionEntity) -> Result<i32>;
-    async fn remove(&self, mission_id: i32, chief_id: i32) -> Result<()>;
+    async fn pending_applications(&self, mission_id: i32) -> Result<Vec<MissionApplicationModel>>;
+    /// Adds the applicant to the crew and returns their brawler id.
+    async fn accept_application(&self, mission_id: i32, application_id: i32) -> Result<i32>;
+    /// Returns the applicant's brawler id.
+    async fn reject_application(&self, mission_id: i32, application_id: i32) -> Result<i32>;
 }
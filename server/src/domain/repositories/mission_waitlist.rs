@@ -0,0 +1,20 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::domain::{
+    entities::mission_waitlist::AddMissionWaitlistEntity,
+    value_objects::mission_waitlist_model::WaitlistPositionModel,
+};
+
+#[async_trait]
+pub trait MissionWaitlistRepository {
+    /// Queues the brawler only while the mission is open and full.
+    async fn join(&self, add_waitlist_entity: AddMissionWaitlistEntity) -> Result<()>;
+    /// Returns how many entries were removed.
+    async fn leave(&self, mission_id: i32, brawler_id: i32) -> Result<usize>;
+    async fn position(
+        &self,
+        mission_id: i32,
+        brawler_id: i32,
+    ) -> Result<Option<WaitlistPositionModel>>;
+}
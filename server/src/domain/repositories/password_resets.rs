@@ -0,0 +1,15 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::domain::entities::password_resets::AddPasswordResetEntity;
+
+#[async_trait]
+pub trait PasswordResetRepository: Send + Sync {
+    /// Stores a new reset token, replacing the brawler's unused ones so only the
+    /// latest link works
+    async fn issue(&self, add_password_reset_entity: AddPasswordResetEntity) -> Result<()>;
+    /// Uses up the token behind `token_hash` and sets the brawler's password in
+    /// the same transaction. Returns the brawler id, or `None` when the token is
+    /// unknown, used or expired.
+    async fn consume(&self, token_hash: String, password_hash: String) -> Result<Option<i32>>;
+}
@@ -1,4 +1,4 @@
-use crate::domain::entities::private_messages::PrivateMessage;
+use crate::domain::{entities::private_messages::PrivateMessage, errors::AppResult};
 use async_trait::async_trait;
 
 #[async_trait]
@@ -8,13 +8,13 @@ pub trait PrivateMessageRepository: Send + Sync {
         sender_id: i32,
         receiver_id: i32,
         content: String,
-    ) -> Result<PrivateMessage, String>;
+    ) -> AppResult<PrivateMessage>;
     async fn get_conversation(
         &self,
         user1_id: i32,
         user2_id: i32,
-    ) -> Result<Vec<PrivateMessage>, String>;
-    async fn mark_as_read(&self, receiver_id: i32, sender_id: i32) -> Result<(), String>;
-    async fn get_unread_count(&self, user_id: i32) -> Result<i64, String>;
-    async fn get_recent_chats(&self, user_id: i32) -> Result<Vec<PrivateMessage>, String>;
+    ) -> AppResult<Vec<PrivateMessage>>;
+    async fn mark_as_read(&self, receiver_id: i32, sender_id: i32) -> AppResult<()>;
+    async fn get_unread_count(&self, user_id: i32) -> AppResult<i64>;
+    async fn get_recent_chats(&self, user_id: i32) -> AppResult<Vec<PrivateMessage>>;
 }
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+
+use crate::domain::{
+    entities::sessions::{AddSessionEntity, RefreshRotation, SessionEntity},
+    value_objects::session_model::SessionClient,
+};
+
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
+    /// Opens a session with its first refresh token and returns the session id
+    async fn start(
+        &self,
+        add_session_entity: AddSessionEntity,
+        token_hash: String,
+        expires_at: NaiveDateTime,
+    ) -> Result<i32>;
+    /// Marks the token behind `token_hash` used and stores `next_hash` in its session
+    /// in the same transaction, so a token can only be rotated once.
+    async fn rotate(
+        &self,
+        token_hash: String,
+        next_hash: String,
+        next_expires_at: NaiveDateTime,
+        client: SessionClient,
+    ) -> Result<RefreshRotation>;
+    async fn is_active(&self, session_id: i32) -> Result<bool>;
+    async fn list_active(&self, brawler_id: i32) -> Result<Vec<SessionEntity>>;
+    /// Revokes one of the brawler's sessions; `NotFound` if it isn't theirs or is
+    /// already revoked
+    async fn revoke(&self, brawler_id: i32, session_id: i32) -> Result<()>;
+    /// Revokes the session the refresh token belongs to, returning its id. Unknown
+    /// tokens and revoked sessions give `None`.
+    async fn revoke_by_token(&self, token_hash: String) -> Result<Option<i32>>;
+    /// Returns the ids of the sessions that were still active
+    async fn revoke_all_for_brawler(&self, brawler_id: i32) -> Result<Vec<i32>>;
+    /// Like `revoke_all_for_brawler`, but keeps `keep_session_id` signed in
+    async fn revoke_all_except(&self, brawler_id: i32, keep_session_id: i32) -> Result<Vec<i32>>;
+}
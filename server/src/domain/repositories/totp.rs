@@ -0,0 +1,32 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::domain::entities::totp::BrawlerTotpEntity;
+
+#[async_trait]
+pub trait TotpRepository: Send + Sync {
+    async fn find(&self, brawler_id: i32) -> Result<Option<BrawlerTotpEntity>>;
+    /// Stores a new, unconfirmed secret, replacing an earlier unconfirmed one.
+    /// Conflict when 2FA is already on.
+    async fn begin_enrollment(&self, brawler_id: i32, secret: String) -> Result<()>;
+    /// Turns 2FA on with the step of the first code and the recovery code hashes.
+    /// NotFound without a pending enrollment.
+    async fn confirm(
+        &self,
+        brawler_id: i32,
+        step: i64,
+        recovery_code_hashes: Vec<String>,
+    ) -> Result<()>;
+    /// Records `step` as used. False when it, or a later step, already was.
+    async fn accept_step(&self, brawler_id: i32, step: i64) -> Result<bool>;
+    /// Uses up a recovery code. False when it is unknown or already used.
+    async fn use_recovery_code(&self, brawler_id: i32, code_hash: String) -> Result<bool>;
+    async fn replace_recovery_codes(
+        &self,
+        brawler_id: i32,
+        recovery_code_hashes: Vec<String>,
+    ) -> Result<()>;
+    async fn remaining_recovery_codes(&self, brawler_id: i32) -> Result<i64>;
+    /// Turns 2FA off, dropping the secret and recovery codes
+    async fn disable(&self, brawler_id: i32) -> Result<()>;
+}
@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+
+use crate::domain::{
+    entities::notifications::{AddNotificationEntity, NotificationEntity},
+    errors::AppResult,
+    value_objects::mission_statuses::MissionStatuses,
+};
+
+/// Repository operations bound to one open transaction.
+///
+/// Everything done through the same `UnitOfWork` is committed together when the
+/// closure given to [`TransactionProvider::transaction`] returns `Ok`, and rolled
+/// back when it returns `Err`.
+pub trait UnitOfWork {
+    fn crew_ids(&mut self, mission_id: i32) -> AppResult<Vec<i32>>;
+    fn set_mission_status(
+        &mut self,
+        mission_id: i32,
+        chief_id: i32,
+        status: MissionStatuses,
+    ) -> AppResult<i32>;
+    fn delete_mission(&mut self, mission_id: i32, chief_id: i32) -> AppResult<()>;
+    fn soft_delete_mission(&mut self, mission_id: i32, chief_id: i32) -> AppResult<()>;
+    fn delete_mission_comments(&mut self, mission_id: i32) -> AppResult<()>;
+    /// Takes the brawler out of the crew, holding the mission row until commit
+    /// so a direct join can't race the waitlist for the freed slot.
+    fn remove_crew_member(&mut self, mission_id: i32, brawler_id: i32) -> AppResult<()>;
+    /// Moves the first waitlisted brawler into the crew if the mission is open and
+    /// has a free slot. Returns the promoted brawler's id.
+    fn promote_from_waitlist(&mut self, mission_id: i32) -> AppResult<Option<i32>>;
+    fn add_notification(
+        &mut self,
+        notification: AddNotificationEntity,
+    ) -> AppResult<NotificationEntity>;
+}
+
+#[async_trait]
+pub trait TransactionProvider {
+    async fn transaction<T, F>(&self, f: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut dyn UnitOfWork) -> AppResult<T> + Send + 'static;
+}
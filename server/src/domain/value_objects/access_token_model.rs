@@ -0,0 +1,91 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{entities::access_tokens::AccessTokenEntity, errors::AppError};
+
+/// What a personal access token starts with, so `auth` can tell it from a JWT
+pub const ACCESS_TOKEN_PREFIX: &str = "vat_";
+
+/// What a personal access token may do. Routes name the scope they need; the
+/// rest can't be reached with one.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum TokenScope {
+    /// Reading missions, their chat and profiles
+    #[serde(rename = "read")]
+    Read,
+    /// Creating, editing and running the brawler's own missions
+    #[serde(rename = "missions:write")]
+    MissionsWrite,
+    /// Posting to mission chat
+    #[serde(rename = "comments:write")]
+    CommentsWrite,
+}
+
+impl Display for TokenScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenScope::Read => write!(f, "read"),
+            TokenScope::MissionsWrite => write!(f, "missions:write"),
+            TokenScope::CommentsWrite => write!(f, "comments:write"),
+        }
+    }
+}
+
+impl FromStr for TokenScope {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(TokenScope::Read),
+            "missions:write" => Ok(TokenScope::MissionsWrite),
+            "comments:write" => Ok(TokenScope::CommentsWrite),
+            other => Err(AppError::Validation(format!("Unknown scope: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAccessTokenModel {
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    /// Never expires when left out
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessTokenModel {
+    pub id: i32,
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+impl From<AccessTokenEntity> for AccessTokenModel {
+    fn from(token: AccessTokenEntity) -> Self {
+        Self {
+            id: token.id,
+            name: token.name,
+            // The column only holds known scopes
+            scopes: token
+                .scopes
+                .iter()
+                .filter_map(|scope| scope.parse().ok())
+                .collect(),
+            created_at: token.created_at,
+            last_used_at: token.last_used_at,
+            expires_at: token.expires_at,
+        }
+    }
+}
+
+/// Answer to minting a token; `token` is shown only this once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedAccessTokenModel {
+    pub token: String,
+    #[serde(flatten)]
+    pub access_token: AccessTokenModel,
+}
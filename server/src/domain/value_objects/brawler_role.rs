@@ -0,0 +1,76 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{entities::brawlers::BrawlerEntity, errors::AppError};
+
+/// Site-wide rank of a brawler, lowest first, so `>=` reads as "at least"
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum BrawlerRole {
+    #[default]
+    User,
+    /// May do chief-only things on any mission, like kicking crew or clearing chat
+    Moderator,
+    /// A moderator who can also change roles
+    Admin,
+}
+
+impl BrawlerRole {
+    pub fn can_moderate(self) -> bool {
+        self >= BrawlerRole::Moderator
+    }
+}
+
+impl Display for BrawlerRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BrawlerRole::User => write!(f, "user"),
+            BrawlerRole::Moderator => write!(f, "moderator"),
+            BrawlerRole::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl FromStr for BrawlerRole {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "user" => Ok(BrawlerRole::User),
+            "moderator" => Ok(BrawlerRole::Moderator),
+            "admin" => Ok(BrawlerRole::Admin),
+            other => Err(AppError::Validation(format!("Unknown role: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetRoleModel {
+    pub role: BrawlerRole,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleFilter {
+    /// Moderators and admins when left out
+    pub role: Option<BrawlerRole>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrawlerRoleModel {
+    pub id: i32,
+    pub username: String,
+    pub display_name: String,
+    pub role: BrawlerRole,
+}
+
+impl From<BrawlerEntity> for BrawlerRoleModel {
+    fn from(brawler: BrawlerEntity) -> Self {
+        Self {
+            role: brawler.role(),
+            id: brawler.id,
+            username: brawler.username,
+            display_name: brawler.display_name,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// Who may become crew of a mission without the chief stepping in.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinPolicy {
+    #[default]
+    Open,
+    ApprovalRequired,
+    InviteOnly,
+}
+
+impl Display for JoinPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JoinPolicy::Open => write!(f, "open"),
+            JoinPolicy::ApprovalRequired => write!(f, "approval_required"),
+            JoinPolicy::InviteOnly => write!(f, "invite_only"),
+        }
+    }
+}
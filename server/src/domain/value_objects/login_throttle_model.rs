@@ -0,0 +1,39 @@
+/// What failed logins are counted against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrottleScope {
+    Username,
+    Ip,
+}
+
+impl ThrottleScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThrottleScope::Username => "username",
+            ThrottleScope::Ip => "ip",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThrottleKey {
+    pub scope: ThrottleScope,
+    pub subject: String,
+}
+
+impl ThrottleKey {
+    /// Counted whether or not the account exists, so lockouts don't reveal that.
+    /// Case-folded so `Alice` and `alice` share one counter.
+    pub fn username(username: &str) -> Self {
+        Self {
+            scope: ThrottleScope::Username,
+            subject: username.trim().to_lowercase().chars().take(255).collect(),
+        }
+    }
+
+    pub fn ip(ip: &str) -> Self {
+        Self {
+            scope: ThrottleScope::Ip,
+            subject: ip.to_string(),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaStatusModel {
+    pub enabled: bool,
+    pub recovery_codes_left: i64,
+}
+
+/// What an authenticator app needs; `otpauth_uri` is usually shown as a QR code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaEnrollmentModel {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaCodeModel {
+    pub code: String,
+}
+
+/// Shown once; only their hashes are kept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryCodesModel {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisableMfaModel {
+    pub password: String,
+    /// An authenticator code or a recovery code
+    pub code: String,
+}
+
+/// Second step of a login: the pending token and an authenticator or recovery code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaLoginModel {
+    pub mfa_token: String,
+    pub code: String,
+}
+
+/// Login answer in place of a passport when a code is still needed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaChallengeModel {
+    pub mfa_required: bool,
+    pub mfa_token: String,
+    /// seconds left to post the code
+    pub expires_in: i64,
+}
@@ -0,0 +1,52 @@
+use std::fmt::Display;
+
+use chrono::NaiveDateTime;
+use diesel::{
+    QueryableByName,
+    sql_types::{Int4, Timestamp, Varchar},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ApplicationStatuses {
+    #[default]
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+impl Display for ApplicationStatuses {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApplicationStatuses::Pending => write!(f, "pending"),
+            ApplicationStatuses::Accepted => write!(f, "accepted"),
+            ApplicationStatuses::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, QueryableByName)]
+pub struct MissionApplicationModel {
+    #[diesel(sql_type = Int4)]
+    pub id: i32,
+    #[diesel(sql_type = Int4)]
+    pub mission_id: i32,
+    #[diesel(sql_type = Int4)]
+    pub brawler_id: i32,
+    #[diesel(sql_type = Varchar)]
+    pub brawler_display_name: String,
+    #[diesel(sql_type = Varchar)]
+    pub brawler_avatar_url: String,
+    #[diesel(sql_type = Varchar)]
+    pub status: String,
+    #[diesel(sql_type = Timestamp)]
+    pub created_at: NaiveDateTime,
+}
+
+/// What `POST /crew/join/{id}` ended up doing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JoinOutcome {
+    Joined,
+    /// Mission needs the chief's approval; holds the new application id.
+    Applied(i32),
+}
@@ -3,9 +3,10 @@ use diesel::{
     QueryableByName,
     sql_types::{Int4, Text, Timestamp, Varchar},
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, QueryableByName)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, QueryableByName, JsonSchema)]
 pub struct MissionCommentModel {
     #[diesel(sql_type = Int4)]
     pub id: i32,
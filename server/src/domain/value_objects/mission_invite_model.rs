@@ -0,0 +1,75 @@
+use std::fmt::Display;
+
+use chrono::NaiveDateTime;
+use diesel::{
+    QueryableByName,
+    sql_types::{Int4, Timestamp, Varchar},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub enum InvitationStatuses {
+    #[default]
+    Pending,
+    Accepted,
+    Declined,
+    Revoked,
+}
+
+impl Display for InvitationStatuses {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvitationStatuses::Pending => write!(f, "pending"),
+            InvitationStatuses::Accepted => write!(f, "accepted"),
+            InvitationStatuses::Declined => write!(f, "declined"),
+            InvitationStatuses::Revoked => write!(f, "revoked"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateInviteLinkModel {
+    /// Unlimited when not set
+    pub max_uses: Option<i32>,
+    /// Falls back to `INVITE_TTL_HOURS`
+    pub expires_in_hours: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InviteLinkModel {
+    pub id: i32,
+    pub mission_id: i32,
+    pub token: String,
+    pub max_uses: Option<i32>,
+    pub uses: i32,
+    pub expires_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendInvitationModel {
+    pub brawler_id: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, QueryableByName)]
+pub struct MissionInvitationModel {
+    #[diesel(sql_type = Int4)]
+    pub id: i32,
+    #[diesel(sql_type = Int4)]
+    pub mission_id: i32,
+    #[diesel(sql_type = Varchar)]
+    pub mission_name: String,
+    #[diesel(sql_type = Int4)]
+    pub inviter_id: i32,
+    #[diesel(sql_type = Varchar)]
+    pub inviter_display_name: String,
+    #[diesel(sql_type = Int4)]
+    pub invitee_id: i32,
+    #[diesel(sql_type = Varchar)]
+    pub invitee_display_name: String,
+    #[diesel(sql_type = Varchar)]
+    pub status: String,
+    #[diesel(sql_type = Timestamp)]
+    pub created_at: NaiveDateTime,
+}
@@ -1,13 +1,13 @@
 use chrono::{DateTime, NaiveDateTime, Utc};
 use diesel::{
     QueryableByName,
-    sql_types::{BigInt, Int4, Nullable, Text, Timestamp, Varchar},
+    sql_types::{BigInt, Bool, Int4, Nullable, Text, Timestamp, Varchar},
 };
 use serde::{Deserialize, Serialize};
 
 use crate::domain::{
     entities::missions::{AddMissionEntity, EditMissionEntity},
-    value_objects::mission_statuses::MissionStatuses,
+    value_objects::{join_policy::JoinPolicy, mission_statuses::MissionStatuses},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, QueryableByName)]
@@ -42,6 +42,10 @@ pub struct MissionModel {
     pub deleted_at: Option<NaiveDateTime>,
     #[diesel(sql_type = Varchar)]
     pub category: String,
+    #[diesel(sql_type = Varchar)]
+    pub join_policy: String,
+    #[diesel(sql_type = Bool)]
+    pub is_private: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -52,6 +56,8 @@ pub struct AddMissionModel {
     pub scheduled_at: Option<DateTime<Utc>>,
     pub location: Option<String>,
     pub category: Option<String>,
+    pub join_policy: Option<JoinPolicy>,
+    pub is_private: Option<bool>,
 }
 
 impl AddMissionModel {
@@ -65,6 +71,8 @@ impl AddMissionModel {
             scheduled_at: self.scheduled_at.map(|dt| dt.naive_utc()),
             location: self.location.clone(),
             category: self.category.clone().unwrap_or("Other".to_string()),
+            join_policy: self.join_policy.clone().unwrap_or_default().to_string(),
+            is_private: self.is_private.unwrap_or(false),
         }
     }
 }
@@ -77,6 +85,8 @@ pub struct EditMissionModel {
     pub scheduled_at: Option<DateTime<Utc>>,
     pub location: Option<String>,
     pub category: Option<String>,
+    pub join_policy: Option<JoinPolicy>,
+    pub is_private: Option<bool>,
 }
 
 impl EditMissionModel {
@@ -89,6 +99,8 @@ impl EditMissionModel {
             scheduled_at: self.scheduled_at.map(|dt| dt.naive_utc()),
             location: self.location.clone(),
             category: self.category.clone(),
+            join_policy: self.join_policy.as_ref().map(|p| p.to_string()),
+            is_private: self.is_private,
         }
     }
 }
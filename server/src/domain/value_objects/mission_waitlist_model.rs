@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaitlistPositionModel {
+    pub mission_id: i32,
+    /// 1-based place in the queue
+    pub position: i64,
+    pub total: i64,
+}
@@ -1,7 +1,18 @@
+pub mod access_token_model;
 pub mod base64_img;
 pub mod brawler_model;
+pub mod brawler_role;
+pub mod join_policy;
+pub mod login_throttle_model;
+pub mod mfa_model;
+pub mod mission_application_model;
 pub mod mission_comment_model;
 pub mod mission_filter;
+pub mod mission_invite_model;
 pub mod mission_model;
 pub mod mission_statuses;
+pub mod mission_waitlist_model;
+pub mod oauth_model;
+pub mod password_model;
+pub mod session_model;
 pub mod uploaded_img;
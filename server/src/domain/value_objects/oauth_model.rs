@@ -0,0 +1,57 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::entities::brawler_identities::BrawlerIdentityEntity;
+
+/// The provider whose verified account id also fills `brawlers.discord_id`
+pub const DISCORD: &str = "discord";
+
+/// An account at an outside provider, as it gets linked to a brawler
+#[derive(Debug, Clone)]
+pub struct ExternalIdentity {
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub display_name: Option<String>,
+}
+
+/// Where to send the browser; the client keeps `state` to check the callback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizeModel {
+    pub authorize_url: String,
+    pub state: String,
+}
+
+/// The query string the provider redirected back with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthCallbackModel {
+    pub code: String,
+    pub state: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrawlerIdentityModel {
+    pub provider: String,
+    pub email: Option<String>,
+    pub display_name: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_login_at: Option<NaiveDateTime>,
+}
+
+impl From<BrawlerIdentityEntity> for BrawlerIdentityModel {
+    fn from(identity: BrawlerIdentityEntity) -> Self {
+        Self {
+            provider: identity.provider,
+            email: identity.email,
+            display_name: identity.display_name,
+            created_at: identity.created_at,
+            last_login_at: identity.last_login_at,
+        }
+    }
+}
+
+/// The password is only needed to unlink the last provider
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UnlinkIdentityModel {
+    pub password: Option<String>,
+}
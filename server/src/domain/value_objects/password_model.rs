@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::errors::{AppError, AppResult};
+
+pub const MIN_PASSWORD_LEN: usize = 8;
+
+/// Rules for a password being set, not for one being checked at login
+pub fn validate_new_password(password: &str) -> AppResult<()> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(AppError::Validation(format!(
+            "Password must be at least {} characters",
+            MIN_PASSWORD_LEN
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangePasswordModel {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordResetRequestModel {
+    pub username: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordResetModel {
+    pub token: String,
+    pub new_password: String,
+}
@@ -0,0 +1,36 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::entities::sessions::SessionEntity;
+
+/// Where a login comes from. The user agent is whatever the client sends; the IP
+/// is the peer address, or the one a trusted proxy forwarded.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionClient {
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionModel {
+    pub id: i32,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: NaiveDateTime,
+    /// The session making this request
+    pub current: bool,
+}
+
+impl SessionModel {
+    pub fn from_entity(session: SessionEntity, current_session_id: i32) -> Self {
+        Self {
+            id: session.id,
+            user_agent: session.user_agent,
+            ip: session.ip,
+            created_at: session.created_at,
+            last_used_at: session.last_used_at,
+            current: session.id == current_session_id,
+        }
+    }
+}
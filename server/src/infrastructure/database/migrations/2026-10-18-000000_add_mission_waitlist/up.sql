@@ -0,0 +1,9 @@
+CREATE TABLE mission_waitlist (
+    id SERIAL PRIMARY KEY,
+    mission_id INT NOT NULL REFERENCES missions(id) ON DELETE CASCADE,
+    brawler_id INT NOT NULL REFERENCES brawlers(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT unique_mission_waitlist UNIQUE (mission_id, brawler_id)
+);
+
+CREATE INDEX idx_mission_waitlist_mission_id ON mission_waitlist(mission_id, id);
@@ -0,0 +1,2 @@
+DROP TABLE mission_applications;
+ALTER TABLE missions DROP COLUMN join_policy;
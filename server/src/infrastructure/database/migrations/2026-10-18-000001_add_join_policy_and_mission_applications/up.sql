@@ -0,0 +1,15 @@
+ALTER TABLE missions ADD COLUMN join_policy VARCHAR(32) NOT NULL DEFAULT 'open';
+
+CREATE TABLE mission_applications (
+    id SERIAL PRIMARY KEY,
+    mission_id INT NOT NULL REFERENCES missions(id) ON DELETE CASCADE,
+    brawler_id INT NOT NULL REFERENCES brawlers(id) ON DELETE CASCADE,
+    status VARCHAR(32) NOT NULL DEFAULT 'pending',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- One open application per brawler; they may re-apply after a rejection
+CREATE UNIQUE INDEX unique_pending_mission_application
+    ON mission_applications(mission_id, brawler_id)
+    WHERE status = 'pending';
@@ -0,0 +1,3 @@
+DROP TABLE mission_invitations;
+DROP TABLE mission_invite_links;
+ALTER TABLE missions DROP COLUMN is_private;
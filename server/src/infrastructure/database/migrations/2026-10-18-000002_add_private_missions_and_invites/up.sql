@@ -0,0 +1,30 @@
+ALTER TABLE missions ADD COLUMN is_private BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Shareable links; the token itself is a signed claim over the row id and expiry
+CREATE TABLE mission_invite_links (
+    id SERIAL PRIMARY KEY,
+    mission_id INT NOT NULL REFERENCES missions(id) ON DELETE CASCADE,
+    created_by INT NOT NULL REFERENCES brawlers(id) ON DELETE CASCADE,
+    max_uses INT,
+    uses INT NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP NOT NULL,
+    revoked_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_mission_invite_links_mission ON mission_invite_links(mission_id);
+
+-- Direct invitations from the chief to a friend
+CREATE TABLE mission_invitations (
+    id SERIAL PRIMARY KEY,
+    mission_id INT NOT NULL REFERENCES missions(id) ON DELETE CASCADE,
+    inviter_id INT NOT NULL REFERENCES brawlers(id) ON DELETE CASCADE,
+    invitee_id INT NOT NULL REFERENCES brawlers(id) ON DELETE CASCADE,
+    status VARCHAR(32) NOT NULL DEFAULT 'pending',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX unique_pending_mission_invitation
+    ON mission_invitations(mission_id, invitee_id)
+    WHERE status = 'pending';
@@ -0,0 +1 @@
+ALTER TABLE brawlers DROP COLUMN last_seen_at;
@@ -0,0 +1 @@
+ALTER TABLE brawlers ADD COLUMN last_seen_at TIMESTAMP;
@@ -0,0 +1,9 @@
+-- Fan-outs too large for a NOTIFY payload (8000 bytes) are parked here and only
+-- their id is sent. Rows are short-lived; publishers prune old ones.
+CREATE TABLE ws_fanout_overflow (
+    id BIGSERIAL PRIMARY KEY,
+    payload TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_ws_fanout_overflow_created_at ON ws_fanout_overflow (created_at);
@@ -0,0 +1,19 @@
+-- One row per issued refresh token. A login starts a family; each refresh marks
+-- the presented token used and issues the next one in the same family, so a used
+-- token coming back means it was copied and the family gets revoked.
+CREATE SEQUENCE refresh_token_families;
+
+CREATE TABLE refresh_tokens (
+    id SERIAL PRIMARY KEY,
+    brawler_id INT NOT NULL REFERENCES brawlers(id) ON DELETE CASCADE,
+    family_id INT NOT NULL DEFAULT nextval('refresh_token_families'),
+    -- SHA-256 of the token; the token itself is never stored
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP,
+    revoked_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_refresh_tokens_brawler ON refresh_tokens(brawler_id);
+CREATE INDEX idx_refresh_tokens_family ON refresh_tokens(family_id);
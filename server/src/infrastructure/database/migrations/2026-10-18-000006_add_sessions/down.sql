@@ -0,0 +1,12 @@
+CREATE SEQUENCE refresh_token_families;
+SELECT setval('refresh_token_families', COALESCE((SELECT MAX(id) FROM sessions), 0) + 1, false);
+
+ALTER TABLE refresh_tokens ADD COLUMN revoked_at TIMESTAMP;
+UPDATE refresh_tokens r SET revoked_at = s.revoked_at FROM sessions s WHERE s.id = r.session_id;
+
+ALTER INDEX idx_refresh_tokens_session RENAME TO idx_refresh_tokens_family;
+ALTER TABLE refresh_tokens DROP CONSTRAINT refresh_tokens_session_id_fkey;
+ALTER TABLE refresh_tokens ALTER COLUMN session_id SET DEFAULT nextval('refresh_token_families');
+ALTER TABLE refresh_tokens RENAME COLUMN session_id TO family_id;
+
+DROP TABLE IF EXISTS sessions;
@@ -0,0 +1,35 @@
+-- A session is one login on one device: what a refresh token family belongs to
+-- and what access tokens name in their `sid` claim. Revoking it ends both.
+CREATE TABLE sessions (
+    id SERIAL PRIMARY KEY,
+    brawler_id INT NOT NULL REFERENCES brawlers(id) ON DELETE CASCADE,
+    user_agent TEXT,
+    ip VARCHAR(64),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- Bumped on login and on every refresh
+    last_used_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    revoked_at TIMESTAMP
+);
+
+CREATE INDEX idx_sessions_brawler ON sessions(brawler_id);
+
+-- Existing token families become sessions with the same ids
+INSERT INTO sessions (id, brawler_id, created_at, last_used_at, revoked_at)
+SELECT family_id,
+       MIN(brawler_id),
+       MIN(created_at),
+       MAX(created_at),
+       CASE WHEN BOOL_AND(revoked_at IS NOT NULL) THEN MAX(revoked_at) END
+FROM refresh_tokens
+GROUP BY family_id;
+
+SELECT setval('sessions_id_seq', COALESCE((SELECT MAX(id) FROM sessions), 0) + 1, false);
+
+ALTER TABLE refresh_tokens RENAME COLUMN family_id TO session_id;
+ALTER TABLE refresh_tokens ALTER COLUMN session_id DROP DEFAULT;
+ALTER TABLE refresh_tokens
+    ADD CONSTRAINT refresh_tokens_session_id_fkey
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE;
+ALTER TABLE refresh_tokens DROP COLUMN revoked_at;
+ALTER INDEX idx_refresh_tokens_family RENAME TO idx_refresh_tokens_session;
+DROP SEQUENCE refresh_token_families;
@@ -0,0 +1,12 @@
+-- Single-use tokens mailed to a brawler who forgot their password. Only the
+-- SHA-256 of each token is stored.
+CREATE TABLE password_reset_tokens (
+    id SERIAL PRIMARY KEY,
+    brawler_id INT NOT NULL REFERENCES brawlers(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_password_reset_tokens_brawler ON password_reset_tokens(brawler_id);
@@ -0,0 +1,24 @@
+-- Recent failed logins, one row per username and one per client IP. A row
+-- holds the failures since the last success, lockout or quiet period.
+CREATE TABLE login_throttles (
+    scope VARCHAR(16) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    failures INT NOT NULL DEFAULT 0,
+    last_failed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    locked_until TIMESTAMP,
+    PRIMARY KEY (scope, subject)
+);
+
+-- Audit trail: one row each time a username or IP gets locked out
+CREATE TABLE login_lockouts (
+    id SERIAL PRIMARY KEY,
+    scope VARCHAR(16) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    brawler_id INT REFERENCES brawlers(id) ON DELETE SET NULL,
+    ip VARCHAR(64),
+    failures INT NOT NULL,
+    locked_until TIMESTAMP NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_login_lockouts_subject ON login_lockouts(scope, subject);
@@ -0,0 +1,20 @@
+-- TOTP second factor. A row exists from enrollment on; 2FA is on once
+-- `confirmed_at` is set by a first valid code. The secret has to be readable to
+-- check codes, so it is stored as is.
+CREATE TABLE brawler_totp (
+    brawler_id INT PRIMARY KEY REFERENCES brawlers(id) ON DELETE CASCADE,
+    secret VARCHAR(64) NOT NULL,
+    confirmed_at TIMESTAMP,
+    -- Time step of the last accepted code; older and equal steps are refused
+    last_used_step BIGINT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Single-use codes for a lost authenticator, stored as SHA-256 hashes
+CREATE TABLE totp_recovery_codes (
+    id SERIAL PRIMARY KEY,
+    brawler_id INT NOT NULL REFERENCES brawlers(id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL,
+    used_at TIMESTAMP,
+    UNIQUE (brawler_id, code_hash)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE brawlers ADD COLUMN role VARCHAR(16) NOT NULL DEFAULT 'user'
+    CONSTRAINT brawlers_role_check CHECK (role IN ('user', 'moderator', 'admin'));
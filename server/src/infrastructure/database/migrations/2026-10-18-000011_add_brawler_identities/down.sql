@@ -0,0 +1 @@
+DROP TABLE brawler_identities;
@@ -0,0 +1,14 @@
+-- Accounts at outside OAuth/OIDC providers a brawler can sign in with. The
+-- subject is the provider's own id for the account, which never changes.
+CREATE TABLE brawler_identities (
+    id SERIAL PRIMARY KEY,
+    brawler_id INT NOT NULL REFERENCES brawlers(id) ON DELETE CASCADE,
+    provider VARCHAR(32) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    display_name VARCHAR(255),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_login_at TIMESTAMP,
+    UNIQUE (provider, subject),
+    UNIQUE (brawler_id, provider)
+);
@@ -0,0 +1,16 @@
+-- Long-lived tokens brawlers mint for scripts and bots. Only the hash of the
+-- token is stored, and each one is limited to the scopes it was minted with.
+CREATE TABLE personal_access_tokens (
+    id SERIAL PRIMARY KEY,
+    brawler_id INT NOT NULL REFERENCES brawlers(id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    scopes TEXT[] NOT NULL
+        CHECK (scopes <@ ARRAY['read', 'missions:write', 'comments:write']::TEXT[]),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP,
+    expires_at TIMESTAMP,
+    revoked_at TIMESTAMP
+);
+
+CREATE INDEX idx_personal_access_tokens_brawler ON personal_access_tokens(brawler_id);
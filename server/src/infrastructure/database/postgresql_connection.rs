@@ -1,19 +1,41 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use anyhow::Result;
 use diesel::{
     PgConnection,
-    r2d2::{ConnectionManager, Pool},
+    r2d2::{ConnectionManager, Pool, PoolError},
 };
+use tokio::task::JoinError;
+
+use crate::config::config_model::Database;
 
 pub type PgPoolSquad = Pool<ConnectionManager<PgConnection>>;
 
-pub fn establish_connection(database_url: &str) -> Result<PgPoolSquad> {
-    let manager = ConnectionManager::<PgConnection>::new(database_url);
+pub fn establish_connection(database: &Database) -> Result<PgPoolSquad> {
+    let manager = ConnectionManager::<PgConnection>::new(&database.url);
     let pool = Pool::builder()
-        .max_size(5)
-        .min_idle(Some(0))
-        .connection_timeout(Duration::from_secs(10))
+        .max_size(database.max_connections)
+        .min_idle(Some(database.min_idle))
+        .connection_timeout(Duration::from_secs(database.connection_timeout))
+        .idle_timeout(Some(Duration::from_secs(database.idle_timeout)))
         .build(manager)?;
     Ok(pool)
 }
+
+/// Check out a connection and run `f` on Tokio's blocking thread pool.
+///
+/// Diesel is synchronous, so every repository goes through here instead of
+/// touching the pool inside an `async fn` and stalling the runtime workers.
+pub async fn with_connection<T, E, F>(db_pool: &Arc<PgPoolSquad>, f: F) -> Result<T, E>
+where
+    F: FnOnce(&mut PgConnection) -> Result<T, E> + Send + 'static,
+    T: Send + 'static,
+    E: From<PoolError> + From<JoinError> + Send + 'static,
+{
+    let db_pool = Arc::clone(db_pool);
+    tokio::task::spawn_blocking(move || {
+        let mut conn = db_pool.get()?;
+        f(&mut conn)
+    })
+    .await?
+}
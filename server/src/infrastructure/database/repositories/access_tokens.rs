@@ -0,0 +1,98 @@
+use anyhow::{Ok, Result};
+use async_trait::async_trait;
+use diesel::{
+    BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl,
+    SelectableHelper,
+    dsl::{now, update},
+    insert_into,
+};
+use std::sync::Arc;
+
+use crate::{
+    domain::{
+        entities::access_tokens::{AccessTokenEntity, AddAccessTokenEntity},
+        errors::AppError,
+        repositories::access_tokens::AccessTokenRepository,
+    },
+    infrastructure::database::{
+        postgresql_connection::{PgPoolSquad, with_connection},
+        schema::personal_access_tokens,
+    },
+};
+
+pub struct AccessTokenPostgres {
+    db_pool: Arc<PgPoolSquad>,
+}
+
+impl AccessTokenPostgres {
+    pub fn new(db_pool: Arc<PgPoolSquad>) -> Self {
+        Self { db_pool }
+    }
+}
+
+#[async_trait]
+impl AccessTokenRepository for AccessTokenPostgres {
+    async fn create(&self, token: AddAccessTokenEntity) -> Result<AccessTokenEntity> {
+        with_connection(&self.db_pool, move |conn| {
+            let created = insert_into(personal_access_tokens::table)
+                .values(token)
+                .returning(AccessTokenEntity::as_returning())
+                .get_result(conn)?;
+            Ok(created)
+        })
+        .await
+    }
+
+    async fn list_active(&self, brawler_id: i32) -> Result<Vec<AccessTokenEntity>> {
+        with_connection(&self.db_pool, move |conn| {
+            let tokens = personal_access_tokens::table
+                .filter(personal_access_tokens::brawler_id.eq(brawler_id))
+                .filter(personal_access_tokens::revoked_at.is_null())
+                .filter(
+                    personal_access_tokens::expires_at
+                        .is_null()
+                        .or(personal_access_tokens::expires_at.gt(now)),
+                )
+                .order(personal_access_tokens::id.desc())
+                .select(AccessTokenEntity::as_select())
+                .load::<AccessTokenEntity>(conn)?;
+            Ok(tokens)
+        })
+        .await
+    }
+
+    async fn revoke(&self, brawler_id: i32, token_id: i32) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            let revoked = update(personal_access_tokens::table.find(token_id))
+                .filter(personal_access_tokens::brawler_id.eq(brawler_id))
+                .filter(personal_access_tokens::revoked_at.is_null())
+                .set(personal_access_tokens::revoked_at.eq(now))
+                .execute(conn)?;
+
+            if revoked == 0 {
+                return Err(AppError::NotFound("Access token not found".to_string()).into());
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn authenticate(&self, token_hash: String) -> Result<Option<AccessTokenEntity>> {
+        with_connection(&self.db_pool, move |conn| {
+            let token = update(personal_access_tokens::table)
+                .filter(personal_access_tokens::token_hash.eq(token_hash))
+                .filter(personal_access_tokens::revoked_at.is_null())
+                .filter(
+                    personal_access_tokens::expires_at
+                        .is_null()
+                        .or(personal_access_tokens::expires_at.gt(now)),
+                )
+                .set(personal_access_tokens::last_used_at.eq(now))
+                .returning(AccessTokenEntity::as_returning())
+                .get_result(conn)
+                .optional()?;
+            Ok(token)
+        })
+        .await
+    }
+}
@@ -0,0 +1,188 @@
+use anyhow::{Ok, Result};
+use async_trait::async_trait;
+use diesel::{
+    BoolExpressionMethods, Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl,
+    SelectableHelper, delete,
+    dsl::{now, update},
+    insert_into,
+    pg::PgConnection,
+    result::{DatabaseErrorKind, Error as DieselError},
+};
+use std::sync::Arc;
+
+use crate::{
+    domain::{
+        entities::{
+            brawler_identities::{AddBrawlerIdentityEntity, BrawlerIdentityEntity},
+            brawlers::RegisterExternalBrawlerEntity,
+        },
+        errors::AppError,
+        repositories::brawler_identities::BrawlerIdentityRepository,
+        value_objects::oauth_model::{DISCORD, ExternalIdentity},
+    },
+    infrastructure::database::{
+        postgresql_connection::{PgPoolSquad, with_connection},
+        schema::{brawler_identities, brawlers},
+    },
+};
+
+pub struct BrawlerIdentityPostgres {
+    db_pool: Arc<PgPoolSquad>,
+}
+
+impl BrawlerIdentityPostgres {
+    pub fn new(db_pool: Arc<PgPoolSquad>) -> Self {
+        Self { db_pool }
+    }
+}
+
+/// Says which unique constraint a clash hit, in words the client can show
+fn conflict(err: DieselError) -> anyhow::Error {
+    let DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, info) = &err else {
+        return err.into();
+    };
+    let message = match info.constraint_name() {
+        Some("unique_username") => "Username is already taken",
+        Some("brawler_identities_provider_subject_key") => {
+            "That account is already linked to another brawler"
+        }
+        Some("brawler_identities_brawler_id_provider_key") => {
+            "An account from that provider is already linked"
+        }
+        _ => return err.into(),
+    };
+    AppError::Conflict(message.to_string()).into()
+}
+
+fn insert_identity(
+    conn: &mut PgConnection,
+    brawler_id: i32,
+    identity: ExternalIdentity,
+) -> Result<BrawlerIdentityEntity> {
+    if identity.provider == DISCORD {
+        update(brawlers::table.find(brawler_id))
+            .set(brawlers::discord_id.eq(&identity.subject))
+            .execute(conn)?;
+    }
+    let inserted = insert_into(brawler_identities::table)
+        .values(AddBrawlerIdentityEntity {
+            brawler_id,
+            provider: identity.provider,
+            subject: identity.subject,
+            email: identity.email,
+            display_name: identity.display_name,
+        })
+        .returning(BrawlerIdentityEntity::as_returning())
+        .get_result(conn)
+        .map_err(conflict)?;
+    Ok(inserted)
+}
+
+#[async_trait]
+impl BrawlerIdentityRepository for BrawlerIdentityPostgres {
+    async fn find(
+        &self,
+        provider: String,
+        subject: String,
+    ) -> Result<Option<BrawlerIdentityEntity>> {
+        with_connection(&self.db_pool, move |conn| {
+            let identity = brawler_identities::table
+                .filter(brawler_identities::provider.eq(provider))
+                .filter(brawler_identities::subject.eq(subject))
+                .select(BrawlerIdentityEntity::as_select())
+                .first::<BrawlerIdentityEntity>(conn)
+                .optional()?;
+            Ok(identity)
+        })
+        .await
+    }
+
+    async fn list(&self, brawler_id: i32) -> Result<Vec<BrawlerIdentityEntity>> {
+        with_connection(&self.db_pool, move |conn| {
+            let identities = brawler_identities::table
+                .filter(brawler_identities::brawler_id.eq(brawler_id))
+                .order(brawler_identities::provider.asc())
+                .select(BrawlerIdentityEntity::as_select())
+                .load::<BrawlerIdentityEntity>(conn)?;
+            Ok(identities)
+        })
+        .await
+    }
+
+    async fn register(
+        &self,
+        brawler: RegisterExternalBrawlerEntity,
+        identity: ExternalIdentity,
+    ) -> Result<i32> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                let brawler_id = insert_into(brawlers::table)
+                    .values(&brawler)
+                    .returning(brawlers::id)
+                    .get_result::<i32>(conn)
+                    .map_err(conflict)?;
+                insert_identity(conn, brawler_id, identity)?;
+                Ok(brawler_id)
+            })
+        })
+        .await
+    }
+
+    async fn link(
+        &self,
+        brawler_id: i32,
+        identity: ExternalIdentity,
+    ) -> Result<BrawlerIdentityEntity> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| insert_identity(conn, brawler_id, identity))
+        })
+        .await
+    }
+
+    async fn unlink(&self, brawler_id: i32, provider: String) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                let subject = delete(
+                    brawler_identities::table.filter(
+                        brawler_identities::brawler_id
+                            .eq(brawler_id)
+                            .and(brawler_identities::provider.eq(&provider)),
+                    ),
+                )
+                .returning(brawler_identities::subject)
+                .get_result::<String>(conn)
+                .optional()?;
+                let Some(subject) = subject else {
+                    return Err(
+                        AppError::NotFound(format!("No {provider} account is linked")).into(),
+                    );
+                };
+
+                // Only when it still holds the id the link put there
+                if provider == DISCORD {
+                    update(
+                        brawlers::table.filter(
+                            brawlers::id
+                                .eq(brawler_id)
+                                .and(brawlers::discord_id.eq(&subject)),
+                        ),
+                    )
+                    .set(brawlers::discord_id.eq(None::<String>))
+                    .execute(conn)?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn touch_login(&self, identity_id: i32) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            update(brawler_identities::table.find(identity_id))
+                .set(brawler_identities::last_login_at.eq(now))
+                .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+}
@@ -1,21 +1,26 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper, insert_into};
+use chrono::NaiveDateTime;
+use diesel::{
+    Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper, dsl::now, insert_into,
+};
 use std::sync::Arc;
 
 use crate::{
     domain::{
         entities::brawlers::{BrawlerEntity, RegisterBrawlerEntity},
+        errors::AppError,
         repositories::brawlers::BrawlerRepository,
         value_objects::{
-            base64_img::Base64Img, mission_model::MissionModel, uploaded_img::UploadedImg,
+            base64_img::Base64Img, brawler_role::BrawlerRole, mission_model::MissionModel,
+            oauth_model::DISCORD, uploaded_img::UploadedImg,
         },
     },
     infrastructure::{
         cloudinary::{self, UploadImageOptions},
         database::{
-            postgresql_connection::PgPoolSquad,
-            schema::{brawlers, crew_memberships},
+            postgresql_connection::{PgPoolSquad, with_connection},
+            schema::{brawler_identities, brawlers, crew_memberships},
         },
         jwt::jwt_model::Passport,
     },
@@ -34,48 +39,64 @@ impl BrawlerPostgres {
 #[async_trait]
 impl BrawlerRepository for BrawlerPostgres {
     async fn register(&self, register_brawler_entity: RegisterBrawlerEntity) -> Result<Passport> {
-        let mut connection = Arc::clone(&self.db_pool).get()?;
+        let display_name = register_brawler_entity.display_name.clone();
 
-        let user_id = insert_into(brawlers::table)
-            .values(&register_brawler_entity)
-            .returning(brawlers::id)
-            .get_result::<i32>(&mut connection)?;
+        let user_id = with_connection(&self.db_pool, move |conn| {
+            insert_into(brawlers::table)
+                .values(&register_brawler_entity)
+                .returning(brawlers::id)
+                .get_result::<i32>(conn)
+                .map_err(anyhow::Error::from)
+        })
+        .await?;
 
-        let display_name = register_brawler_entity.display_name;
-        Passport::new(user_id, display_name, None, None, None, None, None, None)
+        Ok(Passport::new(
+            user_id,
+            display_name,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            BrawlerRole::User,
+        ))
     }
 
     async fn find_by_id(&self, id: i32) -> Result<BrawlerEntity> {
-        let mut connection = Arc::clone(&self.db_pool).get()?;
-
-        let result = brawlers::table
-            .find(id)
-            .select(BrawlerEntity::as_select())
-            .first::<BrawlerEntity>(&mut connection)?;
+        with_connection(&self.db_pool, move |conn| {
+            let result = brawlers::table
+                .find(id)
+                .select(BrawlerEntity::as_select())
+                .first::<BrawlerEntity>(conn)?;
 
-        Ok(result)
+            Ok(result)
+        })
+        .await
     }
 
     async fn find_by_username(&self, username: String) -> Result<BrawlerEntity> {
-        let mut connection = Arc::clone(&self.db_pool).get()?;
-
-        let result = brawlers::table
-            .filter(brawlers::username.eq(username))
-            .select(BrawlerEntity::as_select())
-            .first::<BrawlerEntity>(&mut connection)?;
+        with_connection(&self.db_pool, move |conn| {
+            let result = brawlers::table
+                .filter(brawlers::username.eq(username))
+                .select(BrawlerEntity::as_select())
+                .first::<BrawlerEntity>(conn)?;
 
-        Ok(result)
+            Ok(result)
+        })
+        .await
     }
 
     async fn find_many(&self, ids: Vec<i32>) -> Result<Vec<BrawlerEntity>> {
-        let mut connection = Arc::clone(&self.db_pool).get()?;
+        with_connection(&self.db_pool, move |conn| {
+            let results = brawlers::table
+                .filter(brawlers::id.eq_any(ids))
+                .select(BrawlerEntity::as_select())
+                .load::<BrawlerEntity>(conn)?;
 
-        let results = brawlers::table
-            .filter(brawlers::id.eq_any(ids))
-            .select(BrawlerEntity::as_select())
-            .load::<BrawlerEntity>(&mut connection)?;
-
-        Ok(results)
+            Ok(results)
+        })
+        .await
     }
 
     async fn upload_base64img(
@@ -86,33 +107,35 @@ impl BrawlerRepository for BrawlerPostgres {
     ) -> Result<UploadedImg> {
         let uploaded_img = cloudinary::upload(base64img, opt).await?;
 
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-
-        diesel::update(brawlers::table)
-            .filter(brawlers::id.eq(user_id))
-            .set((
-                brawlers::avatar_url.eq(uploaded_img.url.clone()),
-                brawlers::avatar_public_id.eq(uploaded_img.public_id.clone()),
-            ))
-            .execute(&mut conn)?;
+        let (url, public_id) = (uploaded_img.url.clone(), uploaded_img.public_id.clone());
+        with_connection(&self.db_pool, move |conn| {
+            diesel::update(brawlers::table)
+                .filter(brawlers::id.eq(user_id))
+                .set((
+                    brawlers::avatar_url.eq(url),
+                    brawlers::avatar_public_id.eq(public_id),
+                ))
+                .execute(conn)
+                .map_err(anyhow::Error::from)
+        })
+        .await?;
 
         Ok(uploaded_img)
     }
 
     async fn crew_counting(&self, mission_id: i32) -> Result<u32> {
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-
-        let count = crew_memberships::table
-            .filter(crew_memberships::mission_id.eq(mission_id))
-            .count()
-            .get_result::<i64>(&mut conn)?;
+        with_connection(&self.db_pool, move |conn| {
+            let count = crew_memberships::table
+                .filter(crew_memberships::mission_id.eq(mission_id))
+                .count()
+                .get_result::<i64>(conn)?;
 
-        Ok(count as u32)
+            Ok(count as u32)
+        })
+        .await
     }
 
     async fn get_missions(&self, brawler_id: i32) -> Result<Vec<MissionModel>> {
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-
         let sql = r#"
 SELECT
     missions.id,
@@ -129,7 +152,9 @@ SELECT
     missions.scheduled_at,
     missions.location,
     missions.deleted_at,
-    missions.category
+    missions.category,
+    missions.join_policy,
+    missions.is_private
 FROM missions
 LEFT JOIN brawlers ON brawlers.id = missions.chief_id
 WHERE missions.deleted_at IS NULL
@@ -137,11 +162,14 @@ WHERE missions.deleted_at IS NULL
 ORDER BY missions.created_at DESC
         "#;
 
-        let results = diesel::sql_query(sql)
-            .bind::<diesel::sql_types::Int4, _>(brawler_id)
-            .load::<MissionModel>(&mut conn)?;
+        with_connection(&self.db_pool, move |conn| {
+            let results = diesel::sql_query(sql)
+                .bind::<diesel::sql_types::Int4, _>(brawler_id)
+                .load::<MissionModel>(conn)?;
 
-        Ok(results)
+            Ok(results)
+        })
+        .await
     }
 
     async fn update_profile(
@@ -149,36 +177,65 @@ ORDER BY missions.created_at DESC
         brawler_id: i32,
         model: crate::domain::value_objects::brawler_model::UpdateBrawlerModel,
     ) -> Result<Passport> {
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-
-        diesel::update(brawlers::table)
-            .filter(brawlers::id.eq(brawler_id))
-            .set((
-                model
-                    .display_name
-                    .as_ref()
-                    .map(|v| brawlers::display_name.eq(v)),
-                model.bio.as_ref().map(|v| brawlers::bio.eq(v)),
-                model
-                    .discord_id
-                    .as_ref()
-                    .map(|v| brawlers::discord_id.eq(v)),
-                model
-                    .contact_email
-                    .as_ref()
-                    .map(|v| brawlers::contact_email.eq(v)),
-                model.instagram.as_ref().map(|v| brawlers::instagram.eq(v)),
-                model.facebook.as_ref().map(|v| brawlers::facebook.eq(v)),
-            ))
-            .execute(&mut conn)?;
-
-        let brawler = brawlers::table
-            .find(brawler_id)
-            .select(BrawlerEntity::as_select())
-            .first::<BrawlerEntity>(&mut conn)?;
+        let brawler = with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                // A linked Discord account owns `discord_id`. Locking the row keeps a
+                // link from landing between the check and the update.
+                let current_discord_id = brawlers::table
+                    .find(brawler_id)
+                    .select(brawlers::discord_id)
+                    .for_update()
+                    .first::<Option<String>>(conn)?;
+                if model.discord_id.is_some() && model.discord_id != current_discord_id {
+                    let linked = diesel::select(diesel::dsl::exists(
+                        brawler_identities::table
+                            .filter(brawler_identities::brawler_id.eq(brawler_id))
+                            .filter(brawler_identities::provider.eq(DISCORD)),
+                    ))
+                    .get_result::<bool>(conn)?;
+                    if linked {
+                        return Err(AppError::Validation(
+                        "Discord id comes from the linked Discord account; unlink it to change it"
+                            .to_string(),
+                    )
+                    .into());
+                    }
+                }
+
+                diesel::update(brawlers::table)
+                    .filter(brawlers::id.eq(brawler_id))
+                    .set((
+                        model
+                            .display_name
+                            .as_ref()
+                            .map(|v| brawlers::display_name.eq(v)),
+                        model.bio.as_ref().map(|v| brawlers::bio.eq(v)),
+                        model
+                            .discord_id
+                            .as_ref()
+                            .map(|v| brawlers::discord_id.eq(v)),
+                        model
+                            .contact_email
+                            .as_ref()
+                            .map(|v| brawlers::contact_email.eq(v)),
+                        model.instagram.as_ref().map(|v| brawlers::instagram.eq(v)),
+                        model.facebook.as_ref().map(|v| brawlers::facebook.eq(v)),
+                    ))
+                    .execute(conn)?;
+
+                let brawler = brawlers::table
+                    .find(brawler_id)
+                    .select(BrawlerEntity::as_select())
+                    .first::<BrawlerEntity>(conn)?;
+
+                Ok::<_, anyhow::Error>(brawler)
+            })
+        })
+        .await?;
 
         // but we return it to update basic info on client if needed.
-        Passport::new(
+        let role = brawler.role();
+        Ok(Passport::new(
             brawler.id,
             brawler.display_name,
             brawler.avatar_url,
@@ -187,6 +244,61 @@ ORDER BY missions.created_at DESC
             brawler.contact_email,
             brawler.instagram,
             brawler.facebook,
-        )
+            role,
+        ))
+    }
+
+    async fn touch_last_seen(&self, brawler_id: i32) -> Result<NaiveDateTime> {
+        with_connection(&self.db_pool, move |conn| {
+            let last_seen_at = diesel::update(brawlers::table.find(brawler_id))
+                .set(brawlers::last_seen_at.eq(now))
+                .returning(brawlers::last_seen_at)
+                .get_result::<Option<NaiveDateTime>>(conn)?;
+
+            Ok(last_seen_at.unwrap_or_default())
+        })
+        .await
+    }
+
+    async fn update_password(&self, brawler_id: i32, password_hash: String) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            let updated = diesel::update(brawlers::table.find(brawler_id))
+                .set(brawlers::password.eq(password_hash))
+                .execute(conn)?;
+
+            if updated == 0 {
+                return Err(AppError::NotFound("Brawler not found".to_string()).into());
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_by_roles(&self, roles: Vec<BrawlerRole>) -> Result<Vec<BrawlerEntity>> {
+        let roles: Vec<String> = roles.iter().map(BrawlerRole::to_string).collect();
+        with_connection(&self.db_pool, move |conn| {
+            let results = brawlers::table
+                .filter(brawlers::role.eq_any(roles))
+                .order(brawlers::username.asc())
+                .select(BrawlerEntity::as_select())
+                .load::<BrawlerEntity>(conn)?;
+
+            Ok(results)
+        })
+        .await
+    }
+
+    async fn set_role(&self, brawler_id: i32, role: BrawlerRole) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            let updated = diesel::update(brawlers::table.find(brawler_id))
+                .set(brawlers::role.eq(role.to_string()))
+                .execute(conn)?;
+
+            if updated == 0 {
+                return Err(AppError::NotFound("Brawler not found".to_string()).into());
+            }
+            Ok(())
+        })
+        .await
     }
 }
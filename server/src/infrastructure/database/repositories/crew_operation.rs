@@ -1,17 +1,25 @@
 use anyhow::{Ok, Result};
 use async_trait::async_trait;
-use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, insert_into};
+use chrono::NaiveDateTime;
+use diesel::{
+    Connection, ExpressionMethods, PgConnection, QueryDsl, QueryResult, RunQueryDsl, insert_into,
+    result::{DatabaseErrorKind, Error as DieselError},
+};
 use std::sync::Arc;
 
 use crate::{
     domain::{
         entities::crew_memberships::CrewMemberShips,
+        errors::AppError,
         repositories::crew_operation::CrewOperationRepository,
-        value_objects::{mission_model::MissionModel, mission_statuses::MissionStatuses},
+        value_objects::{
+            mission_application_model::ApplicationStatuses, mission_model::MissionModel,
+            mission_statuses::MissionStatuses,
+        },
     },
     infrastructure::database::{
-        postgresql_connection::PgPoolSquad,
-        schema::{crew_memberships, mission_comments, missions},
+        postgresql_connection::{PgPoolSquad, with_connection},
+        schema::{crew_memberships, mission_applications, mission_waitlist, missions},
     },
 };
 
@@ -25,64 +33,128 @@ impl CrewOperationPostgres {
     }
 }
 
-#[async_trait]
-impl CrewOperationRepository for CrewOperationPostgres {
-    async fn join(&self, crew_member_ships: CrewMemberShips) -> Result<()> {
-        let mut conn = Arc::clone(&self.db_pool).get()?;
+/// Whether a mission can take another crew member
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum MissionSlots {
+    /// Deleted, or not open
+    Closed,
+    Full,
+    Free,
+}
 
-        let mission_status: String = missions::table
-            .select(missions::status)
-            .filter(missions::id.eq(crew_member_ships.mission_id))
-            .first(&mut conn)?;
+/// Locks the mission row and reads its [`MissionSlots`].
+///
+/// Call inside a transaction; joins, waitlist joins and promotions then queue
+/// on the row lock, so two brawlers can't both take the last slot.
+pub(crate) fn lock_mission(conn: &mut PgConnection, mission_id: i32) -> QueryResult<MissionSlots> {
+    let (mission_status, max_crew, deleted_at) = missions::table
+        .select((missions::status, missions::max_crew, missions::deleted_at))
+        .filter(missions::id.eq(mission_id))
+        .for_update()
+        .first::<(String, i32, Option<NaiveDateTime>)>(conn)?;
+
+    if deleted_at.is_some() || mission_status != MissionStatuses::Open.to_string() {
+        return QueryResult::Ok(MissionSlots::Closed);
+    }
+
+    let crew_count: i64 = crew_memberships::table
+        .filter(crew_memberships::mission_id.eq(mission_id))
+        .count()
+        .get_result(conn)?;
 
-        if mission_status != MissionStatuses::Open.to_string() {
-            return Err(anyhow::anyhow!("Mission is not open for joining"));
+    QueryResult::Ok(if crew_count >= max_crew as i64 {
+        MissionSlots::Full
+    } else {
+        MissionSlots::Free
+    })
+}
+
+/// Locks the mission row with [`lock_mission`] and checks that a slot is free
+pub(crate) fn lock_open_slot(conn: &mut PgConnection, mission_id: i32) -> Result<()> {
+    match lock_mission(conn, mission_id)? {
+        MissionSlots::Closed => {
+            Err(AppError::Conflict("Mission is not joinable in current status".to_string()).into())
         }
+        MissionSlots::Full => Err(AppError::Conflict("Mission is full".to_string()).into()),
+        MissionSlots::Free => Ok(()),
+    }
+}
 
-        insert_into(crew_memberships::table)
-            .values(crew_member_ships)
-            .execute(&mut conn)?;
-        Ok(())
+/// Adds the membership once [`lock_open_slot`] passes. Call inside a transaction.
+pub(crate) fn insert_crew_member(
+    conn: &mut PgConnection,
+    crew_member_ships: &CrewMemberShips,
+) -> Result<()> {
+    lock_open_slot(conn, crew_member_ships.mission_id)?;
+
+    insert_into(crew_memberships::table)
+        .values(crew_member_ships)
+        .execute(conn)
+        .map_err(|e| match e {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                AppError::Conflict("You are already a crew member of this mission".to_string())
+                    .into()
+            }
+            other => anyhow::Error::from(other),
+        })?;
+
+    // A slot opened up before promotion got to them; they're crew now
+    diesel::delete(mission_waitlist::table)
+        .filter(mission_waitlist::mission_id.eq(crew_member_ships.mission_id))
+        .filter(mission_waitlist::brawler_id.eq(crew_member_ships.brawler_id))
+        .execute(conn)?;
+    Ok(())
+}
+
+#[async_trait]
+impl CrewOperationRepository for CrewOperationPostgres {
+    async fn join(&self, crew_member_ships: CrewMemberShips) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| insert_crew_member(conn, &crew_member_ships))
+        })
+        .await
     }
 
-    async fn leave(&self, crew_member_ships: CrewMemberShips) -> Result<()> {
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-
-        // 1. Remove the membership
-        diesel::delete(crew_memberships::table)
-            .filter(crew_memberships::brawler_id.eq(crew_member_ships.brawler_id))
-            .filter(crew_memberships::mission_id.eq(crew_member_ships.mission_id))
-            .execute(&mut conn)?;
-
-        // 2. Check if the mission is soft-deleted and has 0 members remaining
-        use diesel::OptionalExtension;
-        let mission_info: Option<(bool, i32)> = missions::table
-            .select((missions::deleted_at.is_not_null(), missions::id))
-            .filter(missions::id.eq(crew_member_ships.mission_id))
-            .first::<(bool, i32)>(&mut conn)
-            .optional()?;
-
-        if let Some((is_deleted, mid)) = mission_info {
-            if is_deleted {
-                let count: i64 = crew_memberships::table
-                    .filter(crew_memberships::mission_id.eq(mid))
-                    .count()
-                    .get_result(&mut conn)?;
-
-                if count == 0 {
-                    // 3. HARD DELETE: Last crew member left a deleted mission. Clean up DB.
-                    diesel::delete(mission_comments::table)
-                        .filter(mission_comments::mission_id.eq(mid))
-                        .execute(&mut conn)?;
-
-                    diesel::delete(missions::table)
-                        .filter(missions::id.eq(mid))
-                        .execute(&mut conn)?;
+    async fn apply(&self, crew_member_ships: CrewMemberShips) -> Result<i32> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                lock_open_slot(conn, crew_member_ships.mission_id)?;
+
+                let already_crew = diesel::select(diesel::dsl::exists(
+                    crew_memberships::table
+                        .filter(crew_memberships::mission_id.eq(crew_member_ships.mission_id))
+                        .filter(crew_memberships::brawler_id.eq(crew_member_ships.brawler_id)),
+                ))
+                .get_result::<bool>(conn)?;
+                if already_crew {
+                    return Err(AppError::Conflict(
+                        "You are already a crew member of this mission".to_string(),
+                    )
+                    .into());
                 }
-            }
-        }
 
-        Ok(())
+                let application_id = insert_into(mission_applications::table)
+                    .values((
+                        mission_applications::mission_id.eq(crew_member_ships.mission_id),
+                        mission_applications::brawler_id.eq(crew_member_ships.brawler_id),
+                        mission_applications::status.eq(ApplicationStatuses::Pending.to_string()),
+                    ))
+                    .returning(mission_applications::id)
+                    .get_result::<i32>(conn)
+                    .map_err(|e| match e {
+                        DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                            AppError::Conflict(
+                                "You already have a pending application for this mission"
+                                    .to_string(),
+                            )
+                            .into()
+                        }
+                        other => anyhow::Error::from(other),
+                    })?;
+                Ok(application_id)
+            })
+        })
+        .await
     }
 
     async fn get_my_joined_missions(&self, brawler_id: i32) -> Result<Vec<MissionModel>> {
@@ -101,7 +173,9 @@ SELECT m.id,
        m.scheduled_at,
        m.location,
        m.deleted_at,
-       m.category
+       m.category,
+       m.join_policy,
+       m.is_private
 FROM missions m
 INNER JOIN crew_memberships cm ON cm.mission_id = m.id AND cm.brawler_id = $1
 LEFT JOIN brawlers b ON b.id = m.chief_id
@@ -109,11 +183,13 @@ LEFT JOIN brawlers b ON b.id = m.chief_id
 ORDER BY cm.joined_at DESC
         "#;
 
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-        let rows = diesel::sql_query(sql)
-            .bind::<diesel::sql_types::Int4, _>(brawler_id)
-            .load::<MissionModel>(&mut conn)?;
+        with_connection(&self.db_pool, move |conn| {
+            let rows = diesel::sql_query(sql)
+                .bind::<diesel::sql_types::Int4, _>(brawler_id)
+                .load::<MissionModel>(conn)?;
 
-        Ok(rows)
+            Ok(rows)
+        })
+        .await
     }
 }
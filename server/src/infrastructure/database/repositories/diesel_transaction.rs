@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use diesel::{
+    Connection, ExpressionMethods, OptionalExtension, PgConnection, QueryDsl, RunQueryDsl,
+    dsl::now, insert_into, update,
+};
+
+use crate::{
+    domain::{
+        entities::{
+            crew_memberships::CrewMemberShips,
+            notifications::{AddNotificationEntity, NotificationEntity},
+        },
+        errors::AppResult,
+        repositories::transaction_provider::{TransactionProvider, UnitOfWork},
+        value_objects::mission_statuses::MissionStatuses,
+    },
+    infrastructure::database::{
+        postgresql_connection::{PgPoolSquad, with_connection},
+        repositories::crew_operation::{MissionSlots, lock_mission},
+        schema::{crew_memberships, mission_comments, mission_waitlist, missions, notifications},
+    },
+};
+
+pub struct TransactionProviderPostgres {
+    db_pool: Arc<PgPoolSquad>,
+}
+
+impl TransactionProviderPostgres {
+    pub fn new(db_pool: Arc<PgPoolSquad>) -> Self {
+        Self { db_pool }
+    }
+}
+
+#[async_trait]
+impl TransactionProvider for TransactionProviderPostgres {
+    async fn transaction<T, F>(&self, f: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut dyn UnitOfWork) -> AppResult<T> + Send + 'static,
+    {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| f(&mut PgUnitOfWork { conn }))
+        })
+        .await
+    }
+}
+
+struct PgUnitOfWork<'a> {
+    conn: &'a mut PgConnection,
+}
+
+impl UnitOfWork for PgUnitOfWork<'_> {
+    fn crew_ids(&mut self, mission_id: i32) -> AppResult<Vec<i32>> {
+        Ok(crew_memberships::table
+            .filter(crew_memberships::mission_id.eq(mission_id))
+            .select(crew_memberships::brawler_id)
+            .load::<i32>(self.conn)?)
+    }
+
+    fn set_mission_status(
+        &mut self,
+        mission_id: i32,
+        chief_id: i32,
+        status: MissionStatuses,
+    ) -> AppResult<i32> {
+        Ok(update(missions::table)
+            .filter(missions::id.eq(mission_id))
+            .filter(missions::chief_id.eq(chief_id))
+            .filter(missions::deleted_at.is_null())
+            .set((missions::status.eq(status.to_string()),))
+            .returning(missions::id)
+            .get_result::<i32>(self.conn)?)
+    }
+
+    fn delete_mission(&mut self, mission_id: i32, chief_id: i32) -> AppResult<()> {
+        diesel::delete(missions::table)
+            .filter(missions::id.eq(mission_id))
+            .filter(missions::chief_id.eq(chief_id))
+            .execute(self.conn)?;
+        Ok(())
+    }
+
+    fn soft_delete_mission(&mut self, mission_id: i32, chief_id: i32) -> AppResult<()> {
+        update(missions::table)
+            .filter(missions::id.eq(mission_id))
+            .filter(missions::chief_id.eq(chief_id))
+            .filter(missions::deleted_at.is_null())
+            .filter(missions::status.ne(MissionStatuses::InProgress.to_string()))
+            .set((missions::deleted_at.eq(now),))
+            .execute(self.conn)?;
+        Ok(())
+    }
+
+    fn delete_mission_comments(&mut self, mission_id: i32) -> AppResult<()> {
+        diesel::delete(mission_comments::table)
+            .filter(mission_comments::mission_id.eq(mission_id))
+            .execute(self.conn)?;
+        Ok(())
+    }
+
+    fn remove_crew_member(&mut self, mission_id: i32, brawler_id: i32) -> AppResult<()> {
+        missions::table
+            .select(missions::id)
+            .filter(missions::id.eq(mission_id))
+            .for_update()
+            .first::<i32>(self.conn)
+            .optional()?;
+        diesel::delete(crew_memberships::table)
+            .filter(crew_memberships::mission_id.eq(mission_id))
+            .filter(crew_memberships::brawler_id.eq(brawler_id))
+            .execute(self.conn)?;
+        Ok(())
+    }
+
+    fn promote_from_waitlist(&mut self, mission_id: i32) -> AppResult<Option<i32>> {
+        if lock_mission(self.conn, mission_id).optional()? != Some(MissionSlots::Free) {
+            return Ok(None);
+        }
+
+        let Some((entry_id, brawler_id)) = mission_waitlist::table
+            .select((mission_waitlist::id, mission_waitlist::brawler_id))
+            .filter(mission_waitlist::mission_id.eq(mission_id))
+            .order(mission_waitlist::id.asc())
+            .first::<(i32, i32)>(self.conn)
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        diesel::delete(mission_waitlist::table.find(entry_id)).execute(self.conn)?;
+        insert_into(crew_memberships::table)
+            .values(CrewMemberShips {
+                mission_id,
+                brawler_id,
+            })
+            .execute(self.conn)?;
+
+        Ok(Some(brawler_id))
+    }
+
+    fn add_notification(
+        &mut self,
+        notification: AddNotificationEntity,
+    ) -> AppResult<NotificationEntity> {
+        Ok(insert_into(notifications::table)
+            .values(notification)
+            .get_result(self.conn)?)
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::{Ok, Result};
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use diesel::{
+    BoolExpressionMethods, Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper,
+    delete, dsl::update, insert_into,
+};
+use std::sync::Arc;
+
+use crate::{
+    domain::{
+        entities::login_throttles::{AddLoginLockoutEntity, LoginThrottleEntity},
+        repositories::login_throttles::LoginThrottleRepository,
+        value_objects::login_throttle_model::ThrottleKey,
+    },
+    infrastructure::database::{
+        postgresql_connection::{PgPoolSquad, with_connection},
+        schema::{login_lockouts, login_throttles},
+    },
+};
+
+pub struct LoginThrottlePostgres {
+    db_pool: Arc<PgPoolSquad>,
+}
+
+impl LoginThrottlePostgres {
+    pub fn new(db_pool: Arc<PgPoolSquad>) -> Self {
+        Self { db_pool }
+    }
+}
+
+#[async_trait]
+impl LoginThrottleRepository for LoginThrottlePostgres {
+    async fn find(&self, keys: Vec<ThrottleKey>) -> Result<Vec<LoginThrottleEntity>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        with_connection(&self.db_pool, move |conn| {
+            let mut query = login_throttles::table.into_boxed();
+            for key in keys {
+                query = query.or_filter(
+                    login_throttles::scope
+                        .eq(key.scope.as_str())
+                        .and(login_throttles::subject.eq(key.subject)),
+                );
+            }
+            let rows = query
+                .select(LoginThrottleEntity::as_select())
+                .load::<LoginThrottleEntity>(conn)?;
+            Ok(rows)
+        })
+        .await
+    }
+
+    async fn record_failure(&self, key: ThrottleKey, since: NaiveDateTime) -> Result<i32> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                let now = Utc::now().naive_utc();
+                let scope = key.scope.as_str();
+
+                insert_into(login_throttles::table)
+                    .values((
+                        login_throttles::scope.eq(scope),
+                        login_throttles::subject.eq(&key.subject),
+                        login_throttles::failures.eq(0),
+                        login_throttles::last_failed_at.eq(now),
+                    ))
+                    .on_conflict_do_nothing()
+                    .execute(conn)?;
+
+                // Row lock so concurrent failures each count
+                let row = login_throttles::table.find((scope, &key.subject));
+                let (failures, last_failed_at) = row
+                    .select((login_throttles::failures, login_throttles::last_failed_at))
+                    .for_update()
+                    .first::<(i32, NaiveDateTime)>(conn)?;
+                let failures = if last_failed_at < since {
+                    1
+                } else {
+                    failures + 1
+                };
+
+                update(row)
+                    .set((
+                        login_throttles::failures.eq(failures),
+                        login_throttles::last_failed_at.eq(now),
+                    ))
+                    .execute(conn)?;
+
+                // Keys that have gone quiet and aren't locked no longer matter
+                delete(login_throttles::table)
+                    .filter(login_throttles::last_failed_at.lt(since))
+                    .filter(
+                        login_throttles::locked_until
+                            .is_null()
+                            .or(login_throttles::locked_until.lt(now)),
+                    )
+                    .execute(conn)?;
+
+                Ok(failures)
+            })
+        })
+        .await
+    }
+
+    async fn lock(&self, lockout: AddLoginLockoutEntity) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                update(
+                    login_throttles::table.find((lockout.scope.as_str(), lockout.subject.as_str())),
+                )
+                .set((
+                    login_throttles::failures.eq(0),
+                    login_throttles::locked_until.eq(lockout.locked_until),
+                ))
+                .execute(conn)?;
+
+                insert_into(login_lockouts::table)
+                    .values(&lockout)
+                    .execute(conn)?;
+
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn clear(&self, key: ThrottleKey) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            delete(login_throttles::table.find((key.scope.as_str(), key.subject))).execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
         repositories::mission_comment::MissionCommentRepository,
         value_objects::mission_comment_model::MissionCommentModel,
     },
-    infrastructure::database::postgresql_connection::PgPoolSquad,
+    infrastructure::database::postgresql_connection::{PgPoolSquad, with_connection},
 };
 use anyhow::Result;
 use async_trait::async_trait;
@@ -31,17 +31,18 @@ impl MissionCommentRepository for MissionCommentPostgres {
         use crate::infrastructure::database::schema::mission_comments;
         use diesel::ExpressionMethods;
 
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-        let inserted_id: i32 = diesel::insert_into(mission_comments::table)
-            .values((
-                mission_comments::mission_id.eq(mission_id),
-                mission_comments::brawler_id.eq(brawler_id),
-                mission_comments::content.eq(content),
-            ))
-            .returning(mission_comments::id)
-            .get_result(&mut conn)?;
+        let content = content.to_string();
+        with_connection(&self.db_pool, move |conn| {
+            let inserted_id: i32 = diesel::insert_into(mission_comments::table)
+                .values((
+                    mission_comments::mission_id.eq(mission_id),
+                    mission_comments::brawler_id.eq(brawler_id),
+                    mission_comments::content.eq(content),
+                ))
+                .returning(mission_comments::id)
+                .get_result(conn)?;
 
-        let sql = r#"
+            let sql = r#"
             SELECT c.id, c.mission_id, c.brawler_id, 
                    b.display_name as brawler_display_name,
                    COALESCE(b.avatar_url, '') as brawler_avatar_url,
@@ -51,11 +52,13 @@ impl MissionCommentRepository for MissionCommentPostgres {
             WHERE c.id = $1
         "#;
 
-        let result = diesel::sql_query(sql)
-            .bind::<diesel::sql_types::Int4, _>(inserted_id)
-            .get_result::<MissionCommentModel>(&mut conn)?;
+            let result = diesel::sql_query(sql)
+                .bind::<diesel::sql_types::Int4, _>(inserted_id)
+                .get_result::<MissionCommentModel>(conn)?;
 
-        Ok(result)
+            Ok(result)
+        })
+        .await
     }
 
     async fn get_by_mission_id(&self, mission_id: i32) -> Result<Vec<MissionCommentModel>> {
@@ -70,11 +73,13 @@ impl MissionCommentRepository for MissionCommentPostgres {
             ORDER BY c.created_at ASC
         "#;
 
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-        let comments = diesel::sql_query(sql)
-            .bind::<diesel::sql_types::Int4, _>(mission_id)
-            .load::<MissionCommentModel>(&mut conn)?;
-        Ok(comments)
+        with_connection(&self.db_pool, move |conn| {
+            let comments = diesel::sql_query(sql)
+                .bind::<diesel::sql_types::Int4, _>(mission_id)
+                .load::<MissionCommentModel>(conn)?;
+            Ok(comments)
+        })
+        .await
     }
 
     async fn clear_by_mission_id(&self, mission_id: i32) -> Result<()> {
@@ -82,9 +87,13 @@ impl MissionCommentRepository for MissionCommentPostgres {
         use diesel::ExpressionMethods;
         use diesel::QueryDsl;
 
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-        diesel::delete(mission_comments::table.filter(mission_comments::mission_id.eq(mission_id)))
-            .execute(&mut conn)?;
-        Ok(())
+        with_connection(&self.db_pool, move |conn| {
+            diesel::delete(
+                mission_comments::table.filter(mission_comments::mission_id.eq(mission_id)),
+            )
+            .execute(conn)?;
+            Ok(())
+        })
+        .await
     }
 }
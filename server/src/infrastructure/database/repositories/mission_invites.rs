@@ -0,0 +1,304 @@
+use anyhow::{Ok, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use diesel::{
+    Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, SelectableHelper,
+    dsl::{now, update},
+    insert_into,
+    result::{DatabaseErrorKind, Error as DieselError},
+};
+use std::sync::Arc;
+
+use crate::{
+    domain::{
+        entities::{
+            crew_memberships::CrewMemberShips,
+            mission_invites::{
+                AddMissionInvitationEntity, AddMissionInviteLinkEntity, MissionInvitationEntity,
+                MissionInviteLinkEntity,
+            },
+        },
+        errors::AppError,
+        repositories::mission_invites::MissionInviteRepository,
+        value_objects::mission_invite_model::{InvitationStatuses, MissionInvitationModel},
+    },
+    infrastructure::database::{
+        postgresql_connection::{PgPoolSquad, with_connection},
+        repositories::crew_operation::insert_crew_member,
+        schema::{crew_memberships, mission_invitations, mission_invite_links},
+    },
+};
+
+pub struct MissionInvitePostgres {
+    db_pool: Arc<PgPoolSquad>,
+}
+
+impl MissionInvitePostgres {
+    pub fn new(db_pool: Arc<PgPoolSquad>) -> Self {
+        Self { db_pool }
+    }
+}
+
+const INVITATION_SQL: &str = r#"
+SELECT i.id,
+       i.mission_id,
+       m.name AS mission_name,
+       i.inviter_id,
+       COALESCE(inviter.display_name, '') AS inviter_display_name,
+       i.invitee_id,
+       COALESCE(invitee.display_name, '') AS invitee_display_name,
+       i.status,
+       i.created_at
+FROM mission_invitations i
+INNER JOIN missions m ON m.id = i.mission_id
+LEFT JOIN brawlers inviter ON inviter.id = i.inviter_id
+LEFT JOIN brawlers invitee ON invitee.id = i.invitee_id
+"#;
+
+fn invitation_not_found() -> anyhow::Error {
+    AppError::NotFound("Invitation not found".to_string()).into()
+}
+
+#[async_trait]
+impl MissionInviteRepository for MissionInvitePostgres {
+    async fn create_link(
+        &self,
+        add_link_entity: AddMissionInviteLinkEntity,
+    ) -> Result<MissionInviteLinkEntity> {
+        with_connection(&self.db_pool, move |conn| {
+            let link = insert_into(mission_invite_links::table)
+                .values(add_link_entity)
+                .returning(MissionInviteLinkEntity::as_returning())
+                .get_result(conn)?;
+
+            Ok(link)
+        })
+        .await
+    }
+
+    async fn links(&self, mission_id: i32) -> Result<Vec<MissionInviteLinkEntity>> {
+        with_connection(&self.db_pool, move |conn| {
+            let links = mission_invite_links::table
+                .filter(mission_invite_links::mission_id.eq(mission_id))
+                .order(mission_invite_links::created_at.desc())
+                .select(MissionInviteLinkEntity::as_select())
+                .load(conn)?;
+
+            Ok(links)
+        })
+        .await
+    }
+
+    async fn revoke_link(&self, mission_id: i32, link_id: i32) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            let revoked = update(mission_invite_links::table)
+                .filter(mission_invite_links::id.eq(link_id))
+                .filter(mission_invite_links::mission_id.eq(mission_id))
+                .filter(mission_invite_links::revoked_at.is_null())
+                .set(mission_invite_links::revoked_at.eq(now))
+                .execute(conn)?;
+
+            if revoked == 0 {
+                return Err(AppError::NotFound("Invite link not found".to_string()).into());
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn join_by_link(&self, link_id: i32, brawler_id: i32) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                // Row lock so the last use of a limited link can't be spent twice
+                let link = mission_invite_links::table
+                    .find(link_id)
+                    .select(MissionInviteLinkEntity::as_select())
+                    .for_update()
+                    .first(conn)
+                    .optional()?
+                    .ok_or_else(|| AppError::NotFound("Invite link not found".to_string()))?;
+
+                if link.revoked_at.is_some() {
+                    return Err(AppError::Forbidden(
+                        "This invite link has been revoked".to_string(),
+                    )
+                    .into());
+                }
+                if link.expires_at <= Utc::now().naive_utc() {
+                    return Err(
+                        AppError::Forbidden("This invite link has expired".to_string()).into(),
+                    );
+                }
+                if link.max_uses.is_some_and(|max_uses| link.uses >= max_uses) {
+                    return Err(AppError::Conflict(
+                        "This invite link has reached its max uses".to_string(),
+                    )
+                    .into());
+                }
+
+                insert_crew_member(
+                    conn,
+                    &CrewMemberShips {
+                        mission_id: link.mission_id,
+                        brawler_id,
+                    },
+                )?;
+
+                update(mission_invite_links::table.find(link_id))
+                    .set(mission_invite_links::uses.eq(mission_invite_links::uses + 1))
+                    .execute(conn)?;
+
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn invite(&self, add_invitation_entity: AddMissionInvitationEntity) -> Result<i32> {
+        with_connection(&self.db_pool, move |conn| {
+            let already_crew = diesel::select(diesel::dsl::exists(
+                crew_memberships::table
+                    .filter(crew_memberships::mission_id.eq(add_invitation_entity.mission_id))
+                    .filter(crew_memberships::brawler_id.eq(add_invitation_entity.invitee_id)),
+            ))
+            .get_result::<bool>(conn)?;
+            if already_crew {
+                return Err(AppError::Conflict(
+                    "This brawler is already a crew member of this mission".to_string(),
+                )
+                .into());
+            }
+
+            let invitation_id = insert_into(mission_invitations::table)
+                .values(add_invitation_entity)
+                .returning(mission_invitations::id)
+                .get_result::<i32>(conn)
+                .map_err(|e| match e {
+                    DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                        AppError::Conflict(
+                            "This brawler already has a pending invitation to this mission"
+                                .to_string(),
+                        )
+                        .into()
+                    }
+                    other => anyhow::Error::from(other),
+                })?;
+            Ok(invitation_id)
+        })
+        .await
+    }
+
+    async fn pending_for_mission(&self, mission_id: i32) -> Result<Vec<MissionInvitationModel>> {
+        let sql = format!(
+            "{INVITATION_SQL} WHERE i.mission_id = $1 AND i.status = $2 ORDER BY i.created_at ASC"
+        );
+        let pending = InvitationStatuses::Pending.to_string();
+
+        with_connection(&self.db_pool, move |conn| {
+            let rows = diesel::sql_query(sql)
+                .bind::<diesel::sql_types::Int4, _>(mission_id)
+                .bind::<diesel::sql_types::Varchar, _>(pending)
+                .load::<MissionInvitationModel>(conn)?;
+
+            Ok(rows)
+        })
+        .await
+    }
+
+    async fn pending_for_brawler(&self, brawler_id: i32) -> Result<Vec<MissionInvitationModel>> {
+        let sql = format!(
+            "{INVITATION_SQL} WHERE i.invitee_id = $1 AND i.status = $2 AND m.deleted_at IS NULL \
+             ORDER BY i.created_at DESC"
+        );
+        let pending = InvitationStatuses::Pending.to_string();
+
+        with_connection(&self.db_pool, move |conn| {
+            let rows = diesel::sql_query(sql)
+                .bind::<diesel::sql_types::Int4, _>(brawler_id)
+                .bind::<diesel::sql_types::Varchar, _>(pending)
+                .load::<MissionInvitationModel>(conn)?;
+
+            Ok(rows)
+        })
+        .await
+    }
+
+    async fn accept(&self, invitation_id: i32, invitee_id: i32) -> Result<MissionInvitationEntity> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                let invitation = mission_invitations::table
+                    .find(invitation_id)
+                    .filter(mission_invitations::invitee_id.eq(invitee_id))
+                    .filter(mission_invitations::status.eq(InvitationStatuses::Pending.to_string()))
+                    .select(MissionInvitationEntity::as_select())
+                    .for_update()
+                    .first(conn)
+                    .optional()?
+                    .ok_or_else(invitation_not_found)?;
+
+                insert_crew_member(
+                    conn,
+                    &CrewMemberShips {
+                        mission_id: invitation.mission_id,
+                        brawler_id: invitee_id,
+                    },
+                )?;
+
+                let invitation = update(mission_invitations::table.find(invitation.id))
+                    .set((
+                        mission_invitations::status.eq(InvitationStatuses::Accepted.to_string()),
+                        mission_invitations::updated_at.eq(now),
+                    ))
+                    .returning(MissionInvitationEntity::as_returning())
+                    .get_result(conn)?;
+
+                Ok(invitation)
+            })
+        })
+        .await
+    }
+
+    async fn decline(
+        &self,
+        invitation_id: i32,
+        invitee_id: i32,
+    ) -> Result<MissionInvitationEntity> {
+        with_connection(&self.db_pool, move |conn| {
+            let invitation = update(mission_invitations::table)
+                .filter(mission_invitations::id.eq(invitation_id))
+                .filter(mission_invitations::invitee_id.eq(invitee_id))
+                .filter(mission_invitations::status.eq(InvitationStatuses::Pending.to_string()))
+                .set((
+                    mission_invitations::status.eq(InvitationStatuses::Declined.to_string()),
+                    mission_invitations::updated_at.eq(now),
+                ))
+                .returning(MissionInvitationEntity::as_returning())
+                .get_result(conn)
+                .optional()?
+                .ok_or_else(invitation_not_found)?;
+
+            Ok(invitation)
+        })
+        .await
+    }
+
+    async fn revoke(&self, mission_id: i32, invitation_id: i32) -> Result<MissionInvitationEntity> {
+        with_connection(&self.db_pool, move |conn| {
+            let invitation = update(mission_invitations::table)
+                .filter(mission_invitations::id.eq(invitation_id))
+                .filter(mission_invitations::mission_id.eq(mission_id))
+                .filter(mission_invitations::status.eq(InvitationStatuses::Pending.to_string()))
+                .set((
+                    mission_invitations::status.eq(InvitationStatuses::Revoked.to_string()),
+                    mission_invitations::updated_at.eq(now),
+                ))
+                .returning(MissionInvitationEntity::as_returning())
+                .get_result(conn)
+                .optional()?
+                .ok_or_else(invitation_not_found)?;
+
+            Ok(invitation)
+        })
+        .await
+    }
+}
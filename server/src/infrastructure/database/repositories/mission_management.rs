@@ -1,14 +1,28 @@
 use crate::{
     domain::{
-        entities::missions::{AddMissionEntity, EditMissionEntity},
+        entities::{
+            crew_memberships::CrewMemberShips,
+            missions::{AddMissionEntity, EditMissionEntity},
+        },
+        errors::AppError,
         repositories::mission_management::MissionManagementRepository,
-        value_objects::mission_statuses::MissionStatuses,
+        value_objects::{
+            mission_application_model::{ApplicationStatuses, MissionApplicationModel},
+            mission_statuses::MissionStatuses,
+        },
+    },
+    infrastructure::database::{
+        postgresql_connection::{PgPoolSquad, with_connection},
+        repositories::crew_operation::insert_crew_member,
+        schema::{mission_applications, missions},
     },
-    infrastructure::database::{postgresql_connection::PgPoolSquad, schema::missions},
 };
 use anyhow::{Ok, Result};
 use async_trait::async_trait;
-use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, dsl::now, dsl::update, insert_into};
+use diesel::{
+    Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, dsl::now, dsl::update,
+    insert_into,
+};
 use std::sync::Arc;
 
 pub struct MissionManagementPostgres {
@@ -24,58 +38,110 @@ impl MissionManagementPostgres {
 #[async_trait]
 impl MissionManagementRepository for MissionManagementPostgres {
     async fn add(&self, add_mission_entity: AddMissionEntity) -> Result<i32> {
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-        let result = insert_into(missions::table)
-            .values(add_mission_entity)
-            .returning(missions::id)
-            .get_result::<i32>(&mut conn)?;
-        Ok(result)
+        with_connection(&self.db_pool, move |conn| {
+            let result = insert_into(missions::table)
+                .values(add_mission_entity)
+                .returning(missions::id)
+                .get_result::<i32>(conn)?;
+            Ok(result)
+        })
+        .await
     }
 
     async fn edit(&self, mission_id: i32, edit_mission_entity: EditMissionEntity) -> Result<i32> {
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-        let result = update(missions::table)
-            .filter(missions::id.eq(mission_id))
-            .filter(missions::deleted_at.is_null())
-            .filter(missions::status.eq(MissionStatuses::Open.to_string()))
-            .set(edit_mission_entity)
-            .returning(missions::id)
-            .get_result::<i32>(&mut conn)?;
-        Ok(result)
+        with_connection(&self.db_pool, move |conn| {
+            let result = update(missions::table)
+                .filter(missions::id.eq(mission_id))
+                .filter(missions::deleted_at.is_null())
+                .filter(missions::status.eq(MissionStatuses::Open.to_string()))
+                .set(edit_mission_entity)
+                .returning(missions::id)
+                .get_result::<i32>(conn)?;
+            Ok(result)
+        })
+        .await
     }
 
-    async fn remove(&self, mission_id: i32, chief_id: i32) -> Result<()> {
-        let mut conn = Arc::clone(&self.db_pool).get()?;
+    async fn pending_applications(&self, mission_id: i32) -> Result<Vec<MissionApplicationModel>> {
+        let sql = r#"
+SELECT a.id,
+       a.mission_id,
+       a.brawler_id,
+       COALESCE(b.display_name, '') AS brawler_display_name,
+       COALESCE(b.avatar_url, '') AS brawler_avatar_url,
+       a.status,
+       a.created_at
+FROM mission_applications a
+LEFT JOIN brawlers b ON b.id = a.brawler_id
+WHERE a.mission_id = $1 AND a.status = $2
+ORDER BY a.created_at ASC
+        "#;
+        let pending = ApplicationStatuses::Pending.to_string();
 
-        use crate::infrastructure::database::schema::{crew_memberships, mission_comments};
+        with_connection(&self.db_pool, move |conn| {
+            let rows = diesel::sql_query(sql)
+                .bind::<diesel::sql_types::Int4, _>(mission_id)
+                .bind::<diesel::sql_types::Varchar, _>(pending)
+                .load::<MissionApplicationModel>(conn)?;
 
-        // 1. Check if there are any crew members
-        let crew_count: i64 = crew_memberships::table
-            .filter(crew_memberships::mission_id.eq(mission_id))
-            .count()
-            .get_result(&mut conn)?;
+            Ok(rows)
+        })
+        .await
+    }
 
-        if crew_count == 0 {
-            // 2a. HARD DELETE: No one to notify, just clean up
-            diesel::delete(mission_comments::table)
-                .filter(mission_comments::mission_id.eq(mission_id))
-                .execute(&mut conn)?;
+    async fn accept_application(&self, mission_id: i32, application_id: i32) -> Result<i32> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                let brawler_id = mission_applications::table
+                    .select(mission_applications::brawler_id)
+                    .filter(mission_applications::id.eq(application_id))
+                    .filter(mission_applications::mission_id.eq(mission_id))
+                    .filter(
+                        mission_applications::status.eq(ApplicationStatuses::Pending.to_string()),
+                    )
+                    .for_update()
+                    .first::<i32>(conn)
+                    .optional()?
+                    .ok_or_else(|| AppError::NotFound("Application not found".to_string()))?;
 
-            diesel::delete(missions::table)
-                .filter(missions::id.eq(mission_id))
-                .filter(missions::chief_id.eq(chief_id))
-                .execute(&mut conn)?;
-        } else {
-            // 2b. SOFT DELETE: Keep it so crew members can see Status: REMOVED and leave
-            update(missions::table)
-                .filter(missions::id.eq(mission_id))
-                .filter(missions::chief_id.eq(chief_id))
-                .filter(missions::deleted_at.is_null())
-                .filter(missions::status.ne(MissionStatuses::InProgress.to_string()))
-                .set((missions::deleted_at.eq(now),))
-                .execute(&mut conn)?;
-        }
+                insert_crew_member(
+                    conn,
+                    &CrewMemberShips {
+                        mission_id,
+                        brawler_id,
+                    },
+                )?;
+
+                update(mission_applications::table.find(application_id))
+                    .set((
+                        mission_applications::status.eq(ApplicationStatuses::Accepted.to_string()),
+                        mission_applications::updated_at.eq(now),
+                    ))
+                    .execute(conn)?;
+
+                Ok(brawler_id)
+            })
+        })
+        .await
+    }
+
+    async fn reject_application(&self, mission_id: i32, application_id: i32) -> Result<i32> {
+        with_connection(&self.db_pool, move |conn| {
+            let brawler_id = update(mission_applications::table)
+                .filter(mission_applications::id.eq(application_id))
+                .filter(mission_applications::mission_id.eq(mission_id))
+                .filter(mission_applications::status.eq(ApplicationStatuses::Pending.to_string()))
+                .set((
+                    mission_applications::status.eq(ApplicationStatuses::Rejected.to_string()),
+                    mission_applications::updated_at.eq(now),
+                ))
+                .returning(mission_applications::brawler_id)
+                .get_result::<i32>(conn)
+                .optional()?
+                .ok_or_else(|| AppError::NotFound("Application not found".to_string()))?;
 
-        Ok(())
+            Ok(brawler_id)
+        })
+        .await
     }
 }
@@ -11,7 +11,7 @@ use crate::{
             brawler_model::BrawlerModel, mission_filter::MissionFilter, mission_model::MissionModel,
         },
     },
-    infrastructure::database::postgresql_connection::PgPoolSquad,
+    infrastructure::database::postgresql_connection::{PgPoolSquad, with_connection},
 };
 
 pub struct MissionViewingPostgres {
@@ -42,21 +42,25 @@ SELECT m.id,
        m.scheduled_at,
        m.location,
        m.deleted_at,
-       m.category
+       m.category,
+       m.join_policy,
+       m.is_private
 FROM missions m
 LEFT JOIN brawlers b ON b.id = m.chief_id
 LEFT JOIN crew_memberships cm ON cm.mission_id = m.id
 WHERE m.id = $1
 GROUP BY m.id, b.display_name, b.avatar_url, m.name, m.description, m.status,
-         m.chief_id, m.max_crew, m.created_at, m.updated_at, m.scheduled_at, m.location, m.deleted_at, m.category
+         m.chief_id, m.max_crew, m.created_at, m.updated_at, m.scheduled_at, m.location, m.deleted_at, m.category, m.join_policy, m.is_private
 LIMIT 1
         "#;
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-        let result = diesel::sql_query(sql)
-            .bind::<diesel::sql_types::Int4, _>(mission_id)
-            .get_result::<MissionModel>(&mut conn)?;
-
-        Ok(result)
+        with_connection(&self.db_pool, move |conn| {
+            let result = diesel::sql_query(sql)
+                .bind::<diesel::sql_types::Int4, _>(mission_id)
+                .get_result::<MissionModel>(conn)?;
+
+            Ok(result)
+        })
+        .await
     }
 
     async fn get_all(&self, filter: &MissionFilter) -> Result<Vec<MissionModel>> {
@@ -77,11 +81,14 @@ SELECT m.id,
        m.scheduled_at,
        m.location,
        m.deleted_at,
-       m.category
+       m.category,
+       m.join_policy,
+       m.is_private
 FROM missions m
 LEFT JOIN brawlers b ON b.id = m.chief_id
 LEFT JOIN crew_memberships cm ON cm.mission_id = m.id
 WHERE m.deleted_at IS NULL
+  AND m.is_private = FALSE
   AND ($1::varchar IS NULL OR m.status = $1)
   AND ($2::varchar IS NULL OR m.name ILIKE $2)
   AND ($3::int4 IS NULL OR m.chief_id != $3)
@@ -91,7 +98,7 @@ WHERE m.deleted_at IS NULL
   )))
   AND ($4::varchar IS NULL OR m.category = $4)
 GROUP BY m.id, b.display_name, b.avatar_url, m.name, m.description, m.status,
-         m.chief_id, m.max_crew, m.created_at, m.updated_at, m.scheduled_at, m.location, m.deleted_at, m.category
+         m.chief_id, m.max_crew, m.created_at, m.updated_at, m.scheduled_at, m.location, m.deleted_at, m.category, m.join_policy, m.is_private
 HAVING ($5::bool IS NULL OR ($5 = true AND COUNT(cm.brawler_id) < m.max_crew) OR ($5 = false AND COUNT(cm.brawler_id) >= m.max_crew))
 ORDER BY m.created_at DESC
         "#;
@@ -102,29 +109,33 @@ ORDER BY m.created_at DESC
         let category_bind: Option<String> = filter.category.clone();
         let is_available_bind: Option<bool> = filter.is_available;
 
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-        let rows = diesel::sql_query(sql)
-            .bind::<Nullable<Varchar>, _>(status_bind)
-            .bind::<Nullable<Varchar>, _>(name_bind)
-            .bind::<Nullable<Int4>, _>(exclude_user_bind)
-            .bind::<Nullable<Varchar>, _>(category_bind)
-            .bind::<Nullable<Bool>, _>(is_available_bind)
-            .load::<MissionModel>(&mut conn)?;
-
-        Ok(rows)
+        with_connection(&self.db_pool, move |conn| {
+            let rows = diesel::sql_query(sql)
+                .bind::<Nullable<Varchar>, _>(status_bind)
+                .bind::<Nullable<Varchar>, _>(name_bind)
+                .bind::<Nullable<Int4>, _>(exclude_user_bind)
+                .bind::<Nullable<Varchar>, _>(category_bind)
+                .bind::<Nullable<Bool>, _>(is_available_bind)
+                .load::<MissionModel>(conn)?;
+
+            Ok(rows)
+        })
+        .await
     }
 
     async fn crew_counting(&self, mission_id: i32) -> Result<u32> {
         use crate::infrastructure::database::schema::crew_memberships;
         use diesel::ExpressionMethods;
 
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-        let val = crew_memberships::table
-            .filter(crew_memberships::mission_id.eq(mission_id))
-            .count()
-            .get_result::<i64>(&mut conn)?;
+        with_connection(&self.db_pool, move |conn| {
+            let val = crew_memberships::table
+                .filter(crew_memberships::mission_id.eq(mission_id))
+                .count()
+                .get_result::<i64>(conn)?;
 
-        Ok(val as u32)
+            Ok(val as u32)
+        })
+        .await
     }
 
     async fn get_crew(&self, mission_id: i32) -> Result<Vec<BrawlerModel>> {
@@ -148,11 +159,13 @@ LEFT JOIN (
 ) j ON j.brawler_id = b.id
 WHERE cm.mission_id = $1
 "#;
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-        let brawler_list = diesel::sql_query(sql)
-            .bind::<diesel::sql_types::Int4, _>(mission_id)
-            .load::<BrawlerModel>(&mut conn)?;
-
-        Ok(brawler_list)
+        with_connection(&self.db_pool, move |conn| {
+            let brawler_list = diesel::sql_query(sql)
+                .bind::<diesel::sql_types::Int4, _>(mission_id)
+                .load::<BrawlerModel>(conn)?;
+
+            Ok(brawler_list)
+        })
+        .await
     }
 }
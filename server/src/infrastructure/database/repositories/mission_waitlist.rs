@@ -0,0 +1,119 @@
+use anyhow::{Ok, Result};
+use async_trait::async_trait;
+use diesel::{
+    Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, insert_into,
+    result::{DatabaseErrorKind, Error as DieselError},
+};
+use std::sync::Arc;
+
+use crate::{
+    domain::{
+        entities::mission_waitlist::AddMissionWaitlistEntity, errors::AppError,
+        repositories::mission_waitlist::MissionWaitlistRepository,
+        value_objects::mission_waitlist_model::WaitlistPositionModel,
+    },
+    infrastructure::database::{
+        postgresql_connection::{PgPoolSquad, with_connection},
+        repositories::crew_operation::{MissionSlots, lock_mission},
+        schema::mission_waitlist,
+    },
+};
+
+pub struct MissionWaitlistPostgres {
+    db_pool: Arc<PgPoolSquad>,
+}
+
+impl MissionWaitlistPostgres {
+    pub fn new(db_pool: Arc<PgPoolSquad>) -> Self {
+        Self { db_pool }
+    }
+}
+
+#[async_trait]
+impl MissionWaitlistRepository for MissionWaitlistPostgres {
+    async fn join(&self, add_waitlist_entity: AddMissionWaitlistEntity) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                // Same lock as crew join, so a freed slot can't slip past the waitlist check
+                match lock_mission(conn, add_waitlist_entity.mission_id)? {
+                    MissionSlots::Closed => {
+                        return Err(AppError::Conflict(
+                            "Mission is not joinable in current status".to_string(),
+                        )
+                        .into());
+                    }
+                    MissionSlots::Free => {
+                        return Err(AppError::Conflict(
+                            "Mission still has free slots, join it directly".to_string(),
+                        )
+                        .into());
+                    }
+                    MissionSlots::Full => {}
+                }
+
+                insert_into(mission_waitlist::table)
+                    .values(add_waitlist_entity)
+                    .execute(conn)
+                    .map_err(|e| match e {
+                        DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                            AppError::Conflict(
+                                "You are already on the waitlist for this mission".to_string(),
+                            )
+                            .into()
+                        }
+                        other => anyhow::Error::from(other),
+                    })?;
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn leave(&self, mission_id: i32, brawler_id: i32) -> Result<usize> {
+        with_connection(&self.db_pool, move |conn| {
+            let removed = diesel::delete(mission_waitlist::table)
+                .filter(mission_waitlist::mission_id.eq(mission_id))
+                .filter(mission_waitlist::brawler_id.eq(brawler_id))
+                .execute(conn)?;
+
+            Ok(removed)
+        })
+        .await
+    }
+
+    async fn position(
+        &self,
+        mission_id: i32,
+        brawler_id: i32,
+    ) -> Result<Option<WaitlistPositionModel>> {
+        with_connection(&self.db_pool, move |conn| {
+            let Some(entry_id) = mission_waitlist::table
+                .select(mission_waitlist::id)
+                .filter(mission_waitlist::mission_id.eq(mission_id))
+                .filter(mission_waitlist::brawler_id.eq(brawler_id))
+                .first::<i32>(conn)
+                .optional()?
+            else {
+                return Ok(None);
+            };
+
+            let ahead: i64 = mission_waitlist::table
+                .filter(mission_waitlist::mission_id.eq(mission_id))
+                .filter(mission_waitlist::id.lt(entry_id))
+                .count()
+                .get_result(conn)?;
+
+            let total: i64 = mission_waitlist::table
+                .filter(mission_waitlist::mission_id.eq(mission_id))
+                .count()
+                .get_result(conn)?;
+
+            Ok(Some(WaitlistPositionModel {
+                mission_id,
+                position: ahead + 1,
+                total,
+            }))
+        })
+        .await
+    }
+}
@@ -1,10 +1,17 @@
+pub mod access_tokens;
+pub mod brawler_identities;
 pub mod brawlers;
 pub mod crew_operation;
+pub mod diesel_transaction;
 pub mod friendships;
-// pub mod diesel_transaction;
+pub mod login_throttles;
 pub mod mission_comment;
+pub mod mission_invites;
 pub mod mission_management;
-pub mod mission_operation;
 pub mod mission_viewing;
+pub mod mission_waitlist;
 pub mod notifications;
+pub mod password_resets;
 pub mod private_messages;
+pub mod sessions;
+pub mod totp;
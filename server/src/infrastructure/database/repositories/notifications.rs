@@ -3,7 +3,7 @@ use crate::{
         entities::notifications::{AddNotificationEntity, NotificationEntity},
         repositories::notifications::NotificationRepository,
     },
-    infrastructure::database::postgresql_connection::PgPoolSquad,
+    infrastructure::database::postgresql_connection::{PgPoolSquad, with_connection},
 };
 use anyhow::Result;
 use async_trait::async_trait;
@@ -25,67 +25,77 @@ impl NotificationRepository for NotificationPostgres {
     async fn add(&self, notification: AddNotificationEntity) -> Result<NotificationEntity> {
         use crate::infrastructure::database::schema::notifications;
 
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-        let result = diesel::insert_into(notifications::table)
-            .values((
-                notifications::brawler_id.eq(notification.brawler_id),
-                notifications::type_.eq(notification.type_),
-                notifications::content.eq(notification.content),
-                notifications::related_id.eq(notification.related_id),
-            ))
-            .get_result(&mut conn)?;
-
-        Ok(result)
+        with_connection(&self.db_pool, move |conn| {
+            let result = diesel::insert_into(notifications::table)
+                .values((
+                    notifications::brawler_id.eq(notification.brawler_id),
+                    notifications::type_.eq(notification.type_),
+                    notifications::content.eq(notification.content),
+                    notifications::related_id.eq(notification.related_id),
+                ))
+                .get_result(conn)?;
+
+            Ok(result)
+        })
+        .await
     }
 
     async fn get_by_user(&self, user_id: i32) -> Result<Vec<NotificationEntity>> {
         use crate::infrastructure::database::schema::notifications;
 
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-        let results = notifications::table
-            .filter(notifications::brawler_id.eq(user_id))
-            .order(notifications::created_at.desc())
-            .limit(50)
-            .load::<NotificationEntity>(&mut conn)?;
+        with_connection(&self.db_pool, move |conn| {
+            let results = notifications::table
+                .filter(notifications::brawler_id.eq(user_id))
+                .order(notifications::created_at.desc())
+                .limit(50)
+                .load::<NotificationEntity>(conn)?;
 
-        Ok(results)
+            Ok(results)
+        })
+        .await
     }
 
     async fn mark_as_read(&self, notification_id: i32, user_id: i32) -> Result<()> {
         use crate::infrastructure::database::schema::notifications;
 
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-        diesel::update(
-            notifications::table.filter(
-                notifications::id
-                    .eq(notification_id)
-                    .and(notifications::brawler_id.eq(user_id)),
-            ),
-        )
-        .set(notifications::is_read.eq(true))
-        .execute(&mut conn)?;
-
-        Ok(())
+        with_connection(&self.db_pool, move |conn| {
+            diesel::update(
+                notifications::table.filter(
+                    notifications::id
+                        .eq(notification_id)
+                        .and(notifications::brawler_id.eq(user_id)),
+                ),
+            )
+            .set(notifications::is_read.eq(true))
+            .execute(conn)?;
+
+            Ok(())
+        })
+        .await
     }
 
     async fn mark_all_as_read(&self, user_id: i32) -> Result<()> {
         use crate::infrastructure::database::schema::notifications;
 
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-        diesel::update(notifications::table.filter(notifications::brawler_id.eq(user_id)))
-            .set(notifications::is_read.eq(true))
-            .execute(&mut conn)?;
+        with_connection(&self.db_pool, move |conn| {
+            diesel::update(notifications::table.filter(notifications::brawler_id.eq(user_id)))
+                .set(notifications::is_read.eq(true))
+                .execute(conn)?;
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     async fn delete_for_user(&self, user_id: i32) -> Result<()> {
         use crate::infrastructure::database::schema::notifications;
 
-        let mut conn = Arc::clone(&self.db_pool).get()?;
-        diesel::delete(notifications::table.filter(notifications::brawler_id.eq(user_id)))
-            .execute(&mut conn)?;
+        with_connection(&self.db_pool, move |conn| {
+            diesel::delete(notifications::table.filter(notifications::brawler_id.eq(user_id)))
+                .execute(conn)?;
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 }
@@ -0,0 +1,87 @@
+use anyhow::{Ok, Result};
+use async_trait::async_trait;
+use diesel::{
+    BoolExpressionMethods, Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl,
+    delete,
+    dsl::{now, update},
+    insert_into,
+};
+use std::sync::Arc;
+
+use crate::{
+    domain::{
+        entities::password_resets::AddPasswordResetEntity,
+        repositories::password_resets::PasswordResetRepository,
+    },
+    infrastructure::database::{
+        postgresql_connection::{PgPoolSquad, with_connection},
+        schema::{brawlers, password_reset_tokens},
+    },
+};
+
+pub struct PasswordResetPostgres {
+    db_pool: Arc<PgPoolSquad>,
+}
+
+impl PasswordResetPostgres {
+    pub fn new(db_pool: Arc<PgPoolSquad>) -> Self {
+        Self { db_pool }
+    }
+}
+
+#[async_trait]
+impl PasswordResetRepository for PasswordResetPostgres {
+    async fn issue(&self, add_password_reset_entity: AddPasswordResetEntity) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                // Used and expired tokens are no use to anyone either
+                delete(password_reset_tokens::table)
+                    .filter(
+                        password_reset_tokens::brawler_id
+                            .eq(add_password_reset_entity.brawler_id)
+                            .or(password_reset_tokens::expires_at.lt(now)),
+                    )
+                    .execute(conn)?;
+
+                insert_into(password_reset_tokens::table)
+                    .values(add_password_reset_entity)
+                    .execute(conn)?;
+
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn consume(&self, token_hash: String, password_hash: String) -> Result<Option<i32>> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                // Row lock so the same link can't be used twice at once
+                let brawler_id = password_reset_tokens::table
+                    .filter(password_reset_tokens::token_hash.eq(token_hash))
+                    .filter(password_reset_tokens::used_at.is_null())
+                    .filter(password_reset_tokens::expires_at.gt(now))
+                    .select(password_reset_tokens::brawler_id)
+                    .for_update()
+                    .first::<i32>(conn)
+                    .optional()?;
+                let Some(brawler_id) = brawler_id else {
+                    return Ok(None);
+                };
+
+                update(password_reset_tokens::table)
+                    .filter(password_reset_tokens::brawler_id.eq(brawler_id))
+                    .filter(password_reset_tokens::used_at.is_null())
+                    .set(password_reset_tokens::used_at.eq(now))
+                    .execute(conn)?;
+
+                update(brawlers::table.find(brawler_id))
+                    .set(brawlers::password.eq(password_hash))
+                    .execute(conn)?;
+
+                Ok(Some(brawler_id))
+            })
+        })
+        .await
+    }
+}
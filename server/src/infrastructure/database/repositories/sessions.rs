@@ -0,0 +1,247 @@
+use anyhow::{Ok, Result};
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use diesel::{
+    BoolExpressionMethods, Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl,
+    SelectableHelper, delete,
+    dsl::{exists, now, select, update},
+    insert_into,
+};
+use std::sync::Arc;
+
+use crate::{
+    domain::{
+        entities::sessions::{
+            AddRefreshTokenEntity, AddSessionEntity, RefreshRotation, RefreshTokenEntity,
+            SessionEntity,
+        },
+        errors::AppError,
+        repositories::sessions::SessionRepository,
+        value_objects::session_model::SessionClient,
+    },
+    infrastructure::database::{
+        postgresql_connection::{PgPoolSquad, with_connection},
+        schema::{refresh_tokens, sessions},
+    },
+};
+
+pub struct SessionPostgres {
+    db_pool: Arc<PgPoolSquad>,
+}
+
+impl SessionPostgres {
+    pub fn new(db_pool: Arc<PgPoolSquad>) -> Self {
+        Self { db_pool }
+    }
+}
+
+#[async_trait]
+impl SessionRepository for SessionPostgres {
+    async fn start(
+        &self,
+        add_session_entity: AddSessionEntity,
+        token_hash: String,
+        expires_at: NaiveDateTime,
+    ) -> Result<i32> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                let brawler_id = add_session_entity.brawler_id;
+
+                // Expired tokens are no use for reuse detection any more
+                delete(refresh_tokens::table)
+                    .filter(refresh_tokens::brawler_id.eq(brawler_id))
+                    .filter(refresh_tokens::expires_at.lt(now))
+                    .execute(conn)?;
+
+                let session_id = insert_into(sessions::table)
+                    .values(add_session_entity)
+                    .returning(sessions::id)
+                    .get_result::<i32>(conn)?;
+
+                insert_into(refresh_tokens::table)
+                    .values(AddRefreshTokenEntity {
+                        brawler_id,
+                        session_id,
+                        token_hash,
+                        expires_at,
+                    })
+                    .execute(conn)?;
+
+                Ok(session_id)
+            })
+        })
+        .await
+    }
+
+    async fn rotate(
+        &self,
+        token_hash: String,
+        next_hash: String,
+        next_expires_at: NaiveDateTime,
+        client: SessionClient,
+    ) -> Result<RefreshRotation> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                // Row lock so two refreshes racing with the same token can't both win
+                let Some(token) = refresh_tokens::table
+                    .filter(refresh_tokens::token_hash.eq(&token_hash))
+                    .select(RefreshTokenEntity::as_select())
+                    .for_update()
+                    .first(conn)
+                    .optional()?
+                else {
+                    return Ok(RefreshRotation::Rejected);
+                };
+
+                let revoked = sessions::table
+                    .find(token.session_id)
+                    .select(sessions::revoked_at.is_not_null())
+                    .first::<bool>(conn)?;
+                if revoked || token.expires_at <= Utc::now().naive_utc() {
+                    return Ok(RefreshRotation::Rejected);
+                }
+
+                if token.used_at.is_some() {
+                    update(sessions::table.find(token.session_id))
+                        .set(sessions::revoked_at.eq(now))
+                        .execute(conn)?;
+
+                    return Ok(RefreshRotation::Reused {
+                        brawler_id: token.brawler_id,
+                        session_id: token.session_id,
+                    });
+                }
+
+                update(refresh_tokens::table.find(token.id))
+                    .set(refresh_tokens::used_at.eq(now))
+                    .execute(conn)?;
+
+                insert_into(refresh_tokens::table)
+                    .values(AddRefreshTokenEntity {
+                        brawler_id: token.brawler_id,
+                        session_id: token.session_id,
+                        token_hash: next_hash,
+                        expires_at: next_expires_at,
+                    })
+                    .execute(conn)?;
+
+                update(sessions::table.find(token.session_id))
+                    .set((
+                        sessions::last_used_at.eq(now),
+                        sessions::user_agent.eq(client.user_agent),
+                        sessions::ip.eq(client.ip),
+                    ))
+                    .execute(conn)?;
+
+                Ok(RefreshRotation::Rotated {
+                    brawler_id: token.brawler_id,
+                    session_id: token.session_id,
+                })
+            })
+        })
+        .await
+    }
+
+    async fn is_active(&self, session_id: i32) -> Result<bool> {
+        with_connection(&self.db_pool, move |conn| {
+            let active = select(exists(
+                sessions::table
+                    .find(session_id)
+                    .filter(sessions::revoked_at.is_null()),
+            ))
+            .get_result::<bool>(conn)?;
+
+            Ok(active)
+        })
+        .await
+    }
+
+    async fn list_active(&self, brawler_id: i32) -> Result<Vec<SessionEntity>> {
+        with_connection(&self.db_pool, move |conn| {
+            // A session whose refresh tokens have all expired is over too
+            let live_tokens = refresh_tokens::table
+                .filter(refresh_tokens::session_id.eq(sessions::id))
+                .filter(refresh_tokens::expires_at.gt(now));
+
+            let results = sessions::table
+                .filter(
+                    sessions::brawler_id
+                        .eq(brawler_id)
+                        .and(sessions::revoked_at.is_null()),
+                )
+                .filter(exists(live_tokens))
+                .order(sessions::last_used_at.desc())
+                .select(SessionEntity::as_select())
+                .load(conn)?;
+
+            Ok(results)
+        })
+        .await
+    }
+
+    async fn revoke(&self, brawler_id: i32, session_id: i32) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            let revoked = update(sessions::table.find(session_id))
+                .filter(sessions::brawler_id.eq(brawler_id))
+                .filter(sessions::revoked_at.is_null())
+                .set(sessions::revoked_at.eq(now))
+                .execute(conn)?;
+
+            if revoked == 0 {
+                return Err(AppError::NotFound("Session not found".to_string()).into());
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn revoke_by_token(&self, token_hash: String) -> Result<Option<i32>> {
+        with_connection(&self.db_pool, move |conn| {
+            let session_id = refresh_tokens::table
+                .filter(refresh_tokens::token_hash.eq(token_hash))
+                .select(refresh_tokens::session_id)
+                .first::<i32>(conn)
+                .optional()?;
+            let Some(session_id) = session_id else {
+                return Ok(None);
+            };
+
+            let revoked = update(sessions::table.find(session_id))
+                .filter(sessions::revoked_at.is_null())
+                .set(sessions::revoked_at.eq(now))
+                .execute(conn)?;
+
+            Ok((revoked > 0).then_some(session_id))
+        })
+        .await
+    }
+
+    async fn revoke_all_for_brawler(&self, brawler_id: i32) -> Result<Vec<i32>> {
+        with_connection(&self.db_pool, move |conn| {
+            let session_ids = update(sessions::table)
+                .filter(sessions::brawler_id.eq(brawler_id))
+                .filter(sessions::revoked_at.is_null())
+                .set(sessions::revoked_at.eq(now))
+                .returning(sessions::id)
+                .get_results::<i32>(conn)?;
+
+            Ok(session_ids)
+        })
+        .await
+    }
+
+    async fn revoke_all_except(&self, brawler_id: i32, keep_session_id: i32) -> Result<Vec<i32>> {
+        with_connection(&self.db_pool, move |conn| {
+            let session_ids = update(sessions::table)
+                .filter(sessions::brawler_id.eq(brawler_id))
+                .filter(sessions::id.ne(keep_session_id))
+                .filter(sessions::revoked_at.is_null())
+                .set(sessions::revoked_at.eq(now))
+                .returning(sessions::id)
+                .get_results::<i32>(conn)?;
+
+            Ok(session_ids)
+        })
+        .await
+    }
+}
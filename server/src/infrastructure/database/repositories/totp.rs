@@ -0,0 +1,215 @@
+use anyhow::{Ok, Result};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use diesel::{
+    BoolExpressionMethods, Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl,
+    SelectableHelper, delete,
+    dsl::{now, update},
+    insert_into,
+    pg::PgConnection,
+};
+use std::sync::Arc;
+
+use crate::{
+    domain::{
+        entities::totp::BrawlerTotpEntity, errors::AppError, repositories::totp::TotpRepository,
+    },
+    infrastructure::database::{
+        postgresql_connection::{PgPoolSquad, with_connection},
+        schema::{brawler_totp, totp_recovery_codes},
+    },
+};
+
+pub struct TotpPostgres {
+    db_pool: Arc<PgPoolSquad>,
+}
+
+impl TotpPostgres {
+    pub fn new(db_pool: Arc<PgPoolSquad>) -> Self {
+        Self { db_pool }
+    }
+}
+
+fn insert_recovery_codes(
+    conn: &mut PgConnection,
+    brawler_id: i32,
+    recovery_code_hashes: Vec<String>,
+) -> Result<()> {
+    delete(totp_recovery_codes::table.filter(totp_recovery_codes::brawler_id.eq(brawler_id)))
+        .execute(conn)?;
+
+    let rows: Vec<_> = recovery_code_hashes
+        .into_iter()
+        .map(|code_hash| {
+            (
+                totp_recovery_codes::brawler_id.eq(brawler_id),
+                totp_recovery_codes::code_hash.eq(code_hash),
+            )
+        })
+        .collect();
+    insert_into(totp_recovery_codes::table)
+        .values(rows)
+        .execute(conn)?;
+    Ok(())
+}
+
+#[async_trait]
+impl TotpRepository for TotpPostgres {
+    async fn find(&self, brawler_id: i32) -> Result<Option<BrawlerTotpEntity>> {
+        with_connection(&self.db_pool, move |conn| {
+            let totp = brawler_totp::table
+                .find(brawler_id)
+                .select(BrawlerTotpEntity::as_select())
+                .first::<BrawlerTotpEntity>(conn)
+                .optional()?;
+            Ok(totp)
+        })
+        .await
+    }
+
+    async fn begin_enrollment(&self, brawler_id: i32, secret: String) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                let confirmed_at = brawler_totp::table
+                    .find(brawler_id)
+                    .select(brawler_totp::confirmed_at)
+                    .for_update()
+                    .first::<Option<NaiveDateTime>>(conn)
+                    .optional()?;
+
+                match confirmed_at {
+                    Some(Some(_)) => {
+                        return Err(AppError::Conflict(
+                            "Two-factor authentication is already enabled".to_string(),
+                        )
+                        .into());
+                    }
+                    // Only a pending enrollment is overwritten
+                    Some(None) => {
+                        update(brawler_totp::table.find(brawler_id))
+                            .set((
+                                brawler_totp::secret.eq(secret),
+                                brawler_totp::last_used_step.eq(None::<i64>),
+                                brawler_totp::created_at.eq(now),
+                            ))
+                            .execute(conn)?;
+                    }
+                    None => {
+                        insert_into(brawler_totp::table)
+                            .values((
+                                brawler_totp::brawler_id.eq(brawler_id),
+                                brawler_totp::secret.eq(secret),
+                            ))
+                            .execute(conn)?;
+                    }
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn confirm(
+        &self,
+        brawler_id: i32,
+        step: i64,
+        recovery_code_hashes: Vec<String>,
+    ) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                let confirmed = update(
+                    brawler_totp::table
+                        .find(brawler_id)
+                        .filter(brawler_totp::confirmed_at.is_null()),
+                )
+                .set((
+                    brawler_totp::confirmed_at.eq(now),
+                    brawler_totp::last_used_step.eq(step),
+                ))
+                .execute(conn)?;
+                if confirmed == 0 {
+                    return Err(AppError::NotFound(
+                        "No two-factor enrollment to confirm".to_string(),
+                    )
+                    .into());
+                }
+
+                insert_recovery_codes(conn, brawler_id, recovery_code_hashes)
+            })
+        })
+        .await
+    }
+
+    async fn accept_step(&self, brawler_id: i32, step: i64) -> Result<bool> {
+        with_connection(&self.db_pool, move |conn| {
+            // A single conditional update, so two requests can't both use a code
+            let accepted = update(
+                brawler_totp::table
+                    .find(brawler_id)
+                    .filter(brawler_totp::confirmed_at.is_not_null())
+                    .filter(
+                        brawler_totp::last_used_step
+                            .is_null()
+                            .or(brawler_totp::last_used_step.lt(step)),
+                    ),
+            )
+            .set(brawler_totp::last_used_step.eq(step))
+            .execute(conn)?;
+            Ok(accepted == 1)
+        })
+        .await
+    }
+
+    async fn use_recovery_code(&self, brawler_id: i32, code_hash: String) -> Result<bool> {
+        with_connection(&self.db_pool, move |conn| {
+            let used = update(
+                totp_recovery_codes::table
+                    .filter(totp_recovery_codes::brawler_id.eq(brawler_id))
+                    .filter(totp_recovery_codes::code_hash.eq(code_hash))
+                    .filter(totp_recovery_codes::used_at.is_null()),
+            )
+            .set(totp_recovery_codes::used_at.eq(now))
+            .execute(conn)?;
+            Ok(used == 1)
+        })
+        .await
+    }
+
+    async fn replace_recovery_codes(
+        &self,
+        brawler_id: i32,
+        recovery_code_hashes: Vec<String>,
+    ) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| insert_recovery_codes(conn, brawler_id, recovery_code_hashes))
+        })
+        .await
+    }
+
+    async fn remaining_recovery_codes(&self, brawler_id: i32) -> Result<i64> {
+        with_connection(&self.db_pool, move |conn| {
+            let remaining = totp_recovery_codes::table
+                .filter(totp_recovery_codes::brawler_id.eq(brawler_id))
+                .filter(totp_recovery_codes::used_at.is_null())
+                .count()
+                .get_result::<i64>(conn)?;
+            Ok(remaining)
+        })
+        .await
+    }
+
+    async fn disable(&self, brawler_id: i32) -> Result<()> {
+        with_connection(&self.db_pool, move |conn| {
+            conn.transaction(|conn| {
+                delete(
+                    totp_recovery_codes::table
+                        .filter(totp_recovery_codes::brawler_id.eq(brawler_id)),
+                )
+                .execute(conn)?;
+                delete(brawler_totp::table.find(brawler_id)).execute(conn)?;
+                Ok(())
+            })
+        })
+        .await
+    }
+}
@@ -24,6 +24,37 @@ diesel::table! {
         instagram -> Nullable<Varchar>,
         #[max_length = 255]
         facebook -> Nullable<Varchar>,
+        last_seen_at -> Nullable<Timestamp>,
+        #[max_length = 16]
+        role -> Varchar,
+    }
+}
+
+diesel::table! {
+    brawler_identities (id) {
+        id -> Int4,
+        brawler_id -> Int4,
+        #[max_length = 32]
+        provider -> Varchar,
+        #[max_length = 255]
+        subject -> Varchar,
+        #[max_length = 255]
+        email -> Nullable<Varchar>,
+        #[max_length = 255]
+        display_name -> Nullable<Varchar>,
+        created_at -> Timestamp,
+        last_login_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    brawler_totp (brawler_id) {
+        brawler_id -> Int4,
+        #[max_length = 64]
+        secret -> Varchar,
+        confirmed_at -> Nullable<Timestamp>,
+        last_used_step -> Nullable<Int8>,
+        created_at -> Timestamp,
     }
 }
 
@@ -47,6 +78,46 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    login_lockouts (id) {
+        id -> Int4,
+        #[max_length = 16]
+        scope -> Varchar,
+        #[max_length = 255]
+        subject -> Varchar,
+        brawler_id -> Nullable<Int4>,
+        #[max_length = 64]
+        ip -> Nullable<Varchar>,
+        failures -> Int4,
+        locked_until -> Timestamp,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    login_throttles (scope, subject) {
+        #[max_length = 16]
+        scope -> Varchar,
+        #[max_length = 255]
+        subject -> Varchar,
+        failures -> Int4,
+        last_failed_at -> Timestamp,
+        locked_until -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    mission_applications (id) {
+        id -> Int4,
+        mission_id -> Int4,
+        brawler_id -> Int4,
+        #[max_length = 32]
+        status -> Varchar,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     mission_comments (id) {
         id -> Int4,
@@ -57,6 +128,41 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    mission_invitations (id) {
+        id -> Int4,
+        mission_id -> Int4,
+        inviter_id -> Int4,
+        invitee_id -> Int4,
+        #[max_length = 32]
+        status -> Varchar,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    mission_invite_links (id) {
+        id -> Int4,
+        mission_id -> Int4,
+        created_by -> Int4,
+        max_uses -> Nullable<Int4>,
+        uses -> Int4,
+        expires_at -> Timestamp,
+        revoked_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    mission_waitlist (id) {
+        id -> Int4,
+        mission_id -> Int4,
+        brawler_id -> Int4,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     missions (id) {
         id -> Int4,
@@ -74,6 +180,9 @@ diesel::table! {
         #[max_length = 255]
         location -> Nullable<Varchar>,
         category -> Varchar,
+        #[max_length = 32]
+        join_policy -> Varchar,
+        is_private -> Bool,
     }
 }
 
@@ -91,6 +200,34 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    password_reset_tokens (id) {
+        id -> Int4,
+        brawler_id -> Int4,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    personal_access_tokens (id) {
+        id -> Int4,
+        brawler_id -> Int4,
+        #[max_length = 64]
+        name -> Varchar,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        scopes -> Array<Text>,
+        created_at -> Timestamp,
+        last_used_at -> Nullable<Timestamp>,
+        expires_at -> Nullable<Timestamp>,
+        revoked_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     private_messages (id) {
         id -> Int4,
@@ -102,19 +239,93 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    refresh_tokens (id) {
+        id -> Int4,
+        brawler_id -> Int4,
+        session_id -> Int4,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    sessions (id) {
+        id -> Int4,
+        brawler_id -> Int4,
+        user_agent -> Nullable<Text>,
+        #[max_length = 64]
+        ip -> Nullable<Varchar>,
+        created_at -> Timestamp,
+        last_used_at -> Timestamp,
+        revoked_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    totp_recovery_codes (id) {
+        id -> Int4,
+        brawler_id -> Int4,
+        #[max_length = 64]
+        code_hash -> Varchar,
+        used_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    ws_fanout_overflow (id) {
+        id -> Int8,
+        payload -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::joinable!(brawler_identities -> brawlers (brawler_id));
+diesel::joinable!(brawler_totp -> brawlers (brawler_id));
 diesel::joinable!(crew_memberships -> brawlers (brawler_id));
 diesel::joinable!(crew_memberships -> missions (mission_id));
+diesel::joinable!(login_lockouts -> brawlers (brawler_id));
+diesel::joinable!(mission_applications -> brawlers (brawler_id));
+diesel::joinable!(mission_applications -> missions (mission_id));
 diesel::joinable!(mission_comments -> brawlers (brawler_id));
 diesel::joinable!(mission_comments -> missions (mission_id));
+diesel::joinable!(mission_invite_links -> brawlers (created_by));
+diesel::joinable!(mission_invite_links -> missions (mission_id));
+diesel::joinable!(mission_invitations -> missions (mission_id));
+diesel::joinable!(mission_waitlist -> brawlers (brawler_id));
+diesel::joinable!(mission_waitlist -> missions (mission_id));
 diesel::joinable!(missions -> brawlers (chief_id));
 diesel::joinable!(notifications -> brawlers (brawler_id));
+diesel::joinable!(password_reset_tokens -> brawlers (brawler_id));
+diesel::joinable!(personal_access_tokens -> brawlers (brawler_id));
+diesel::joinable!(refresh_tokens -> brawlers (brawler_id));
+diesel::joinable!(refresh_tokens -> sessions (session_id));
+diesel::joinable!(sessions -> brawlers (brawler_id));
+diesel::joinable!(totp_recovery_codes -> brawlers (brawler_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    brawler_identities,
+    brawler_totp,
     brawlers,
     crew_memberships,
     friendships,
+    login_lockouts,
+    login_throttles,
+    mission_applications,
     mission_comments,
+    mission_invitations,
+    mission_invite_links,
+    mission_waitlist,
     missions,
     notifications,
+    password_reset_tokens,
+    personal_access_tokens,
     private_messages,
+    refresh_tokens,
+    sessions,
+    totp_recovery_codes,
+    ws_fanout_overflow,
 );
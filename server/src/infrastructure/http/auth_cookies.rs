@@ -0,0 +1,198 @@
+//! Cookie session mode (`AUTH_COOKIES=true`).
+//!
+//! Access and refresh tokens are set as HttpOnly cookies and left out of response
+//! bodies, so page scripts never see them. Since browsers attach cookies to
+//! cross-site requests too, state-changing requests authenticated by cookie must
+//! echo the readable CSRF cookie in the `X-CSRF-Token` header (double submit).
+//!
+//! Sign-ins at a provider set one more cookie in either mode, binding the state
+//! to the browser that started them.
+
+use aws_lc_rs::constant_time::verify_slices_are_equal;
+use axum::{
+    Json,
+    http::{HeaderMap, Method, StatusCode},
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use cookie::time::Duration;
+
+use crate::{
+    application::use_cases::oauth::STATE_TTL_MINUTES,
+    config::config_loader::{get_auth_cookie_env, get_jwt_env},
+    domain::errors::{AppError, AppResult},
+    infrastructure::{jwt::jwt_model::Passport, secure_token},
+};
+
+pub const ACCESS_COOKIE: &str = "vibe_access";
+pub const REFRESH_COOKIE: &str = "vibe_refresh";
+pub const CSRF_COOKIE: &str = "vibe_csrf";
+pub const CSRF_HEADER: &str = "x-csrf-token";
+pub const OAUTH_STATE_COOKIE: &str = "vibe_oauth_state";
+
+/// Sent on every API call, including WebSocket handshakes
+const ACCESS_PATH: &str = "/api";
+/// Only refresh and logout need the refresh token
+const REFRESH_PATH: &str = "/api/authentication";
+/// Readable by the page wherever it is served from
+const CSRF_PATH: &str = "/";
+/// Sign-in callbacks and linking
+const OAUTH_PATH: &str = "/api/oauth";
+
+pub fn enabled() -> AppResult<bool> {
+    Ok(get_auth_cookie_env()?.enabled)
+}
+
+fn cookie(
+    name: &'static str,
+    value: String,
+    path: &'static str,
+    http_only: bool,
+    max_age: Duration,
+    secure: bool,
+) -> Cookie<'static> {
+    Cookie::build((name, value))
+        .path(path)
+        .http_only(http_only)
+        .secure(secure)
+        .same_site(SameSite::Strict)
+        .max_age(max_age)
+        .build()
+}
+
+/// Moves the passport's tokens into cookies. A new refresh token also gets a new
+/// CSRF token.
+fn set_session_cookies(mut jar: CookieJar, passport: &mut Passport) -> AppResult<CookieJar> {
+    let secure = get_auth_cookie_env()?.secure;
+    let jwt_env = get_jwt_env()?;
+
+    let access_token = std::mem::take(&mut passport.token);
+    if !access_token.is_empty() {
+        jar = jar.add(cookie(
+            ACCESS_COOKIE,
+            access_token,
+            ACCESS_PATH,
+            true,
+            Duration::minutes(jwt_env.access_ttl_minutes),
+            secure,
+        ));
+    }
+
+    if let Some(refresh_token) = passport.refresh_token.take() {
+        let session_age = Duration::days(jwt_env.ttl);
+        jar = jar
+            .add(cookie(
+                REFRESH_COOKIE,
+                refresh_token,
+                REFRESH_PATH,
+                true,
+                session_age,
+                secure,
+            ))
+            .add(cookie(
+                CSRF_COOKIE,
+                secure_token::generate()?,
+                CSRF_PATH,
+                false,
+                session_age,
+                secure,
+            ));
+    }
+
+    Ok(jar)
+}
+
+/// Responds with `passport`. In cookie mode its tokens go into cookies instead
+/// of the body.
+pub fn passport_response(status: StatusCode, jar: CookieJar, mut passport: Passport) -> Response {
+    let jar = match enabled() {
+        Ok(false) => return (status, Json(passport)).into_response(),
+        Ok(true) => set_session_cookies(jar, &mut passport),
+        Err(e) => Err(e),
+    };
+    match jar {
+        Ok(jar) => (status, jar, Json(passport)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// `204 No Content` for a logout, removing the session cookies in cookie mode
+pub fn logged_out_response(jar: CookieJar) -> Response {
+    match enabled() {
+        Ok(true) => {
+            let jar = jar
+                .remove(Cookie::build(ACCESS_COOKIE).path(ACCESS_PATH))
+                .remove(Cookie::build(REFRESH_COOKIE).path(REFRESH_PATH))
+                .remove(Cookie::build(CSRF_COOKIE).path(CSRF_PATH));
+            (StatusCode::NO_CONTENT, jar).into_response()
+        }
+        Ok(false) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Checks the double-submitted CSRF token of a request authenticated by cookie.
+/// Safe methods change nothing, so they pass without one.
+pub fn verify_csrf(method: &Method, headers: &HeaderMap, jar: &CookieJar) -> AppResult<()> {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return Ok(());
+    }
+    check_csrf_header(headers, jar)
+}
+
+fn check_csrf_header(headers: &HeaderMap, jar: &CookieJar) -> AppResult<()> {
+    let expected = jar.get(CSRF_COOKIE).map(|cookie| cookie.value());
+    let submitted = headers
+        .get(CSRF_HEADER)
+        .and_then(|value| value.to_str().ok());
+    match (expected, submitted) {
+        (Some(expected), Some(submitted))
+            if !expected.is_empty()
+                && verify_slices_are_equal(expected.as_bytes(), submitted.as_bytes()).is_ok() =>
+        {
+            Ok(())
+        }
+        _ => Err(AppError::Forbidden(
+            "Missing or invalid CSRF token".to_string(),
+        )),
+    }
+}
+
+/// In cookie mode, the refresh token from its cookie once the request's CSRF
+/// token checks out
+pub fn refresh_token(headers: &HeaderMap, jar: &CookieJar) -> AppResult<Option<String>> {
+    if !enabled()? {
+        return Ok(None);
+    }
+    let Some(token) = jar
+        .get(REFRESH_COOKIE)
+        .map(|cookie| cookie.value().to_string())
+    else {
+        return Ok(None);
+    };
+    check_csrf_header(headers, jar)?;
+    Ok(Some(token))
+}
+
+/// Keeps a provider sign-in's nonce in this browser until its state expires
+pub fn with_oauth_state(jar: CookieJar, nonce: String) -> AppResult<CookieJar> {
+    let cookie = Cookie::build((OAUTH_STATE_COOKIE, nonce))
+        .path(OAUTH_PATH)
+        .http_only(true)
+        .secure(get_auth_cookie_env()?.secure)
+        // The provider sends the browser back from another site
+        .same_site(SameSite::Lax)
+        .max_age(Duration::minutes(STATE_TTL_MINUTES))
+        .build();
+    Ok(jar.add(cookie))
+}
+
+/// The nonce `with_oauth_state` kept, and `jar` without it: a state is good for
+/// one callback
+pub fn take_oauth_state(jar: CookieJar) -> (CookieJar, Option<String>) {
+    let nonce = jar
+        .get(OAUTH_STATE_COOKIE)
+        .map(|cookie| cookie.value().to_string());
+    let jar = jar.remove(Cookie::build(OAUTH_STATE_COOKIE).path(OAUTH_PATH));
+    (jar, nonce)
+}
@@ -0,0 +1,94 @@
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{HeaderMap, header, request::Parts},
+};
+
+use crate::domain::value_objects::session_model::SessionClient;
+
+const MAX_USER_AGENT_LEN: usize = 512;
+
+/// Proxies whose `X-Forwarded-For` is believed. Without this extension no
+/// proxy is trusted and the client IP is always the peer address.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(pub Arc<Vec<IpAddr>>);
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+fn truncated(mut value: String, max_len: usize) -> String {
+    if value.len() > max_len {
+        let mut end = max_len;
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+        value.truncate(end);
+    }
+    value
+}
+
+/// The peer, unless it is a trusted proxy: then the nearest `X-Forwarded-For`
+/// hop, read from the right, that isn't one. Hops left of the first untrusted
+/// address could have been written by anyone.
+fn client_ip(peer: IpAddr, forwarded_for: &[&str], trusted: &[IpAddr]) -> IpAddr {
+    let mut client = peer;
+    let hops = forwarded_for
+        .iter()
+        .rev()
+        .flat_map(|value| value.rsplit(','));
+    for hop in hops {
+        if !trusted.contains(&client) {
+            break;
+        }
+        match hop.trim().parse() {
+            Ok(ip) => client = ip,
+            Err(_) => break,
+        }
+    }
+    client
+}
+
+/// Reads the user agent and client IP. The IP is the peer address, or what a
+/// trusted proxy forwarded; see `TrustedProxies`.
+impl<S> FromRequestParts<S> for SessionClient
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let user_agent = header_value(&parts.headers, header::USER_AGENT.as_str());
+
+        let forwarded_for: Vec<&str> = parts
+            .headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect();
+        let trusted = parts
+            .extensions
+            .get::<TrustedProxies>()
+            .map(|TrustedProxies(proxies)| proxies.as_slice())
+            .unwrap_or_default();
+        let ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| client_ip(addr.ip(), &forwarded_for, trusted));
+
+        Ok(Self {
+            user_agent: user_agent.map(|ua| truncated(ua, MAX_USER_AGENT_LEN)),
+            ip: ip.map(|ip| ip.to_string()),
+        })
+    }
+}
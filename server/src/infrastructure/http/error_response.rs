@@ -0,0 +1,62 @@
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+use crate::{
+    config::{config_loader::get_stage, stage::Stage},
+    domain::errors::AppError,
+};
+
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    pub code: &'static str,
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+}
+
+impl AppError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status_code();
+
+        let body = match &self {
+            AppError::Internal(e) => {
+                tracing::error!("Internal error: {:?}", e);
+
+                // Never leak database internals outside of local/dev
+                let details = match get_stage() {
+                    Stage::Production => None,
+                    _ => Some(serde_json::json!({ "cause": format!("{:#}", e) })),
+                };
+                ErrorBody {
+                    code: self.code(),
+                    message: "Internal server error".to_string(),
+                    details,
+                }
+            }
+            _ => ErrorBody {
+                code: self.code(),
+                message: self.to_string(),
+                details: None,
+            },
+        };
+
+        (status, Json(body)).into_response()
+    }
+}
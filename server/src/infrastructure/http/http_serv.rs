@@ -2,9 +2,9 @@ use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use anyhow::{Ok, Result};
 use axum::{
-    Router,
+    Extension, Router,
     http::{
-        Method, StatusCode,
+        HeaderName, Method, StatusCode,
         header::{AUTHORIZATION, CONTENT_TYPE},
     },
     middleware,
@@ -19,13 +19,41 @@ use tower_http::{
 use tracing::info;
 
 use crate::{
-    config::config_model::DotEnvyConfig,
+    application::use_cases::{
+        mission_comment::MissionCommentUseCase, mission_viewing::MissionViewingUseCase,
+        presence::PresenceUseCase,
+    },
+    config::{
+        config_loader::{get_mailer_env, get_oauth_env, get_proxy_env},
+        config_model::{BroadcastBackendKind, DotEnvyConfig},
+    },
+    domain::{errors::AppError, value_objects::brawler_role::BrawlerRole},
     infrastructure::{
-        database::postgresql_connection::PgPoolSquad,
-        http::{middlewares::auth::auth, routers},
+        database::{
+            postgresql_connection::PgPoolSquad,
+            repositories::{
+                access_tokens::AccessTokenPostgres, brawlers::BrawlerPostgres,
+                friendships::FriendshipPostgres, mission_comment::MissionCommentPostgres,
+                mission_viewing::MissionViewingPostgres, notifications::NotificationPostgres,
+                private_messages::PrivateMessagePostgres, sessions::SessionPostgres,
+            },
+        },
+        http::{
+            auth_cookies,
+            client_info::TrustedProxies,
+            middlewares::auth::{AccessTokenCheck, SessionCheck, auth, require_role},
+            routers,
+        },
+        mailer::{self, Mailer},
+        oauth::{self, IdentityProvider},
         websocket::{
-            handler::{global_ws_handler, ws_handler},
+            broadcast::InMemoryBroadcast,
+            handler::{
+                GlobalSocketState, MissionRoomState, global_ws_handler, ws_handler, ws_metrics,
+            },
+            heartbeat::HeartbeatConfig,
             manager::ConnectionManager,
+            pg_broadcast::PgBroadcast,
         },
     },
 };
@@ -36,18 +64,64 @@ fn static_serve() -> Router {
     Router::new().fallback_service(service)
 }
 
-fn api_serve(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Router {
+fn api_serve(
+    db_pool: Arc<PgPoolSquad>,
+    manager: Arc<ConnectionManager>,
+    mailer: Arc<dyn Mailer>,
+    identity_providers: Vec<Arc<dyn IdentityProvider>>,
+) -> Router {
     // WebSocket routes
+    let mission_viewing_repository = Arc::new(MissionViewingPostgres::new(Arc::clone(&db_pool)));
+    let mission_room_state = Arc::new(MissionRoomState {
+        manager: Arc::clone(&manager),
+        use_case: MissionViewingUseCase::new(Arc::clone(&mission_viewing_repository)),
+        comment_use_case: MissionCommentUseCase::new(
+            Arc::new(MissionCommentPostgres::new(Arc::clone(&db_pool))),
+            mission_viewing_repository,
+        ),
+        notification_repo: Arc::new(NotificationPostgres::new(Arc::clone(&db_pool))),
+    });
+    let global_socket_state = Arc::new(GlobalSocketState {
+        manager: Arc::clone(&manager),
+        presence: PresenceUseCase::new(
+            Arc::new(FriendshipPostgres::new(Arc::clone(&db_pool))),
+            Arc::new(BrawlerPostgres::new(Arc::clone(&db_pool))),
+            Arc::clone(&manager),
+        ),
+        rooms: Arc::clone(&mission_room_state),
+    });
     let ws_router = Router::new()
-        .route("/mission/{id}", axum::routing::get(ws_handler))
         .route(
-            "/global",
-            axum::routing::get(global_ws_handler).route_layer(middleware::from_fn(auth)),
+            "/mission/{id}",
+            axum::routing::get(ws_handler).route_layer(middleware::from_fn(auth)),
+        )
+        .with_state(mission_room_state)
+        .merge(
+            Router::new()
+                .route(
+                    "/global",
+                    axum::routing::get(global_ws_handler).route_layer(middleware::from_fn(auth)),
+                )
+                .with_state(global_socket_state),
         )
-        .with_state(Arc::clone(&manager));
+        .merge(
+            Router::new()
+                .route("/metrics", axum::routing::get(ws_metrics))
+                // Connection and room counts are for operators only
+                .route_layer(middleware::from_fn_with_state(
+                    BrawlerRole::Admin,
+                    require_role,
+                ))
+                .route_layer(middleware::from_fn(auth))
+                .with_state(Arc::clone(&manager)),
+        );
 
     Router::new()
-        .nest("/brawler", routers::brawlers::routes(Arc::clone(&db_pool)))
+        .nest(
+            "/brawler",
+            routers::brawlers::routes(Arc::clone(&db_pool), Arc::clone(&manager))
+                .merge(routers::access_tokens::routes(Arc::clone(&db_pool))),
+        )
         .nest(
             "/view",
             routers::mission_viewing::routes(Arc::clone(&db_pool)),
@@ -58,7 +132,17 @@ fn api_serve(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Rout
         )
         .nest(
             "/crew",
-            routers::crew_operation::routes(Arc::clone(&db_pool), Arc::clone(&manager)),
+            routers::crew_operation::routes(Arc::clone(&db_pool), Arc::clone(&manager)).merge(
+                routers::mission_invites::join_routes(Arc::clone(&db_pool), Arc::clone(&manager)),
+            ),
+        )
+        .nest(
+            "/invites",
+            routers::mission_invites::routes(Arc::clone(&db_pool), Arc::clone(&manager)),
+        )
+        .nest(
+            "/waitlist",
+            routers::mission_waitlist::routes(Arc::clone(&db_pool)),
         )
         .nest(
             "/mission-management",
@@ -66,7 +150,15 @@ fn api_serve(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Rout
         )
         .nest(
             "/authentication",
-            routers::authentication::routes(Arc::clone(&db_pool)),
+            routers::authentication::routes(Arc::clone(&db_pool), Arc::clone(&manager), mailer),
+        )
+        .nest(
+            "/oauth",
+            routers::oauth::routes(Arc::clone(&db_pool), identity_providers),
+        )
+        .nest(
+            "/admin",
+            routers::admin::routes(Arc::clone(&db_pool), Arc::clone(&manager)),
         )
         .nest("/util", routers::default_router::routes())
         .nest(
@@ -84,21 +176,83 @@ fn api_serve(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Rout
         .nest(
             "/messages",
             routers::private_messages::routes(
-                Arc::new(crate::infrastructure::database::repositories::private_messages::PrivateMessagePostgres::new(Arc::clone(&db_pool))),
+                Arc::new(PrivateMessagePostgres::new(Arc::clone(&db_pool))),
                 Arc::clone(&manager),
-                Arc::new(crate::infrastructure::database::repositories::notifications::NotificationPostgres::new(Arc::clone(&db_pool)))
-            ).route_layer(middleware::from_fn(auth)),
+                Arc::new(NotificationPostgres::new(Arc::clone(&db_pool))),
+            )
+            .route_layer(middleware::from_fn(auth)),
         )
         .nest("/ws", ws_router)
-        .fallback(|| async { (StatusCode::NOT_FOUND, "API not found") })
+        .nest(
+            "/events",
+            routers::events::routes(Arc::clone(&db_pool), Arc::clone(&manager)),
+        )
+        .fallback(|| async { AppError::NotFound("API not found".to_string()) })
+        // For `auth`, which rejects tokens of revoked sessions
+        .layer(Extension(SessionCheck(Arc::new(SessionPostgres::new(
+            Arc::clone(&db_pool),
+        )))))
+        // and personal access tokens that are unknown, expired or revoked
+        .layer(Extension(AccessTokenCheck(Arc::new(
+            AccessTokenPostgres::new(db_pool),
+        ))))
+}
+
+/// Logs the path only: query strings can carry `token=` on WebSocket handshakes
+fn request_span<B>(request: &axum::http::Request<B>) -> tracing::Span {
+    tracing::debug_span!(
+        "request",
+        method = %request.method(),
+        path = %request.uri().path(),
+        version = ?request.version(),
+    )
 }
 
 pub async fn start(config: Arc<DotEnvyConfig>, db_pool: Arc<PgPoolSquad>) -> Result<()> {
-    let manager = Arc::new(ConnectionManager::new());
+    let heartbeat = HeartbeatConfig {
+        interval: Duration::from_secs(config.websocket.heartbeat_interval),
+        timeout: Duration::from_secs(config.websocket.heartbeat_timeout),
+    };
+    let manager = match config.websocket.broadcast_backend {
+        BroadcastBackendKind::Memory => {
+            ConnectionManager::with_backend(heartbeat, Arc::new(InMemoryBroadcast))
+        }
+        BroadcastBackendKind::Postgres => {
+            let backend = Arc::new(PgBroadcast::new(config.database.url.clone())?);
+            let manager = ConnectionManager::with_backend(heartbeat, backend.clone());
+            backend.spawn_listener(manager.clone())?;
+            manager
+        }
+    };
+    info!(
+        "WebSocket broadcast backend: {:?}",
+        config.websocket.broadcast_backend
+    );
+    let manager = Arc::new(manager);
+
+    let mailer_env = get_mailer_env()?;
+    info!("Mailer: {:?}", mailer_env.kind);
+    let mailer = mailer::from_env(mailer_env);
+
+    let identity_providers = oauth::from_env(get_oauth_env()?);
+    info!(
+        "Sign-in providers: {:?}",
+        identity_providers
+            .iter()
+            .map(|provider| provider.name())
+            .collect::<Vec<_>>()
+    );
+
+    let trusted_proxies = get_proxy_env()?.trusted_proxies;
+    info!("Trusted proxies: {:?}", trusted_proxies);
 
     let app = Router::new()
         .merge(static_serve())
-        .nest("/api", api_serve(db_pool, manager))
+        .nest(
+            "/api",
+            api_serve(db_pool, manager, mailer, identity_providers),
+        )
+        .layer(Extension(TrustedProxies(Arc::new(trusted_proxies))))
         .layer(tower_http::timeout::TimeoutLayer::with_status_code(
             StatusCode::REQUEST_TIMEOUT,
             Duration::from_secs(config.server.timeout),
@@ -117,17 +271,26 @@ pub async fn start(config: Arc<DotEnvyConfig>, db_pool: Arc<PgPoolSquad>) -> Res
                     Method::OPTIONS,
                 ])
                 .allow_origin(Any)
-                .allow_headers([AUTHORIZATION, CONTENT_TYPE]),
+                .allow_headers([
+                    AUTHORIZATION,
+                    CONTENT_TYPE,
+                    HeaderName::from_static("last-event-id"),
+                    HeaderName::from_static(auth_cookies::CSRF_HEADER),
+                ]),
         )
-        .layer(TraceLayer::new_for_http());
+        .layer(TraceLayer::new_for_http().make_span_with(request_span));
 
     let addr = SocketAddr::from(([0, 0, 0, 0], config.server.port));
     let listener = TcpListener::bind(addr).await?;
 
     info!("Server start on port {}", config.server.port);
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    // Peer addresses identify clients for login sessions and throttling
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?;
 
     Ok(())
 }
@@ -1,46 +1,177 @@
+use std::sync::Arc;
+
 use axum::{
-    extract::Request,
-    http::{StatusCode, header},
+    extract::{Request, State},
+    http::header,
     middleware::Next,
     response::Response,
 };
+use axum_extra::extract::cookie::CookieJar;
+
+use crate::{
+    config::config_loader::get_jwt_env,
+    domain::{
+        errors::AppError,
+        repositories::{access_tokens::AccessTokenRepository, sessions::SessionRepository},
+        value_objects::{
+            access_token_model::{ACCESS_TOKEN_PREFIX, TokenScope},
+            brawler_role::BrawlerRole,
+        },
+    },
+    infrastructure::{http::auth_cookies, jwt::verify_token, secure_token},
+};
+
+/// Session store the middleware checks tokens against, provided to it as a
+/// request extension by the API router
+#[derive(Clone)]
+pub struct SessionCheck(pub Arc<dyn SessionRepository>);
+
+/// Personal access token store the middleware checks those tokens against,
+/// provided next to `SessionCheck`
+#[derive(Clone)]
+pub struct AccessTokenCheck(pub Arc<dyn AccessTokenRepository>);
+
+/// The scope a route needs from a personal access token. Layer it outside
+/// `auth`, which reads it, e.g.
+/// `.route_layer(middleware::from_fn(auth)).route_layer(Extension(RouteScope(TokenScope::Read)))`.
+/// Routes without one turn those tokens away.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RouteScope(pub TokenScope);
+
+/// The session an authenticated request's token belongs to, next to the user id
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurrentSession(pub i32);
 
-use crate::{config::config_loader::get_jwt_env, infrastructure::jwt::verify_token};
+/// The role an authenticated request's token was signed with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurrentRole(pub BrawlerRole);
 
-pub async fn auth(mut req: Request, next: Next) -> Result<Response, StatusCode> {
-    tracing::debug!("Auth middleware called for: {}", req.uri());
-    // 1. Try to get token from Authorization header
-    let token_header = req
+fn unauthorized() -> AppError {
+    AppError::Unauthorized("Unauthorized".to_string())
+}
+
+/// The access token of a request. A Bearer header always works. In cookie mode
+/// the access cookie is taken too, with a CSRF check; otherwise a `token=` query
+/// parameter is, for WebSocket handshakes that can't set headers.
+fn request_token(req: &Request) -> Result<String, AppError> {
+    let bearer = req
         .headers()
         .get(header::AUTHORIZATION)
         .and_then(|value| value.to_str().ok())
         .and_then(|value| value.strip_prefix("Bearer "));
+    if let Some(token) = bearer {
+        return Ok(token.to_string());
+    }
 
-    // 2. If not in header, try query parameter (for WebSockets)
-    let token = if let Some(t) = token_header {
-        t.to_string()
-    } else {
-        req.uri()
-            .query()
-            .and_then(|q| {
-                q.split('&')
-                    .find(|p| p.starts_with("token="))
-                    .map(|p| p.trim_start_matches("token=").to_string())
-            })
-            .ok_or(StatusCode::UNAUTHORIZED)?
-    };
+    if auth_cookies::enabled()? {
+        let jar = CookieJar::from_headers(req.headers());
+        let token = jar
+            .get(auth_cookies::ACCESS_COOKIE)
+            .map(|cookie| cookie.value().to_string())
+            .ok_or_else(unauthorized)?;
+        auth_cookies::verify_csrf(req.method(), req.headers(), &jar)?;
+        return Ok(token);
+    }
+
+    req.uri()
+        .query()
+        .and_then(|q| {
+            q.split('&')
+                .find(|p| p.starts_with("token="))
+                .map(|p| p.trim_start_matches("token=").to_string())
+        })
+        .ok_or_else(unauthorized)
+}
+
+pub async fn auth(mut req: Request, next: Next) -> Result<Response, AppError> {
+    tracing::debug!("Auth middleware called for: {}", req.uri().path());
+    let token = request_token(&req)?;
+    if token.starts_with(ACCESS_TOKEN_PREFIX) {
+        return access_token_auth(req, token, next).await;
+    }
 
-    let jwt_env = get_jwt_env().unwrap();
+    let jwt_env = get_jwt_env()?;
     let secret = jwt_env.secret;
 
-    let claims = verify_token(secret, token).map_err(|_| StatusCode::UNAUTHORIZED)?;
+    let claims = verify_token(secret, token).map_err(|_| unauthorized())?;
 
-    let user_id = claims
-        .sub
-        .parse::<i32>()
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+    let user_id = claims.sub.parse::<i32>().map_err(|_| unauthorized())?;
+
+    // A valid signature isn't enough once the session has been logged out
+    let SessionCheck(sessions) = req
+        .extensions()
+        .get::<SessionCheck>()
+        .cloned()
+        .ok_or_else(|| AppError::Internal(anyhow::anyhow!("SessionCheck extension missing")))?;
+    if !sessions.is_active(claims.sid).await? {
+        return Err(AppError::Unauthorized(
+            "Session has been revoked".to_string(),
+        ));
+    }
 
     req.extensions_mut().insert(user_id);
+    req.extensions_mut().insert(CurrentSession(claims.sid));
+    req.extensions_mut().insert(CurrentRole(claims.role));
+
+    Ok(next.run(req).await)
+}
+
+/// A personal access token acts as its brawler, with the plain user role and no
+/// session, on the routes that take one of its scopes
+async fn access_token_auth(
+    mut req: Request,
+    token: String,
+    next: Next,
+) -> Result<Response, AppError> {
+    let Some(RouteScope(required)) = req.extensions().get::<RouteScope>().copied() else {
+        return Err(AppError::Forbidden(
+            "Access tokens can't be used here".to_string(),
+        ));
+    };
+    let AccessTokenCheck(access_tokens) = req
+        .extensions()
+        .get::<AccessTokenCheck>()
+        .cloned()
+        .ok_or_else(|| AppError::Internal(anyhow::anyhow!("AccessTokenCheck extension missing")))?;
+
+    let access_token = access_tokens
+        .authenticate(secure_token::hash(&token))
+        .await?
+        .ok_or_else(|| {
+            AppError::Unauthorized("Invalid, expired or revoked access token".to_string())
+        })?;
+    if !access_token.scopes.contains(&required.to_string()) {
+        return Err(AppError::Forbidden(format!(
+            "Requires the {} scope",
+            required
+        )));
+    }
+
+    req.extensions_mut().insert(access_token.brawler_id);
+    req.extensions_mut().insert(CurrentRole(BrawlerRole::User));
+
+    Ok(next.run(req).await)
+}
+
+/// Lets only callers with at least the given role through, e.g.
+/// `middleware::from_fn_with_state(BrawlerRole::Admin, require_role)`. Layer it
+/// inside `auth`, which provides the caller's role.
+pub async fn require_role(
+    State(required): State<BrawlerRole>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let role = req
+        .extensions()
+        .get::<CurrentRole>()
+        .map(|CurrentRole(role)| *role)
+        .ok_or_else(unauthorized)?;
+    if role < required {
+        return Err(AppError::Forbidden(format!(
+            "Requires the {} role",
+            required
+        )));
+    }
 
     Ok(next.run(req).await)
 }
@@ -1,3 +1,6 @@
+pub mod auth_cookies;
+pub mod client_info;
+pub mod error_response;
 pub mod http_serv;
 pub mod middlewares;
 pub mod routers;
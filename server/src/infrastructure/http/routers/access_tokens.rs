@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    response::IntoResponse,
+    routing::{delete, get, post},
+};
+
+use crate::{
+    application::use_cases::access_tokens::AccessTokenUseCase,
+    domain::value_objects::access_token_model::CreateAccessTokenModel,
+    infrastructure::{
+        database::{
+            postgresql_connection::PgPoolSquad, repositories::access_tokens::AccessTokenPostgres,
+        },
+        http::middlewares::auth::auth,
+    },
+};
+
+pub async fn create(
+    State(user_case): State<Arc<AccessTokenUseCase>>,
+    Extension(user_id): Extension<i32>,
+    Json(model): Json<CreateAccessTokenModel>,
+) -> impl IntoResponse {
+    match user_case.create(user_id, model).await {
+        Ok(created) => (StatusCode::CREATED, Json(created)).into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn list(
+    State(user_case): State<Arc<AccessTokenUseCase>>,
+    Extension(user_id): Extension<i32>,
+) -> impl IntoResponse {
+    match user_case.list(user_id).await {
+        Ok(tokens) => (StatusCode::OK, Json(tokens)).into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn revoke(
+    State(user_case): State<Arc<AccessTokenUseCase>>,
+    Extension(user_id): Extension<i32>,
+    Path(token_id): Path<i32>,
+) -> impl IntoResponse {
+    match user_case.revoke(user_id, token_id).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Managing tokens takes a signed-in session; an access token can't mint more
+pub fn routes(db_pool: Arc<PgPoolSquad>) -> Router {
+    let user_case = AccessTokenUseCase::new(Arc::new(AccessTokenPostgres::new(db_pool)));
+
+    Router::new()
+        .route("/tokens", post(create))
+        .route("/tokens", get(list))
+        .route("/tokens/{token_id}", delete(revoke))
+        .route_layer(middleware::from_fn(auth))
+        .with_state(Arc::new(user_case))
+}
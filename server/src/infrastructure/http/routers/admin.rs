@@ -0,0 +1,71 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    middleware,
+    response::IntoResponse,
+    routing::{get, put},
+};
+use std::sync::Arc;
+
+use crate::{
+    application::use_cases::admin::AdminUseCase,
+    domain::{
+        repositories::brawlers::BrawlerRepository,
+        value_objects::brawler_role::{BrawlerRole, RoleFilter, SetRoleModel},
+    },
+    infrastructure::{
+        database::{
+            postgresql_connection::PgPoolSquad,
+            repositories::{brawlers::BrawlerPostgres, sessions::SessionPostgres},
+        },
+        http::middlewares::auth::{auth, require_role},
+        websocket::manager::ConnectionManager,
+    },
+};
+
+pub fn routes(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Router {
+    let repository = BrawlerPostgres::new(Arc::clone(&db_pool));
+    let sessions = SessionPostgres::new(db_pool);
+    let user_case = AdminUseCase::new(Arc::new(repository), Arc::new(sessions), manager);
+
+    Router::new()
+        .route("/brawlers", get(list_brawlers))
+        .route("/brawlers/{id}/role", put(set_role))
+        .route_layer(middleware::from_fn_with_state(
+            BrawlerRole::Admin,
+            require_role,
+        ))
+        .route_layer(middleware::from_fn(auth))
+        .with_state(Arc::new(user_case))
+}
+
+pub async fn list_brawlers<T>(
+    State(user_case): State<Arc<AdminUseCase<T>>>,
+    Query(filter): Query<RoleFilter>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    match user_case.list(filter).await {
+        Ok(brawlers) => (StatusCode::OK, Json(brawlers)).into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn set_role<T>(
+    State(user_case): State<Arc<AdminUseCase<T>>>,
+    Extension(user_id): Extension<i32>,
+    Path(brawler_id): Path<i32>,
+    Json(model): Json<SetRoleModel>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    match user_case.set_role(user_id, brawler_id, model).await {
+        Ok(brawler) => (StatusCode::OK, Json(brawler)).into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
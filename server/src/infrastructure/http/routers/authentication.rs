@@ -1,35 +1,340 @@
 use std::sync::Arc;
 
-use axum::{Json, Router, extract::State, http::StatusCode, response::IntoResponse, routing::post};
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    middleware,
+    response::IntoResponse,
+    routing::{delete, get, post},
+};
+use axum_extra::extract::cookie::CookieJar;
 
 use crate::{
-    application::use_cases::authentication::AuthenticationUseCase,
-    domain::repositories::brawlers::BrawlerRepository,
+    application::use_cases::{
+        authentication::{AuthenticationUseCase, LoginOutcome},
+        login_throttle::LoginThrottle,
+        mfa::MfaUseCase,
+        password_reset::PasswordResetUseCase,
+    },
+    domain::{
+        errors::{AppError, AppResult},
+        repositories::brawlers::BrawlerRepository,
+        value_objects::{
+            mfa_model::{DisableMfaModel, MfaCodeModel, MfaLoginModel},
+            password_model::{PasswordResetModel, PasswordResetRequestModel},
+            session_model::SessionClient,
+        },
+    },
     infrastructure::{
-        database::{postgresql_connection::PgPoolSquad, repositories::brawlers::BrawlerPostgres},
-        jwt::authentication_model::LoginModel,
+        database::{
+            postgresql_connection::PgPoolSquad,
+            repositories::{
+                brawlers::BrawlerPostgres, login_throttles::LoginThrottlePostgres,
+                password_resets::PasswordResetPostgres, sessions::SessionPostgres,
+                totp::TotpPostgres,
+            },
+        },
+        http::{
+            auth_cookies::{self, logged_out_response, passport_response},
+            middlewares::auth::{CurrentSession, auth},
+        },
+        jwt::authentication_model::{LoginModel, RefreshTokenModel},
+        mailer::Mailer,
+        websocket::manager::ConnectionManager,
     },
 };
 
+/// The refresh token from the body or, in cookie mode, from its cookie
+fn presented_refresh_token(
+    body: Option<Json<RefreshTokenModel>>,
+    headers: &HeaderMap,
+    jar: &CookieJar,
+) -> AppResult<RefreshTokenModel> {
+    if let Some(Json(model)) = body {
+        return Ok(model);
+    }
+    auth_cookies::refresh_token(headers, jar)?
+        .map(|refresh_token| RefreshTokenModel { refresh_token })
+        .ok_or_else(|| AppError::Unauthorized("Invalid or expired refresh token".to_string()))
+}
+
 pub async fn login<T>(
     State(user_case): State<Arc<AuthenticationUseCase<T>>>,
+    client: SessionClient,
+    jar: CookieJar,
     Json(model): Json<LoginModel>,
 ) -> impl IntoResponse
 where
     T: BrawlerRepository + Send + Sync,
 {
-    match user_case.login(model).await {
-        Ok(passport) => (StatusCode::OK, Json(passport)).into_response(),
+    match user_case.login(model, client).await {
+        Ok(LoginOutcome::Passport(passport)) => passport_response(StatusCode::OK, jar, passport),
+        Ok(LoginOutcome::MfaRequired(challenge)) => {
+            (StatusCode::OK, Json(challenge)).into_response()
+        }
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn verify_mfa<T>(
+    State(user_case): State<Arc<AuthenticationUseCase<T>>>,
+    client: SessionClient,
+    jar: CookieJar,
+    Json(model): Json<MfaLoginModel>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    match user_case.verify_mfa(model, client).await {
+        Ok(passport) => passport_response(StatusCode::OK, jar, passport),
 
-        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
-pub fn routes(db_pool: Arc<PgPoolSquad>) -> Router {
-    let repository = BrawlerPostgres::new(db_pool);
-    let user_case = AuthenticationUseCase::new(Arc::new(repository));
+pub async fn refresh<T>(
+    State(user_case): State<Arc<AuthenticationUseCase<T>>>,
+    client: SessionClient,
+    headers: HeaderMap,
+    jar: CookieJar,
+    body: Option<Json<RefreshTokenModel>>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    let model = match presented_refresh_token(body, &headers, &jar) {
+        Ok(model) => model,
+        Err(e) => return e.into_response(),
+    };
+    match user_case.refresh(model, client).await {
+        Ok(passport) => passport_response(StatusCode::OK, jar, passport),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn logout<T>(
+    State(user_case): State<Arc<AuthenticationUseCase<T>>>,
+    headers: HeaderMap,
+    jar: CookieJar,
+    body: Option<Json<RefreshTokenModel>>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    let model = match presented_refresh_token(body, &headers, &jar) {
+        Ok(model) => model,
+        Err(e) => return e.into_response(),
+    };
+    match user_case.logout(model).await {
+        Ok(_) => logged_out_response(jar),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn logout_all<T>(
+    State(user_case): State<Arc<AuthenticationUseCase<T>>>,
+    Extension(user_id): Extension<i32>,
+    jar: CookieJar,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    match user_case.logout_all(user_id).await {
+        Ok(_) => logged_out_response(jar),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn list_sessions<T>(
+    State(user_case): State<Arc<AuthenticationUseCase<T>>>,
+    Extension(user_id): Extension<i32>,
+    Extension(CurrentSession(session_id)): Extension<CurrentSession>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    match user_case.list_sessions(user_id, session_id).await {
+        Ok(sessions) => (StatusCode::OK, Json(sessions)).into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn revoke_session<T>(
+    State(user_case): State<Arc<AuthenticationUseCase<T>>>,
+    Extension(user_id): Extension<i32>,
+    Path(session_id): Path<i32>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    match user_case.revoke_session(user_id, session_id).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Always `202 Accepted`, whether or not a mail went out
+pub async fn request_password_reset<T>(
+    State(user_case): State<Arc<PasswordResetUseCase<T>>>,
+    Json(model): Json<PasswordResetRequestModel>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    match user_case.request_reset(model).await {
+        Ok(_) => StatusCode::ACCEPTED.into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn reset_password<T>(
+    State(user_case): State<Arc<PasswordResetUseCase<T>>>,
+    Json(model): Json<PasswordResetModel>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    match user_case.reset_password(model).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn mfa_status<T>(
+    State(user_case): State<Arc<MfaUseCase<T>>>,
+    Extension(user_id): Extension<i32>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    match user_case.status(user_id).await {
+        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn enroll_mfa<T>(
+    State(user_case): State<Arc<MfaUseCase<T>>>,
+    Extension(user_id): Extension<i32>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    match user_case.enroll(user_id).await {
+        Ok(enrollment) => (StatusCode::CREATED, Json(enrollment)).into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn confirm_mfa<T>(
+    State(user_case): State<Arc<MfaUseCase<T>>>,
+    Extension(user_id): Extension<i32>,
+    Json(model): Json<MfaCodeModel>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    match user_case.confirm(user_id, model).await {
+        Ok(codes) => (StatusCode::OK, Json(codes)).into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn regenerate_recovery_codes<T>(
+    State(user_case): State<Arc<MfaUseCase<T>>>,
+    Extension(user_id): Extension<i32>,
+    Json(model): Json<MfaCodeModel>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    match user_case.regenerate_recovery_codes(user_id, model).await {
+        Ok(codes) => (StatusCode::OK, Json(codes)).into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn disable_mfa<T>(
+    State(user_case): State<Arc<MfaUseCase<T>>>,
+    Extension(user_id): Extension<i32>,
+    Json(model): Json<DisableMfaModel>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    match user_case.disable(user_id, model).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub fn routes(
+    db_pool: Arc<PgPoolSquad>,
+    manager: Arc<ConnectionManager>,
+    mailer: Arc<dyn Mailer>,
+) -> Router {
+    let repository = Arc::new(BrawlerPostgres::new(Arc::clone(&db_pool)));
+    let sessions = Arc::new(SessionPostgres::new(Arc::clone(&db_pool)));
+    let totp_repository = Arc::new(TotpPostgres::new(Arc::clone(&db_pool)));
+    let user_case = AuthenticationUseCase::new(
+        Arc::clone(&repository),
+        sessions.clone(),
+        Arc::clone(&manager),
+        LoginThrottle::new(Arc::new(LoginThrottlePostgres::new(Arc::clone(&db_pool)))),
+        totp_repository.clone(),
+    );
+    let mfa_case = MfaUseCase::new(Arc::clone(&repository), totp_repository);
+    let password_reset_case = PasswordResetUseCase::new(
+        repository,
+        Arc::new(PasswordResetPostgres::new(db_pool)),
+        sessions,
+        mailer,
+        manager,
+    );
+
+    // Refresh and logout are proven by the refresh token, since the access token
+    // may already have expired
+    let protected_routes: Router<_> = Router::new()
+        .route("/logout-all", post(logout_all))
+        .route("/sessions", get(list_sessions))
+        .route("/sessions/{id}", delete(revoke_session))
+        .route_layer(middleware::from_fn(auth));
 
     Router::new()
+        .merge(protected_routes)
         .route("/login", post(login))
+        .route("/refresh", post(refresh))
+        .route("/logout", post(logout))
+        .route("/mfa/verify", post(verify_mfa))
         .with_state(Arc::new(user_case))
+        .merge(
+            Router::new()
+                .route("/mfa", get(mfa_status))
+                .route("/mfa/enroll", post(enroll_mfa))
+                .route("/mfa/confirm", post(confirm_mfa))
+                .route("/mfa/recovery-codes", post(regenerate_recovery_codes))
+                .route("/mfa/disable", post(disable_mfa))
+                .route_layer(middleware::from_fn(auth))
+                .with_state(Arc::new(mfa_case)),
+        )
+        .merge(
+            Router::new()
+                .route("/password-reset", post(request_password_reset))
+                .route("/password-reset/confirm", post(reset_password))
+                .with_state(Arc::new(password_reset_case)),
+        )
 }
@@ -5,6 +5,7 @@ use axum::{
     response::IntoResponse,
     routing::{get, patch, post},
 };
+use axum_extra::extract::cookie::CookieJar;
 use std::sync::Arc;
 
 use crate::{
@@ -12,47 +13,62 @@ use crate::{
     domain::{
         repositories::brawlers::BrawlerRepository,
         value_objects::{
+            access_token_model::TokenScope,
             brawler_model::{RegisterBrawlerModel, UpdateBrawlerModel},
+            password_model::ChangePasswordModel,
+            session_model::SessionClient,
             uploaded_img::UploadBase64Img,
         },
     },
     infrastructure::{
-        database::{postgresql_connection::PgPoolSquad, repositories::brawlers::BrawlerPostgres},
-        http::middlewares::auth::auth,
+        database::{
+            postgresql_connection::PgPoolSquad,
+            repositories::{brawlers::BrawlerPostgres, sessions::SessionPostgres},
+        },
+        http::{
+            auth_cookies::passport_response,
+            middlewares::auth::{CurrentSession, RouteScope, auth},
+        },
+        websocket::manager::ConnectionManager,
     },
 };
 
-pub fn routes(db_pool: Arc<PgPoolSquad>) -> Router {
-    let repository = BrawlerPostgres::new(db_pool);
-    let user_case = BrawlersUseCase::new(Arc::new(repository));
+pub fn routes(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Router {
+    let repository = BrawlerPostgres::new(Arc::clone(&db_pool));
+    let sessions = SessionPostgres::new(db_pool);
+    let user_case = BrawlersUseCase::new(Arc::new(repository), Arc::new(sessions), manager);
 
     let protected_routes: Router<_> = Router::new()
         .route("/avatar", post(upload_avatar))
-        .route("/my-missions", get(get_missions))
         .route("/profile", patch(update_profile))
-        .route("/{id}", get(get_brawler_by_id))
+        .route("/password", patch(change_password))
         .route_layer(axum::middleware::from_fn(auth));
+    let read_routes: Router<_> = Router::new()
+        .route("/my-missions", get(get_missions))
+        .route("/{id}", get(get_brawler_by_id))
+        .route_layer(axum::middleware::from_fn(auth))
+        .route_layer(Extension(RouteScope(TokenScope::Read)));
 
     Router::new()
         .merge(protected_routes)
+        .merge(read_routes)
         .route("/register", post(register))
         .with_state(Arc::new(user_case))
 }
 
 pub async fn register<T>(
     State(user_case): State<Arc<BrawlersUseCase<T>>>,
+    client: SessionClient,
+    jar: CookieJar,
     Json(model): Json<RegisterBrawlerModel>,
 ) -> impl IntoResponse
 where
     T: BrawlerRepository + Send + Sync,
 {
-    match user_case.register(model).await {
-        Ok(passport) => (AxumStatusCode::CREATED, Json(passport)).into_response(),
+    match user_case.register(model, client).await {
+        Ok(passport) => passport_response(AxumStatusCode::CREATED, jar, passport),
 
-        Err(e) => {
-            tracing::error!("Register error: {:?}", e);
-            (AxumStatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
-        }
+        Err(e) => e.into_response(),
     }
 }
 
@@ -70,7 +86,7 @@ where
     {
         Ok(upload_img) => (AxumStatusCode::OK, Json(upload_img)).into_response(),
 
-        Err(e) => (AxumStatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -84,22 +100,40 @@ where
     match user_case.get_my_missions(user_id).await {
         Ok(missions) => (AxumStatusCode::OK, Json(missions)).into_response(),
 
-        Err(e) => (AxumStatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
 pub async fn update_profile<T>(
     State(user_case): State<Arc<BrawlersUseCase<T>>>,
     Extension(user_id): Extension<i32>,
+    Extension(CurrentSession(session_id)): Extension<CurrentSession>,
+    jar: CookieJar,
     Json(model): Json<UpdateBrawlerModel>,
 ) -> impl IntoResponse
 where
     T: BrawlerRepository + Send + Sync,
 {
-    match user_case.update_profile(user_id, model).await {
-        Ok(passport) => (AxumStatusCode::OK, Json(passport)).into_response(),
+    match user_case.update_profile(user_id, session_id, model).await {
+        Ok(passport) => passport_response(AxumStatusCode::OK, jar, passport),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn change_password<T>(
+    State(user_case): State<Arc<BrawlersUseCase<T>>>,
+    Extension(user_id): Extension<i32>,
+    Extension(CurrentSession(session_id)): Extension<CurrentSession>,
+    Json(model): Json<ChangePasswordModel>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    match user_case.change_password(user_id, session_id, model).await {
+        Ok(_) => AxumStatusCode::NO_CONTENT.into_response(),
 
-        Err(e) => (AxumStatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -112,6 +146,6 @@ where
 {
     match user_case.get_brawler_by_id(id).await {
         Ok(brawler) => (AxumStatusCode::OK, Json(brawler)).into_response(),
-        Err(e) => (AxumStatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
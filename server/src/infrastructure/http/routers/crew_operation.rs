@@ -15,71 +15,124 @@ use crate::{
         entities::notifications::AddNotificationEntity,
         repositories::{
             crew_operation::CrewOperationRepository, mission_viewing::MissionViewingRepository,
-            notifications::NotificationRepository,
+            notifications::NotificationRepository, transaction_provider::TransactionProvider,
         },
+        value_objects::{mission_application_model::JoinOutcome, mission_model::MissionModel},
     },
     infrastructure::{
         database::{
             postgresql_connection::PgPoolSquad,
             repositories::{
-                crew_operation::CrewOperationPostgres, mission_viewing::MissionViewingPostgres,
-                notifications::NotificationPostgres,
+                crew_operation::CrewOperationPostgres,
+                diesel_transaction::TransactionProviderPostgres,
+                mission_viewing::MissionViewingPostgres, notifications::NotificationPostgres,
             },
         },
-        http::middlewares::auth::auth,
-        websocket::{handler::WSMessage, manager::ConnectionManager},
+        http::{middlewares::auth::auth, routers::mission_waitlist::push_promotion},
+        websocket::{
+            manager::{ConnectionManager, RoomCloseReason},
+            protocol::{CrewChange, JoinRequest, ServerEvent},
+        },
     },
 };
 
-pub struct CrewState<T1, T2>
+pub struct CrewState<T1, T2, T3>
 where
     T1: CrewOperationRepository + Send + Sync,
     T2: MissionViewingRepository + Send + Sync,
+    T3: TransactionProvider + Send + Sync,
 {
-    pub use_case: CrewOperationUseCase<T1, T2>,
+    pub use_case: CrewOperationUseCase<T1, T2, T3>,
     pub manager: Arc<ConnectionManager>,
     pub viewing_repository: Arc<T2>,
     pub notification_repo: Arc<dyn NotificationRepository>,
 }
 
-pub async fn join<T1, T2>(
-    State(state): State<Arc<CrewState<T1, T2>>>,
+/// Real-time side of a new crew member, plus the chief's stored notification.
+pub async fn push_crew_joined(
+    manager: &ConnectionManager,
+    notification_repo: &dyn NotificationRepository,
+    mission: &MissionModel,
+    brawler_id: i32,
+) {
+    let ws_msg = ServerEvent::NewCrewJoined(CrewChange {
+        mission_id: mission.id,
+        mission_name: mission.name.clone(),
+        brawler_id,
+    });
+
+    // 1. Notify EVERYONE (for public list real-time update)
+    manager.broadcast_all(ws_msg.clone()).await;
+
+    // 2. Broadcast to the specific room (for in-room UI update)
+    manager.broadcast(mission.id, ws_msg.clone()).await;
+
+    // 3. Notify the CHIEF globally and save to DB
+    let _ = notification_repo
+        .add(AddNotificationEntity {
+            brawler_id: mission.chief_id,
+            type_: "new_crew_joined".to_string(),
+            content: format!("A new crew member joined your mission: {}", mission.name),
+            related_id: Some(mission.id),
+        })
+        .await;
+    manager.notify_user(mission.chief_id, ws_msg).await;
+}
+
+pub async fn join<T1, T2, T3>(
+    State(state): State<Arc<CrewState<T1, T2, T3>>>,
     Extension(user_id): Extension<i32>,
     Path(mission_id): Path<i32>,
 ) -> impl IntoResponse
 where
     T1: CrewOperationRepository + Send + Sync + 'static,
     T2: MissionViewingRepository + Send + Sync + 'static,
+    T3: TransactionProvider + Send + Sync + 'static,
 {
     match state.use_case.join(mission_id, user_id).await {
-        Ok(_) => {
+        Ok(JoinOutcome::Applied(application_id)) => {
+            // Approval required: only the chief hears about it until they decide
             if let Ok(mission) = state.viewing_repository.get_one(mission_id).await {
-                let ws_msg = WSMessage {
-                    msg_type: "new_crew_joined".to_string(),
-                    data: serde_json::json!({
-                        "mission_id": mission_id,
-                        "mission_name": mission.name,
-                        "brawler_id": user_id
-                    }),
-                };
-
-                // 1. Notify EVERYONE (for public list real-time update)
-                state.manager.broadcast_all(ws_msg.clone()).await;
-
-                // 2. Broadcast to the specific room (for in-room UI update)
-                state.manager.broadcast(mission_id, ws_msg.clone()).await;
-
-                // 3. Notify the CHIEF globally and save to DB
                 let _ = state
                     .notification_repo
                     .add(AddNotificationEntity {
                         brawler_id: mission.chief_id,
-                        type_: "new_crew_joined".to_string(),
-                        content: format!("A new crew member joined your mission: {}", mission.name),
+                        type_: "join_request".to_string(),
+                        content: format!("Someone asked to join your mission: {}", mission.name),
                         related_id: Some(mission_id),
                     })
                     .await;
-                state.manager.notify_user(mission.chief_id, ws_msg).await;
+                state
+                    .manager
+                    .notify_user(
+                        mission.chief_id,
+                        ServerEvent::JoinRequest(JoinRequest {
+                            mission_id,
+                            mission_name: mission.name.clone(),
+                            application_id,
+                            brawler_id: user_id,
+                        }),
+                    )
+                    .await;
+            }
+            (
+                StatusCode::ACCEPTED,
+                format!(
+                    "Join request for Mission_id:{} sent to the chief",
+                    mission_id
+                ),
+            )
+                .into_response()
+        }
+        Ok(JoinOutcome::Joined) => {
+            if let Ok(mission) = state.viewing_repository.get_one(mission_id).await {
+                push_crew_joined(
+                    &state.manager,
+                    state.notification_repo.as_ref(),
+                    &mission,
+                    user_id,
+                )
+                .await;
             }
             (
                 StatusCode::OK,
@@ -87,36 +140,38 @@ where
             )
                 .into_response()
         }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
-pub async fn leave<T1, T2>(
-    State(state): State<Arc<CrewState<T1, T2>>>,
+pub async fn leave<T1, T2, T3>(
+    State(state): State<Arc<CrewState<T1, T2, T3>>>,
     Extension(user_id): Extension<i32>,
     Path(mission_id): Path<i32>,
 ) -> impl IntoResponse
 where
     T1: CrewOperationRepository + Send + Sync + 'static,
     T2: MissionViewingRepository + Send + Sync + 'static,
+    T3: TransactionProvider + Send + Sync + 'static,
 {
     match state.use_case.leave(mission_id, user_id).await {
-        Ok(_) => {
+        Ok(promoted) => {
             if let Ok(mission) = state.viewing_repository.get_one(mission_id).await {
-                let ws_msg = WSMessage {
-                    msg_type: "crew_left".to_string(),
-                    data: serde_json::json!({
-                        "mission_id": mission_id,
-                        "mission_name": mission.name,
-                        "brawler_id": user_id
-                    }),
-                };
+                let ws_msg = ServerEvent::CrewLeft(CrewChange {
+                    mission_id,
+                    mission_name: mission.name.clone(),
+                    brawler_id: user_id,
+                });
 
                 // 1. Notify EVERYONE (for public list real-time update)
                 state.manager.broadcast_all(ws_msg.clone()).await;
 
                 // 2. Broadcast to the specific room (for in-room UI update)
                 state.manager.broadcast(mission_id, ws_msg.clone()).await;
+                state
+                    .manager
+                    .close_room_member(mission_id, user_id, RoomCloseReason::LeftMission)
+                    .await;
 
                 // 3. PERSIST FOR CHIEF
                 let _ = state
@@ -129,6 +184,11 @@ where
                     })
                     .await;
                 state.manager.notify_user(mission.chief_id, ws_msg).await;
+
+                // 4. The freed slot went to the next brawler on the waitlist
+                if let Some(brawler_id) = promoted {
+                    push_promotion(&state.manager, mission_id, &mission.name, brawler_id).await;
+                }
             }
             (
                 StatusCode::OK,
@@ -137,22 +197,23 @@ where
                 .into_response()
         }
 
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
 /// ดึงรายการภารกิจที่ผู้ใช้เข้าร่วมอยู่
-pub async fn get_my_joined_missions<T1, T2>(
-    State(state): State<Arc<CrewState<T1, T2>>>,
+pub async fn get_my_joined_missions<T1, T2, T3>(
+    State(state): State<Arc<CrewState<T1, T2, T3>>>,
     Extension(user_id): Extension<i32>,
 ) -> impl IntoResponse
 where
     T1: CrewOperationRepository + Send + Sync + 'static,
     T2: MissionViewingRepository + Send + Sync + 'static,
+    T3: TransactionProvider + Send + Sync + 'static,
 {
     match state.use_case.get_my_joined_missions(user_id).await {
         Ok(missions) => (StatusCode::OK, Json(missions)).into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -165,6 +226,7 @@ pub fn routes(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Rou
     let use_case = CrewOperationUseCase::new(
         Arc::new(crew_operation_repository),
         Arc::clone(&viewing_repository_arc),
+        Arc::new(TransactionProviderPostgres::new(Arc::clone(&db_pool))),
     );
 
     let state = Arc::new(CrewState {
@@ -0,0 +1,153 @@
+use std::{convert::Infallible, sync::Arc};
+
+use axum::{
+    Extension, Router,
+    extract::{Query, State},
+    http::HeaderMap,
+    middleware,
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::get,
+};
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+
+use crate::{
+    application::use_cases::presence::PresenceUseCase,
+    domain::errors::AppError,
+    infrastructure::{
+        database::{
+            postgresql_connection::PgPoolSquad,
+            repositories::{brawlers::BrawlerPostgres, friendships::FriendshipPostgres},
+        },
+        http::middlewares::auth::{CurrentSession, auth},
+        websocket::{
+            event_log::{Cursor, StreamKey},
+            handler::ResumeQuery,
+            manager::{ConnectionManager, session_revoked},
+            protocol::{ServerEvent, ServerFrame},
+        },
+    },
+};
+
+/// Frames queued for one slow client before its channel starts lagging
+const SSE_BUFFER: usize = 32;
+
+pub struct EventsRouterState {
+    pub manager: Arc<ConnectionManager>,
+    pub presence: PresenceUseCase,
+}
+
+/// The user's global stream as Server-Sent Events, for clients that can't open a
+/// WebSocket. Each event's data is the same frame the global socket would send,
+/// and its id is the frame's `seq`, so `Last-Event-ID` resumes like `?since=`.
+pub async fn stream_events(
+    State(state): State<Arc<EventsRouterState>>,
+    Extension(user_id): Extension<i32>,
+    Extension(CurrentSession(session_id)): Extension<CurrentSession>,
+    Query(resume): Query<ResumeQuery>,
+    headers: HeaderMap,
+) -> Response {
+    // Browsers send Last-Event-ID on their own reconnects; `?since=` covers the first connect
+    let since = match headers.get("last-event-id") {
+        Some(value) => match value.to_str().ok().and_then(|v| v.trim().parse().ok()) {
+            Some(since) => Some(since),
+            None => {
+                return AppError::Validation("Last-Event-ID must be an event seq".to_string())
+                    .into_response();
+            }
+        },
+        None => resume.since,
+    };
+
+    let keep_alive = KeepAlive::new().interval(state.manager.heartbeat().interval);
+    let (tx, rx) = mpsc::channel(SSE_BUFFER);
+    tokio::spawn(pump(state, user_id, session_id, since, tx));
+
+    let stream = futures::stream::unfold(rx, |mut rx| async move {
+        let frame = rx.recv().await?;
+        Some((Ok::<_, Infallible>(to_event(&frame)), rx))
+    });
+    Sse::new(stream).keep_alive(keep_alive).into_response()
+}
+
+fn to_event(frame: &ServerFrame) -> Event {
+    let data = serde_json::to_string(frame).unwrap_or_default();
+    // A resync moves the client's position too, or it would resync on every reconnect
+    let id = match &frame.event {
+        ServerEvent::ResyncRequired(resync) => Some(resync.latest_seq),
+        _ => frame.seq,
+    };
+    match id {
+        Some(id) => Event::default().id(id.to_string()).data(data),
+        None => Event::default().data(data),
+    }
+}
+
+/// Feed the user's stream into one response until the client goes away or its
+/// login session is revoked
+async fn pump(
+    state: Arc<EventsRouterState>,
+    user_id: i32,
+    session_id: i32,
+    since: Option<u64>,
+    tx: mpsc::Sender<ServerFrame>,
+) {
+    let manager = &state.manager;
+    let mut rx = manager.subscribe_user(user_id).await;
+    let mut revocations = manager.session_revocations();
+
+    // An SSE client is online just like a socket
+    if let Err(e) = state.presence.session_started(user_id).await {
+        tracing::error!("Failed to record presence of user {}: {}", user_id, e);
+    }
+
+    let forward = async {
+        let mut cursor = Cursor::new(StreamKey::User(user_id));
+        let mut frames = match since {
+            Some(since) => cursor.resume(manager, since).await,
+            None => Vec::new(),
+        };
+        loop {
+            for frame in frames {
+                if tx.send(frame).await.is_err() {
+                    return;
+                }
+            }
+            frames = match rx.recv().await {
+                Ok(frame) if cursor.accept(&frame) => vec![frame],
+                Ok(_) => Vec::new(),
+                Err(RecvError::Lagged(_)) => cursor.catch_up(manager).await,
+                Err(RecvError::Closed) => return,
+            };
+        }
+    };
+
+    // The response body drops its receiver once the client disconnects
+    tokio::select! {
+        _ = forward => {}
+        _ = tx.closed() => {}
+        _ = session_revoked(&mut revocations, session_id) => {}
+    }
+    drop(rx);
+    manager.unsubscribe_user(user_id).await;
+
+    if let Err(e) = state.presence.session_ended(user_id).await {
+        tracing::error!("Failed to record presence of user {}: {}", user_id, e);
+    }
+}
+
+pub fn routes(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Router {
+    let presence = PresenceUseCase::new(
+        Arc::new(FriendshipPostgres::new(Arc::clone(&db_pool))),
+        Arc::new(BrawlerPostgres::new(Arc::clone(&db_pool))),
+        Arc::clone(&manager),
+    );
+    let state = Arc::new(EventsRouterState { manager, presence });
+
+    Router::new()
+        .route("/", get(stream_events))
+        .route_layer(middleware::from_fn(auth))
+        .with_state(state)
+}
@@ -9,8 +9,14 @@ use axum::{
 use std::sync::Arc;
 
 use crate::{
-    application::use_cases::{friendships::FriendshipUseCase, notifications::NotificationUseCase},
-    domain::repositories::brawlers::BrawlerRepository,
+    application::use_cases::{
+        friendships::FriendshipUseCase, notifications::NotificationUseCase,
+        presence::PresenceUseCase,
+    },
+    domain::{
+        errors::AppError,
+        repositories::{brawlers::BrawlerRepository, friendship_repository::FriendshipRepository},
+    },
     infrastructure::{
         database::{
             postgresql_connection::PgPoolSquad,
@@ -27,7 +33,7 @@ use crate::{
 pub struct FriendshipRouterState {
     pub use_case: FriendshipUseCase,
     pub brawler_repo: Arc<dyn BrawlerRepository + Send + Sync>,
-    pub ws_manager: Arc<ConnectionManager>,
+    pub presence: PresenceUseCase,
 }
 
 pub async fn send_request(
@@ -37,7 +43,7 @@ pub async fn send_request(
 ) -> impl IntoResponse {
     match state.use_case.send_request(user_id, receiver_id).await {
         Ok(_) => StatusCode::OK.into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -48,7 +54,7 @@ pub async fn accept_request(
 ) -> impl IntoResponse {
     match state.use_case.accept_request(user_id, request_id).await {
         Ok(_) => StatusCode::OK.into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -59,7 +65,7 @@ pub async fn reject_request(
 ) -> impl IntoResponse {
     match state.use_case.reject_request(user_id, request_id).await {
         Ok(_) => StatusCode::OK.into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -70,27 +76,28 @@ pub async fn delete_friend(
 ) -> impl IntoResponse {
     match state.use_case.remove_friend(user_id, friend_id).await {
         Ok(_) => StatusCode::OK.into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
 pub async fn get_pending(
     State(state): State<Arc<FriendshipRouterState>>,
     Extension(user_id): Extension<i32>,
-) -> Json<serde_json::Value> {
+) -> impl IntoResponse {
     match state.use_case.list_pending(user_id).await {
-        Ok(requests) => Json(serde_json::json!(requests)),
-        Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
+        Ok(requests) => Json(requests).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
+/// Online friends only; strangers' presence is not visible
 pub async fn get_online_users(
     State(state): State<Arc<FriendshipRouterState>>,
-) -> Json<serde_json::Value> {
-    let online_ids = state.ws_manager.get_online_users().await;
-    match state.brawler_repo.find_many(online_ids).await {
-        Ok(users) => Json(serde_json::json!(users)),
-        Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
+    Extension(user_id): Extension<i32>,
+) -> impl IntoResponse {
+    match state.presence.online_friends(user_id).await {
+        Ok(users) => Json(users).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -98,27 +105,27 @@ pub async fn get_status(
     State(state): State<Arc<FriendshipRouterState>>,
     Extension(user_id): Extension<i32>,
     Path(other_id): Path<i32>,
-) -> Json<serde_json::Value> {
+) -> impl IntoResponse {
     match state
         .use_case
         .get_friendship_status(user_id, other_id)
         .await
     {
-        Ok(status) => Json(serde_json::json!({ "status": status })),
-        Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
+        Ok(status) => Json(serde_json::json!({ "status": status })).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
 pub async fn get_friends(
     State(state): State<Arc<FriendshipRouterState>>,
     Extension(user_id): Extension<i32>,
-) -> Json<serde_json::Value> {
+) -> impl IntoResponse {
     match state.use_case.list_friends(user_id).await {
         Ok(friend_ids) => match state.brawler_repo.find_many(friend_ids).await {
-            Ok(users) => Json(serde_json::json!(users)),
-            Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
+            Ok(users) => Json(users).into_response(),
+            Err(e) => AppError::from(e).into_response(),
         },
-        Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -130,16 +137,21 @@ pub fn routes(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Rou
     let notification_use_case = Arc::new(NotificationUseCase::new(notification_repo));
 
     let use_case = FriendshipUseCase::new(
-        friendship_repo,
+        Arc::clone(&friendship_repo) as Arc<dyn FriendshipRepository>,
         Arc::clone(&brawler_repo) as Arc<dyn BrawlerRepository + Send + Sync>,
         notification_use_case,
         Arc::clone(&manager),
     );
+    let presence = PresenceUseCase::new(
+        friendship_repo,
+        Arc::clone(&brawler_repo) as Arc<dyn BrawlerRepository + Send + Sync>,
+        manager,
+    );
 
     let state = Arc::new(FriendshipRouterState {
         use_case,
         brawler_repo,
-        ws_manager: manager,
+        presence,
     });
 
     Router::new()
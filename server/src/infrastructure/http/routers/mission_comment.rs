@@ -4,7 +4,10 @@ use crate::{
     domain::repositories::{
         mission_viewing::MissionViewingRepository, notifications::NotificationRepository,
     },
-    domain::value_objects::mission_comment_model::AddMissionCommentModel,
+    domain::value_objects::{
+        access_token_model::TokenScope,
+        mission_comment_model::{AddMissionCommentModel, MissionCommentModel},
+    },
     infrastructure::{
         database::{
             postgresql_connection::PgPoolSquad,
@@ -13,8 +16,11 @@ use crate::{
                 notifications::NotificationPostgres,
             },
         },
-        http::middlewares::auth::auth,
-        websocket::{handler::WSMessage, manager::ConnectionManager},
+        http::middlewares::auth::{CurrentRole, RouteScope, auth},
+        websocket::{
+            manager::ConnectionManager,
+            protocol::{ClearChat, NewChatMessage, ServerEvent},
+        },
     },
 };
 use axum::{
@@ -46,21 +52,106 @@ pub fn routes(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Rou
         notification_repo,
     });
 
-    Router::new()
+    let read_routes: Router<_> = Router::new()
         .route("/{mission_id}", get(get_comments))
+        .route_layer(middleware::from_fn(auth))
+        .route_layer(Extension(RouteScope(TokenScope::Read)));
+    let write_routes: Router<_> = Router::new()
         .route("/{mission_id}", post(add_comment))
-        .route("/{mission_id}", delete(clear_comments))
         .route_layer(middleware::from_fn(auth))
+        .route_layer(Extension(RouteScope(TokenScope::CommentsWrite)));
+
+    Router::new()
+        .merge(read_routes)
+        .merge(write_routes)
+        .route(
+            "/{mission_id}",
+            delete(clear_comments).route_layer(middleware::from_fn(auth)),
+        )
         .with_state(state)
 }
 
+/// Real-time side of a new comment: the room broadcast for people in the chat,
+/// plus a stored notification and toast for the chief and crew outside it.
+/// Shared by the REST endpoint and `chat_message` frames on the room socket.
+pub async fn push_new_comment<T>(
+    manager: &ConnectionManager,
+    notification_repo: &dyn NotificationRepository,
+    mission_viewing_repository: &T,
+    comment: &MissionCommentModel,
+) where
+    T: MissionViewingRepository + Send + Sync,
+{
+    let mission_id = comment.mission_id;
+    let user_id = comment.brawler_id;
+
+    // 1. BROADCAST NEW COMMENT VIA ROOM-BASED WEBSOCKET (for people currently in the chat room)
+    manager
+        .broadcast(mission_id, ServerEvent::NewComment(comment.clone()))
+        .await;
+
+    // 2. SEND GLOBAL NOTIFICATIONS (for people not currently in the room)
+    let Ok(mission) = mission_viewing_repository.get_one(mission_id).await else {
+        return;
+    };
+
+    let notification = ServerEvent::NewChatMessage(NewChatMessage {
+        mission_id,
+        mission_name: mission.name.clone(),
+        sender_name: comment.brawler_display_name.clone(),
+        content: comment.content.clone(),
+    });
+
+    tracing::info!("Sending global chat notification: {:?}", notification);
+
+    // Notify Chief (if not the sender)
+    if mission.chief_id != user_id {
+        let _ = notification_repo
+            .add(AddNotificationEntity {
+                brawler_id: mission.chief_id,
+                type_: "new_chat_message".to_string(),
+                content: format!(
+                    "[{}] {}: \"{}\"",
+                    mission.name, comment.brawler_display_name, comment.content
+                ),
+                related_id: Some(mission_id),
+            })
+            .await;
+
+        manager
+            .notify_user(mission.chief_id, notification.clone())
+            .await;
+    }
+
+    // Notify all crew members (if not the sender)
+    if let Ok(crew) = mission_viewing_repository.get_crew(mission_id).await {
+        for member in crew {
+            if member.id != user_id {
+                let _ = notification_repo
+                    .add(AddNotificationEntity {
+                        brawler_id: member.id,
+                        type_: "new_chat_message".to_string(),
+                        content: format!(
+                            "[{}] {}: \"{}\"",
+                            mission.name, comment.brawler_display_name, comment.content
+                        ),
+                        related_id: Some(mission_id),
+                    })
+                    .await;
+
+                manager.notify_user(member.id, notification.clone()).await;
+            }
+        }
+    }
+}
+
 async fn get_comments(
     State(state): State<Arc<CommentState>>,
     Path(mission_id): Path<i32>,
 ) -> impl IntoResponse {
     match state.use_case.get_comments(mission_id).await {
         Ok(comments) => (StatusCode::OK, Json(comments)).into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -76,106 +167,40 @@ async fn add_comment(
         .await
     {
         Ok(comment) => {
-            // 1. BROADCAST NEW COMMENT VIA ROOM-BASED WEBSOCKET (for people currently in the chat room)
-            let ws_msg = WSMessage {
-                msg_type: "new_comment".to_string(),
-                data: serde_json::to_value(&comment).unwrap_or_default(),
-            };
-            state.manager.broadcast(mission_id, ws_msg.clone()).await;
-
-            // 2. SEND GLOBAL NOTIFICATIONS (for people not currently in the room)
-            if let Ok(mission) = state
-                .use_case
-                .mission_viewing_repository
-                .get_one(mission_id)
-                .await
-            {
-                let notification = WSMessage {
-                    msg_type: "new_chat_message".to_string(),
-                    data: serde_json::json!({
-                        "mission_id": mission_id,
-                        "mission_name": mission.name,
-                        "sender_name": comment.brawler_display_name,
-                        "content": comment.content,
-                    }),
-                };
-
-                tracing::info!("Sending global chat notification: {:?}", notification);
-
-                // Notify Chief (if not the sender)
-                if mission.chief_id != user_id {
-                    let _ = state
-                        .notification_repo
-                        .add(AddNotificationEntity {
-                            brawler_id: mission.chief_id,
-                            type_: "new_chat_message".to_string(),
-                            content: format!(
-                                "[{}] {}: \"{}\"",
-                                mission.name, comment.brawler_display_name, comment.content
-                            ),
-                            related_id: Some(mission_id),
-                        })
-                        .await;
-
-                    state
-                        .manager
-                        .notify_user(mission.chief_id, notification.clone())
-                        .await;
-                }
-
-                // Notify all crew members (if not the sender)
-                if let Ok(crew) = state
-                    .use_case
-                    .mission_viewing_repository
-                    .get_crew(mission_id)
-                    .await
-                {
-                    for member in crew {
-                        if member.id != user_id {
-                            let _ = state
-                                .notification_repo
-                                .add(AddNotificationEntity {
-                                    brawler_id: member.id,
-                                    type_: "new_chat_message".to_string(),
-                                    content: format!(
-                                        "[{}] {}: \"{}\"",
-                                        mission.name, comment.brawler_display_name, comment.content
-                                    ),
-                                    related_id: Some(mission_id),
-                                })
-                                .await;
-
-                            state
-                                .manager
-                                .notify_user(member.id, notification.clone())
-                                .await;
-                        }
-                    }
-                }
-            }
+            push_new_comment(
+                &state.manager,
+                state.notification_repo.as_ref(),
+                state.use_case.mission_viewing_repository.as_ref(),
+                &comment,
+            )
+            .await;
 
             (StatusCode::CREATED, Json(comment)).into_response()
         }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
 async fn clear_comments(
     State(state): State<Arc<CommentState>>,
     Extension(user_id): Extension<i32>,
+    Extension(CurrentRole(role)): Extension<CurrentRole>,
     Path(mission_id): Path<i32>,
 ) -> impl IntoResponse {
-    match state.use_case.clear_comments(mission_id, user_id).await {
+    match state
+        .use_case
+        .clear_comments(mission_id, user_id, role)
+        .await
+    {
         Ok(_) => {
             // BROADCAST CLEAR VIA WEBSOCKET
-            let ws_msg = WSMessage {
-                msg_type: "clear_chat".to_string(),
-                data: serde_json::json!({ "mission_id": mission_id }),
-            };
-            state.manager.broadcast(mission_id, ws_msg).await;
+            state
+                .manager
+                .broadcast(mission_id, ServerEvent::ClearChat(ClearChat { mission_id }))
+                .await;
 
             (StatusCode::OK, "Chat cleared").into_response()
         }
-        Err(e) => (StatusCode::FORBIDDEN, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
@@ -0,0 +1,295 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    response::IntoResponse,
+    routing::{delete, get, patch, post},
+};
+
+use crate::{
+    application::use_cases::mission_invites::MissionInviteUseCase,
+    domain::{
+        entities::notifications::AddNotificationEntity,
+        repositories::notifications::NotificationRepository,
+        value_objects::mission_invite_model::{CreateInviteLinkModel, SendInvitationModel},
+    },
+    infrastructure::{
+        database::{
+            postgresql_connection::PgPoolSquad,
+            repositories::{
+                friendships::FriendshipPostgres, mission_invites::MissionInvitePostgres,
+                mission_viewing::MissionViewingPostgres, notifications::NotificationPostgres,
+            },
+        },
+        http::{middlewares::auth::auth, routers::crew_operation::push_crew_joined},
+        websocket::{
+            manager::ConnectionManager,
+            protocol::{InvitationDeclined, InvitationRevoked, MissionInvitation, ServerEvent},
+        },
+    },
+};
+
+pub struct MissionInviteState {
+    pub use_case:
+        MissionInviteUseCase<MissionInvitePostgres, MissionViewingPostgres, FriendshipPostgres>,
+    pub manager: Arc<ConnectionManager>,
+    pub notification_repo: Arc<dyn NotificationRepository>,
+}
+
+pub async fn create_link(
+    State(state): State<Arc<MissionInviteState>>,
+    Extension(user_id): Extension<i32>,
+    Path(mission_id): Path<i32>,
+    Json(create_link_model): Json<CreateInviteLinkModel>,
+) -> impl IntoResponse {
+    match state
+        .use_case
+        .create_link(mission_id, user_id, create_link_model)
+        .await
+    {
+        Ok(link) => (StatusCode::CREATED, Json(link)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn links(
+    State(state): State<Arc<MissionInviteState>>,
+    Extension(user_id): Extension<i32>,
+    Path(mission_id): Path<i32>,
+) -> impl IntoResponse {
+    match state.use_case.links(mission_id, user_id).await {
+        Ok(links) => (StatusCode::OK, Json(links)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn revoke_link(
+    State(state): State<Arc<MissionInviteState>>,
+    Extension(user_id): Extension<i32>,
+    Path((mission_id, link_id)): Path<(i32, i32)>,
+) -> impl IntoResponse {
+    match state
+        .use_case
+        .revoke_link(mission_id, link_id, user_id)
+        .await
+    {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn join_by_invite(
+    State(state): State<Arc<MissionInviteState>>,
+    Extension(user_id): Extension<i32>,
+    Path(token): Path<String>,
+) -> impl IntoResponse {
+    match state.use_case.join_by_invite(token, user_id).await {
+        Ok(mission) => {
+            push_crew_joined(
+                &state.manager,
+                state.notification_repo.as_ref(),
+                &mission,
+                user_id,
+            )
+            .await;
+            (StatusCode::OK, Json(mission)).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn invite(
+    State(state): State<Arc<MissionInviteState>>,
+    Extension(user_id): Extension<i32>,
+    Path(mission_id): Path<i32>,
+    Json(send_invitation_model): Json<SendInvitationModel>,
+) -> impl IntoResponse {
+    let invitee_id = send_invitation_model.brawler_id;
+    match state.use_case.invite(mission_id, user_id, invitee_id).await {
+        Ok((mission, invitation_id)) => {
+            let _ = state
+                .notification_repo
+                .add(AddNotificationEntity {
+                    brawler_id: invitee_id,
+                    type_: "mission_invitation".to_string(),
+                    content: format!(
+                        "{} invited you to join: {}",
+                        mission.chief_display_name, mission.name
+                    ),
+                    related_id: Some(mission_id),
+                })
+                .await;
+            state
+                .manager
+                .notify_user(
+                    invitee_id,
+                    ServerEvent::MissionInvitation(MissionInvitation {
+                        invitation_id,
+                        mission_id,
+                        mission_name: mission.name.clone(),
+                        inviter_id: user_id,
+                        inviter_display_name: mission.chief_display_name.clone(),
+                    }),
+                )
+                .await;
+
+            (
+                StatusCode::CREATED,
+                Json(serde_json::json!({ "invitation_id": invitation_id })),
+            )
+                .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn mission_invitations(
+    State(state): State<Arc<MissionInviteState>>,
+    Extension(user_id): Extension<i32>,
+    Path(mission_id): Path<i32>,
+) -> impl IntoResponse {
+    match state
+        .use_case
+        .mission_invitations(mission_id, user_id)
+        .await
+    {
+        Ok(invitations) => (StatusCode::OK, Json(invitations)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn revoke(
+    State(state): State<Arc<MissionInviteState>>,
+    Extension(user_id): Extension<i32>,
+    Path((mission_id, invitation_id)): Path<(i32, i32)>,
+) -> impl IntoResponse {
+    match state
+        .use_case
+        .revoke(mission_id, invitation_id, user_id)
+        .await
+    {
+        Ok((mission, invitee_id)) => {
+            // Nothing stored; just drop it from the invitee's open list
+            state
+                .manager
+                .notify_user(
+                    invitee_id,
+                    ServerEvent::InvitationRevoked(InvitationRevoked {
+                        invitation_id,
+                        mission_id,
+                        mission_name: mission.name,
+                    }),
+                )
+                .await;
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn my_invitations(
+    State(state): State<Arc<MissionInviteState>>,
+    Extension(user_id): Extension<i32>,
+) -> impl IntoResponse {
+    match state.use_case.my_invitations(user_id).await {
+        Ok(invitations) => (StatusCode::OK, Json(invitations)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn accept(
+    State(state): State<Arc<MissionInviteState>>,
+    Extension(user_id): Extension<i32>,
+    Path(invitation_id): Path<i32>,
+) -> impl IntoResponse {
+    match state.use_case.accept(invitation_id, user_id).await {
+        Ok(mission) => {
+            push_crew_joined(
+                &state.manager,
+                state.notification_repo.as_ref(),
+                &mission,
+                user_id,
+            )
+            .await;
+            (StatusCode::OK, Json(mission)).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn decline(
+    State(state): State<Arc<MissionInviteState>>,
+    Extension(user_id): Extension<i32>,
+    Path(invitation_id): Path<i32>,
+) -> impl IntoResponse {
+    match state.use_case.decline(invitation_id, user_id).await {
+        Ok(mission) => {
+            let _ = state
+                .notification_repo
+                .add(AddNotificationEntity {
+                    brawler_id: mission.chief_id,
+                    type_: "invitation_declined".to_string(),
+                    content: format!("Your invitation to '{}' was declined.", mission.name),
+                    related_id: Some(mission.id),
+                })
+                .await;
+            state
+                .manager
+                .notify_user(
+                    mission.chief_id,
+                    ServerEvent::InvitationDeclined(InvitationDeclined {
+                        invitation_id,
+                        mission_id: mission.id,
+                        mission_name: mission.name,
+                        brawler_id: user_id,
+                    }),
+                )
+                .await;
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+fn state(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Arc<MissionInviteState> {
+    let use_case = MissionInviteUseCase::new(
+        Arc::new(MissionInvitePostgres::new(Arc::clone(&db_pool))),
+        Arc::new(MissionViewingPostgres::new(Arc::clone(&db_pool))),
+        Arc::new(FriendshipPostgres::new(Arc::clone(&db_pool))),
+    );
+    let notification_repo = Arc::new(NotificationPostgres::new(Arc::clone(&db_pool)));
+
+    Arc::new(MissionInviteState {
+        use_case,
+        manager,
+        notification_repo,
+    })
+}
+
+pub fn routes(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Router {
+    Router::new()
+        // invitee side
+        .route("/", get(my_invitations))
+        .route("/{invitation_id}/accept", patch(accept))
+        .route("/{invitation_id}/decline", patch(decline))
+        // chief side
+        .route("/mission/{mission_id}", post(invite))
+        .route("/mission/{mission_id}", get(mission_invitations))
+        .route("/mission/{mission_id}/{invitation_id}", delete(revoke))
+        .route("/mission/{mission_id}/links", post(create_link))
+        .route("/mission/{mission_id}/links", get(links))
+        .route("/mission/{mission_id}/links/{link_id}", delete(revoke_link))
+        .route_layer(middleware::from_fn(auth))
+        .with_state(state(db_pool, manager))
+}
+
+/// Merged into the `/crew` router.
+pub fn join_routes(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Router {
+    Router::new()
+        .route("/join-by-invite/{token}", post(join_by_invite))
+        .route_layer(middleware::from_fn(auth))
+        .with_state(state(db_pool, manager))
+}
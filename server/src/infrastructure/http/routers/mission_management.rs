@@ -6,10 +6,13 @@ use axum::{
     http::StatusCode,
     middleware,
     response::IntoResponse,
-    routing::{delete, patch, post},
+    routing::{delete, get, patch, post},
 };
 
-use crate::infrastructure::websocket::handler::WSMessage;
+use crate::infrastructure::websocket::{
+    manager::RoomCloseReason,
+    protocol::{CrewChange, MissionRef, ServerEvent},
+};
 use crate::{
     application::use_cases::mission_management::MissionManagementUseCase,
     domain::{
@@ -17,23 +20,31 @@ use crate::{
         repositories::{
             mission_viewing::MissionViewingRepository, notifications::NotificationRepository,
         },
-        value_objects::mission_model::{AddMissionModel, EditMissionModel},
+        value_objects::{
+            access_token_model::TokenScope,
+            mission_model::{AddMissionModel, EditMissionModel},
+        },
     },
     infrastructure::{
         database::{
             postgresql_connection::PgPoolSquad,
             repositories::{
+                diesel_transaction::TransactionProviderPostgres,
                 mission_management::MissionManagementPostgres,
                 mission_viewing::MissionViewingPostgres, notifications::NotificationPostgres,
             },
         },
-        http::middlewares::auth::auth,
+        http::middlewares::auth::{CurrentRole, RouteScope, auth},
         websocket::manager::ConnectionManager,
     },
 };
 
 pub struct MissionManagementState {
-    pub use_case: MissionManagementUseCase<MissionManagementPostgres, MissionViewingPostgres>,
+    pub use_case: MissionManagementUseCase<
+        MissionManagementPostgres,
+        MissionViewingPostgres,
+        TransactionProviderPostgres,
+    >,
     pub manager: Arc<ConnectionManager>,
     pub notification_repo: Arc<dyn NotificationRepository>,
 }
@@ -47,7 +58,7 @@ pub async fn add(
     match state.use_case.add(user_id, model).await {
         Ok(mission_id) => (StatusCode::CREATED, mission_id.to_string()).into_response(),
 
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -64,13 +75,14 @@ pub async fn edit(
         )
             .into_response(),
 
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
 pub async fn remove(
     State(state): State<Arc<MissionManagementState>>,
     Extension(user_id): Extension<i32>,
+    Extension(CurrentRole(role)): Extension<CurrentRole>,
     Path(mission_id): Path<i32>,
 ) -> impl IntoResponse {
     // 1. Get mission info and crew before deletion (to know who to notify)
@@ -85,16 +97,13 @@ pub async fn remove(
         .get_crew(mission_id)
         .await;
 
-    match state.use_case.remove(mission_id, user_id).await {
+    match state.use_case.remove(mission_id, user_id, role).await {
         Ok(_) => {
             if let (Ok(mission), Ok(crew)) = (mission_info, crew_info) {
-                let ws_msg = WSMessage {
-                    msg_type: "mission_deleted".to_string(),
-                    data: serde_json::json!({
-                        "mission_id": mission_id,
-                        "mission_name": mission.name,
-                    }),
-                };
+                let ws_msg = ServerEvent::MissionDeleted(MissionRef {
+                    mission_id,
+                    mission_name: mission.name.clone(),
+                });
 
                 tracing::info!(
                     "Mission {} deleted, notifying {} crew members and chief",
@@ -102,24 +111,10 @@ pub async fn remove(
                     crew.len()
                 );
 
-                // Notify all crew members globally and save to DB
+                // Notify all crew members globally (notifications were stored by the use case)
                 for member in crew {
+                    // Don't notify the chief who deleted it
                     if member.id != user_id {
-                        // Save notification to DB
-                        let _ = state
-                            .notification_repo
-                            .add(AddNotificationEntity {
-                                brawler_id: member.id,
-                                type_: "mission_deleted".to_string(),
-                                content: format!(
-                                    "Mission '{}' has been removed by the chief.",
-                                    mission.name
-                                ),
-                                related_id: Some(mission_id),
-                            })
-                            .await;
-
-                        // Don't notify the chief who deleted it
                         state.manager.notify_user(member.id, ws_msg.clone()).await;
                     }
                 }
@@ -128,6 +123,12 @@ pub async fn remove(
                 state.manager.broadcast(mission_id, ws_msg).await;
             }
 
+            // The room is gone; drop everyone still connected to it
+            state
+                .manager
+                .close_room(mission_id, RoomCloseReason::MissionDeleted)
+                .await;
+
             (
                 StatusCode::OK,
                 format!("Remove mission_id: {} completed!!", mission_id),
@@ -135,16 +136,116 @@ pub async fn remove(
                 .into_response()
         }
 
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn applications(
+    State(state): State<Arc<MissionManagementState>>,
+    Extension(user_id): Extension<i32>,
+    Path(mission_id): Path<i32>,
+) -> impl IntoResponse {
+    match state.use_case.applications(mission_id, user_id).await {
+        Ok(applications) => (StatusCode::OK, Json(applications)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn accept_application(
+    State(state): State<Arc<MissionManagementState>>,
+    Extension(user_id): Extension<i32>,
+    Path((mission_id, application_id)): Path<(i32, i32)>,
+) -> impl IntoResponse {
+    match state
+        .use_case
+        .accept_application(mission_id, application_id, user_id)
+        .await
+    {
+        Ok((mission, brawler_id)) => {
+            // 1. Tell the applicant and save to DB
+            let _ = state
+                .notification_repo
+                .add(AddNotificationEntity {
+                    brawler_id,
+                    type_: "application_accepted".to_string(),
+                    content: format!("Your request to join '{}' was accepted!", mission.name),
+                    related_id: Some(mission_id),
+                })
+                .await;
+            state
+                .manager
+                .notify_user(
+                    brawler_id,
+                    ServerEvent::ApplicationAccepted(MissionRef {
+                        mission_id,
+                        mission_name: mission.name.clone(),
+                    }),
+                )
+                .await;
+
+            // 2. Crew count changed for everyone else
+            let ws_msg = ServerEvent::NewCrewJoined(CrewChange {
+                mission_id,
+                mission_name: mission.name.clone(),
+                brawler_id,
+            });
+            state.manager.broadcast_all(ws_msg.clone()).await;
+            state.manager.broadcast(mission_id, ws_msg).await;
+
+            StatusCode::OK.into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn reject_application(
+    State(state): State<Arc<MissionManagementState>>,
+    Extension(user_id): Extension<i32>,
+    Path((mission_id, application_id)): Path<(i32, i32)>,
+) -> impl IntoResponse {
+    match state
+        .use_case
+        .reject_application(mission_id, application_id, user_id)
+        .await
+    {
+        Ok((mission, brawler_id)) => {
+            let _ = state
+                .notification_repo
+                .add(AddNotificationEntity {
+                    brawler_id,
+                    type_: "application_rejected".to_string(),
+                    content: format!("Your request to join '{}' was declined.", mission.name),
+                    related_id: Some(mission_id),
+                })
+                .await;
+            state
+                .manager
+                .notify_user(
+                    brawler_id,
+                    ServerEvent::ApplicationRejected(MissionRef {
+                        mission_id,
+                        mission_name: mission.name.clone(),
+                    }),
+                )
+                .await;
+
+            StatusCode::OK.into_response()
+        }
+        Err(e) => e.into_response(),
     }
 }
 
 pub fn routes(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Router {
     let mission_repository = MissionManagementPostgres::new(Arc::clone(&db_pool));
     let viewing_repositiory = MissionViewingPostgres::new(Arc::clone(&db_pool));
+    let transaction_provider = TransactionProviderPostgres::new(Arc::clone(&db_pool));
+    let use_case = MissionManagementUseCase::new(
+        Arc::new(mission_repository),
+        Arc::new(viewing_repositiory),
+        Arc::new(transaction_provider),
+    );
+
     let notification_repo = Arc::new(NotificationPostgres::new(Arc::clone(&db_pool)));
-    let use_case =
-        MissionManagementUseCase::new(Arc::new(mission_repository), Arc::new(viewing_repositiory));
 
     let state = Arc::new(MissionManagementState {
         use_case,
@@ -152,10 +253,28 @@ pub fn routes(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Rou
         notification_repo,
     });
 
-    Router::new()
+    // Bots with a `missions:write` token can run missions of their own
+    let token_routes: Router<_> = Router::new()
         .route("/", post(add))
         .route("/{mission_id}", patch(edit))
         .route("/{mission_id}", delete(remove))
         .route_layer(middleware::from_fn(auth))
+        .route_layer(Extension(RouteScope(TokenScope::MissionsWrite)));
+
+    let application_routes: Router<_> = Router::new()
+        .route("/{mission_id}/applications", get(applications))
+        .route(
+            "/{mission_id}/applications/{application_id}/accept",
+            patch(accept_application),
+        )
+        .route(
+            "/{mission_id}/applications/{application_id}/reject",
+            patch(reject_application),
+        )
+        .route_layer(middleware::from_fn(auth));
+
+    Router::new()
+        .merge(token_routes)
+        .merge(application_routes)
         .with_state(state)
 }
@@ -14,31 +14,38 @@ use crate::{
     domain::{
         entities::notifications::AddNotificationEntity,
         repositories::{
-            mission_operation::MissionOperationRepository,
             mission_viewing::MissionViewingRepository, notifications::NotificationRepository,
+            transaction_provider::TransactionProvider,
         },
+        value_objects::{access_token_model::TokenScope, mission_statuses::MissionStatuses},
     },
     infrastructure::{
         database::{
             postgresql_connection::PgPoolSquad,
             repositories::{
-                mission_operation::MissionOperationPostgres,
+                diesel_transaction::TransactionProviderPostgres,
                 mission_viewing::MissionViewingPostgres, notifications::NotificationPostgres,
             },
         },
-        http::middlewares::auth::auth,
-        websocket::{handler::WSMessage, manager::ConnectionManager},
+        http::{
+            middlewares::auth::{CurrentRole, RouteScope, auth},
+            routers::mission_waitlist::push_promotion,
+        },
+        websocket::{
+            manager::{ConnectionManager, RoomCloseReason},
+            protocol::{CrewChange, MissionStatusChanged, ServerEvent},
+        },
     },
 };
 
 pub struct MissionOperationState<T1, T2>
 where
-    T1: MissionOperationRepository + Send + Sync,
-    T2: MissionViewingRepository + Send + Sync,
+    T1: MissionViewingRepository + Send + Sync,
+    T2: TransactionProvider + Send + Sync,
 {
     pub use_case: MissionOperationUseCase<T1, T2>,
     pub manager: Arc<ConnectionManager>,
-    pub viewing_repository: Arc<T2>,
+    pub viewing_repository: Arc<T1>,
     pub notification_repo: Arc<dyn NotificationRepository>,
 }
 
@@ -48,47 +55,34 @@ pub async fn in_progress<T1, T2>(
     Path(mission_id): Path<i32>,
 ) -> impl IntoResponse
 where
-    T1: MissionOperationRepository + Send + Sync + 'static,
-    T2: MissionViewingRepository + Send + Sync + 'static,
+    T1: MissionViewingRepository + Send + Sync + 'static,
+    T2: TransactionProvider + Send + Sync + 'static,
 {
     match state.use_case.in_progress(mission_id, user_id).await {
         Ok(_) => {
             // 1. Notify all crew members globally (for toast)
-            if let Ok(crew) = state.viewing_repository.get_crew(mission_id).await {
-                if let Ok(mission) = state.viewing_repository.get_one(mission_id).await {
-                    let ws_msg = WSMessage {
-                        msg_type: "mission_started".to_string(),
-                        data: serde_json::json!({
-                            "mission_id": mission_id,
-                            "mission_name": mission.name,
-                            "new_status": "InProgress"
-                        }),
-                    };
-                    for member in crew {
-                        // Save notification to DB for each member
-                        let _ = state
-                            .notification_repo
-                            .add(AddNotificationEntity {
-                                brawler_id: member.id,
-                                type_: "mission_started".to_string(),
-                                content: format!("Mission '{}' has started!", mission.name),
-                                related_id: Some(mission_id),
-                            })
-                            .await;
-
-                        state.manager.notify_user(member.id, ws_msg.clone()).await;
-                    }
+            if let Ok(crew) = state.viewing_repository.get_crew(mission_id).await
+                && let Ok(mission) = state.viewing_repository.get_one(mission_id).await
+            {
+                let ws_msg = ServerEvent::MissionStarted(MissionStatusChanged {
+                    mission_id,
+                    mission_name: mission.name.clone(),
+                    new_status: MissionStatuses::InProgress.to_string(),
+                });
+                // Notifications are stored by the use case together with the status change
+                for member in crew {
+                    state.manager.notify_user(member.id, ws_msg.clone()).await;
+                }
 
-                    // 2. Broadcast to EVERYONE (for public list/manager/dashboard real-time update)
-                    state.manager.broadcast_all(ws_msg.clone()).await;
+                // 2. Broadcast to EVERYONE (for public list/manager/dashboard real-time update)
+                state.manager.broadcast_all(ws_msg.clone()).await;
 
-                    // 3. Broadcast to the specific room (for in-room UI update)
-                    state.manager.broadcast(mission_id, ws_msg).await;
-                }
+                // 3. Broadcast to the specific room (for in-room UI update)
+                state.manager.broadcast(mission_id, ws_msg).await;
             }
             (StatusCode::OK, mission_id.to_string()).into_response()
         }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -98,66 +92,40 @@ pub async fn to_completed<T1, T2>(
     Path(mission_id): Path<i32>,
 ) -> impl IntoResponse
 where
-    T1: MissionOperationRepository + Send + Sync + 'static,
-    T2: MissionViewingRepository + Send + Sync + 'static,
+    T1: MissionViewingRepository + Send + Sync + 'static,
+    T2: TransactionProvider + Send + Sync + 'static,
 {
     match state.use_case.to_completed(mission_id, user_id).await {
         Ok(mission_id) => {
             // Broadcast completion to the room
             if let Ok(mission) = state.viewing_repository.get_one(mission_id).await {
-                let ws_msg = WSMessage {
-                    msg_type: "mission_completed".to_string(),
-                    data: serde_json::json!({
-                        "mission_id": mission_id,
-                        "mission_name": mission.name,
-                        "new_status": "Completed"
-                    }),
-                };
+                let ws_msg = ServerEvent::MissionCompleted(MissionStatusChanged {
+                    mission_id,
+                    mission_name: mission.name.clone(),
+                    new_status: MissionStatuses::Completed.to_string(),
+                });
                 // 1. Broadcast to EVERYONE (Dashboard/Manager real-time update)
                 state.manager.broadcast_all(ws_msg.clone()).await;
 
                 // 2. Broadcast to the specific room (In-room UI update)
                 state.manager.broadcast(mission_id, ws_msg.clone()).await;
 
-                // 3. PERSIST FOR CHIEF & CREW
+                // 3. Real-time toast for crew (notifications were stored with the status change)
                 let crew = state
                     .viewing_repository
                     .get_crew(mission_id)
                     .await
                     .unwrap_or_default();
 
-                // For Chief
-                let _ = state
-                    .notification_repo
-                    .add(AddNotificationEntity {
-                        brawler_id: mission.chief_id,
-                        type_: "mission_completed".to_string(),
-                        content: format!("Mission '{}' has been COMPLETED!", mission.name),
-                        related_id: Some(mission_id),
-                    })
-                    .await;
-
-                // For Crew members
                 for member in crew {
                     if member.id != mission.chief_id {
-                        let _ = state
-                            .notification_repo
-                            .add(AddNotificationEntity {
-                                brawler_id: member.id,
-                                type_: "mission_completed".to_string(),
-                                content: format!("Mission '{}' has been COMPLETED!", mission.name),
-                                related_id: Some(mission_id),
-                            })
-                            .await;
-
-                        // Also notify via Global WS for real-time toast
                         state.manager.notify_user(member.id, ws_msg.clone()).await;
                     }
                 }
             }
             (StatusCode::OK, mission_id.to_string()).into_response()
         }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -167,90 +135,66 @@ pub async fn to_failed<T1, T2>(
     Path(mission_id): Path<i32>,
 ) -> impl IntoResponse
 where
-    T1: MissionOperationRepository + Send + Sync + 'static,
-    T2: MissionViewingRepository + Send + Sync + 'static,
+    T1: MissionViewingRepository + Send + Sync + 'static,
+    T2: TransactionProvider + Send + Sync + 'static,
 {
     match state.use_case.to_failed(mission_id, user_id).await {
         Ok(mission_id) => {
             // Broadcast failure to the room
             if let Ok(mission) = state.viewing_repository.get_one(mission_id).await {
-                let ws_msg = WSMessage {
-                    msg_type: "mission_failed".to_string(),
-                    data: serde_json::json!({
-                        "mission_id": mission_id,
-                        "mission_name": mission.name,
-                        "new_status": "Failed"
-                    }),
-                };
+                let ws_msg = ServerEvent::MissionFailed(MissionStatusChanged {
+                    mission_id,
+                    mission_name: mission.name.clone(),
+                    new_status: MissionStatuses::Failed.to_string(),
+                });
                 // 1. Broadcast to EVERYONE (Dashboard/Manager real-time update)
                 state.manager.broadcast_all(ws_msg.clone()).await;
 
                 // 2. Broadcast to the specific room (In-room UI update)
                 state.manager.broadcast(mission_id, ws_msg.clone()).await;
 
-                // 3. PERSIST FOR CHIEF & CREW
+                // 3. Real-time toast for crew (notifications were stored with the status change)
                 let crew = state
                     .viewing_repository
                     .get_crew(mission_id)
                     .await
                     .unwrap_or_default();
 
-                // For Chief
-                let _ = state
-                    .notification_repo
-                    .add(AddNotificationEntity {
-                        brawler_id: mission.chief_id,
-                        type_: "mission_failed".to_string(),
-                        content: format!("Mission '{}' has FAILED.", mission.name),
-                        related_id: Some(mission_id),
-                    })
-                    .await;
-
-                // For Crew members
                 for member in crew {
                     if member.id != mission.chief_id {
-                        let _ = state
-                            .notification_repo
-                            .add(AddNotificationEntity {
-                                brawler_id: member.id,
-                                type_: "mission_failed".to_string(),
-                                content: format!("Mission '{}' has FAILED.", mission.name),
-                                related_id: Some(mission_id),
-                            })
-                            .await;
-
-                        // Also notify via Global WS for real-time toast
                         state.manager.notify_user(member.id, ws_msg.clone()).await;
                     }
                 }
             }
             (StatusCode::OK, mission_id.to_string()).into_response()
         }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
 pub async fn kick<T1, T2>(
     State(state): State<Arc<MissionOperationState<T1, T2>>>,
     Extension(user_id): Extension<i32>,
+    Extension(CurrentRole(role)): Extension<CurrentRole>,
     Path((mission_id, brawler_id)): Path<(i32, i32)>,
 ) -> impl IntoResponse
 where
-    T1: MissionOperationRepository + Send + Sync + 'static,
-    T2: MissionViewingRepository + Send + Sync + 'static,
+    T1: MissionViewingRepository + Send + Sync + 'static,
+    T2: TransactionProvider + Send + Sync + 'static,
 {
-    match state.use_case.kick(mission_id, brawler_id, user_id).await {
-        Ok(_) => {
+    match state
+        .use_case
+        .kick(mission_id, brawler_id, user_id, role)
+        .await
+    {
+        Ok(promoted) => {
             // Notify the kicked member and the room
             if let Ok(mission) = state.viewing_repository.get_one(mission_id).await {
-                let ws_msg = WSMessage {
-                    msg_type: "kicked_from_mission".to_string(),
-                    data: serde_json::json!({
-                        "mission_id": mission_id,
-                        "mission_name": mission.name,
-                        "brawler_id": brawler_id, // Include brawler_id so frontend knows who was kicked
-                    }),
-                };
+                let ws_msg = ServerEvent::KickedFromMission(CrewChange {
+                    mission_id,
+                    mission_name: mission.name.clone(),
+                    brawler_id,
+                });
 
                 // 1. Notify the kicked user globally (for toast) and save to DB
                 let _ = state
@@ -270,23 +214,33 @@ where
 
                 // 3. Broadcast to the specific room (for in-room UI reaction)
                 state.manager.broadcast(mission_id, ws_msg).await;
+
+                // 4. They're no longer crew, so they lose the room stream
+                state
+                    .manager
+                    .close_room_member(mission_id, brawler_id, RoomCloseReason::Kicked)
+                    .await;
+
+                // 5. The freed slot went to the next brawler on the waitlist
+                if let Some(promoted_id) = promoted {
+                    push_promotion(&state.manager, mission_id, &mission.name, promoted_id).await;
+                }
             }
             StatusCode::OK.into_response()
         }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
 pub fn routes(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Router {
-    let mission_repository = MissionOperationPostgres::new(Arc::clone(&db_pool));
     let viewing_repository = MissionViewingPostgres::new(Arc::clone(&db_pool));
     let viewing_repository_arc = Arc::new(viewing_repository);
     let notification_repo = Arc::new(NotificationPostgres::new(Arc::clone(&db_pool)));
 
-    let use_case = MissionOperationUseCase::new(
-        Arc::new(mission_repository),
-        Arc::clone(&viewing_repository_arc),
-    );
+    let transaction_provider = Arc::new(TransactionProviderPostgres::new(Arc::clone(&db_pool)));
+
+    let use_case =
+        MissionOperationUseCase::new(Arc::clone(&viewing_repository_arc), transaction_provider);
 
     let state = Arc::new(MissionOperationState {
         use_case,
@@ -295,11 +249,18 @@ pub fn routes(db_pool: Arc<PgPoolSquad>, manager: Arc<ConnectionManager>) -> Rou
         notification_repo,
     });
 
-    Router::new()
+    let token_routes: Router<_> = Router::new()
         .route("/in-progress/{mission_id}", patch(in_progress))
         .route("/to-completed/{mission_id}", patch(to_completed))
         .route("/to-failed/{mission_id}", patch(to_failed))
-        .route("/kick/{mission_id}/{brawler_id}", patch(kick))
         .route_layer(middleware::from_fn(auth))
+        .route_layer(Extension(RouteScope(TokenScope::MissionsWrite)));
+
+    Router::new()
+        .merge(token_routes)
+        .route(
+            "/kick/{mission_id}/{brawler_id}",
+            patch(kick).route_layer(middleware::from_fn(auth)),
+        )
         .with_state(state)
 }
@@ -29,7 +29,7 @@ where
     match user_case.get_one(mission_id).await {
         Ok(model) => (StatusCode::OK, Json(model)).into_response(),
 
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -43,7 +43,7 @@ where
     match user_case.get_all(&filter).await {
         Ok(model) => (StatusCode::OK, Json(model)).into_response(),
 
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
@@ -57,7 +57,7 @@ where
     match user_case.get_crew(mission_id).await {
         Ok(model) => (StatusCode::OK, Json(model)).into_response(),
 
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
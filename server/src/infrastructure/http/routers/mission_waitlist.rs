@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    response::IntoResponse,
+    routing::{delete, get, post},
+};
+
+use crate::{
+    application::use_cases::mission_waitlist::MissionWaitlistUseCase,
+    domain::repositories::{
+        mission_viewing::MissionViewingRepository, mission_waitlist::MissionWaitlistRepository,
+    },
+    infrastructure::{
+        database::{
+            postgresql_connection::PgPoolSquad,
+            repositories::{
+                mission_viewing::MissionViewingPostgres, mission_waitlist::MissionWaitlistPostgres,
+            },
+        },
+        http::middlewares::auth::auth,
+        websocket::{
+            manager::ConnectionManager,
+            protocol::{CrewChange, MissionRef, ServerEvent},
+        },
+    },
+};
+
+pub struct MissionWaitlistState<T1, T2>
+where
+    T1: MissionWaitlistRepository + Send + Sync,
+    T2: MissionViewingRepository + Send + Sync,
+{
+    pub use_case: MissionWaitlistUseCase<T1, T2>,
+}
+
+/// Real-time side of a waitlist promotion: toast for the promoted brawler and a
+/// crew update for everyone else. The notification row is stored by the use case.
+pub async fn push_promotion(
+    manager: &ConnectionManager,
+    mission_id: i32,
+    mission_name: &str,
+    brawler_id: i32,
+) {
+    manager
+        .notify_user(
+            brawler_id,
+            ServerEvent::WaitlistPromoted(MissionRef {
+                mission_id,
+                mission_name: mission_name.to_string(),
+            }),
+        )
+        .await;
+
+    let ws_msg = ServerEvent::NewCrewJoined(CrewChange {
+        mission_id,
+        mission_name: mission_name.to_string(),
+        brawler_id,
+    });
+    manager.broadcast_all(ws_msg.clone()).await;
+    manager.broadcast(mission_id, ws_msg).await;
+}
+
+pub async fn join<T1, T2>(
+    State(state): State<Arc<MissionWaitlistState<T1, T2>>>,
+    Extension(user_id): Extension<i32>,
+    Path(mission_id): Path<i32>,
+) -> impl IntoResponse
+where
+    T1: MissionWaitlistRepository + Send + Sync + 'static,
+    T2: MissionViewingRepository + Send + Sync + 'static,
+{
+    match state.use_case.join(mission_id, user_id).await {
+        Ok(position) => (StatusCode::CREATED, Json(position)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn leave<T1, T2>(
+    State(state): State<Arc<MissionWaitlistState<T1, T2>>>,
+    Extension(user_id): Extension<i32>,
+    Path(mission_id): Path<i32>,
+) -> impl IntoResponse
+where
+    T1: MissionWaitlistRepository + Send + Sync + 'static,
+    T2: MissionViewingRepository + Send + Sync + 'static,
+{
+    match state.use_case.leave(mission_id, user_id).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn position<T1, T2>(
+    State(state): State<Arc<MissionWaitlistState<T1, T2>>>,
+    Extension(user_id): Extension<i32>,
+    Path(mission_id): Path<i32>,
+) -> impl IntoResponse
+where
+    T1: MissionWaitlistRepository + Send + Sync + 'static,
+    T2: MissionViewingRepository + Send + Sync + 'static,
+{
+    match state.use_case.position(mission_id, user_id).await {
+        Ok(position) => (StatusCode::OK, Json(position)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub fn routes(db_pool: Arc<PgPoolSquad>) -> Router {
+    let waitlist_repository = MissionWaitlistPostgres::new(Arc::clone(&db_pool));
+    let viewing_repository = MissionViewingPostgres::new(Arc::clone(&db_pool));
+
+    let use_case =
+        MissionWaitlistUseCase::new(Arc::new(waitlist_repository), Arc::new(viewing_repository));
+
+    let state = Arc::new(MissionWaitlistState { use_case });
+
+    Router::new()
+        .route("/{mission_id}", post(join))
+        .route("/{mission_id}", delete(leave))
+        .route("/{mission_id}", get(position))
+        .route_layer(middleware::from_fn(auth))
+        .with_state(state)
+}
@@ -1,11 +1,17 @@
+pub mod access_tokens;
+pub mod admin;
 pub mod authentication;
 pub mod brawlers;
 pub mod crew_operation;
 pub mod default_router;
+pub mod events;
 pub mod friendships;
 pub mod mission_comment;
+pub mod mission_invites;
 pub mod mission_management;
 pub mod mission_operation;
 pub mod mission_viewing;
+pub mod mission_waitlist;
 pub mod notifications;
+pub mod oauth;
 pub mod private_messages;
@@ -1,6 +1,6 @@
 use crate::infrastructure::http::middlewares::auth::auth;
 use crate::{
-    domain::repositories::notifications::NotificationRepository,
+    domain::{errors::AppError, repositories::notifications::NotificationRepository},
     infrastructure::database::{
         postgresql_connection::PgPoolSquad, repositories::notifications::NotificationPostgres,
     },
@@ -22,10 +22,10 @@ pub struct NotificationRouterState {
 pub async fn get_my_notifications(
     State(state): State<Arc<NotificationRouterState>>,
     Extension(user_id): Extension<i32>,
-) -> Json<serde_json::Value> {
+) -> impl IntoResponse {
     match state.repo.get_by_user(user_id).await {
-        Ok(notifications) => Json(serde_json::json!(notifications)),
-        Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
+        Ok(notifications) => (StatusCode::OK, Json(notifications)).into_response(),
+        Err(e) => AppError::from(e).into_response(),
     }
 }
 
@@ -36,7 +36,7 @@ pub async fn mark_as_read(
 ) -> impl IntoResponse {
     match state.repo.mark_as_read(id, user_id).await {
         Ok(_) => StatusCode::OK.into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => AppError::from(e).into_response(),
     }
 }
 
@@ -46,7 +46,7 @@ pub async fn mark_all_as_read(
 ) -> impl IntoResponse {
     match state.repo.mark_all_as_read(user_id).await {
         Ok(_) => StatusCode::OK.into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => AppError::from(e).into_response(),
     }
 }
 
@@ -56,7 +56,7 @@ pub async fn clear_notifications(
 ) -> impl IntoResponse {
     match state.repo.delete_for_user(user_id).await {
         Ok(_) => StatusCode::OK.into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => AppError::from(e).into_response(),
     }
 }
 
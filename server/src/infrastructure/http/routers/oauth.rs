@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+};
+use axum_extra::extract::cookie::CookieJar;
+
+use crate::{
+    application::use_cases::{authentication::LoginOutcome, oauth::OAuthUseCase},
+    domain::{
+        errors::AppResult,
+        repositories::brawlers::BrawlerRepository,
+        value_objects::{
+            oauth_model::{AuthorizeModel, OAuthCallbackModel, UnlinkIdentityModel},
+            session_model::SessionClient,
+        },
+    },
+    infrastructure::{
+        database::{
+            postgresql_connection::PgPoolSquad,
+            repositories::{
+                brawler_identities::BrawlerIdentityPostgres, brawlers::BrawlerPostgres,
+                sessions::SessionPostgres, totp::TotpPostgres,
+            },
+        },
+        http::{
+            auth_cookies::{passport_response, take_oauth_state, with_oauth_state},
+            middlewares::auth::auth,
+        },
+        oauth::IdentityProvider,
+    },
+};
+
+pub async fn list_providers<T>(State(user_case): State<Arc<OAuthUseCase<T>>>) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    (StatusCode::OK, Json(user_case.providers())).into_response()
+}
+
+/// The state's nonce goes into a cookie the callback has to come with
+fn started_response(jar: CookieJar, started: AppResult<(AuthorizeModel, String)>) -> Response {
+    let (model, nonce) = match started {
+        Ok(started) => started,
+        Err(e) => return e.into_response(),
+    };
+    match with_oauth_state(jar, nonce) {
+        Ok(jar) => (StatusCode::OK, jar, Json(model)).into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn authorize<T>(
+    State(user_case): State<Arc<OAuthUseCase<T>>>,
+    jar: CookieJar,
+    Path(provider): Path<String>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    started_response(jar, user_case.authorize(&provider, None))
+}
+
+pub async fn authorize_link<T>(
+    State(user_case): State<Arc<OAuthUseCase<T>>>,
+    Extension(user_id): Extension<i32>,
+    jar: CookieJar,
+    Path(provider): Path<String>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    started_response(jar, user_case.authorize(&provider, Some(user_id)))
+}
+
+pub async fn callback<T>(
+    State(user_case): State<Arc<OAuthUseCase<T>>>,
+    client: SessionClient,
+    jar: CookieJar,
+    Json(model): Json<OAuthCallbackModel>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    let (jar, nonce) = take_oauth_state(jar);
+    match user_case.login(model, nonce, client).await {
+        Ok(LoginOutcome::Passport(passport)) => passport_response(StatusCode::OK, jar, passport),
+        Ok(LoginOutcome::MfaRequired(challenge)) => {
+            (StatusCode::OK, jar, Json(challenge)).into_response()
+        }
+
+        Err(e) => (jar, e).into_response(),
+    }
+}
+
+pub async fn identities<T>(
+    State(user_case): State<Arc<OAuthUseCase<T>>>,
+    Extension(user_id): Extension<i32>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    match user_case.identities(user_id).await {
+        Ok(identities) => (StatusCode::OK, Json(identities)).into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn link<T>(
+    State(user_case): State<Arc<OAuthUseCase<T>>>,
+    Extension(user_id): Extension<i32>,
+    jar: CookieJar,
+    Json(model): Json<OAuthCallbackModel>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    let (jar, nonce) = take_oauth_state(jar);
+    match user_case.link(user_id, model, nonce).await {
+        Ok(identity) => (StatusCode::CREATED, jar, Json(identity)).into_response(),
+
+        Err(e) => (jar, e).into_response(),
+    }
+}
+
+/// The body, with the password, is only needed for the last provider
+pub async fn unlink<T>(
+    State(user_case): State<Arc<OAuthUseCase<T>>>,
+    Extension(user_id): Extension<i32>,
+    Path(provider): Path<String>,
+    body: Option<Json<UnlinkIdentityModel>>,
+) -> impl IntoResponse
+where
+    T: BrawlerRepository + Send + Sync,
+{
+    let model = body.map(|Json(model)| model).unwrap_or_default();
+    match user_case.unlink(user_id, provider, model).await {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+
+        Err(e) => e.into_response(),
+    }
+}
+
+pub fn routes(db_pool: Arc<PgPoolSquad>, providers: Vec<Arc<dyn IdentityProvider>>) -> Router {
+    let user_case = OAuthUseCase::new(
+        Arc::new(BrawlerPostgres::new(Arc::clone(&db_pool))),
+        Arc::new(BrawlerIdentityPostgres::new(Arc::clone(&db_pool))),
+        Arc::new(SessionPostgres::new(Arc::clone(&db_pool))),
+        Arc::new(TotpPostgres::new(db_pool)),
+        providers,
+    );
+
+    // Linking is a second sign-in at the provider by someone already logged in
+    let protected_routes: Router<_> = Router::new()
+        .route("/link/{provider}", get(authorize_link))
+        .route("/identities", get(identities).post(link))
+        .route("/identities/{provider}", delete(unlink))
+        .route_layer(middleware::from_fn(auth));
+
+    Router::new()
+        .merge(protected_routes)
+        .route("/providers", get(list_providers))
+        .route("/authorize/{provider}", get(authorize))
+        .route("/callback", post(callback))
+        .with_state(Arc::new(user_case))
+}
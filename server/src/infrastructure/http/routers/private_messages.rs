@@ -10,8 +10,14 @@ use crate::domain::entities::private_messages::CreatePrivateMessage;
 use crate::domain::repositories::{
     notifications::NotificationRepository, private_messages::PrivateMessageRepository,
 };
-use crate::infrastructure::websocket::handler::WSMessage;
 use crate::infrastructure::websocket::manager::ConnectionManager;
+use crate::infrastructure::websocket::protocol::ServerEvent;
+
+type MessagesState = (
+    Arc<dyn PrivateMessageRepository>,
+    Arc<ConnectionManager>,
+    Arc<dyn NotificationRepository>,
+);
 
 pub fn routes(
     pm_repo: Arc<dyn PrivateMessageRepository>,
@@ -28,11 +34,7 @@ pub fn routes(
 }
 
 async fn send_message(
-    State((pm_repo, ws_manager, notification_repo)): State<(
-        Arc<dyn PrivateMessageRepository>,
-        Arc<ConnectionManager>,
-        Arc<dyn NotificationRepository>,
-    )>,
+    State((pm_repo, ws_manager, notification_repo)): State<MessagesState>,
     Extension(user_id): Extension<i32>,
     Json(payload): Json<CreatePrivateMessage>,
 ) -> impl IntoResponse {
@@ -58,75 +60,53 @@ async fn send_message(
 
             // 2. Send via WebSocket if recipient is online
             ws_manager
-                .notify_user(
-                    msg.receiver_id,
-                    WSMessage {
-                        msg_type: "private_message".to_string(),
-                        data: serde_json::to_value(&msg).unwrap_or_default(),
-                    },
-                )
+                .notify_user(msg.receiver_id, ServerEvent::PrivateMessage(msg.clone()))
                 .await;
 
             (axum::http::StatusCode::CREATED, Json(msg)).into_response()
         }
-        Err(e) => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
 async fn get_conversation(
-    State((pm_repo, _, _)): State<(
-        Arc<dyn PrivateMessageRepository>,
-        Arc<ConnectionManager>,
-        Arc<dyn NotificationRepository>,
-    )>,
+    State((pm_repo, _, _)): State<MessagesState>,
     Extension(user_id): Extension<i32>,
     axum::extract::Path(with_id): axum::extract::Path<i32>,
 ) -> impl IntoResponse {
     match pm_repo.get_conversation(user_id, with_id).await {
         Ok(msgs) => Json(msgs).into_response(),
-        Err(e) => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
 async fn get_unread_count(
-    State((pm_repo, _, _)): State<(
-        Arc<dyn PrivateMessageRepository>,
-        Arc<ConnectionManager>,
-        Arc<dyn NotificationRepository>,
-    )>,
+    State((pm_repo, _, _)): State<MessagesState>,
     Extension(user_id): Extension<i32>,
 ) -> impl IntoResponse {
     match pm_repo.get_unread_count(user_id).await {
         Ok(count) => Json(serde_json::json!({ "count": count })).into_response(),
-        Err(e) => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
 async fn get_recent_chats(
-    State((pm_repo, _, _)): State<(
-        Arc<dyn PrivateMessageRepository>,
-        Arc<ConnectionManager>,
-        Arc<dyn NotificationRepository>,
-    )>,
+    State((pm_repo, _, _)): State<MessagesState>,
     Extension(user_id): Extension<i32>,
 ) -> impl IntoResponse {
     match pm_repo.get_recent_chats(user_id).await {
         Ok(chats) => Json(chats).into_response(),
-        Err(e) => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        Err(e) => e.into_response(),
     }
 }
 
 async fn mark_as_read(
-    State((pm_repo, _, _)): State<(
-        Arc<dyn PrivateMessageRepository>,
-        Arc<ConnectionManager>,
-        Arc<dyn NotificationRepository>,
-    )>,
+    State((pm_repo, _, _)): State<MessagesState>,
     Extension(user_id): Extension<i32>,
     axum::extract::Path(sender_id): axum::extract::Path<i32>,
 ) -> impl IntoResponse {
     match pm_repo.mark_as_read(user_id, sender_id).await {
         Ok(_) => axum::http::StatusCode::OK.into_response(),
-        Err(e) => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        Err(e) => e.into_response(),
     }
 }
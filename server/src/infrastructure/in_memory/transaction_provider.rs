@@ -0,0 +1,179 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::domain::{
+    entities::{
+        crew_memberships::CrewMemberShips,
+        mission_waitlist::MissionWaitlistEntity,
+        missions::MissionEntity,
+        notifications::{AddNotificationEntity, NotificationEntity},
+    },
+    errors::{AppError, AppResult},
+    repositories::transaction_provider::{TransactionProvider, UnitOfWork},
+    value_objects::mission_statuses::MissionStatuses,
+};
+
+#[derive(Debug, Clone)]
+pub struct InMemoryComment {
+    pub mission_id: i32,
+    pub brawler_id: i32,
+    pub content: String,
+}
+
+/// Tables touched by [`UnitOfWork`], kept in plain vectors.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryStore {
+    pub missions: Vec<MissionEntity>,
+    pub crew_memberships: Vec<CrewMemberShips>,
+    pub mission_comments: Vec<InMemoryComment>,
+    pub mission_waitlist: Vec<MissionWaitlistEntity>,
+    pub notifications: Vec<NotificationEntity>,
+}
+
+/// In-memory stand-in for `TransactionProviderPostgres`, meant for tests.
+///
+/// Each transaction works on a copy of the store, which only replaces the
+/// shared one when the closure succeeds.
+#[derive(Default)]
+pub struct InMemoryTransactionProvider {
+    store: Mutex<InMemoryStore>,
+}
+
+impl InMemoryTransactionProvider {
+    pub fn new(store: InMemoryStore) -> Self {
+        Self {
+            store: Mutex::new(store),
+        }
+    }
+
+    pub fn snapshot(&self) -> InMemoryStore {
+        self.store.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl TransactionProvider for InMemoryTransactionProvider {
+    async fn transaction<T, F>(&self, f: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut dyn UnitOfWork) -> AppResult<T> + Send + 'static,
+    {
+        let mut store = self.store.lock().unwrap();
+        let mut working_copy = store.clone();
+        let result = f(&mut working_copy)?;
+        *store = working_copy;
+        Ok(result)
+    }
+}
+
+impl InMemoryStore {
+    fn active_mission(&mut self, mission_id: i32, chief_id: i32) -> AppResult<&mut MissionEntity> {
+        self.missions
+            .iter_mut()
+            .find(|m| m.id == mission_id && m.chief_id == chief_id && m.deleted_at.is_none())
+            .ok_or_else(|| AppError::NotFound("Record not found".to_string()))
+    }
+}
+
+impl UnitOfWork for InMemoryStore {
+    fn crew_ids(&mut self, mission_id: i32) -> AppResult<Vec<i32>> {
+        Ok(self
+            .crew_memberships
+            .iter()
+            .filter(|c| c.mission_id == mission_id)
+            .map(|c| c.brawler_id)
+            .collect())
+    }
+
+    fn set_mission_status(
+        &mut self,
+        mission_id: i32,
+        chief_id: i32,
+        status: MissionStatuses,
+    ) -> AppResult<i32> {
+        let mission = self.active_mission(mission_id, chief_id)?;
+        mission.status = status.to_string();
+        mission.updated_at = Utc::now().naive_utc();
+        Ok(mission.id)
+    }
+
+    fn delete_mission(&mut self, mission_id: i32, chief_id: i32) -> AppResult<()> {
+        self.missions
+            .retain(|m| !(m.id == mission_id && m.chief_id == chief_id));
+        Ok(())
+    }
+
+    fn soft_delete_mission(&mut self, mission_id: i32, chief_id: i32) -> AppResult<()> {
+        if let Ok(mission) = self.active_mission(mission_id, chief_id)
+            && mission.status != MissionStatuses::InProgress.to_string()
+        {
+            mission.deleted_at = Some(Utc::now().naive_utc());
+        }
+        Ok(())
+    }
+
+    fn delete_mission_comments(&mut self, mission_id: i32) -> AppResult<()> {
+        self.mission_comments.retain(|c| c.mission_id != mission_id);
+        Ok(())
+    }
+
+    fn remove_crew_member(&mut self, mission_id: i32, brawler_id: i32) -> AppResult<()> {
+        self.crew_memberships
+            .retain(|c| !(c.mission_id == mission_id && c.brawler_id == brawler_id));
+        Ok(())
+    }
+
+    fn promote_from_waitlist(&mut self, mission_id: i32) -> AppResult<Option<i32>> {
+        let Some(mission) = self.missions.iter().find(|m| m.id == mission_id) else {
+            return Ok(None);
+        };
+        let crew_count = self
+            .crew_memberships
+            .iter()
+            .filter(|c| c.mission_id == mission_id)
+            .count() as i32;
+        if mission.deleted_at.is_some()
+            || mission.status != MissionStatuses::Open.to_string()
+            || crew_count >= mission.max_crew
+        {
+            return Ok(None);
+        }
+
+        let Some(index) = self
+            .mission_waitlist
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| w.mission_id == mission_id)
+            .min_by_key(|(_, w)| w.id)
+            .map(|(i, _)| i)
+        else {
+            return Ok(None);
+        };
+
+        let entry = self.mission_waitlist.remove(index);
+        self.crew_memberships.push(CrewMemberShips {
+            mission_id,
+            brawler_id: entry.brawler_id,
+        });
+        Ok(Some(entry.brawler_id))
+    }
+
+    fn add_notification(
+        &mut self,
+        notification: AddNotificationEntity,
+    ) -> AppResult<NotificationEntity> {
+        let entity = NotificationEntity {
+            id: self.notifications.iter().map(|n| n.id).max().unwrap_or(0) + 1,
+            brawler_id: notification.brawler_id,
+            type_: notification.type_,
+            content: notification.content,
+            related_id: notification.related_id,
+            is_read: false,
+            created_at: Utc::now().naive_utc(),
+        };
+        self.notifications.push(entity.clone());
+        Ok(entity)
+    }
+}
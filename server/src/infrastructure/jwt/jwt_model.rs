@@ -5,11 +5,14 @@ use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
 use crate::config::config_loader::get_jwt_env;
+use crate::domain::value_objects::brawler_role::BrawlerRole;
 use crate::infrastructure::jwt::generate_token;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Passport {
     pub id: i32,
+    /// Empty, and left out, when it was set as a cookie instead
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub token: String,
     pub display_name: String,
     pub avatar_url: Option<String>,
@@ -18,9 +21,17 @@ pub struct Passport {
     pub contact_email: Option<String>,
     pub instagram: Option<String>,
     pub facebook: Option<String>,
+    #[serde(default)]
+    pub role: BrawlerRole,
+    /// Only set when a login starts or is refreshed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
 }
 
 impl Passport {
+    /// The profile part. `token` stays empty until [`Passport::with_session`]
+    /// signs one for the login it belongs to.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         user_id: i32,
         display_name: String,
@@ -30,17 +41,11 @@ impl Passport {
         contact_email: Option<String>,
         instagram: Option<String>,
         facebook: Option<String>,
-    ) -> Result<Self> {
-        let jwt_env = get_jwt_env()?;
-        let claims = Claims {
-            sub: user_id.to_string(),
-            exp: (Utc::now() + Duration::days(jwt_env.ttl)).timestamp() as usize,
-            iat: Utc::now().timestamp() as usize,
-        };
-        let token = generate_token(jwt_env.secret, &claims)?;
-        Ok(Self {
+        role: BrawlerRole,
+    ) -> Self {
+        Self {
             id: user_id,
-            token,
+            token: String::new(),
             display_name,
             avatar_url,
             bio,
@@ -48,13 +53,88 @@ impl Passport {
             contact_email,
             instagram,
             facebook,
-        })
+            role,
+            refresh_token: None,
+        }
+    }
+
+    /// Signs a short-lived access token for `session_id`
+    pub fn with_session(mut self, session_id: i32) -> Result<Self> {
+        let jwt_env = get_jwt_env()?;
+        let claims = Claims {
+            sub: self.id.to_string(),
+            sid: session_id,
+            role: self.role,
+            exp: (Utc::now() + Duration::minutes(jwt_env.access_ttl_minutes)).timestamp() as usize,
+            iat: Utc::now().timestamp() as usize,
+        };
+        self.token = generate_token(jwt_env.secret, &claims)?;
+        Ok(self)
+    }
+}
+
+/// Payload of an invite link token. Whether the link is still usable (revoked,
+/// used up) is checked against its row; the signature only proves we issued it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InviteClaims {
+    pub invite_id: i32,
+    pub mission_id: i32,
+    pub exp: usize,
+    pub iat: usize,
+}
+
+/// Payload of the token a login gets in place of a passport when the account has
+/// two-factor authentication on. It has no `sid`, so it can't pass as an access
+/// token; it only lets the holder post a code for that login.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MfaPendingClaims {
+    pub sub: String,
+    pub mfa_pending: bool,
+    pub exp: usize,
+    pub iat: usize,
+}
+
+impl MfaPendingClaims {
+    pub fn sign(brawler_id: i32, ttl_minutes: i64) -> Result<String> {
+        let claims = Self {
+            sub: brawler_id.to_string(),
+            mfa_pending: true,
+            exp: (Utc::now() + Duration::minutes(ttl_minutes)).timestamp() as usize,
+            iat: Utc::now().timestamp() as usize,
+        };
+        generate_token(get_jwt_env()?.secret, &claims)
+    }
+}
+
+/// Payload of the `state` an outside sign-in carries through the provider and
+/// back. It ties the callback to the sign-in we started, and to the brawler
+/// when it links an account instead of logging in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthStateClaims {
+    pub provider: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_brawler_id: Option<i32>,
+    /// Random per sign-in; the PKCE verifier is derived from it
+    pub nonce: String,
+    pub exp: usize,
+    pub iat: usize,
+}
+
+impl OAuthStateClaims {
+    pub fn sign(&self) -> Result<String> {
+        generate_token(get_jwt_env()?.secret, self)
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
+    /// Session the token was issued to; revoking it rejects the token
+    pub sid: i32,
+    /// As of when the token was signed. Tokens from before roles existed carry
+    /// none and count as a plain user.
+    #[serde(default)]
+    pub role: BrawlerRole,
     pub exp: usize,
     pub iat: usize,
 }
@@ -3,8 +3,9 @@ pub mod jwt_model;
 
 use anyhow::{Ok, Result};
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use serde::Serialize;
 
-pub fn generate_token(secret: String, claims: &jwt_model::Claims) -> Result<String> {
+pub fn generate_token<T: Serialize>(secret: String, claims: &T) -> Result<String> {
     let token = encode(
         &Header::default(),
         claims,
@@ -23,3 +24,39 @@ pub fn verify_token(secret: String, token: String) -> Result<jwt_model::Claims>
 
     Ok(token.claims)
 }
+
+pub fn verify_invite_token(secret: String, token: String) -> Result<jwt_model::InviteClaims> {
+    let token = decode::<jwt_model::InviteClaims>(
+        &token,
+        &DecodingKey::from_secret(secret.as_ref()),
+        &Validation::default(),
+    )?;
+
+    Ok(token.claims)
+}
+
+pub fn verify_mfa_pending_token(
+    secret: String,
+    token: String,
+) -> Result<jwt_model::MfaPendingClaims> {
+    let token = decode::<jwt_model::MfaPendingClaims>(
+        &token,
+        &DecodingKey::from_secret(secret.as_ref()),
+        &Validation::default(),
+    )?;
+    if !token.claims.mfa_pending {
+        anyhow::bail!("Not a two-factor login token");
+    }
+
+    Ok(token.claims)
+}
+
+pub fn verify_oauth_state(secret: String, token: String) -> Result<jwt_model::OAuthStateClaims> {
+    let token = decode::<jwt_model::OAuthStateClaims>(
+        &token,
+        &DecodingKey::from_secret(secret.as_ref()),
+        &Validation::default(),
+    )?;
+
+    Ok(token.claims)
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use tokio::io::AsyncWriteExt;
+
+use super::{Mail, Mailer};
+
+/// Local development stand-in: mails go to the log, or are appended to `file`
+/// so links in them can be copied
+pub struct LogMailer {
+    file: Option<String>,
+}
+
+impl LogMailer {
+    pub fn new(file: Option<String>) -> Self {
+        Self { file }
+    }
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, mail: &Mail) -> Result<()> {
+        mail.check_headers()?;
+        let Some(path) = &self.file else {
+            tracing::info!("Mail to {} ({}):\n{}", mail.to, mail.subject, mail.body);
+            return Ok(());
+        };
+
+        let entry = format!(
+            "Date: {}\nTo: {}\nSubject: {}\n\n{}\n\n",
+            Utc::now().to_rfc2822(),
+            mail.to,
+            mail.subject,
+            mail.body
+        );
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        file.write_all(entry.as_bytes()).await?;
+        // A tokio file finishes writing in the background unless flushed
+        file.flush().await?;
+        Ok(())
+    }
+}
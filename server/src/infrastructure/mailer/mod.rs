@@ -0,0 +1,45 @@
+//! Outgoing mail. Use cases only see [`Mailer`]; `MAILER` picks SMTP or the log
+//! stand-in used for local development.
+
+pub mod log_mailer;
+pub mod smtp;
+
+use std::sync::Arc;
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+
+use crate::config::config_model::{MailerEnv, MailerKind};
+
+/// A plain-text mail to one recipient
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl Mail {
+    /// Header values can't carry line breaks, or they could add headers of their own
+    pub fn check_headers(&self) -> Result<()> {
+        if [&self.to, &self.subject]
+            .iter()
+            .any(|value| value.contains(['\r', '\n']))
+        {
+            bail!("Mail headers must not contain line breaks");
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, mail: &Mail) -> Result<()>;
+}
+
+pub fn from_env(env: MailerEnv) -> Arc<dyn Mailer> {
+    match env.kind {
+        MailerKind::Log => Arc::new(log_mailer::LogMailer::new(env.file)),
+        MailerKind::Smtp => Arc::new(smtp::SmtpMailer::new(env.smtp, env.from)),
+    }
+}
@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use async_trait::async_trait;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::Utc;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+use tokio_native_tls::{TlsConnector, native_tls};
+
+use super::{Mail, Mailer};
+use crate::{
+    config::config_model::{SmtpEnv, SmtpSecurity},
+    infrastructure::secure_token,
+};
+
+/// Gives up on a server that stops answering, so a request isn't held forever
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sends each mail over its own SMTP connection. Enough for the occasional
+/// account mail; there is no pooling or retry queue.
+pub struct SmtpMailer {
+    env: SmtpEnv,
+    from: String,
+}
+
+impl SmtpMailer {
+    pub fn new(env: SmtpEnv, from: String) -> Self {
+        Self { env, from }
+    }
+
+    async fn deliver(&self, mail: &Mail) -> Result<()> {
+        let tcp = TcpStream::connect((self.env.host.as_str(), self.env.port))
+            .await
+            .with_context(|| format!("Failed to connect to {}", self.env.host))?;
+
+        match self.env.security {
+            SmtpSecurity::None => self.session(BufReader::new(tcp), true, mail).await,
+            SmtpSecurity::Tls => {
+                let tls = tls_connector()?.connect(&self.env.host, tcp).await?;
+                self.session(BufReader::new(tls), true, mail).await
+            }
+            SmtpSecurity::StartTls => {
+                let mut plain = BufReader::new(tcp);
+                expect(&mut plain, 220).await?;
+                command(&mut plain, &format!("EHLO {}", self.helo_domain()), 250).await?;
+                command(&mut plain, "STARTTLS", 220).await?;
+                let tls = tls_connector()?
+                    .connect(&self.env.host, plain.into_inner())
+                    .await?;
+                // The server forgets the first EHLO once TLS is up, and sends no new greeting
+                self.session(BufReader::new(tls), false, mail).await
+            }
+        }
+    }
+
+    async fn session<S>(&self, mut stream: BufReader<S>, greeting: bool, mail: &Mail) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        if greeting {
+            expect(&mut stream, 220).await?;
+        }
+        command(&mut stream, &format!("EHLO {}", self.helo_domain()), 250).await?;
+
+        if let (Some(username), Some(password)) = (&self.env.username, &self.env.password) {
+            let credentials = STANDARD.encode(format!("\0{username}\0{password}"));
+            command(&mut stream, &format!("AUTH PLAIN {credentials}"), 235)
+                .await
+                .context("SMTP authentication failed")?;
+        }
+
+        command(
+            &mut stream,
+            &format!("MAIL FROM:<{}>", envelope_address(&self.from)),
+            250,
+        )
+        .await?;
+        command(
+            &mut stream,
+            &format!("RCPT TO:<{}>", envelope_address(&mail.to)),
+            250,
+        )
+        .await?;
+        command(&mut stream, "DATA", 354).await?;
+
+        let message = self.message(mail)?;
+        stream.write_all(message.as_bytes()).await?;
+        stream.write_all(b"\r\n.\r\n").await?;
+        stream.flush().await?;
+        expect(&mut stream, 250).await?;
+
+        // The mail is accepted; a rude hang-up after this doesn't matter
+        let _ = command(&mut stream, "QUIT", 221).await;
+        Ok(())
+    }
+
+    fn helo_domain(&self) -> &str {
+        envelope_address(&self.from)
+            .rsplit_once('@')
+            .map_or("localhost", |(_, domain)| domain)
+    }
+
+    /// Headers and dot-stuffed body, lines ending in CRLF
+    fn message(&self, mail: &Mail) -> Result<String> {
+        let mut message = format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMessage-ID: <{}@{}>\r\n\
+             MIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\n\
+             Content-Transfer-Encoding: 8bit\r\n\r\n",
+            self.from,
+            mail.to,
+            mail.subject,
+            Utc::now().to_rfc2822(),
+            secure_token::generate()?,
+            self.helo_domain(),
+        );
+        for line in mail.body.lines() {
+            // A lone "." would end the DATA section
+            if line.starts_with('.') {
+                message.push('.');
+            }
+            message.push_str(line);
+            message.push_str("\r\n");
+        }
+        Ok(message)
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, mail: &Mail) -> Result<()> {
+        mail.check_headers()?;
+        tokio::time::timeout(SMTP_TIMEOUT, self.deliver(mail))
+            .await
+            .map_err(|_| anyhow!("SMTP server {} timed out", self.env.host))?
+    }
+}
+
+fn tls_connector() -> Result<TlsConnector> {
+    Ok(TlsConnector::from(native_tls::TlsConnector::new()?))
+}
+
+/// `a@b` out of `Name <a@b>`
+fn envelope_address(address: &str) -> &str {
+    match (address.find('<'), address.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &address[start + 1..end],
+        _ => address.trim(),
+    }
+}
+
+/// Reads one reply, which may span several `250-...` lines
+async fn read_reply<S>(stream: &mut BufReader<S>) -> Result<(u16, String)>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut text = String::new();
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line).await? == 0 {
+            bail!("SMTP server closed the connection");
+        }
+        let line = line.trim_end();
+        let code = line
+            .get(..3)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| anyhow!("Malformed SMTP reply: {line}"))?;
+        text.push_str(line.get(4..).unwrap_or_default());
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok((code, text));
+        }
+        text.push('\n');
+    }
+}
+
+async fn expect<S>(stream: &mut BufReader<S>, code: u16) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (got, text) = read_reply(stream).await?;
+    if got != code {
+        bail!("SMTP server replied {got} {text}, expected {code}");
+    }
+    Ok(())
+}
+
+async fn command<S>(stream: &mut BufReader<S>, line: &str, code: u16) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    stream.write_all(line.as_bytes()).await?;
+    stream.write_all(b"\r\n").await?;
+    stream.flush().await?;
+    expect(stream, code).await
+}
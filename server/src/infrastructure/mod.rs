@@ -2,5 +2,10 @@ pub mod argon2;
 pub mod cloudinary;
 pub mod database;
 pub mod http;
+pub mod in_memory;
 pub mod jwt;
+pub mod mailer;
+pub mod oauth;
+pub mod secure_token;
+pub mod totp;
 pub mod websocket;
@@ -0,0 +1,155 @@
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::config::config_model::{OAuthProviderEnv, OAuthProviderKind};
+
+use super::{IdentityProvider, ProviderProfile};
+
+/// Any provider with an authorization code flow and a userinfo endpoint
+pub struct OAuthProvider {
+    env: OAuthProviderEnv,
+    redirect_uri: String,
+    client: reqwest::Client,
+}
+
+impl OAuthProvider {
+    pub fn new(env: OAuthProviderEnv, redirect_uri: String) -> Self {
+        Self {
+            env,
+            redirect_uri,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+/// `GET /users/@me`
+#[derive(Deserialize)]
+struct DiscordUser {
+    id: String,
+    username: String,
+    global_name: Option<String>,
+    avatar: Option<String>,
+    email: Option<String>,
+    #[serde(default)]
+    verified: bool,
+}
+
+impl From<DiscordUser> for ProviderProfile {
+    fn from(user: DiscordUser) -> Self {
+        Self {
+            avatar_url: user.avatar.map(|avatar| {
+                format!(
+                    "https://cdn.discordapp.com/avatars/{}/{avatar}.png",
+                    user.id
+                )
+            }),
+            subject: user.id,
+            display_name: user.global_name,
+            username: Some(user.username),
+            email: user.email,
+            email_verified: user.verified,
+        }
+    }
+}
+
+/// Standard OpenID Connect userinfo claims
+#[derive(Deserialize)]
+struct OidcUserInfo {
+    sub: String,
+    preferred_username: Option<String>,
+    name: Option<String>,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    picture: Option<String>,
+}
+
+impl From<OidcUserInfo> for ProviderProfile {
+    fn from(info: OidcUserInfo) -> Self {
+        Self {
+            subject: info.sub,
+            username: info.preferred_username,
+            display_name: info.name,
+            email: info.email,
+            email_verified: info.email_verified,
+            avatar_url: info.picture,
+        }
+    }
+}
+
+/// The body of a successful response; the provider's error text otherwise
+async fn body_of(response: reqwest::Response) -> Result<String> {
+    let status = response.status();
+    let body = response.text().await?;
+    if !status.is_success() {
+        bail!("{status}: {body}");
+    }
+    Ok(body)
+}
+
+#[async_trait]
+impl IdentityProvider for OAuthProvider {
+    fn name(&self) -> &str {
+        &self.env.name
+    }
+
+    fn authorize_url(&self, state: &str, code_challenge: &str) -> String {
+        Url::parse_with_params(
+            &self.env.authorize_url,
+            &[
+                ("response_type", "code"),
+                ("client_id", self.env.client_id.as_str()),
+                ("redirect_uri", self.redirect_uri.as_str()),
+                ("scope", self.env.scopes.as_str()),
+                ("state", state),
+                ("code_challenge", code_challenge),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map(String::from)
+        .unwrap_or_else(|_| self.env.authorize_url.clone())
+    }
+
+    async fn exchange(&self, code: &str, code_verifier: &str) -> Result<ProviderProfile> {
+        let response = self
+            .client
+            .post(&self.env.token_url)
+            .basic_auth(&self.env.client_id, Some(&self.env.client_secret))
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", self.redirect_uri.as_str()),
+                ("code_verifier", code_verifier),
+            ])
+            .send()
+            .await
+            .context("token request failed")?;
+        let token: TokenResponse =
+            serde_json::from_str(&body_of(response).await?).context("unexpected token response")?;
+
+        let response = self
+            .client
+            .get(&self.env.userinfo_url)
+            .bearer_auth(token.access_token)
+            .send()
+            .await
+            .context("userinfo request failed")?;
+        let body = body_of(response).await?;
+        let profile = match self.env.kind {
+            OAuthProviderKind::Discord => {
+                ProviderProfile::from(serde_json::from_str::<DiscordUser>(&body)?)
+            }
+            OAuthProviderKind::Oidc => {
+                ProviderProfile::from(serde_json::from_str::<OidcUserInfo>(&body)?)
+            }
+        };
+        Ok(profile)
+    }
+}
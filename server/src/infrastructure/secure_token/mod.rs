@@ -0,0 +1,27 @@
+//! Random bearer tokens that are stored only as a hash.
+
+use anyhow::{Result, anyhow};
+use aws_lc_rs::{
+    digest::{SHA256, digest},
+    rand::{SecureRandom, SystemRandom},
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+
+/// 256 bits, URL-safe
+pub fn generate() -> Result<String> {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow!("Failed to generate a random token"))?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// Hex SHA-256. The tokens are random, so a fast unsalted hash is enough to keep
+/// a leaked table from being usable.
+pub fn hash(token: &str) -> String {
+    digest(&SHA256, token.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
@@ -0,0 +1,127 @@
+//! Time-based one-time passwords (RFC 6238) the way authenticator apps do them:
+//! HMAC-SHA1 over 30 second steps, 6 digits.
+
+use anyhow::{Result, anyhow};
+use aws_lc_rs::{
+    constant_time::verify_slices_are_equal,
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use data_encoding::BASE32_NOPAD;
+
+pub const STEP_SECONDS: i64 = 30;
+pub const DIGITS: usize = 6;
+/// Steps either side of the current one still accepted, for clock drift
+const DRIFT_STEPS: i64 = 1;
+/// 160 bits, the HMAC-SHA1 block RFC 4226 recommends
+const SECRET_BYTES: usize = 20;
+
+fn random_bytes<const N: usize>() -> Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow!("Failed to generate random bytes"))?;
+    Ok(bytes)
+}
+
+/// A new shared secret, base32 as authenticator apps take it
+pub fn generate_secret() -> Result<String> {
+    Ok(BASE32_NOPAD.encode(&random_bytes::<SECRET_BYTES>()?))
+}
+
+/// Percent-encodes everything but RFC 3986 unreserved characters
+fn encode_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// The `otpauth://` URI authenticator apps scan from a QR code
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+    let issuer = encode_component(issuer);
+    format!(
+        "otpauth://totp/{issuer}:{}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={DIGITS}&period={STEP_SECONDS}",
+        encode_component(account),
+    )
+}
+
+pub fn step_at(unix_seconds: i64) -> i64 {
+    unix_seconds.div_euclid(STEP_SECONDS)
+}
+
+/// The code for one time step
+pub fn code_at(secret: &str, step: i64) -> Result<String> {
+    let key_bytes = BASE32_NOPAD
+        .decode(secret.as_bytes())
+        .map_err(|_| anyhow!("TOTP secret is not base32"))?;
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, &key_bytes);
+    let mac = hmac::sign(&key, &step.to_be_bytes());
+    let mac = mac.as_ref();
+
+    // Dynamic truncation, RFC 4226 section 5.3
+    let offset = (mac[mac.len() - 1] & 0x0f) as usize;
+    let value = u32::from_be_bytes([
+        mac[offset] & 0x7f,
+        mac[offset + 1],
+        mac[offset + 2],
+        mac[offset + 3],
+    ]);
+    Ok(format!(
+        "{:0width$}",
+        value % 10u32.pow(DIGITS as u32),
+        width = DIGITS
+    ))
+}
+
+/// The step `code` belongs to, if it is valid around `unix_seconds`. Callers
+/// must refuse steps already used, so a code can't be replayed.
+pub fn verify(secret: &str, code: &str, unix_seconds: i64) -> Result<Option<i64>> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if code.len() != DIGITS || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return Ok(None);
+    }
+
+    let now = step_at(unix_seconds);
+    for step in now - DRIFT_STEPS..=now + DRIFT_STEPS {
+        if verify_slices_are_equal(code_at(secret, step)?.as_bytes(), code.as_bytes()).is_ok() {
+            return Ok(Some(step));
+        }
+    }
+    Ok(None)
+}
+
+/// Characters in a recovery code: 80 random bits, enough that the stored
+/// SHA-256 hashes can't be brute-forced back
+const RECOVERY_CODE_LEN: usize = 16;
+
+/// Single-use codes for when the authenticator is lost, like
+/// `k3vq-7xme-p2ad-w9cn`
+pub fn generate_recovery_codes(count: usize) -> Result<Vec<String>> {
+    (0..count)
+        .map(|_| {
+            let code = BASE32_NOPAD.encode(&random_bytes::<10>()?).to_lowercase();
+            let groups: Vec<&str> = (0..RECOVERY_CODE_LEN)
+                .step_by(4)
+                .map(|start| &code[start..start + 4])
+                .collect();
+            Ok(groups.join("-"))
+        })
+        .collect()
+}
+
+/// The form a recovery code is hashed in, so case and separators don't matter
+/// when typing it. `None` when it can't be one.
+pub fn normalize_recovery_code(code: &str) -> Option<String> {
+    let code = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    (code.len() == RECOVERY_CODE_LEN).then_some(code)
+}
@@ -0,0 +1,88 @@
+//! How fan-outs reach sockets held by other server instances.
+//!
+//! `ConnectionManager` always delivers to its own sockets first and then hands the
+//! same [`Fanout`] to its backend, which relays it to every other instance. Those
+//! instances feed it back in through [`ConnectionManager::deliver`].
+//!
+//! [`ConnectionManager::deliver`]: super::manager::ConnectionManager::deliver
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use super::{manager::RoomClose, protocol::ServerEvent};
+
+/// One call to `broadcast`, `close_room*`, `notify_user`, `broadcast_all` or
+/// `revoke_session`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Fanout {
+    Room {
+        mission_id: i32,
+        event: ServerEvent,
+    },
+    RoomClose {
+        mission_id: i32,
+        close: RoomClose,
+    },
+    User {
+        user_id: i32,
+        event: ServerEvent,
+    },
+    All {
+        event: ServerEvent,
+    },
+    SessionRevoked {
+        session_id: i32,
+    },
+    /// A user's first socket on `instance_id` opened, or its last one closed
+    Presence {
+        instance_id: String,
+        user_id: i32,
+        online: bool,
+    },
+    /// Everyone with a socket on `instance_id`. Sent periodically, and how a new
+    /// instance introduces itself.
+    PresenceSnapshot {
+        instance_id: String,
+        user_ids: Vec<i32>,
+    },
+}
+
+impl Fanout {
+    /// Typing indicators and read receipts: frequent, and fine to reach other
+    /// instances a moment late, so relays may hold them back to batch them
+    pub fn is_chatter(&self) -> bool {
+        match self {
+            Fanout::Room { event, .. } | Fanout::User { event, .. } => {
+                event.is_ephemeral() || matches!(event, ServerEvent::ReadUpTo(_))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Tells server instances apart, including two in one process
+pub fn new_instance_id() -> String {
+    let started = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    format!("{}-{:x}", std::process::id(), started)
+}
+
+#[async_trait]
+pub trait BroadcastBackend: Send + Sync {
+    /// Relay to the other instances. Must not deliver back to this one, whose
+    /// sockets have already been served.
+    async fn publish(&self, fanout: &Fanout) -> Result<()>;
+}
+
+/// Single-instance backend: local delivery is all there is, so nothing is relayed
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InMemoryBroadcast;
+
+#[async_trait]
+impl BroadcastBackend for InMemoryBroadcast {
+    async fn publish(&self, _fanout: &Fanout) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+//! Recent events per user and per mission room, so a socket that reconnects (or
+//! lags behind its channel) can pick up where it left off.
+//!
+//! Each stream numbers its events. Numbering starts at the log's creation time in
+//! microseconds, so it keeps increasing across restarts and evictions, and a `since`
+//! from a previous run or from another replica falls outside the log and gets a
+//! `resync_required` instead of the wrong events.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::time::Instant;
+
+use super::{
+    manager::ConnectionManager,
+    protocol::{ResyncRequired, ServerEvent, ServerFrame},
+};
+
+/// Events kept per stream. Larger than the live channel buffers, so a lagging
+/// receiver can usually catch up from here.
+pub const EVENT_LOG_CAPACITY: usize = 200;
+/// Logs without a socket are dropped after this long without events
+pub const EVENT_LOG_RETENTION: Duration = Duration::from_secs(15 * 60);
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreamKey {
+    User(i32),
+    Room(i32),
+}
+
+impl StreamKey {
+    pub fn room(&self) -> Option<i32> {
+        match self {
+            StreamKey::Room(mission_id) => Some(*mission_id),
+            StreamKey::User(_) => None,
+        }
+    }
+}
+
+/// What a resuming socket gets before live events
+#[derive(Debug, Clone, PartialEq)]
+pub enum Replay {
+    Events(Vec<ServerFrame>),
+    ResyncRequired(ResyncRequired),
+}
+
+struct StreamLog {
+    next_seq: u64,
+    events: VecDeque<ServerFrame>,
+    touched: Instant,
+}
+
+impl StreamLog {
+    fn new() -> Self {
+        let base = Utc::now().timestamp_micros().max(1) as u64;
+        Self {
+            next_seq: base,
+            events: VecDeque::with_capacity(EVENT_LOG_CAPACITY),
+            touched: Instant::now(),
+        }
+    }
+
+    fn latest_seq(&self) -> u64 {
+        self.next_seq - 1
+    }
+
+    fn append(&mut self, key: StreamKey, event: ServerEvent) -> ServerFrame {
+        let mut frame = ServerFrame::from(event);
+        frame.seq = Some(self.next_seq);
+        frame.room = key.room();
+        self.next_seq += 1;
+
+        if self.events.len() == EVENT_LOG_CAPACITY {
+            self.events.pop_front();
+        }
+        self.events.push_back(frame.clone());
+        self.touched = Instant::now();
+        frame
+    }
+
+    fn replay(&self, since: Option<u64>) -> Replay {
+        let latest_seq = self.latest_seq();
+        let oldest_seq = self
+            .events
+            .front()
+            .and_then(|frame| frame.seq)
+            .unwrap_or(self.next_seq);
+
+        match since {
+            // Nothing missed since `since`, or all of it is still here
+            Some(since) if since <= latest_seq && since.saturating_add(1) >= oldest_seq => {
+                Replay::Events(
+                    self.events
+                        .iter()
+                        .filter(|frame| frame.seq.is_some_and(|seq| seq > since))
+                        .cloned()
+                        .collect(),
+                )
+            }
+            _ => Replay::ResyncRequired(ResyncRequired { latest_seq }),
+        }
+    }
+}
+
+/// Every stream's log. Not synchronised itself; `ConnectionManager` keeps it behind
+/// a lock and holds that lock while sending, so live events go out in order.
+pub struct EventLogs {
+    streams: HashMap<StreamKey, StreamLog>,
+    last_sweep: Instant,
+}
+
+impl EventLogs {
+    pub fn new() -> Self {
+        Self {
+            streams: HashMap::new(),
+            last_sweep: Instant::now(),
+        }
+    }
+
+    /// Start logging a stream if it isn't already, e.g. when a socket subscribes
+    pub fn open(&mut self, key: StreamKey) {
+        self.streams
+            .entry(key)
+            .or_insert_with(StreamLog::new)
+            .touched = Instant::now();
+    }
+
+    pub fn is_open(&self, key: StreamKey) -> bool {
+        self.streams.contains_key(&key)
+    }
+
+    /// Number `event` and keep it. Events that aren't worth replaying are passed
+    /// through without a number, as are events for streams nobody has opened.
+    pub fn append(&mut self, key: StreamKey, event: ServerEvent) -> ServerFrame {
+        let mut frame = match self.streams.get_mut(&key) {
+            Some(log) if !event.is_ephemeral() => log.append(key, event),
+            _ => ServerFrame::from(event),
+        };
+        frame.room = key.room();
+        frame
+    }
+
+    /// User streams currently logged, for `broadcast_all`
+    pub fn users(&self) -> Vec<i32> {
+        self.streams
+            .keys()
+            .filter_map(|key| match key {
+                StreamKey::User(user_id) => Some(*user_id),
+                StreamKey::Room(_) => None,
+            })
+            .collect()
+    }
+
+    /// Events after `since`, or `resync_required` when some of them are gone (or
+    /// `since` is `None`, i.e. the socket never had a position)
+    pub fn replay(&self, key: StreamKey, since: Option<u64>) -> Replay {
+        match self.streams.get(&key) {
+            Some(log) => log.replay(since),
+            None => Replay::ResyncRequired(ResyncRequired { latest_seq: 0 }),
+        }
+    }
+
+    /// Drop logs that went quiet, skipping streams `is_live` still has sockets on.
+    /// Runs at most once per `SWEEP_INTERVAL`.
+    pub fn sweep(&mut self, is_live: impl Fn(StreamKey) -> bool) {
+        if self.last_sweep.elapsed() < SWEEP_INTERVAL {
+            return;
+        }
+        self.last_sweep = Instant::now();
+        self.streams
+            .retain(|key, log| is_live(*key) || log.touched.elapsed() < EVENT_LOG_RETENTION);
+    }
+
+    pub fn len(&self) -> usize {
+        self.streams.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.streams.is_empty()
+    }
+}
+
+/// How far one socket has got in one stream. A replay and the live channel can
+/// overlap, so anything at or below `last_seq` is skipped.
+pub struct Cursor {
+    key: StreamKey,
+    last_seq: Option<u64>,
+}
+
+impl Cursor {
+    pub fn new(key: StreamKey) -> Self {
+        Self {
+            key,
+            last_seq: None,
+        }
+    }
+
+    /// Whether `frame` should go out, moving the cursor past it if so
+    pub fn accept(&mut self, frame: &ServerFrame) -> bool {
+        match (frame.seq, self.last_seq) {
+            (Some(seq), Some(last)) if seq <= last => false,
+            (Some(seq), _) => {
+                self.last_seq = Some(seq);
+                true
+            }
+            (None, _) => true,
+        }
+    }
+
+    /// Logged events after `since`, or a `resync_required` once they are gone
+    pub async fn resume(&mut self, manager: &ConnectionManager, since: u64) -> Vec<ServerFrame> {
+        self.replay(manager, Some(since)).await
+    }
+
+    /// Everything after the last frame sent, for a receiver that lagged behind
+    pub async fn catch_up(&mut self, manager: &ConnectionManager) -> Vec<ServerFrame> {
+        self.replay(manager, self.last_seq).await
+    }
+
+    async fn replay(
+        &mut self,
+        manager: &ConnectionManager,
+        since: Option<u64>,
+    ) -> Vec<ServerFrame> {
+        match manager.replay(self.key, since).await {
+            Replay::Events(frames) => frames.into_iter().filter(|f| self.accept(f)).collect(),
+            Replay::ResyncRequired(resync) => {
+                self.last_seq = Some(resync.latest_seq);
+                let mut frame = ServerFrame::from(ServerEvent::ResyncRequired(resync));
+                frame.room = self.key.room();
+                vec![frame]
+            }
+        }
+    }
+}
+
+impl Default for EventLogs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,115 +1,570 @@
 use axum::{
-    Extension,
+    Extension, Json,
     extract::{
-        Path, State, WebSocketUpgrade,
-        ws::{Message, WebSocket},
+        Path, Query, State, WebSocketUpgrade,
+        ws::{CloseFrame, Message, WebSocket},
     },
-    response::IntoResponse,
+    response::{IntoResponse, Response},
 };
-use futures_util::{SinkExt, StreamExt};
-use serde::{Deserialize, Serialize};
+use futures_util::{Sink, SinkExt, StreamExt};
+use serde::Deserialize;
 use std::sync::Arc;
+use tokio::sync::{broadcast::error::RecvError, mpsc};
 
-use super::manager::ConnectionManager;
+use super::{
+    event_log::{Cursor, StreamKey},
+    heartbeat::{self, Liveness},
+    manager::{
+        ConnectionManager, ConnectionMetrics, RoomSubscription, SESSION_REVOKED_CLOSE_CODE,
+        SESSION_REVOKED_CLOSE_REASON, session_revoked,
+    },
+    protocol::{
+        ChatAck, ClientEvent, ClientFrame, ErrorPayload, PROTOCOL_VERSION, ReadUpTo, ServerEvent,
+        ServerFrame, Typing,
+    },
+    room_subscriptions::RoomSubscriptions,
+};
+use crate::{
+    application::use_cases::{
+        mission_comment::MissionCommentUseCase, mission_viewing::MissionViewingUseCase,
+        presence::PresenceUseCase,
+    },
+    domain::{
+        errors::AppError,
+        repositories::{
+            mission_comment::MissionCommentRepository, mission_viewing::MissionViewingRepository,
+            notifications::NotificationRepository,
+        },
+    },
+    infrastructure::http::{
+        middlewares::auth::CurrentSession, routers::mission_comment::push_new_comment,
+    },
+};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct WSMessage {
-    #[serde(rename = "type")]
-    pub msg_type: String,
-    pub data: serde_json::Value,
+pub struct MissionRoomState<T1, T2>
+where
+    T1: MissionViewingRepository + Send + Sync,
+    T2: MissionCommentRepository + Send + Sync,
+{
+    pub manager: Arc<ConnectionManager>,
+    pub use_case: MissionViewingUseCase<T1>,
+    pub comment_use_case: MissionCommentUseCase<T2, T1>,
+    pub notification_repo: Arc<dyn NotificationRepository>,
 }
 
-/// WebSocket handler for mission chat (Room-based)
-pub async fn ws_handler(
+/// Rooms one global socket may follow at once
+const MAX_ROOM_SUBSCRIPTIONS: usize = 20;
+
+/// `?since=` on either socket (and `/api/events`): the last `seq` the client saw before it dropped
+#[derive(Debug, Default, Deserialize)]
+pub struct ResumeQuery {
+    pub since: Option<u64>,
+}
+
+/// WebSocket handler for mission chat (Room-based). Only the chief and crew get in.
+pub async fn ws_handler<T1, T2>(
     ws: WebSocketUpgrade,
+    Extension(user_id): Extension<i32>,
+    Extension(CurrentSession(session_id)): Extension<CurrentSession>,
     Path(mission_id): Path<i32>,
-    State(manager): State<Arc<ConnectionManager>>,
-) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, mission_id, manager))
+    Query(resume): Query<ResumeQuery>,
+    State(state): State<Arc<MissionRoomState<T1, T2>>>,
+) -> Response
+where
+    T1: MissionViewingRepository + Send + Sync + 'static,
+    T2: MissionCommentRepository + Send + Sync + 'static,
+{
+    if let Err(e) = state.use_case.ensure_room_member(mission_id, user_id).await {
+        return e.into_response();
+    }
+
+    ws.on_upgrade(move |socket| {
+        handle_socket(socket, mission_id, user_id, session_id, resume.since, state)
+    })
+    .into_response()
 }
 
-async fn handle_socket(socket: WebSocket, mission_id: i32, manager: Arc<ConnectionManager>) {
-    let (mut sender, mut receiver) = socket.split();
-    let mut rx = manager.subscribe(mission_id).await;
+fn revoked_close() -> Message {
+    Message::Close(Some(CloseFrame {
+        code: SESSION_REVOKED_CLOSE_CODE,
+        reason: SESSION_REVOKED_CLOSE_REASON.into(),
+    }))
+}
 
-    let mut send_task = tokio::spawn(async move {
-        while let Ok(msg) = rx.recv().await {
-            let json_msg = serde_json::to_string(&msg).unwrap_or_default();
-            if sender.send(Message::Text(json_msg.into())).await.is_err() {
-                break;
+fn to_text(frame: ServerFrame) -> Message {
+    let json_msg = serde_json::to_string(&frame).unwrap_or_default();
+    Message::Text(json_msg.into())
+}
+
+/// Returns false once the socket is gone
+async fn send_frames<S>(sender: &mut S, frames: Vec<ServerFrame>) -> bool
+where
+    S: Sink<Message> + Unpin,
+{
+    for frame in frames {
+        if sender.send(to_text(frame)).await.is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+/// Error frame for a client request that could not be applied
+fn error_frame(request: &str, client_id: Option<String>, error: AppError) -> ServerFrame {
+    let message = match &error {
+        AppError::Internal(e) => {
+            tracing::error!("Internal error on WebSocket: {:?}", e);
+            "Internal server error".to_string()
+        }
+        other => other.to_string(),
+    };
+    ServerEvent::Error(ErrorPayload {
+        request: request.to_string(),
+        client_id,
+        code: error.code().to_string(),
+        message,
+    })
+    .into()
+}
+
+/// Error frame for `event`, keeping a chat message's `client_id`
+fn reject(event: &ClientEvent, error: AppError) -> ServerFrame {
+    let client_id = match event {
+        ClientEvent::ChatMessage(request) => request.client_id.clone(),
+        _ => None,
+    };
+    error_frame(event.name(), client_id, error)
+}
+
+/// Parses one text frame, replying with an error when it can't be used
+fn parse_client_frame(
+    text: &str,
+    reply: &mpsc::UnboundedSender<ServerFrame>,
+) -> Option<ClientEvent> {
+    match serde_json::from_str::<ClientFrame>(text) {
+        Ok(frame) if frame.version != PROTOCOL_VERSION => {
+            let error = AppError::Validation(format!(
+                "Unsupported protocol version {}, expected {}",
+                frame.version, PROTOCOL_VERSION
+            ));
+            let _ = reply.send(reject(&frame.event, error));
+            None
+        }
+        Ok(frame) => Some(frame.event),
+        Err(e) => {
+            let error = AppError::Validation(format!("Invalid message: {}", e));
+            let _ = reply.send(error_frame("unknown", None, error));
+            None
+        }
+    }
+}
+
+/// Applies one client request. Replies meant only for this socket go to `reply`;
+/// everything else fans out through the room.
+async fn handle_client_event<T1, T2>(
+    state: &MissionRoomState<T1, T2>,
+    mission_id: i32,
+    user_id: i32,
+    event: ClientEvent,
+    reply: &mpsc::UnboundedSender<ServerFrame>,
+) where
+    T1: MissionViewingRepository + Send + Sync,
+    T2: MissionCommentRepository + Send + Sync,
+{
+    match event {
+        ClientEvent::ChatMessage(request) => {
+            // Same rules as POST /comment/{mission_id}, so a kicked brawler whose
+            // close frame is still in flight can't slip a message in
+            match state
+                .comment_use_case
+                .add_comment(mission_id, user_id, &request.content)
+                .await
+            {
+                Ok(comment) => {
+                    let ack = ServerEvent::ChatAck(ChatAck {
+                        client_id: request.client_id,
+                        comment_id: comment.id,
+                        created_at: comment.created_at,
+                    });
+                    let _ = reply.send(ack.into());
+                    push_new_comment(
+                        &state.manager,
+                        state.notification_repo.as_ref(),
+                        state.comment_use_case.mission_viewing_repository.as_ref(),
+                        &comment,
+                    )
+                    .await;
+                }
+                Err(e) => {
+                    let _ = reply.send(error_frame("chat_message", request.client_id, e));
+                }
             }
         }
-    });
+        // Ephemeral, nothing is stored
+        ClientEvent::TypingStart(_) => {
+            let typing = Typing {
+                mission_id,
+                brawler_id: user_id,
+            };
+            state
+                .manager
+                .broadcast(mission_id, ServerEvent::TypingStart(typing))
+                .await;
+        }
+        ClientEvent::TypingStop(_) => {
+            let typing = Typing {
+                mission_id,
+                brawler_id: user_id,
+            };
+            state
+                .manager
+                .broadcast(mission_id, ServerEvent::TypingStop(typing))
+                .await;
+        }
+        ClientEvent::ReadUpTo(request) => {
+            state
+                .manager
+                .broadcast(
+                    mission_id,
+                    ServerEvent::ReadUpTo(ReadUpTo {
+                        mission_id,
+                        brawler_id: user_id,
+                        comment_id: request.comment_id,
+                    }),
+                )
+                .await;
+        }
+        ClientEvent::Subscribe(_) | ClientEvent::Unsubscribe(_) => {
+            let error = AppError::Validation(
+                "Room subscriptions are only taken on the global socket".to_string(),
+            );
+            let _ = reply.send(reject(&event, error));
+        }
+    }
+}
+
+async fn handle_socket<T1, T2>(
+    socket: WebSocket,
+    mission_id: i32,
+    user_id: i32,
+    session_id: i32,
+    since: Option<u64>,
+    state: Arc<MissionRoomState<T1, T2>>,
+) where
+    T1: MissionViewingRepository + Send + Sync,
+    T2: MissionCommentRepository + Send + Sync,
+{
+    let (mut sender, mut receiver) = socket.split();
+    let RoomSubscription {
+        mut messages,
+        mut closes,
+    } = state.manager.subscribe(mission_id).await;
+    let mut revocations = state.manager.session_revocations();
+    let (reply_tx, mut replies) = mpsc::unbounded_channel::<ServerFrame>();
+    let heartbeat = state.manager.heartbeat();
+    let liveness = &Liveness::new();
+    state.manager.connection_opened();
+
+    let manager = &state.manager;
+    let send = async move {
+        // Subscribed first, so nothing falls between the replay and live events
+        let mut cursor = Cursor::new(StreamKey::Room(mission_id));
+        if let Some(since) = since
+            && !send_frames(&mut sender, cursor.resume(manager, since).await).await
+        {
+            return;
+        }
 
-    let manager_clone = manager.clone();
-    let mut recv_task = tokio::spawn(async move {
-        while let Some(msg) = receiver.next().await {
-            if let Ok(Message::Text(text)) = msg {
-                if let Ok(ws_msg) = serde_json::from_str::<WSMessage>(&text) {
-                    match ws_msg.msg_type.as_str() {
-                        "ping" => {}
-                        _ => {}
+        let mut pings = heartbeat::ping_interval(heartbeat);
+        loop {
+            tokio::select! {
+                // Flush queued room messages (e.g. mission_deleted) before a close
+                biased;
+                _ = pings.tick() => {
+                    if sender.send(heartbeat::ping()).await.is_err() {
+                        break;
+                    }
+                }
+                reply = replies.recv() => {
+                    let Some(reply) = reply else { break };
+                    if sender.send(to_text(reply)).await.is_err() {
+                        break;
                     }
                 }
+                msg = messages.recv() => {
+                    let frames = match msg {
+                        Ok(frame) if cursor.accept(&frame) => vec![frame],
+                        Ok(_) => continue,
+                        Err(RecvError::Lagged(_)) => cursor.catch_up(manager).await,
+                        Err(RecvError::Closed) => break,
+                    };
+                    if !send_frames(&mut sender, frames).await {
+                        break;
+                    }
+                }
+                close = closes.recv() => {
+                    let Ok(close) = close else { break };
+                    if close.applies_to(user_id) {
+                        let _ = sender
+                            .send(Message::Close(Some(CloseFrame {
+                                code: close.reason.code(),
+                                reason: close.reason.reason().into(),
+                            })))
+                            .await;
+                        break;
+                    }
+                }
+                _ = session_revoked(&mut revocations, session_id) => {
+                    let _ = sender.send(revoked_close()).await;
+                    break;
+                }
             }
         }
-        manager_clone.unsubscribe(mission_id).await;
-    });
+    };
+
+    let recv_state = Arc::clone(&state);
+    let recv = async move {
+        while let Some(Ok(msg)) = receiver.next().await {
+            liveness.touch();
+            let Message::Text(text) = msg else { continue };
+            let Some(event) = parse_client_frame(&text, &reply_tx) else {
+                continue;
+            };
+            match event.mission_id() {
+                Some(other) if other != mission_id => {
+                    let error = AppError::Validation(format!(
+                        "This socket is for mission {}, not {}",
+                        mission_id, other
+                    ));
+                    let _ = reply_tx.send(reject(&event, error));
+                }
+                _ => handle_client_event(&recv_state, mission_id, user_id, event, &reply_tx).await,
+            }
+        }
+    };
 
-    tokio::select! {
-        _ = (&mut send_task) => recv_task.abort(),
-        _ = (&mut recv_task) => send_task.abort(),
+    // Both halves are dropped once any branch finishes, so the receivers are gone
+    // by the time we check whether the room channel can be removed
+    let reaped = tokio::select! {
+        _ = send => false,
+        _ = recv => false,
+        _ = heartbeat::expired(liveness, heartbeat) => true,
     };
+    if reaped {
+        tracing::info!(
+            "Reaped mission {} room socket of user {} after missed heartbeats",
+            mission_id,
+            user_id
+        );
+    }
+    state.manager.unsubscribe(mission_id).await;
+    state.manager.connection_closed(reaped);
 }
 
-/// WebSocket handler for global notifications (User-based)
-pub async fn global_ws_handler(
+pub struct GlobalSocketState<T1, T2>
+where
+    T1: MissionViewingRepository + Send + Sync,
+    T2: MissionCommentRepository + Send + Sync,
+{
+    pub manager: Arc<ConnectionManager>,
+    pub presence: PresenceUseCase,
+    /// Shared with the room socket, for `subscribe` and room requests
+    pub rooms: Arc<MissionRoomState<T1, T2>>,
+}
+
+/// WebSocket handler for global notifications (User-based). Mission rooms can be
+/// followed over it too, with `subscribe`/`unsubscribe`.
+pub async fn global_ws_handler<T1, T2>(
     ws: WebSocketUpgrade,
     Extension(user_id): Extension<i32>,
-    State(manager): State<Arc<ConnectionManager>>,
-) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_global_socket(socket, user_id, manager))
+    Extension(CurrentSession(session_id)): Extension<CurrentSession>,
+    Query(resume): Query<ResumeQuery>,
+    State(state): State<Arc<GlobalSocketState<T1, T2>>>,
+) -> impl IntoResponse
+where
+    T1: MissionViewingRepository + Send + Sync + 'static,
+    T2: MissionCommentRepository + Send + Sync + 'static,
+{
+    ws.on_upgrade(move |socket| {
+        handle_global_socket(socket, user_id, session_id, resume.since, state)
+    })
+}
+
+/// Room subscriptions are handled here; room requests go through the same path as
+/// on the room socket, once the room is subscribed.
+async fn handle_global_client_event<T1, T2>(
+    state: &GlobalSocketState<T1, T2>,
+    user_id: i32,
+    event: ClientEvent,
+    subscriptions: &mut RoomSubscriptions,
+    reply: &mpsc::UnboundedSender<ServerFrame>,
+) where
+    T1: MissionViewingRepository + Send + Sync,
+    T2: MissionCommentRepository + Send + Sync,
+{
+    match event {
+        ClientEvent::Subscribe(ref request) => {
+            let mission_id = request.mission_id;
+            if !subscriptions.is_subscribed(mission_id)
+                && subscriptions.len() >= MAX_ROOM_SUBSCRIPTIONS
+            {
+                let error = AppError::Validation(format!(
+                    "At most {} rooms can be followed at once",
+                    MAX_ROOM_SUBSCRIPTIONS
+                ));
+                let _ = reply.send(reject(&event, error));
+                return;
+            }
+
+            match state
+                .rooms
+                .use_case
+                .ensure_room_member(mission_id, user_id)
+                .await
+            {
+                Ok(_) => subscriptions.subscribe(mission_id, request.since).await,
+                Err(e) => {
+                    let _ = reply.send(reject(&event, e));
+                }
+            }
+        }
+        ClientEvent::Unsubscribe(ref target) => {
+            if !subscriptions.unsubscribe(target.mission_id).await {
+                let error =
+                    AppError::NotFound(format!("Not subscribed to mission {}", target.mission_id));
+                let _ = reply.send(reject(&event, error));
+            }
+        }
+        event => match event.mission_id() {
+            Some(mission_id) if subscriptions.is_subscribed(mission_id) => {
+                handle_client_event(&state.rooms, mission_id, user_id, event, reply).await
+            }
+            Some(mission_id) => {
+                let error = AppError::Forbidden(format!(
+                    "Subscribe to mission {} before sending to it",
+                    mission_id
+                ));
+                let _ = reply.send(reject(&event, error));
+            }
+            None => {
+                let error =
+                    AppError::Validation("mission_id is required on the global socket".to_string());
+                let _ = reply.send(reject(&event, error));
+            }
+        },
+    }
 }
 
-async fn handle_global_socket(socket: WebSocket, user_id: i32, manager: Arc<ConnectionManager>) {
+async fn handle_global_socket<T1, T2>(
+    socket: WebSocket,
+    user_id: i32,
+    session_id: i32,
+    since: Option<u64>,
+    state: Arc<GlobalSocketState<T1, T2>>,
+) where
+    T1: MissionViewingRepository + Send + Sync + 'static,
+    T2: MissionCommentRepository + Send + Sync + 'static,
+{
+    let manager = &state.manager;
     let (mut sender, mut receiver) = socket.split();
     let mut rx = manager.subscribe_user(user_id).await;
+    let mut revocations = manager.session_revocations();
+    // Room events, replies and subscription changes, all bound for this socket
+    let (out_tx, mut out) = mpsc::unbounded_channel::<ServerFrame>();
+    let mut subscriptions = RoomSubscriptions::new(Arc::clone(manager), user_id, out_tx.clone());
+    let heartbeat = manager.heartbeat();
+    let liveness = &Liveness::new();
+    manager.connection_opened();
 
-    // Broadcast online status
-    manager
-        .broadcast_all(WSMessage {
-            msg_type: "agent_online".to_string(),
-            data: serde_json::json!({ "user_id": user_id }),
-        })
-        .await;
-
-    let mut send_task = tokio::spawn(async move {
-        while let Ok(msg) = rx.recv().await {
-            let json_msg = serde_json::to_string(&msg).unwrap_or_default();
-            if sender.send(Message::Text(json_msg.into())).await.is_err() {
-                break;
-            }
+    // Friends only hear about it if this is the first tab/device
+    if let Err(e) = state.presence.session_started(user_id).await {
+        tracing::error!("Failed to record presence of user {}: {}", user_id, e);
+    }
+
+    let send = async move {
+        let mut cursor = Cursor::new(StreamKey::User(user_id));
+        if let Some(since) = since
+            && !send_frames(&mut sender, cursor.resume(manager, since).await).await
+        {
+            return;
         }
-    });
 
-    let manager_clone = manager.clone();
-    let mut recv_task = tokio::spawn(async move {
-        while let Some(_) = receiver.next().await {
-            // Keep connection alive, can handle client-to-server global msgs here
+        let mut pings = heartbeat::ping_interval(heartbeat);
+        loop {
+            tokio::select! {
+                _ = pings.tick() => {
+                    if sender.send(heartbeat::ping()).await.is_err() {
+                        break;
+                    }
+                }
+                frame = out.recv() => {
+                    let Some(frame) = frame else { break };
+                    if sender.send(to_text(frame)).await.is_err() {
+                        break;
+                    }
+                }
+                msg = rx.recv() => {
+                    let frames = match msg {
+                        Ok(frame) if cursor.accept(&frame) => vec![frame],
+                        Ok(_) => continue,
+                        Err(RecvError::Lagged(_)) => cursor.catch_up(manager).await,
+                        Err(RecvError::Closed) => break,
+                    };
+                    if !send_frames(&mut sender, frames).await {
+                        break;
+                    }
+                }
+                _ = session_revoked(&mut revocations, session_id) => {
+                    let _ = sender.send(revoked_close()).await;
+                    break;
+                }
+            }
         }
-        manager_clone.unsubscribe_user(user_id).await;
+    };
 
-        // Broadcast offline status
-        manager_clone
-            .broadcast_all(WSMessage {
-                msg_type: "agent_offline".to_string(),
-                data: serde_json::json!({ "user_id": user_id }),
-            })
-            .await;
-    });
+    let recv_state = Arc::clone(&state);
+    let recv_subscriptions = &mut subscriptions;
+    let recv = async move {
+        // Anything from the client counts as a sign of life
+        while let Some(Ok(msg)) = receiver.next().await {
+            liveness.touch();
+            let Message::Text(text) = msg else { continue };
+            if let Some(event) = parse_client_frame(&text, &out_tx) {
+                handle_global_client_event(
+                    &recv_state,
+                    user_id,
+                    event,
+                    recv_subscriptions,
+                    &out_tx,
+                )
+                .await;
+            }
+        }
+    };
 
-    tokio::select! {
-        _ = (&mut send_task) => recv_task.abort(),
-        _ = (&mut recv_task) => send_task.abort(),
+    // Cleanup runs after every exit path, so a dead socket can't leave the user online
+    let reaped = tokio::select! {
+        _ = send => false,
+        _ = recv => false,
+        _ = heartbeat::expired(liveness, heartbeat) => true,
     };
+    if reaped {
+        tracing::info!(
+            "Reaped global socket of user {} after missed heartbeats",
+            user_id
+        );
+    }
+    subscriptions.clear().await;
+    manager.unsubscribe_user(user_id).await;
+    manager.connection_closed(reaped);
+
+    // ...and only if this was the last one
+    if let Err(e) = state.presence.session_ended(user_id).await {
+        tracing::error!("Failed to record presence of user {}: {}", user_id, e);
+    }
+}
+
+/// Connection counters for monitoring
+pub async fn ws_metrics(State(manager): State<Arc<ConnectionManager>>) -> Json<ConnectionMetrics> {
+    Json(manager.metrics().await)
 }
@@ -0,0 +1,85 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use axum::extract::ws::Message;
+use tokio::time::{Instant, Interval, MissedTickBehavior, interval_at};
+
+#[derive(Debug, Clone, Copy)]
+pub struct HeartbeatConfig {
+    /// How often the server pings each socket
+    pub interval: Duration,
+    /// How long a socket may go without sending anything (pongs included)
+    pub timeout: Duration,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(30),
+            timeout: Duration::from_secs(90),
+        }
+    }
+}
+
+/// When a socket was last heard from. Shared between the send and receive halves
+/// of one connection.
+pub struct Liveness {
+    started: Instant,
+    last_seen_ms: AtomicU64,
+}
+
+impl Liveness {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            last_seen_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Any frame from the client counts, not only pongs
+    pub fn touch(&self) {
+        let elapsed = self.started.elapsed().as_millis() as u64;
+        self.last_seen_ms.store(elapsed, Ordering::Relaxed);
+    }
+
+    pub fn is_expired(&self, timeout: Duration) -> bool {
+        let last_seen = Duration::from_millis(self.last_seen_ms.load(Ordering::Relaxed));
+        self.started.elapsed().saturating_sub(last_seen) > timeout
+    }
+}
+
+impl Default for Liveness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn ping() -> Message {
+    Message::Ping(Vec::new().into())
+}
+
+/// Ticks every `interval`, starting one interval from now
+pub fn ping_interval(config: HeartbeatConfig) -> Interval {
+    let mut ticker = interval_at(
+        tokio::time::Instant::now() + config.interval,
+        config.interval,
+    );
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker
+}
+
+/// Resolves once the socket has been silent for longer than the timeout. Checked on
+/// the ping cadence, so a dead socket goes within `timeout + interval`. Runs beside
+/// the send half rather than inside it, because a send to a half-open peer can
+/// block once the TCP buffer fills up.
+pub async fn expired(liveness: &Liveness, config: HeartbeatConfig) {
+    let mut ticker = ping_interval(config);
+    loop {
+        ticker.tick().await;
+        if liveness.is_expired(config.timeout) {
+            return;
+        }
+    }
+}
@@ -1,63 +1,272 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::{RwLock, broadcast};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::{
+    sync::{
+        RwLock,
+        broadcast::{self, error::RecvError},
+    },
+    time::Instant,
+};
+use tracing::warn;
 
-use super::handler::WSMessage;
+use super::{
+    broadcast::{BroadcastBackend, Fanout, InMemoryBroadcast, new_instance_id},
+    event_log::{EventLogs, Replay, StreamKey},
+    heartbeat::HeartbeatConfig,
+    protocol::{ServerEvent, ServerFrame},
+};
+
+/// Why the server is ending a mission room connection. Sent to the client as an
+/// application close code (4000-4999).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoomCloseReason {
+    LeftMission,
+    Kicked,
+    MissionDeleted,
+}
+
+impl RoomCloseReason {
+    pub fn code(&self) -> u16 {
+        match self {
+            RoomCloseReason::LeftMission => 4000,
+            RoomCloseReason::Kicked => 4003,
+            RoomCloseReason::MissionDeleted => 4004,
+        }
+    }
+
+    pub fn reason(&self) -> &'static str {
+        match self {
+            RoomCloseReason::LeftMission => "left_mission",
+            RoomCloseReason::Kicked => "kicked",
+            RoomCloseReason::MissionDeleted => "mission_deleted",
+        }
+    }
+}
+
+/// How often an instance tells the others who is online on it
+pub const PRESENCE_ANNOUNCE_INTERVAL: Duration = Duration::from_secs(30);
+/// An instance not heard from for this long is taken to be gone, with its users
+const PRESENCE_TTL: Duration = Duration::from_secs(90);
+
+/// Close code for sockets whose login session was logged out or revoked
+pub const SESSION_REVOKED_CLOSE_CODE: u16 = 4401;
+pub const SESSION_REVOKED_CLOSE_REASON: &str = "session_revoked";
+
+/// Resolves once `session_id` comes through `revocations`. Ids lost to lag are
+/// skipped; the revoked token is refused on its next API call anyway.
+pub async fn session_revoked(revocations: &mut broadcast::Receiver<i32>, session_id: i32) {
+    loop {
+        match revocations.recv().await {
+            Ok(id) if id != session_id => continue,
+            Err(RecvError::Lagged(_)) => continue,
+            Ok(_) | Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+/// Close request for a mission room; `user_id: None` closes everyone in it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoomClose {
+    pub user_id: Option<i32>,
+    pub reason: RoomCloseReason,
+}
+
+impl RoomClose {
+    pub fn applies_to(&self, user_id: i32) -> bool {
+        self.user_id.is_none_or(|id| id == user_id)
+    }
+}
+
+struct MissionRoom {
+    messages: broadcast::Sender<ServerFrame>,
+    closes: broadcast::Sender<RoomClose>,
+}
+
+/// Receivers handed to one mission room connection
+pub struct RoomSubscription {
+    pub messages: broadcast::Receiver<ServerFrame>,
+    pub closes: broadcast::Receiver<RoomClose>,
+}
+
+/// Users online on another instance, as it last announced them
+struct InstancePresence {
+    user_ids: HashSet<i32>,
+    seen_at: Instant,
+}
+
+impl InstancePresence {
+    fn is_fresh(&self) -> bool {
+        self.seen_at.elapsed() < PRESENCE_TTL
+    }
+}
+
+#[derive(Default)]
+struct ConnectionCounters {
+    opened: AtomicU64,
+    closed: AtomicU64,
+    reaped: AtomicU64,
+}
+
+/// Point-in-time view of the socket counters, served at `/api/ws/metrics`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ConnectionMetrics {
+    pub active_connections: u64,
+    pub opened_total: u64,
+    pub closed_total: u64,
+    /// Closed by the server because the heartbeat timed out
+    pub reaped_total: u64,
+    pub rooms: usize,
+    pub online_users: usize,
+}
 
 /// Manages WebSocket connections and broadcasts for each mission and user
 #[derive(Clone)]
 pub struct ConnectionManager {
-    /// Map of mission_id -> broadcast channel
-    channels: Arc<RwLock<HashMap<i32, broadcast::Sender<WSMessage>>>>,
+    /// Map of mission_id -> room channels
+    channels: Arc<RwLock<HashMap<i32, MissionRoom>>>,
     /// Map of user_id -> broadcast channel (for global notifications)
-    user_channels: Arc<RwLock<HashMap<i32, broadcast::Sender<WSMessage>>>>,
+    user_channels: Arc<RwLock<HashMap<i32, broadcast::Sender<ServerFrame>>>>,
+    /// Recent numbered events per user and room, for `?since=` replays
+    logs: Arc<RwLock<EventLogs>>,
+    /// Map of user_id -> open global sockets (tabs/devices) on this instance
+    sessions: Arc<RwLock<HashMap<i32, usize>>>,
+    /// Map of instance id -> users online there, for presence across replicas
+    remote_presence: Arc<RwLock<HashMap<String, InstancePresence>>>,
+    instance_id: String,
+    heartbeat: HeartbeatConfig,
+    counters: Arc<ConnectionCounters>,
+    /// Ids of login sessions that were just revoked; their sockets close
+    revoked_sessions: broadcast::Sender<i32>,
+    /// Relays fan-outs to the other server instances
+    backend: Arc<dyn BroadcastBackend>,
 }
 
 impl ConnectionManager {
     pub fn new() -> Self {
+        Self::with_heartbeat(HeartbeatConfig::default())
+    }
+
+    pub fn with_heartbeat(heartbeat: HeartbeatConfig) -> Self {
+        Self::with_backend(heartbeat, Arc::new(InMemoryBroadcast))
+    }
+
+    pub fn with_backend(heartbeat: HeartbeatConfig, backend: Arc<dyn BroadcastBackend>) -> Self {
         Self {
             channels: Arc::new(RwLock::new(HashMap::new())),
             user_channels: Arc::new(RwLock::new(HashMap::new())),
+            logs: Arc::new(RwLock::new(EventLogs::new())),
+            sessions: Arc::new(RwLock::new(HashMap::new())),
+            remote_presence: Arc::new(RwLock::new(HashMap::new())),
+            instance_id: new_instance_id(),
+            heartbeat,
+            counters: Arc::new(ConnectionCounters::default()),
+            revoked_sessions: broadcast::channel(64).0,
+            backend,
+        }
+    }
+
+    pub fn heartbeat(&self) -> HeartbeatConfig {
+        self.heartbeat
+    }
+
+    pub fn connection_opened(&self) {
+        self.counters.opened.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// `reaped` when the server dropped the socket for missing heartbeats
+    pub fn connection_closed(&self, reaped: bool) {
+        self.counters.closed.fetch_add(1, Ordering::Relaxed);
+        if reaped {
+            self.counters.reaped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub async fn metrics(&self) -> ConnectionMetrics {
+        let opened_total = self.counters.opened.load(Ordering::Relaxed);
+        let closed_total = self.counters.closed.load(Ordering::Relaxed);
+        ConnectionMetrics {
+            active_connections: opened_total.saturating_sub(closed_total),
+            opened_total,
+            closed_total,
+            reaped_total: self.counters.reaped.load(Ordering::Relaxed),
+            rooms: self.channels.read().await.len(),
+            online_users: self.sessions.read().await.len(),
         }
     }
 
     /// Subscribe to a mission's broadcast channel
-    pub async fn subscribe(&self, mission_id: i32) -> broadcast::Receiver<WSMessage> {
+    pub async fn subscribe(&self, mission_id: i32) -> RoomSubscription {
         let mut channels = self.channels.write().await;
 
-        let sender = channels
-            .entry(mission_id)
-            .or_insert_with(|| {
-                let (tx, _rx) = broadcast::channel(100);
-                tx
-            })
-            .clone();
+        let room = channels.entry(mission_id).or_insert_with(|| MissionRoom {
+            messages: broadcast::channel(100).0,
+            closes: broadcast::channel(16).0,
+        });
 
-        sender.subscribe()
+        let subscription = RoomSubscription {
+            messages: room.messages.subscribe(),
+            closes: room.closes.subscribe(),
+        };
+        drop(channels);
+
+        self.open_log(StreamKey::Room(mission_id)).await;
+        subscription
     }
 
     /// Unsubscribe from a mission
     pub async fn unsubscribe(&self, mission_id: i32) {
         let mut channels = self.channels.write().await;
 
-        if let Some(sender) = channels.get(&mission_id) {
-            if sender.receiver_count() == 0 {
-                channels.remove(&mission_id);
-            }
+        if let Some(room) = channels.get(&mission_id)
+            && room.messages.receiver_count() == 0
+        {
+            channels.remove(&mission_id);
         }
     }
 
     /// Broadcast to all subscribers of a mission
-    pub async fn broadcast(&self, mission_id: i32, message: WSMessage) {
-        let channels = self.channels.read().await;
+    pub async fn broadcast(&self, mission_id: i32, message: ServerEvent) {
+        self.fan_out(Fanout::Room {
+            mission_id,
+            event: message,
+        })
+        .await;
+    }
 
-        if let Some(sender) = channels.get(&mission_id) {
-            let _ = sender.send(message);
-        }
+    /// Close one brawler's connections to a mission room
+    pub async fn close_room_member(&self, mission_id: i32, user_id: i32, reason: RoomCloseReason) {
+        self.send_room_close(
+            mission_id,
+            RoomClose {
+                user_id: Some(user_id),
+                reason,
+            },
+        )
+        .await;
+    }
+
+    /// Close every connection to a mission room
+    pub async fn close_room(&self, mission_id: i32, reason: RoomCloseReason) {
+        self.send_room_close(
+            mission_id,
+            RoomClose {
+                user_id: None,
+                reason,
+            },
+        )
+        .await;
+    }
+
+    async fn send_room_close(&self, mission_id: i32, close: RoomClose) {
+        self.fan_out(Fanout::RoomClose { mission_id, close }).await;
     }
 
     /// Subscribe to a user's global notification channel
-    pub async fn subscribe_user(&self, user_id: i32) -> broadcast::Receiver<WSMessage> {
+    pub async fn subscribe_user(&self, user_id: i32) -> broadcast::Receiver<ServerFrame> {
         let mut user_channels = self.user_channels.write().await;
 
         let sender = user_channels
@@ -67,42 +276,272 @@ impl ConnectionManager {
                 tx
             })
             .clone();
+        drop(user_channels);
 
+        self.open_log(StreamKey::User(user_id)).await;
         sender.subscribe()
     }
 
+    /// Keep a log for a stream that just got a socket, and drop logs nobody has
+    /// come back for
+    async fn open_log(&self, key: StreamKey) {
+        let mut logs = self.logs.write().await;
+        logs.open(key);
+
+        let channels = self.channels.read().await;
+        let user_channels = self.user_channels.read().await;
+        logs.sweep(|key| match key {
+            StreamKey::Room(mission_id) => channels.contains_key(&mission_id),
+            StreamKey::User(user_id) => user_channels.contains_key(&user_id),
+        });
+    }
+
+    /// Events of a stream after `since`, for a socket that is resuming or fell
+    /// behind its channel
+    pub async fn replay(&self, key: StreamKey, since: Option<u64>) -> Replay {
+        self.logs.read().await.replay(key, since)
+    }
+
     /// Unsubscribe from a user's global notification channel
     pub async fn unsubscribe_user(&self, user_id: i32) {
         let mut user_channels = self.user_channels.write().await;
 
-        if let Some(sender) = user_channels.get(&user_id) {
-            if sender.receiver_count() == 0 {
-                user_channels.remove(&user_id);
-            }
+        if let Some(sender) = user_channels.get(&user_id)
+            && sender.receiver_count() == 0
+        {
+            user_channels.remove(&user_id);
         }
     }
 
     /// Notify a specific user
-    pub async fn notify_user(&self, user_id: i32, message: WSMessage) {
-        let user_channels = self.user_channels.read().await;
+    pub async fn notify_user(&self, user_id: i32, message: ServerEvent) {
+        self.fan_out(Fanout::User {
+            user_id,
+            event: message,
+        })
+        .await;
+    }
+
+    /// Broadcast to EVERY user's global notification channel
+    pub async fn broadcast_all(&self, message: ServerEvent) {
+        self.fan_out(Fanout::All { event: message }).await;
+    }
+
+    /// Close every socket and event stream opened with the login session's tokens
+    pub async fn revoke_session(&self, session_id: i32) {
+        self.fan_out(Fanout::SessionRevoked { session_id }).await;
+    }
+
+    /// Revoked session ids, for sockets to compare against their own
+    pub fn session_revocations(&self) -> broadcast::Receiver<i32> {
+        self.revoked_sessions.subscribe()
+    }
+
+    /// Serve local sockets, then relay to the other instances. A relay failure
+    /// only costs remote delivery, so it is logged rather than returned.
+    async fn fan_out(&self, fanout: Fanout) {
+        self.deliver(&fanout).await;
+        self.relay(&fanout).await;
+    }
 
-        if let Some(sender) = user_channels.get(&user_id) {
-            let _ = sender.send(message);
+    async fn relay(&self, fanout: &Fanout) {
+        if let Err(e) = self.backend.publish(fanout).await {
+            warn!("Failed to relay fan-out to other instances: {}", e);
         }
     }
 
-    /// Broadcast to EVERY user's global notification channel
-    pub async fn broadcast_all(&self, message: WSMessage) {
-        let user_channels = self.user_channels.read().await;
+    /// Deliver to the sockets held by this instance only. Backends call this for
+    /// fan-outs published elsewhere.
+    ///
+    /// Events are numbered and logged first. The log lock is held until they are
+    /// sent, so every socket sees a stream in `seq` order.
+    pub async fn deliver(&self, fanout: &Fanout) {
+        match fanout {
+            Fanout::Room { mission_id, event } => {
+                let mut logs = self.logs.write().await;
+                let frame = logs.append(StreamKey::Room(*mission_id), event.clone());
+                if let Some(room) = self.channels.read().await.get(mission_id) {
+                    let _ = room.messages.send(frame);
+                }
+            }
+            Fanout::RoomClose { mission_id, close } => {
+                if let Some(room) = self.channels.read().await.get(mission_id) {
+                    let _ = room.closes.send(close.clone());
+                }
+            }
+            Fanout::User { user_id, event } => {
+                let mut logs = self.logs.write().await;
+                let frame = logs.append(StreamKey::User(*user_id), event.clone());
+                if let Some(sender) = self.user_channels.read().await.get(user_id) {
+                    let _ = sender.send(frame);
+                }
+            }
+            Fanout::All { event } => {
+                let mut logs = self.logs.write().await;
+                let user_channels = self.user_channels.read().await;
+                // Users between sockets still need it in their log
+                let mut user_ids = logs.users().into_iter().collect::<HashSet<_>>();
+                user_ids.extend(user_channels.keys());
 
-        for sender in user_channels.values() {
-            let _ = sender.send(message.clone());
+                for user_id in user_ids {
+                    let frame = logs.append(StreamKey::User(user_id), event.clone());
+                    if let Some(sender) = user_channels.get(&user_id) {
+                        let _ = sender.send(frame);
+                    }
+                }
+            }
+            Fanout::SessionRevoked { session_id } => {
+                let _ = self.revoked_sessions.send(*session_id);
+            }
+            Fanout::Presence {
+                instance_id,
+                user_id,
+                online,
+            } => {
+                if *instance_id == self.instance_id {
+                    return;
+                }
+                let is_new = {
+                    let mut remote = self.remote_presence.write().await;
+                    let is_new = !remote.contains_key(instance_id);
+                    let instance =
+                        remote
+                            .entry(instance_id.clone())
+                            .or_insert_with(|| InstancePresence {
+                                user_ids: HashSet::new(),
+                                seen_at: Instant::now(),
+                            });
+                    instance.seen_at = Instant::now();
+                    if *online {
+                        instance.user_ids.insert(*user_id);
+                    } else {
+                        instance.user_ids.remove(user_id);
+                    }
+                    is_new
+                };
+                // It hasn't heard about this instance's users yet
+                if is_new {
+                    self.announce_presence().await;
+                }
+            }
+            Fanout::PresenceSnapshot {
+                instance_id,
+                user_ids,
+            } => {
+                if *instance_id == self.instance_id {
+                    return;
+                }
+                let previous = self.remote_presence.write().await.insert(
+                    instance_id.clone(),
+                    InstancePresence {
+                        user_ids: user_ids.iter().copied().collect(),
+                        seen_at: Instant::now(),
+                    },
+                );
+                if previous.is_none() {
+                    self.announce_presence().await;
+                }
+            }
         }
     }
 
+    /// Tell the other instances everyone online on this one, and forget the
+    /// instances that have gone quiet. Backends that relay call this every
+    /// `PRESENCE_ANNOUNCE_INTERVAL`.
+    pub async fn announce_presence(&self) {
+        self.remote_presence
+            .write()
+            .await
+            .retain(|_, instance| instance.is_fresh());
+        let user_ids = self.sessions.read().await.keys().copied().collect();
+        self.relay(&Fanout::PresenceSnapshot {
+            instance_id: self.instance_id.clone(),
+            user_ids,
+        })
+        .await;
+    }
+
+    async fn online_elsewhere(&self, user_id: i32) -> bool {
+        self.remote_presence
+            .read()
+            .await
+            .values()
+            .any(|instance| instance.is_fresh() && instance.user_ids.contains(&user_id))
+    }
+
+    async fn relay_presence(&self, user_id: i32, online: bool) {
+        self.relay(&Fanout::Presence {
+            instance_id: self.instance_id.clone(),
+            user_id,
+            online,
+        })
+        .await;
+    }
+
+    /// Count a new global socket. Returns true when it is the user's first one on
+    /// any instance, i.e. they just came online.
+    pub async fn session_started(&self, user_id: i32) -> bool {
+        let first_here = {
+            let mut sessions = self.sessions.write().await;
+            let count = sessions.entry(user_id).or_insert(0);
+            *count += 1;
+            *count == 1
+        };
+        if !first_here {
+            return false;
+        }
+        self.relay_presence(user_id, true).await;
+        !self.online_elsewhere(user_id).await
+    }
+
+    /// Returns true when the closed socket was the user's last one on any
+    /// instance, i.e. they just went offline.
+    pub async fn session_ended(&self, user_id: i32) -> bool {
+        let last_here = {
+            let mut sessions = self.sessions.write().await;
+            match sessions.get_mut(&user_id) {
+                Some(count) if *count > 1 => {
+                    *count -= 1;
+                    false
+                }
+                Some(_) => {
+                    sessions.remove(&user_id);
+                    true
+                }
+                None => false,
+            }
+        };
+        if !last_here {
+            return false;
+        }
+        self.relay_presence(user_id, false).await;
+        !self.online_elsewhere(user_id).await
+    }
+
+    pub async fn session_count(&self, user_id: i32) -> usize {
+        self.sessions
+            .read()
+            .await
+            .get(&user_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Users with a socket on this or any other live instance
     pub async fn get_online_users(&self) -> Vec<i32> {
-        let user_channels = self.user_channels.read().await;
-        user_channels.keys().copied().collect()
+        let mut online = self
+            .sessions
+            .read()
+            .await
+            .keys()
+            .copied()
+            .collect::<HashSet<_>>();
+        for instance in self.remote_presence.read().await.values() {
+            if instance.is_fresh() {
+                online.extend(&instance.user_ids);
+            }
+        }
+        online.into_iter().collect()
     }
 }
 
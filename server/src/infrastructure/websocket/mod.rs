@@ -1,2 +1,8 @@
+pub mod broadcast;
+pub mod event_log;
 pub mod handler;
+pub mod heartbeat;
 pub mod manager;
+pub mod pg_broadcast;
+pub mod protocol;
+pub mod room_subscriptions;
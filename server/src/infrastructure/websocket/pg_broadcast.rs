@@ -0,0 +1,268 @@
+//! Postgres LISTEN/NOTIFY relay, so replicas sharing a database also share their
+//! WebSocket fan-outs.
+//!
+//! Every instance publishes on the `ws_fanout` channel and listens on it, each
+//! with a connection of its own outside the request pool. Publishing is queued:
+//! whatever piled up while the last NOTIFY was sent goes out together in the
+//! next one, and typing indicators and read receipts wait a moment for company.
+//! Payloads carry the publishing instance's id so it can skip its own fan-outs,
+//! which it has already delivered. NOTIFY payloads are capped at 8000 bytes;
+//! anything bigger is parked in `ws_fanout_overflow` and only its id is sent.
+//! Fan-outs published while either connection is reconnecting are lost to the
+//! other instances, same as for a socket that drops.
+
+use std::{thread, time::Duration};
+
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use diesel::{
+    Connection, PgConnection, QueryResult,
+    dsl::{IntervalDsl, now},
+    prelude::*,
+    sql_query,
+    sql_types::Text,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use super::{
+    broadcast::{BroadcastBackend, Fanout, new_instance_id},
+    manager::{ConnectionManager, PRESENCE_ANNOUNCE_INTERVAL},
+};
+use crate::infrastructure::database::schema::ws_fanout_overflow;
+
+const CHANNEL: &str = "ws_fanout";
+/// Postgres rejects payloads of 8000 bytes or more
+const MAX_NOTIFY_PAYLOAD: usize = 7999;
+/// Overflow rows only need to outlive the listeners' next poll
+const OVERFLOW_RETENTION_MINUTES: i32 = 5;
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+/// How long typing indicators and read receipts wait to share a NOTIFY
+const CHATTER_BATCH_WINDOW: Duration = Duration::from_millis(100);
+/// Fan-outs taken off the queue for one NOTIFY
+const MAX_BATCH: usize = 64;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Body {
+    Inline(Vec<Fanout>),
+    Overflow(i64),
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    origin: String,
+    #[serde(flatten)]
+    body: Body,
+}
+
+pub struct PgBroadcast {
+    database_url: String,
+    /// Tells this instance's notifications apart from its peers'
+    instance_id: String,
+    outbox: mpsc::UnboundedSender<Fanout>,
+}
+
+impl PgBroadcast {
+    /// Starts the publishing thread, which connects on first use
+    pub fn new(database_url: String) -> Result<Self> {
+        let instance_id = new_instance_id();
+        let (outbox, queue) = mpsc::unbounded_channel();
+
+        let url = database_url.clone();
+        let origin = instance_id.clone();
+        thread::Builder::new()
+            .name("ws-fanout-publisher".to_string())
+            .spawn(move || publish_queued(&url, &origin, queue))?;
+
+        Ok(Self {
+            database_url,
+            instance_id,
+            outbox,
+        })
+    }
+
+    /// Deliver fan-outs published by other instances to `manager`'s sockets, and
+    /// keep announcing who is online on this one.
+    ///
+    /// LISTEN is session state, so this keeps one more connection outside the
+    /// pool, polled on a thread of its own and re-established whenever it fails.
+    pub fn spawn_listener(&self, manager: ConnectionManager) -> Result<()> {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let url = self.database_url.clone();
+        let origin = self.instance_id.clone();
+
+        thread::Builder::new()
+            .name("ws-fanout-listener".to_string())
+            .spawn(move || listen(&url, &origin, &tx))?;
+
+        let announcer = manager.clone();
+        tokio::spawn(async move {
+            while let Some(fanout) = rx.recv().await {
+                manager.deliver(&fanout).await;
+            }
+        });
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(PRESENCE_ANNOUNCE_INTERVAL);
+            loop {
+                ticks.tick().await;
+                announcer.announce_presence().await;
+            }
+        });
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BroadcastBackend for PgBroadcast {
+    async fn publish(&self, fanout: &Fanout) -> Result<()> {
+        self.outbox
+            .send(fanout.clone())
+            .map_err(|_| anyhow!("Fan-out publisher has stopped"))
+    }
+}
+
+/// Runs until every `PgBroadcast` handle on `queue` is gone
+fn publish_queued(database_url: &str, origin: &str, mut queue: mpsc::UnboundedReceiver<Fanout>) {
+    let mut connection: Option<PgConnection> = None;
+
+    while let Some(first) = queue.blocking_recv() {
+        if first.is_chatter() {
+            thread::sleep(CHATTER_BATCH_WINDOW);
+        }
+        let mut batch = vec![first];
+        while batch.len() < MAX_BATCH {
+            match queue.try_recv() {
+                Ok(fanout) => batch.push(fanout),
+                Err(_) => break,
+            }
+        }
+
+        let conn = match connection.as_mut() {
+            Some(conn) => conn,
+            None => match PgConnection::establish(database_url) {
+                Ok(conn) => connection.insert(conn),
+                Err(e) => {
+                    warn!("Fan-out publisher could not connect: {}", e);
+                    thread::sleep(RECONNECT_DELAY);
+                    continue;
+                }
+            },
+        };
+        if let Err(e) = notify(conn, origin, batch) {
+            warn!("Fan-out publisher lost its connection: {}", e);
+            connection = None;
+        }
+    }
+}
+
+/// One NOTIFY for `batch` if it fits, otherwise halves of it in order. A single
+/// fan-out too big for NOTIFY goes through the overflow table.
+fn notify(conn: &mut PgConnection, origin: &str, mut batch: Vec<Fanout>) -> Result<()> {
+    let inline = serde_json::to_string(&Envelope {
+        origin: origin.to_string(),
+        body: Body::Inline(batch.clone()),
+    })?;
+
+    if inline.len() > MAX_NOTIFY_PAYLOAD && batch.len() > 1 {
+        let second_half = batch.split_off(batch.len() / 2);
+        notify(conn, origin, batch)?;
+        return notify(conn, origin, second_half);
+    }
+
+    let payload = if inline.len() <= MAX_NOTIFY_PAYLOAD {
+        inline
+    } else {
+        serde_json::to_string(&Envelope {
+            origin: origin.to_string(),
+            body: Body::Overflow(park(conn, &batch)?),
+        })?
+    };
+
+    sql_query("SELECT pg_notify($1, $2)")
+        .bind::<Text, _>(CHANNEL)
+        .bind::<Text, _>(payload)
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Stores fan-outs too big for NOTIFY and returns the row id to send instead
+fn park(conn: &mut PgConnection, batch: &[Fanout]) -> Result<i64> {
+    diesel::delete(
+        ws_fanout_overflow::table
+            .filter(ws_fanout_overflow::created_at.lt(now - OVERFLOW_RETENTION_MINUTES.minutes())),
+    )
+    .execute(conn)?;
+
+    Ok(diesel::insert_into(ws_fanout_overflow::table)
+        .values(ws_fanout_overflow::payload.eq(serde_json::to_string(batch)?))
+        .returning(ws_fanout_overflow::id)
+        .get_result::<i64>(conn)?)
+}
+
+/// Runs until the manager side of `tx` is gone
+fn listen(database_url: &str, origin: &str, tx: &mpsc::UnboundedSender<Fanout>) {
+    while !tx.is_closed() {
+        match PgConnection::establish(database_url) {
+            Ok(mut conn) => {
+                if let Err(e) = relay(&mut conn, origin, tx) {
+                    warn!("Fan-out listener lost its connection: {}", e);
+                }
+            }
+            Err(e) => warn!("Fan-out listener could not connect: {}", e),
+        }
+
+        if !tx.is_closed() {
+            thread::sleep(RECONNECT_DELAY);
+        }
+    }
+}
+
+/// Forward notifications until the connection fails or `tx` closes
+fn relay(conn: &mut PgConnection, origin: &str, tx: &mpsc::UnboundedSender<Fanout>) -> Result<()> {
+    sql_query(format!("LISTEN {CHANNEL}")).execute(conn)?;
+    info!("Listening for WebSocket fan-outs on {}", CHANNEL);
+
+    while !tx.is_closed() {
+        let notifications = conn.notifications_iter().collect::<QueryResult<Vec<_>>>()?;
+
+        for notification in notifications {
+            match decode(conn, origin, &notification.payload) {
+                Ok(fanouts) => {
+                    for fanout in fanouts {
+                        let _ = tx.send(fanout);
+                    }
+                }
+                Err(e) => warn!("Dropping unreadable fan-out: {}", e),
+            }
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    Ok(())
+}
+
+/// Nothing for this instance's own fan-outs
+fn decode(conn: &mut PgConnection, origin: &str, payload: &str) -> Result<Vec<Fanout>> {
+    let envelope: Envelope = serde_json::from_str(payload)?;
+    if envelope.origin == origin {
+        return Ok(Vec::new());
+    }
+
+    let fanouts = match envelope.body {
+        Body::Inline(fanouts) => fanouts,
+        Body::Overflow(id) => {
+            let payload = ws_fanout_overflow::table
+                .find(id)
+                .select(ws_fanout_overflow::payload)
+                .first::<String>(conn)?;
+            serde_json::from_str(&payload)?
+        }
+    };
+
+    Ok(fanouts)
+}
@@ -0,0 +1,335 @@
+//! Wire protocol for both WebSocket endpoints.
+//!
+//! Every frame is `{ "version": 1, "type": "...", "data": { ... } }`. The enums are
+//! tagged on `type` with the payload under `data`, which is the shape the client
+//! has always read. Events on a user's or room's stream also carry a `seq`; see
+//! `event_log.rs`. Run `cargo run --bin ws_schema` after changing anything here;
+//! `tests/ws_protocol.rs` fails while the checked-in schemas are stale.
+
+use chrono::NaiveDateTime;
+use schemars::{JsonSchema, Schema, schema_for};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{
+    entities::private_messages::PrivateMessage,
+    value_objects::mission_comment_model::MissionCommentModel,
+};
+
+/// Bumped on breaking changes to either enum
+pub const PROTOCOL_VERSION: u32 = 1;
+
+fn protocol_version() -> u32 {
+    PROTOCOL_VERSION
+}
+
+/// Mission referenced by id and name, for toasts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MissionRef {
+    pub mission_id: i32,
+    pub mission_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MissionStatusChanged {
+    pub mission_id: i32,
+    pub mission_name: String,
+    pub new_status: String,
+}
+
+/// Someone joined, left or was kicked from a crew
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CrewChange {
+    pub mission_id: i32,
+    pub mission_name: String,
+    pub brawler_id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct JoinRequest {
+    pub mission_id: i32,
+    pub mission_name: String,
+    pub application_id: i32,
+    pub brawler_id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MissionInvitation {
+    pub invitation_id: i32,
+    pub mission_id: i32,
+    pub mission_name: String,
+    pub inviter_id: i32,
+    pub inviter_display_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct InvitationRevoked {
+    pub invitation_id: i32,
+    pub mission_id: i32,
+    pub mission_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct InvitationDeclined {
+    pub invitation_id: i32,
+    pub mission_id: i32,
+    pub mission_name: String,
+    pub brawler_id: i32,
+}
+
+/// Global toast for a chat message posted while the recipient is outside the room
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct NewChatMessage {
+    pub mission_id: i32,
+    pub mission_name: String,
+    pub sender_name: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ClearChat {
+    pub mission_id: i32,
+}
+
+/// Sent only to the socket that posted the `chat_message`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ChatAck {
+    pub client_id: Option<String>,
+    pub comment_id: i32,
+    pub created_at: NaiveDateTime,
+}
+
+/// Sent only to the socket whose request failed. `code` matches the REST error codes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ErrorPayload {
+    /// `type` of the failed request, or `unknown` if it could not be parsed
+    pub request: String,
+    pub client_id: Option<String>,
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Typing {
+    pub mission_id: i32,
+    pub brawler_id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ReadUpTo {
+    pub mission_id: i32,
+    pub brawler_id: i32,
+    pub comment_id: i32,
+}
+
+/// Sent to the user's friends when their first socket opens or their last one closes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AgentPresence {
+    pub user_id: i32,
+    pub last_seen_at: Option<NaiveDateTime>,
+}
+
+/// Sent instead of a replay when the requested events are no longer kept. The
+/// client should reload over REST and carry on from `latest_seq`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ResyncRequired {
+    pub latest_seq: u64,
+}
+
+/// Confirms a `subscribe` on the global socket. Room events follow with `room` set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Subscribed {
+    pub mission_id: i32,
+}
+
+/// The global socket stopped forwarding a room, on request or because the server
+/// closed the user's membership (same reasons as the room socket's close codes)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Unsubscribed {
+    pub mission_id: i32,
+    /// `left_mission`, `kicked` or `mission_deleted`; missing when the client asked
+    pub reason: Option<String>,
+}
+
+/// Friendship toasts. Kept nested under a `notification` event with their own
+/// `type`, as the client has always received them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FriendNotification {
+    FriendRequest {
+        requester_id: i32,
+        requester_name: String,
+        content: String,
+    },
+    FriendAccepted {
+        friend_id: i32,
+        content: String,
+    },
+}
+
+/// Server-to-client events, on both the mission room and the global socket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum ServerEvent {
+    // Mission lifecycle
+    MissionStarted(MissionStatusChanged),
+    MissionCompleted(MissionStatusChanged),
+    MissionFailed(MissionStatusChanged),
+    MissionDeleted(MissionRef),
+
+    // Crew
+    NewCrewJoined(CrewChange),
+    CrewLeft(CrewChange),
+    KickedFromMission(CrewChange),
+    JoinRequest(JoinRequest),
+    ApplicationAccepted(MissionRef),
+    ApplicationRejected(MissionRef),
+    WaitlistPromoted(MissionRef),
+    MissionInvitation(MissionInvitation),
+    InvitationRevoked(InvitationRevoked),
+    InvitationDeclined(InvitationDeclined),
+
+    // Mission chat
+    NewComment(MissionCommentModel),
+    NewChatMessage(NewChatMessage),
+    ClearChat(ClearChat),
+    ChatAck(ChatAck),
+    TypingStart(Typing),
+    TypingStop(Typing),
+    ReadUpTo(ReadUpTo),
+    Error(ErrorPayload),
+
+    // Social
+    PrivateMessage(PrivateMessage),
+    Notification(FriendNotification),
+    AgentOnline(AgentPresence),
+    AgentOffline(AgentPresence),
+
+    // Connection
+    ResyncRequired(ResyncRequired),
+    Subscribed(Subscribed),
+    Unsubscribed(Unsubscribed),
+}
+
+impl ServerEvent {
+    /// Only matters while it is happening, so it is neither numbered nor replayed
+    pub fn is_ephemeral(&self) -> bool {
+        matches!(
+            self,
+            ServerEvent::TypingStart(_) | ServerEvent::TypingStop(_)
+        )
+    }
+}
+
+// Room requests name their room with `mission_id` on the global socket. The room
+// socket already knows it, so there it may be left out.
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ChatMessageRequest {
+    #[serde(default)]
+    pub mission_id: Option<i32>,
+    pub content: String,
+    /// Opaque id echoed back in the `chat_ack` or `error`
+    #[serde(default)]
+    pub client_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ReadUpToRequest {
+    #[serde(default)]
+    pub mission_id: Option<i32>,
+    pub comment_id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RoomTarget {
+    pub mission_id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SubscribeRequest {
+    pub mission_id: i32,
+    /// Last `seq` seen from this room, to replay what was missed
+    #[serde(default)]
+    pub since: Option<u64>,
+}
+
+/// Client-to-server requests. `subscribe`/`unsubscribe` are global socket only.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum ClientEvent {
+    ChatMessage(ChatMessageRequest),
+    TypingStart(Option<RoomTarget>),
+    TypingStop(Option<RoomTarget>),
+    ReadUpTo(ReadUpToRequest),
+    Subscribe(SubscribeRequest),
+    Unsubscribe(RoomTarget),
+}
+
+impl ClientEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClientEvent::ChatMessage(_) => "chat_message",
+            ClientEvent::TypingStart(_) => "typing_start",
+            ClientEvent::TypingStop(_) => "typing_stop",
+            ClientEvent::ReadUpTo(_) => "read_up_to",
+            ClientEvent::Subscribe(_) => "subscribe",
+            ClientEvent::Unsubscribe(_) => "unsubscribe",
+        }
+    }
+
+    /// The room a room request names, if any
+    pub fn mission_id(&self) -> Option<i32> {
+        match self {
+            ClientEvent::ChatMessage(request) => request.mission_id,
+            ClientEvent::TypingStart(target) | ClientEvent::TypingStop(target) => {
+                target.as_ref().map(|target| target.mission_id)
+            }
+            ClientEvent::ReadUpTo(request) => request.mission_id,
+            ClientEvent::Subscribe(request) => Some(request.mission_id),
+            ClientEvent::Unsubscribe(target) => Some(target.mission_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ServerFrame {
+    pub version: u32,
+    /// Position in the user's or room's event stream. Send the last one seen as
+    /// `?since=` when reconnecting. Missing on replies and ephemeral events.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+    /// Set on events from a mission room's stream, so the global socket's
+    /// subscribers can tell rooms apart. `seq` then counts within that room.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub room: Option<i32>,
+    #[serde(flatten)]
+    pub event: ServerEvent,
+}
+
+impl From<ServerEvent> for ServerFrame {
+    fn from(event: ServerEvent) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            seq: None,
+            room: None,
+            event,
+        }
+    }
+}
+
+/// `version` may be left out by clients that predate it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ClientFrame {
+    #[serde(default = "protocol_version")]
+    pub version: u32,
+    #[serde(flatten)]
+    pub event: ClientEvent,
+}
+
+pub fn server_frame_schema() -> Schema {
+    schema_for!(ServerFrame)
+}
+
+pub fn client_frame_schema() -> Schema {
+    schema_for!(ClientFrame)
+}
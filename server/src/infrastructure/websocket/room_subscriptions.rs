@@ -0,0 +1,176 @@
+//! Mission rooms watched over a global socket.
+//!
+//! Each subscribed room gets a task that forwards its stream into the socket's
+//! outgoing queue, so one connection can follow several rooms. A room close that
+//! applies to the user (left, kicked, deleted) ends that room's task with an
+//! `unsubscribed` frame instead of closing the whole socket.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::{
+    sync::{broadcast::error::RecvError, mpsc},
+    task::JoinHandle,
+};
+
+use super::{
+    event_log::{Cursor, StreamKey},
+    manager::{ConnectionManager, RoomSubscription},
+    protocol::{ServerEvent, ServerFrame, Subscribed, Unsubscribed},
+};
+
+pub struct RoomSubscriptions {
+    manager: Arc<ConnectionManager>,
+    user_id: i32,
+    out: mpsc::UnboundedSender<ServerFrame>,
+    rooms: HashMap<i32, JoinHandle<()>>,
+}
+
+impl RoomSubscriptions {
+    pub fn new(
+        manager: Arc<ConnectionManager>,
+        user_id: i32,
+        out: mpsc::UnboundedSender<ServerFrame>,
+    ) -> Self {
+        Self {
+            manager,
+            user_id,
+            out,
+            rooms: HashMap::new(),
+        }
+    }
+
+    /// Start forwarding a room the caller has checked the user belongs to.
+    /// Subscribing again restarts the room from `since`.
+    pub async fn subscribe(&mut self, mission_id: i32, since: Option<u64>) {
+        self.stop(mission_id).await;
+
+        let subscription = self.manager.subscribe(mission_id).await;
+        let _ = self
+            .out
+            .send(ServerEvent::Subscribed(Subscribed { mission_id }).into());
+
+        let task = forward_room(
+            Arc::clone(&self.manager),
+            self.user_id,
+            mission_id,
+            since,
+            subscription,
+            self.out.clone(),
+        );
+        self.rooms.insert(mission_id, tokio::spawn(task));
+    }
+
+    /// Returns false if the room was not subscribed
+    pub async fn unsubscribe(&mut self, mission_id: i32) -> bool {
+        let was_subscribed = self.is_subscribed(mission_id);
+        self.stop(mission_id).await;
+
+        if was_subscribed {
+            let _ = self.out.send(
+                ServerEvent::Unsubscribed(Unsubscribed {
+                    mission_id,
+                    reason: None,
+                })
+                .into(),
+            );
+        }
+        was_subscribed
+    }
+
+    /// False once the server has closed the room for this user
+    pub fn is_subscribed(&self, mission_id: i32) -> bool {
+        self.rooms
+            .get(&mission_id)
+            .is_some_and(|task| !task.is_finished())
+    }
+
+    pub fn len(&self) -> usize {
+        self.rooms
+            .values()
+            .filter(|task| !task.is_finished())
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Stop every room, for when the socket closes
+    pub async fn clear(&mut self) {
+        let mission_ids = self.rooms.keys().copied().collect::<Vec<_>>();
+        for mission_id in mission_ids {
+            self.stop(mission_id).await;
+        }
+    }
+
+    async fn stop(&mut self, mission_id: i32) {
+        if let Some(task) = self.rooms.remove(&mission_id) {
+            task.abort();
+            // Wait for the receivers to drop so the room channel can be removed
+            let _ = task.await;
+            self.manager.unsubscribe(mission_id).await;
+        }
+    }
+}
+
+async fn forward_room(
+    manager: Arc<ConnectionManager>,
+    user_id: i32,
+    mission_id: i32,
+    since: Option<u64>,
+    subscription: RoomSubscription,
+    out: mpsc::UnboundedSender<ServerFrame>,
+) {
+    let RoomSubscription {
+        mut messages,
+        mut closes,
+    } = subscription;
+    let mut cursor = Cursor::new(StreamKey::Room(mission_id));
+
+    if let Some(since) = since {
+        for frame in cursor.resume(&manager, since).await {
+            if out.send(frame).is_err() {
+                return;
+            }
+        }
+    }
+
+    let reason = loop {
+        tokio::select! {
+            // Flush queued room messages (e.g. mission_deleted) before a close
+            biased;
+            msg = messages.recv() => {
+                let frames = match msg {
+                    Ok(frame) if cursor.accept(&frame) => vec![frame],
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(_)) => cursor.catch_up(&manager).await,
+                    Err(RecvError::Closed) => break None,
+                };
+                for frame in frames {
+                    if out.send(frame).is_err() {
+                        return;
+                    }
+                }
+            }
+            close = closes.recv() => match close {
+                Ok(close) if close.applies_to(user_id) => break Some(close.reason),
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break None,
+            }
+        }
+    };
+
+    if let Some(reason) = reason {
+        let _ = out.send(
+            ServerEvent::Unsubscribed(Unsubscribed {
+                mission_id,
+                reason: Some(reason.reason().to_string()),
+            })
+            .into(),
+        );
+    }
+
+    drop((messages, closes));
+    manager.unsubscribe(mission_id).await;
+}
@@ -22,8 +22,7 @@ async fn main() {
 
     info!(".ENV LOADED");
 
-    let postgres_pool = match postgresql_connection::establish_connection(&dotenvy_env.database.url)
-    {
+    let postgres_pool = match postgresql_connection::establish_connection(&dotenvy_env.database) {
         Ok(pool) => pool,
         Err(err) => {
             error!("Fail to connect: {}", err);
@@ -0,0 +1,243 @@
+//! Personal access tokens: minted and revoked by their brawler, accepted by
+//! `auth` on routes that take one of their scopes.
+//!
+//! The store test needs a migrated database and is ignored unless run with
+//! `--ignored`; see `common`.
+
+mod common;
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::{
+    Extension, Router, middleware,
+    routing::{get, post},
+};
+use chrono::Utc;
+use reqwest::{Method, StatusCode, header::AUTHORIZATION};
+use server::{
+    application::use_cases::access_tokens::AccessTokenUseCase,
+    domain::{
+        entities::access_tokens::{AccessTokenEntity, AddAccessTokenEntity},
+        errors::AppError,
+        repositories::access_tokens::AccessTokenRepository,
+        value_objects::{
+            access_token_model::{CreateAccessTokenModel, TokenScope},
+            brawler_role::BrawlerRole,
+        },
+    },
+    infrastructure::{
+        database::repositories::access_tokens::AccessTokenPostgres,
+        http::middlewares::auth::{AccessTokenCheck, RouteScope, SessionCheck, auth},
+        jwt::jwt_model::Passport,
+        secure_token,
+    },
+};
+
+const TOKEN: &str = "vat_reader-and-commenter";
+
+/// Knows one token, brawler 7's, with the `read` and `comments:write` scopes
+struct OneToken;
+
+#[async_trait]
+impl AccessTokenRepository for OneToken {
+    async fn create(&self, _: AddAccessTokenEntity) -> Result<AccessTokenEntity> {
+        unimplemented!()
+    }
+    async fn list_active(&self, _: i32) -> Result<Vec<AccessTokenEntity>> {
+        unimplemented!()
+    }
+    async fn revoke(&self, _: i32, _: i32) -> Result<()> {
+        unimplemented!()
+    }
+    async fn authenticate(&self, token_hash: String) -> Result<Option<AccessTokenEntity>> {
+        if token_hash != secure_token::hash(TOKEN) {
+            return Ok(None);
+        }
+        Ok(Some(AccessTokenEntity {
+            id: 1,
+            brawler_id: 7,
+            name: "bot".to_string(),
+            token_hash,
+            scopes: vec!["read".to_string(), "comments:write".to_string()],
+            created_at: Utc::now().naive_utc(),
+            last_used_at: None,
+            expires_at: None,
+            revoked_at: None,
+        }))
+    }
+}
+
+async fn whoami(Extension(user_id): Extension<i32>) -> String {
+    user_id.to_string()
+}
+
+/// `/comments` reads with `read` and posts with `comments:write`, `/missions`
+/// posts with `missions:write`, and `/sessions` takes JWTs only
+async fn serve() -> String {
+    common::auth_env("access-tokens-test-secret", false);
+    let scoped = |router: Router, scope| {
+        router
+            .route_layer(middleware::from_fn(auth))
+            .route_layer(Extension(RouteScope(scope)))
+    };
+    let app = Router::new()
+        .merge(scoped(
+            Router::new().route("/comments", get(whoami)),
+            TokenScope::Read,
+        ))
+        .merge(scoped(
+            Router::new().route("/comments", post(whoami)),
+            TokenScope::CommentsWrite,
+        ))
+        .merge(scoped(
+            Router::new().route("/missions", post(whoami)),
+            TokenScope::MissionsWrite,
+        ))
+        .route(
+            "/sessions",
+            get(whoami).route_layer(middleware::from_fn(auth)),
+        )
+        .layer(Extension(SessionCheck(Arc::new(common::ActiveSessions))))
+        .layer(Extension(AccessTokenCheck(Arc::new(OneToken))));
+    common::serve(app).await
+}
+
+async fn call(method: Method, url: String, token: &str) -> (StatusCode, String) {
+    let response = reqwest::Client::new()
+        .request(method, url)
+        .header(AUTHORIZATION, format!("Bearer {token}"))
+        .send()
+        .await
+        .unwrap();
+    (response.status(), response.text().await.unwrap())
+}
+
+#[tokio::test]
+async fn access_tokens_reach_only_routes_of_their_scopes() {
+    let url = serve().await;
+
+    assert_eq!(
+        call(Method::GET, format!("{url}/comments"), TOKEN).await,
+        (StatusCode::OK, "7".to_string())
+    );
+    assert_eq!(
+        call(Method::POST, format!("{url}/comments"), TOKEN).await,
+        (StatusCode::OK, "7".to_string())
+    );
+    assert_eq!(
+        call(Method::POST, format!("{url}/missions"), TOKEN).await.0,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        call(Method::GET, format!("{url}/sessions"), TOKEN).await.0,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        call(Method::GET, format!("{url}/comments"), "vat_unknown")
+            .await
+            .0,
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn jwts_pass_scoped_and_unscoped_routes_alike() {
+    let url = serve().await;
+    let jwt = Passport::new(
+        9,
+        "Jwt".to_string(),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        BrawlerRole::User,
+    )
+    .with_session(1)
+    .unwrap()
+    .token;
+
+    for (method, path) in [
+        (Method::GET, "/comments"),
+        (Method::POST, "/missions"),
+        (Method::GET, "/sessions"),
+    ] {
+        assert_eq!(
+            call(method, format!("{url}{path}"), &jwt).await,
+            (StatusCode::OK, "9".to_string())
+        );
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn postgres_store_mints_tracks_use_and_revokes() {
+    let pool = common::test_pool();
+    let tag = Utc::now().timestamp_nanos_opt().unwrap();
+    let username = format!("pat_{tag}");
+    let brawler_id = common::create_brawler(&pool, username);
+    let repo = Arc::new(AccessTokenPostgres::new(Arc::new(pool)));
+    let use_case = AccessTokenUseCase::new(repo.clone());
+    let model = |name: &str, scopes, expires_in_days| CreateAccessTokenModel {
+        name: name.to_string(),
+        scopes,
+        expires_in_days,
+    };
+
+    for invalid in [
+        model("  ", vec![TokenScope::Read], None),
+        model("bot", vec![], None),
+        model("bot", vec![TokenScope::Read], Some(0)),
+    ] {
+        let Err(AppError::Validation(_)) = use_case.create(brawler_id, invalid).await else {
+            panic!("minted a token from an invalid request");
+        };
+    }
+
+    let created = use_case
+        .create(
+            brawler_id,
+            model(
+                " bot ",
+                vec![
+                    TokenScope::Read,
+                    TokenScope::Read,
+                    TokenScope::CommentsWrite,
+                ],
+                Some(30),
+            ),
+        )
+        .await
+        .unwrap();
+    assert!(created.token.starts_with("vat_"));
+    assert_eq!(created.access_token.name, "bot");
+    assert_eq!(
+        created.access_token.scopes,
+        vec![TokenScope::Read, TokenScope::CommentsWrite]
+    );
+    assert!(created.access_token.expires_at.is_some());
+    assert!(created.access_token.last_used_at.is_none());
+
+    // Only the hash is stored
+    let hash = secure_token::hash(&created.token);
+    let used = repo.authenticate(hash.clone()).await.unwrap().unwrap();
+    assert_eq!(used.brawler_id, brawler_id);
+    assert_ne!(used.token_hash, created.token);
+    let listed = use_case.list(brawler_id).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert!(listed[0].last_used_at.is_some());
+
+    let token_id = created.access_token.id;
+    let Err(AppError::NotFound(_)) = use_case.revoke(brawler_id + 1, token_id).await else {
+        panic!("revoked someone else's token");
+    };
+    use_case.revoke(brawler_id, token_id).await.unwrap();
+    assert!(repo.authenticate(hash).await.unwrap().is_none());
+    assert!(use_case.list(brawler_id).await.unwrap().is_empty());
+    let Err(AppError::NotFound(_)) = use_case.revoke(brawler_id, token_id).await else {
+        panic!("revoked a token twice");
+    };
+}
@@ -0,0 +1,166 @@
+//! Cookie session mode: tokens in HttpOnly cookies, double-submit CSRF checks.
+//!
+//! Every test in this file runs with `AUTH_COOKIES=true`.
+
+mod common;
+
+use std::sync::Arc;
+
+use axum::{Extension, Router, http::StatusCode, middleware, response::IntoResponse, routing::get};
+use axum_extra::extract::cookie::CookieJar;
+use reqwest::header::{AUTHORIZATION, COOKIE, SET_COOKIE};
+use server::{
+    domain::value_objects::brawler_role::BrawlerRole,
+    infrastructure::{
+        http::{
+            auth_cookies::{
+                ACCESS_COOKIE, CSRF_COOKIE, CSRF_HEADER, REFRESH_COOKIE, passport_response,
+            },
+            middlewares::auth::{SessionCheck, auth},
+        },
+        jwt::jwt_model::Passport,
+    },
+};
+
+fn passport() -> Passport {
+    Passport::new(
+        7,
+        "Cookie".to_string(),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        BrawlerRole::User,
+    )
+    .with_session(1)
+    .unwrap()
+}
+
+async fn whoami(Extension(user_id): Extension<i32>) -> String {
+    user_id.to_string()
+}
+
+async fn login(jar: CookieJar) -> impl IntoResponse {
+    let mut passport = passport();
+    passport.refresh_token = Some("refresh-me".to_string());
+    passport_response(StatusCode::OK, jar, passport)
+}
+
+/// `/me` behind `auth` for GET and POST, and `/login` handing out brawler 7's cookies
+async fn serve() -> String {
+    common::auth_env("cookie-test-secret", true);
+    let app = Router::new()
+        .route("/api/me", get(whoami).post(whoami))
+        .route_layer(middleware::from_fn(auth))
+        .route("/api/login", get(login))
+        .layer(Extension(SessionCheck(Arc::new(common::ActiveSessions))));
+    format!("{}/api", common::serve(app).await)
+}
+
+/// Name and raw `Set-Cookie` line of each cookie the response sets
+fn set_cookies(response: &reqwest::Response) -> Vec<(String, String)> {
+    response
+        .headers()
+        .get_all(SET_COOKIE)
+        .iter()
+        .map(|value| {
+            let line = value.to_str().unwrap().to_string();
+            let (name, _) = line.split_once('=').unwrap();
+            (name.to_string(), line)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn tokens_go_into_http_only_cookies_not_the_body() {
+    let url = serve().await;
+    let response = reqwest::get(format!("{url}/login")).await.unwrap();
+    let cookies = set_cookies(&response);
+    let body: serde_json::Value = serde_json::from_str(&response.text().await.unwrap()).unwrap();
+
+    assert_eq!(body["id"], 7);
+    assert!(body.get("token").is_none());
+    assert!(body.get("refresh_token").is_none());
+
+    let line = |name: &str| {
+        cookies
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, line)| line.clone())
+            .unwrap_or_else(|| panic!("{name} not set"))
+    };
+    for name in [ACCESS_COOKIE, REFRESH_COOKIE] {
+        let line = line(name);
+        assert!(line.contains("HttpOnly"), "{line}");
+        assert!(line.contains("Secure"), "{line}");
+        assert!(line.contains("SameSite=Strict"), "{line}");
+    }
+    assert!(line(REFRESH_COOKIE).contains("Path=/api/authentication"));
+    // The page has to read this one to echo it
+    assert!(!line(CSRF_COOKIE).contains("HttpOnly"));
+}
+
+#[tokio::test]
+async fn cookie_requests_need_the_csrf_token_to_change_state() {
+    let url = serve().await;
+    let client = reqwest::Client::new();
+    let token = passport().token;
+    let cookies = format!("{ACCESS_COOKIE}={token}; {CSRF_COOKIE}=csrf-value");
+
+    let response = client
+        .get(format!("{url}/me"))
+        .header(COOKIE, &cookies)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await.unwrap(), "7");
+
+    let response = client
+        .post(format!("{url}/me"))
+        .header(COOKIE, &cookies)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = client
+        .post(format!("{url}/me"))
+        .header(COOKIE, &cookies)
+        .header(CSRF_HEADER, "something-else")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = client
+        .post(format!("{url}/me"))
+        .header(COOKIE, &cookies)
+        .header(CSRF_HEADER, "csrf-value")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // A Bearer header can't be sent by another site, so it needs no CSRF token
+    let response = client
+        .post(format!("{url}/me"))
+        .header(AUTHORIZATION, format!("Bearer {token}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn query_string_tokens_are_refused_in_cookie_mode() {
+    let url = serve().await;
+    let token = passport().token;
+
+    let response = reqwest::get(format!("{url}/me?token={token}"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
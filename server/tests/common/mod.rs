@@ -0,0 +1,249 @@
+//! Fixtures shared by the integration tests.
+//!
+//! Tests that need a migrated database are `#[ignore]`d, so a plain `cargo test`
+//! reports them as ignored rather than passing without a database. Run them with
+//! `TEST_DATABASE_URL=postgres://... cargo test -- --ignored`.
+
+#![allow(dead_code)]
+
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Once},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::Router;
+use chrono::{NaiveDateTime, Utc};
+use diesel::{RunQueryDsl, insert_into};
+use server::{
+    application::use_cases::presence::PresenceUseCase,
+    config::config_model::Database,
+    domain::{
+        entities::{
+            brawlers::{BrawlerEntity, RegisterBrawlerEntity},
+            friendships::{FriendshipEntity, NewFriendshipEntity, PendingRequestDto},
+            sessions::{AddSessionEntity, RefreshRotation, SessionEntity},
+        },
+        errors::AppResult,
+        repositories::{
+            brawlers::BrawlerRepository, friendship_repository::FriendshipRepository,
+            sessions::SessionRepository,
+        },
+        value_objects::{
+            base64_img::Base64Img, brawler_model::UpdateBrawlerModel, brawler_role::BrawlerRole,
+            mission_model::MissionModel, session_model::SessionClient, uploaded_img::UploadedImg,
+        },
+    },
+    infrastructure::{
+        cloudinary::UploadImageOptions,
+        database::{
+            postgresql_connection::{PgPoolSquad, establish_connection},
+            schema::brawlers,
+        },
+        jwt::jwt_model::Passport,
+        websocket::manager::ConnectionManager,
+    },
+};
+use tokio::net::TcpListener;
+
+static AUTH_ENV: Once = Once::new();
+
+/// Signs tokens with `secret` and turns cookie mode on or off. Each test file is
+/// its own binary, and the first call in it wins.
+pub fn auth_env(secret: &str, cookie_mode: bool) {
+    AUTH_ENV.call_once(|| {
+        // SAFETY: set once, before any test reads the environment
+        unsafe {
+            std::env::set_var("JWT_USER_SECRET", secret);
+            std::env::set_var("JWT_TTL", "1");
+            std::env::set_var("AUTH_COOKIES", cookie_mode.to_string());
+        }
+    });
+}
+
+/// Every session is active; `auth` asks nothing else
+pub struct ActiveSessions;
+
+#[async_trait]
+impl SessionRepository for ActiveSessions {
+    async fn start(&self, _: AddSessionEntity, _: String, _: NaiveDateTime) -> Result<i32> {
+        unimplemented!()
+    }
+    async fn rotate(
+        &self,
+        _: String,
+        _: String,
+        _: NaiveDateTime,
+        _: SessionClient,
+    ) -> Result<RefreshRotation> {
+        unimplemented!()
+    }
+    async fn is_active(&self, _: i32) -> Result<bool> {
+        Ok(true)
+    }
+    async fn list_active(&self, _: i32) -> Result<Vec<SessionEntity>> {
+        unimplemented!()
+    }
+    async fn revoke(&self, _: i32, _: i32) -> Result<()> {
+        unimplemented!()
+    }
+    async fn revoke_by_token(&self, _: String) -> Result<Option<i32>> {
+        unimplemented!()
+    }
+    async fn revoke_all_for_brawler(&self, _: i32) -> Result<Vec<i32>> {
+        unimplemented!()
+    }
+    async fn revoke_all_except(&self, _: i32, _: i32) -> Result<Vec<i32>> {
+        unimplemented!()
+    }
+}
+
+/// Serves `app` on a free local port, with peer addresses, and returns its base URL
+pub async fn serve(app: Router) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap()
+    });
+    url
+}
+
+pub fn database_url() -> String {
+    std::env::var("TEST_DATABASE_URL")
+        .expect("TEST_DATABASE_URL must point at a migrated database for ignored tests")
+}
+
+pub fn test_pool() -> PgPoolSquad {
+    establish_connection(&Database {
+        url: database_url(),
+        max_connections: 10,
+        min_idle: 0,
+        connection_timeout: 10,
+        idle_timeout: 60,
+    })
+    .expect("connect to TEST_DATABASE_URL")
+}
+
+/// A brawler that can't log in with a password, for tests to hang rows off
+pub fn create_brawler(pool: &PgPoolSquad, username: String) -> i32 {
+    insert_into(brawlers::table)
+        .values(RegisterBrawlerEntity {
+            display_name: username.clone(),
+            username,
+            password: "not-a-real-hash".to_string(),
+        })
+        .returning(brawlers::id)
+        .get_result(&mut pool.get().unwrap())
+        .unwrap()
+}
+
+/// Brawler 1 is friends with 2; 3 is a stranger
+pub struct Friends;
+
+#[async_trait]
+impl FriendshipRepository for Friends {
+    async fn create(&self, _: NewFriendshipEntity) -> AppResult<FriendshipEntity> {
+        unimplemented!()
+    }
+    async fn find_by_id(&self, _: i32) -> AppResult<Option<FriendshipEntity>> {
+        unimplemented!()
+    }
+    async fn find_by_users(&self, _: i32, _: i32) -> AppResult<Option<FriendshipEntity>> {
+        unimplemented!()
+    }
+    async fn update_status(&self, _: i32, _: &str) -> AppResult<FriendshipEntity> {
+        unimplemented!()
+    }
+    async fn delete(&self, _: i32) -> AppResult<()> {
+        unimplemented!()
+    }
+    async fn list_friends(&self, user_id: i32) -> AppResult<Vec<i32>> {
+        Ok(match user_id {
+            1 => vec![2],
+            2 => vec![1],
+            _ => vec![],
+        })
+    }
+    async fn list_pending_requests(&self, _: i32) -> AppResult<Vec<PendingRequestDto>> {
+        unimplemented!()
+    }
+}
+
+pub struct Brawlers;
+
+fn brawler(id: i32) -> BrawlerEntity {
+    let now = Utc::now().naive_utc();
+    BrawlerEntity {
+        id,
+        username: format!("brawler{id}"),
+        password: String::new(),
+        created_at: now,
+        updated_at: now,
+        display_name: format!("Brawler {id}"),
+        avatar_url: None,
+        avatar_public_id: None,
+        bio: None,
+        discord_id: None,
+        contact_email: None,
+        instagram: None,
+        facebook: None,
+        last_seen_at: None,
+        role: "user".to_string(),
+    }
+}
+
+#[async_trait]
+impl BrawlerRepository for Brawlers {
+    async fn register(&self, _: RegisterBrawlerEntity) -> Result<Passport> {
+        unimplemented!()
+    }
+    async fn find_by_id(&self, id: i32) -> Result<BrawlerEntity> {
+        Ok(brawler(id))
+    }
+    async fn find_by_username(&self, _: String) -> Result<BrawlerEntity> {
+        unimplemented!()
+    }
+    async fn find_many(&self, ids: Vec<i32>) -> Result<Vec<BrawlerEntity>> {
+        Ok(ids.into_iter().map(brawler).collect())
+    }
+    async fn upload_base64img(
+        &self,
+        _: i32,
+        _: Base64Img,
+        _: UploadImageOptions,
+    ) -> Result<UploadedImg> {
+        unimplemented!()
+    }
+    async fn crew_counting(&self, _: i32) -> Result<u32> {
+        unimplemented!()
+    }
+    async fn get_missions(&self, _: i32) -> Result<Vec<MissionModel>> {
+        unimplemented!()
+    }
+    async fn update_profile(&self, _: i32, _: UpdateBrawlerModel) -> Result<Passport> {
+        unimplemented!()
+    }
+    async fn touch_last_seen(&self, _: i32) -> Result<NaiveDateTime> {
+        Ok(Utc::now().naive_utc())
+    }
+    async fn update_password(&self, _: i32, _: String) -> Result<()> {
+        unimplemented!()
+    }
+    async fn find_by_roles(&self, _: Vec<BrawlerRole>) -> Result<Vec<BrawlerEntity>> {
+        unimplemented!()
+    }
+    async fn set_role(&self, _: i32, _: BrawlerRole) -> Result<()> {
+        unimplemented!()
+    }
+}
+
+/// Presence among `Friends`, with `Brawlers` for names
+pub fn presence(manager: &Arc<ConnectionManager>) -> PresenceUseCase {
+    PresenceUseCase::new(Arc::new(Friends), Arc::new(Brawlers), Arc::clone(manager))
+}
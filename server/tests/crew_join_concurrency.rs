@@ -0,0 +1,109 @@
+//! Fires parallel joins at a mission with fewer slots than joiners.
+//!
+//! Needs a migrated database, so it is ignored unless run with `--ignored`;
+//! see `common`.
+
+mod common;
+
+use std::sync::Arc;
+
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, insert_into};
+use server::{
+    application::use_cases::crew_operation::CrewOperationUseCase,
+    domain::{
+        entities::missions::AddMissionEntity,
+        errors::AppError,
+        value_objects::{
+            join_policy::JoinPolicy, mission_application_model::JoinOutcome,
+            mission_statuses::MissionStatuses,
+        },
+    },
+    infrastructure::database::{
+        repositories::{
+            crew_operation::CrewOperationPostgres, diesel_transaction::TransactionProviderPostgres,
+            mission_viewing::MissionViewingPostgres,
+        },
+        schema::{brawlers, crew_memberships, missions},
+    },
+};
+
+const MAX_CREW: i32 = 3;
+const JOINERS: usize = 12;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn parallel_joins_never_exceed_max_crew() {
+    let pool = common::test_pool();
+    let pool = Arc::new(pool);
+    let tag = chrono::Utc::now().timestamp_nanos_opt().unwrap();
+
+    let chief_id = common::create_brawler(&pool, format!("chief_{tag}"));
+    let crew_ids: Vec<i32> = (0..JOINERS)
+        .map(|i| common::create_brawler(&pool, format!("crew_{tag}_{i}")))
+        .collect();
+
+    let mission_id: i32 = insert_into(missions::table)
+        .values(AddMissionEntity {
+            chief_id,
+            name: format!("race_{tag}"),
+            status: MissionStatuses::Open.to_string(),
+            description: None,
+            max_crew: MAX_CREW,
+            scheduled_at: None,
+            location: None,
+            category: "Test".to_string(),
+            join_policy: JoinPolicy::Open.to_string(),
+            is_private: false,
+        })
+        .returning(missions::id)
+        .get_result(&mut pool.get().unwrap())
+        .unwrap();
+
+    let use_case = Arc::new(CrewOperationUseCase::new(
+        Arc::new(CrewOperationPostgres::new(Arc::clone(&pool))),
+        Arc::new(MissionViewingPostgres::new(Arc::clone(&pool))),
+        Arc::new(TransactionProviderPostgres::new(Arc::clone(&pool))),
+    ));
+
+    let handles: Vec<_> = crew_ids
+        .iter()
+        .map(|&brawler_id| {
+            let use_case = Arc::clone(&use_case);
+            tokio::spawn(async move { use_case.join(mission_id, brawler_id).await })
+        })
+        .collect();
+
+    let mut joined = 0;
+    let mut full = 0;
+    for handle in handles {
+        match handle.await.unwrap() {
+            Ok(JoinOutcome::Joined) => joined += 1,
+            Err(AppError::Conflict(message)) if message == "Mission is full" => full += 1,
+            other => panic!("unexpected join result: {other:?}"),
+        }
+    }
+
+    let stored: i64 = crew_memberships::table
+        .filter(crew_memberships::mission_id.eq(mission_id))
+        .count()
+        .get_result(&mut pool.get().unwrap())
+        .unwrap();
+
+    // Clean up before asserting so a failure doesn't leave rows behind
+    let mut conn = pool.get().unwrap();
+    diesel::delete(crew_memberships::table.filter(crew_memberships::mission_id.eq(mission_id)))
+        .execute(&mut conn)
+        .unwrap();
+    diesel::delete(missions::table.filter(missions::id.eq(mission_id)))
+        .execute(&mut conn)
+        .unwrap();
+    let mut brawler_ids = crew_ids;
+    brawler_ids.push(chief_id);
+    diesel::delete(brawlers::table.filter(brawlers::id.eq_any(brawler_ids)))
+        .execute(&mut conn)
+        .unwrap();
+
+    assert_eq!(joined, MAX_CREW);
+    assert_eq!(full, JOINERS as i32 - MAX_CREW);
+    assert_eq!(stored, MAX_CREW as i64);
+}
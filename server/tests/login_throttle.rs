@@ -0,0 +1,337 @@
+//! Login brute-force protection: backoff, lockout and its audit trail.
+//!
+//! The policy tests run against an in-memory store. The last test needs a
+//! migrated database and is ignored unless run with `--ignored`; see `common`.
+
+mod common;
+
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex, Once},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::{
+    Extension, Router,
+    extract::{Path, State},
+    routing::post,
+};
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use server::{
+    application::use_cases::login_throttle::{LoginThrottle, backoff_delay},
+    config::config_loader::get_login_throttle_env,
+    domain::{
+        entities::login_throttles::{AddLoginLockoutEntity, LoginThrottleEntity},
+        errors::AppError,
+        repositories::login_throttles::LoginThrottleRepository,
+        value_objects::{login_throttle_model::ThrottleKey, session_model::SessionClient},
+    },
+    infrastructure::{
+        database::{
+            repositories::login_throttles::LoginThrottlePostgres,
+            schema::{login_lockouts, login_throttles},
+        },
+        http::client_info::TrustedProxies,
+    },
+};
+
+static ENV: Once = Once::new();
+
+fn throttle_env() {
+    ENV.call_once(|| {
+        // SAFETY: set once, before any test reads the environment
+        unsafe {
+            std::env::set_var("LOGIN_MAX_FAILURES", "3");
+            std::env::set_var("LOGIN_IP_MAX_FAILURES", "5");
+            std::env::set_var("LOGIN_BACKOFF_BASE_SECONDS", "1");
+            std::env::set_var("LOGIN_BACKOFF_MAX_SECONDS", "4");
+            std::env::set_var("LOGIN_LOCKOUT_MINUTES", "10");
+            std::env::set_var("LOGIN_FAILURE_WINDOW_MINUTES", "15");
+        }
+    });
+}
+
+/// Same bookkeeping as the Postgres store, in a map
+#[derive(Default)]
+struct MemoryThrottles {
+    rows: Mutex<HashMap<(String, String), LoginThrottleEntity>>,
+    lockouts: Mutex<Vec<AddLoginLockoutEntity>>,
+}
+
+fn map_key(key: &ThrottleKey) -> (String, String) {
+    (key.scope.as_str().to_string(), key.subject.clone())
+}
+
+#[async_trait]
+impl LoginThrottleRepository for MemoryThrottles {
+    async fn find(&self, keys: Vec<ThrottleKey>) -> Result<Vec<LoginThrottleEntity>> {
+        let rows = self.rows.lock().unwrap();
+        Ok(keys
+            .iter()
+            .filter_map(|key| rows.get(&map_key(key)).cloned())
+            .collect())
+    }
+    async fn record_failure(&self, key: ThrottleKey, since: NaiveDateTime) -> Result<i32> {
+        let mut rows = self.rows.lock().unwrap();
+        let row = rows
+            .entry(map_key(&key))
+            .or_insert_with(|| LoginThrottleEntity {
+                scope: key.scope.as_str().to_string(),
+                subject: key.subject.clone(),
+                failures: 0,
+                last_failed_at: Utc::now().naive_utc(),
+                locked_until: None,
+            });
+        row.failures = if row.last_failed_at < since {
+            1
+        } else {
+            row.failures + 1
+        };
+        row.last_failed_at = Utc::now().naive_utc();
+        Ok(row.failures)
+    }
+    async fn lock(&self, lockout: AddLoginLockoutEntity) -> Result<()> {
+        let key = (lockout.scope.clone(), lockout.subject.clone());
+        if let Some(row) = self.rows.lock().unwrap().get_mut(&key) {
+            row.failures = 0;
+            row.locked_until = Some(lockout.locked_until);
+        }
+        self.lockouts.lock().unwrap().push(lockout);
+        Ok(())
+    }
+    async fn clear(&self, key: ThrottleKey) -> Result<()> {
+        self.rows.lock().unwrap().remove(&map_key(&key));
+        Ok(())
+    }
+}
+
+fn setup() -> (Arc<MemoryThrottles>, LoginThrottle) {
+    throttle_env();
+    let store = Arc::new(MemoryThrottles::default());
+    let throttle = LoginThrottle::new(store.clone());
+    (store, throttle)
+}
+
+fn is_throttled(result: Result<(), AppError>) -> bool {
+    matches!(result, Err(AppError::TooManyRequests(_)))
+}
+
+#[test]
+fn backoff_doubles_up_to_its_cap() {
+    throttle_env();
+    let env = get_login_throttle_env().unwrap();
+    let delays: Vec<i64> = (1..=5)
+        .map(|failures| backoff_delay(&env, failures).num_seconds())
+        .collect();
+    assert_eq!(delays, [1, 2, 4, 4, 4]);
+}
+
+#[tokio::test]
+async fn a_failure_delays_the_next_attempt() {
+    let (_, throttle) = setup();
+    throttle.check("alice", Some("198.51.100.1")).await.unwrap();
+
+    throttle
+        .record_failure("alice", Some("198.51.100.1"), Some(1))
+        .await
+        .unwrap();
+
+    assert!(is_throttled(throttle.check("alice", None).await));
+    // The same IP trying another username waits too
+    assert!(is_throttled(
+        throttle.check("bob", Some("198.51.100.1")).await
+    ));
+    assert!(throttle.check("bob", Some("198.51.100.2")).await.is_ok());
+}
+
+#[tokio::test]
+async fn enough_failures_lock_the_username_out_and_are_audited() {
+    let (store, throttle) = setup();
+    for _ in 0..3 {
+        throttle
+            .record_failure("Alice", Some("198.51.100.3"), Some(1))
+            .await
+            .unwrap();
+    }
+
+    let lockouts = store.lockouts.lock().unwrap().clone();
+    assert_eq!(lockouts.len(), 1);
+    assert_eq!(lockouts[0].scope, "username");
+    // Case-folded, so `Alice` and `alice` share one count
+    assert_eq!(lockouts[0].subject, "alice");
+    assert_eq!(lockouts[0].brawler_id, Some(1));
+    assert_eq!(lockouts[0].ip.as_deref(), Some("198.51.100.3"));
+    assert_eq!(lockouts[0].failures, 3);
+
+    let Err(AppError::TooManyRequests(message)) =
+        throttle.check("alice", Some("203.0.113.9")).await
+    else {
+        panic!("locked out username was let through");
+    };
+    // Locked for the whole lockout, not just the backoff
+    let seconds: i64 = message
+        .split_whitespace()
+        .find_map(|word| word.parse().ok())
+        .unwrap();
+    assert!(seconds > 500, "{message}");
+}
+
+#[tokio::test]
+async fn failures_across_usernames_lock_the_ip_out() {
+    let (store, throttle) = setup();
+    for name in ["a1", "a2", "a3", "a4", "a5"] {
+        throttle
+            .record_failure(name, Some("198.51.100.4"), None)
+            .await
+            .unwrap();
+    }
+
+    let lockouts = store.lockouts.lock().unwrap().clone();
+    assert_eq!(lockouts.len(), 1);
+    assert_eq!(lockouts[0].scope, "ip");
+    assert_eq!(lockouts[0].subject, "198.51.100.4");
+    assert!(is_throttled(
+        throttle.check("a6", Some("198.51.100.4")).await
+    ));
+}
+
+#[tokio::test]
+async fn success_clears_the_username_but_not_the_ip() {
+    let (store, throttle) = setup();
+    throttle
+        .record_failure("carol", Some("198.51.100.5"), Some(4))
+        .await
+        .unwrap();
+
+    throttle.record_success("carol").await.unwrap();
+
+    let rows = store.rows.lock().unwrap();
+    assert!(!rows.contains_key(&map_key(&ThrottleKey::username("carol"))));
+    assert_eq!(rows[&map_key(&ThrottleKey::ip("198.51.100.5"))].failures, 1);
+}
+
+/// Every attempt fails, as with a wrong password
+async fn failed_login(
+    State(throttle): State<Arc<LoginThrottle>>,
+    Path(username): Path<String>,
+    client: SessionClient,
+) -> Result<(), AppError> {
+    throttle.check(&username, client.ip.as_deref()).await?;
+    throttle
+        .record_failure(&username, client.ip.as_deref(), None)
+        .await
+}
+
+/// Serves `failed_login`, trusting `proxies`
+async fn serve(throttle: LoginThrottle, proxies: Vec<IpAddr>) -> String {
+    let app = Router::new()
+        .route("/login/{username}", post(failed_login))
+        .layer(Extension(TrustedProxies(Arc::new(proxies))))
+        .with_state(Arc::new(throttle));
+    common::serve(app).await
+}
+
+async fn attempt(url: &str, username: &str, forwarded_for: &str) -> reqwest::StatusCode {
+    reqwest::Client::new()
+        .post(format!("{url}/login/{username}"))
+        .header("x-forwarded-for", forwarded_for)
+        .send()
+        .await
+        .unwrap()
+        .status()
+}
+
+#[tokio::test]
+async fn a_changing_forwarded_for_still_trips_the_ip_limit() {
+    let (store, throttle) = setup();
+    let url = serve(throttle, Vec::new()).await;
+
+    assert!(attempt(&url, "dave", "203.0.113.1").await.is_success());
+    assert_eq!(
+        attempt(&url, "erin", "203.0.113.2").await,
+        reqwest::StatusCode::TOO_MANY_REQUESTS
+    );
+
+    let rows = store.rows.lock().unwrap();
+    assert_eq!(rows[&map_key(&ThrottleKey::ip("127.0.0.1"))].failures, 1);
+    assert!(!rows.contains_key(&map_key(&ThrottleKey::ip("203.0.113.1"))));
+}
+
+#[tokio::test]
+async fn trusted_proxies_forward_the_client_ip() {
+    let (store, throttle) = setup();
+    let url = serve(throttle, vec!["127.0.0.1".parse().unwrap()]).await;
+
+    // Only the hop the proxy appended counts; the client wrote the rest
+    assert!(
+        attempt(&url, "frank", "10.0.0.1, 203.0.113.3")
+            .await
+            .is_success()
+    );
+    assert!(
+        attempt(&url, "grace", "10.0.0.1, 203.0.113.4")
+            .await
+            .is_success()
+    );
+    assert_eq!(
+        attempt(&url, "heidi", "203.0.113.3").await,
+        reqwest::StatusCode::TOO_MANY_REQUESTS
+    );
+
+    let rows = store.rows.lock().unwrap();
+    assert!(rows.contains_key(&map_key(&ThrottleKey::ip("203.0.113.4"))));
+    assert!(!rows.contains_key(&map_key(&ThrottleKey::ip("10.0.0.1"))));
+    assert!(!rows.contains_key(&map_key(&ThrottleKey::ip("127.0.0.1"))));
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn postgres_store_counts_locks_and_clears() {
+    let pool = common::test_pool();
+    let tag = Utc::now().timestamp_nanos_opt().unwrap();
+    let username = format!("throttle_{tag}");
+    let brawler_id = common::create_brawler(&pool, username.clone());
+    let pool = Arc::new(pool);
+    let repo = LoginThrottlePostgres::new(Arc::clone(&pool));
+    let key = ThrottleKey::username(&username);
+    let window = || (Utc::now() - Duration::minutes(15)).naive_utc();
+
+    assert_eq!(repo.record_failure(key.clone(), window()).await.unwrap(), 1);
+    assert_eq!(repo.record_failure(key.clone(), window()).await.unwrap(), 2);
+    // Failures older than the window are forgotten
+    diesel::update(login_throttles::table.find(("username", &key.subject)))
+        .set(login_throttles::last_failed_at.eq((Utc::now() - Duration::minutes(20)).naive_utc()))
+        .execute(&mut pool.get().unwrap())
+        .unwrap();
+    assert_eq!(repo.record_failure(key.clone(), window()).await.unwrap(), 1);
+
+    let locked_until = (Utc::now() + Duration::minutes(10)).naive_utc();
+    repo.lock(AddLoginLockoutEntity {
+        scope: "username".to_string(),
+        subject: key.subject.clone(),
+        brawler_id: Some(brawler_id),
+        ip: Some("198.51.100.6".to_string()),
+        failures: 1,
+        locked_until,
+    })
+    .await
+    .unwrap();
+
+    let rows = repo.find(vec![key.clone()]).await.unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].failures, 0);
+    assert!(rows[0].locked_until.is_some());
+
+    let audited: i64 = login_lockouts::table
+        .filter(login_lockouts::brawler_id.eq(brawler_id))
+        .count()
+        .get_result(&mut pool.get().unwrap())
+        .unwrap();
+    assert_eq!(audited, 1);
+
+    repo.clear(key.clone()).await.unwrap();
+    assert!(repo.find(vec![key]).await.unwrap().is_empty());
+}
@@ -0,0 +1,158 @@
+//! Mailers: the SMTP client against a scripted server, and the log stand-in.
+
+use server::{
+    config::config_model::{SmtpEnv, SmtpSecurity},
+    infrastructure::mailer::{Mail, Mailer, log_mailer::LogMailer, smtp::SmtpMailer},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+};
+
+fn mail(body: &str) -> Mail {
+    Mail {
+        to: "Alice <alice@example.com>".to_string(),
+        subject: "Hello".to_string(),
+        body: body.to_string(),
+    }
+}
+
+/// Accepts one connection, answers like a plain SMTP server and returns every
+/// line the client sent
+async fn fake_smtp_server() -> (u16, tokio::task::JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (read, mut write) = stream.into_split();
+        let mut lines = BufReader::new(read).lines();
+        let mut received = Vec::new();
+        let mut in_data = false;
+
+        write.write_all(b"220 fake ESMTP\r\n").await.unwrap();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            received.push(line.clone());
+            let reply: &[u8] = if in_data {
+                if line != "." {
+                    continue;
+                }
+                in_data = false;
+                b"250 queued\r\n"
+            } else if line.starts_with("EHLO") {
+                b"250-fake\r\n250 AUTH PLAIN\r\n"
+            } else if line.starts_with("AUTH PLAIN") {
+                b"235 ok\r\n"
+            } else if line == "DATA" {
+                in_data = true;
+                b"354 go ahead\r\n"
+            } else if line == "QUIT" {
+                write.write_all(b"221 bye\r\n").await.unwrap();
+                break;
+            } else {
+                b"250 ok\r\n"
+            };
+            write.write_all(reply).await.unwrap();
+        }
+        received
+    });
+    (port, server)
+}
+
+fn smtp_env(port: u16, credentials: bool) -> SmtpEnv {
+    SmtpEnv {
+        host: "127.0.0.1".to_string(),
+        port,
+        username: credentials.then(|| "mailer".to_string()),
+        password: credentials.then(|| "secret".to_string()),
+        security: SmtpSecurity::None,
+    }
+}
+
+#[tokio::test]
+async fn smtp_mailer_speaks_the_protocol() {
+    let (port, server) = fake_smtp_server().await;
+    let mailer = SmtpMailer::new(
+        smtp_env(port, true),
+        "Vibe <no-reply@vibe.test>".to_string(),
+    );
+
+    mailer
+        .send(&mail("Line one\n.hidden dot\nLast line"))
+        .await
+        .unwrap();
+    let received = server.await.unwrap();
+
+    let commands: Vec<&str> = received
+        .iter()
+        .map(String::as_str)
+        .filter(|line| {
+            ["EHLO", "AUTH", "MAIL", "RCPT", "DATA", "QUIT"]
+                .iter()
+                .any(|command| line.starts_with(command))
+        })
+        .collect();
+    assert_eq!(
+        commands,
+        [
+            "EHLO vibe.test",
+            // base64 of "\0mailer\0secret"
+            "AUTH PLAIN AG1haWxlcgBzZWNyZXQ=",
+            "MAIL FROM:<no-reply@vibe.test>",
+            "RCPT TO:<alice@example.com>",
+            "DATA",
+            "QUIT",
+        ]
+    );
+
+    assert!(received.iter().any(|line| line == "Subject: Hello"));
+    assert!(
+        received
+            .iter()
+            .any(|line| line == "To: Alice <alice@example.com>")
+    );
+    // A leading dot is doubled so it isn't read as the end of the data
+    assert!(received.iter().any(|line| line == "..hidden dot"));
+    assert!(received.iter().any(|line| line == "Last line"));
+}
+
+#[tokio::test]
+async fn smtp_mailer_skips_auth_without_credentials() {
+    let (port, server) = fake_smtp_server().await;
+    let mailer = SmtpMailer::new(smtp_env(port, false), "no-reply@vibe.test".to_string());
+
+    mailer.send(&mail("Hi")).await.unwrap();
+    let received = server.await.unwrap();
+
+    assert!(!received.iter().any(|line| line.starts_with("AUTH")));
+    assert!(received.contains(&"MAIL FROM:<no-reply@vibe.test>".to_string()));
+}
+
+#[tokio::test]
+async fn line_breaks_in_headers_are_refused() {
+    let injected = Mail {
+        to: "alice@example.com\r\nBcc: everyone@example.com".to_string(),
+        ..mail("Hi")
+    };
+    // Nothing listens here; the mail must be refused before connecting
+    let mailer = SmtpMailer::new(smtp_env(1, false), "no-reply@vibe.test".to_string());
+
+    assert!(injected.check_headers().is_err());
+    assert!(mailer.send(&injected).await.is_err());
+    assert!(LogMailer::new(None).send(&injected).await.is_err());
+}
+
+#[tokio::test]
+async fn log_mailer_appends_to_its_file() {
+    let path = std::env::temp_dir().join(format!("vibe-mail-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mailer = LogMailer::new(Some(path.to_string_lossy().into_owned()));
+
+    mailer.send(&mail("First")).await.unwrap();
+    mailer.send(&mail("Second")).await.unwrap();
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(written.contains("To: Alice <alice@example.com>"));
+    assert!(written.contains("Subject: Hello"));
+    assert!(written.find("First").unwrap() < written.find("Second").unwrap());
+}
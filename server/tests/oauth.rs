@@ -0,0 +1,477 @@
+//! Sign-in through outside providers, against a local mock of an OIDC provider.
+//!
+//! The last test needs a migrated database and is ignored unless run with
+//! `--ignored`; see `common`.
+
+mod common;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use aws_lc_rs::digest::{SHA256, digest};
+use axum::{
+    Form, Json, Router,
+    extract::State,
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION},
+    response::IntoResponse,
+    routing::{get, post},
+};
+use base64::{
+    Engine,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+};
+use chrono::Utc;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, update};
+use reqwest::Url;
+use serde_json::{Value, json};
+use server::{
+    application::use_cases::{authentication::LoginOutcome, oauth::OAuthUseCase},
+    config::config_model::{OAuthProviderEnv, OAuthProviderKind},
+    domain::{
+        errors::AppError,
+        repositories::brawlers::BrawlerRepository,
+        value_objects::{
+            brawler_model::UpdateBrawlerModel,
+            oauth_model::{OAuthCallbackModel, UnlinkIdentityModel},
+            session_model::SessionClient,
+        },
+    },
+    infrastructure::{
+        argon2,
+        database::{
+            repositories::{
+                brawler_identities::BrawlerIdentityPostgres, brawlers::BrawlerPostgres,
+                sessions::SessionPostgres, totp::TotpPostgres,
+            },
+            schema::brawlers,
+        },
+        oauth::{self, IdentityProvider, provider::OAuthProvider},
+    },
+};
+
+const CLIENT_ID: &str = "vibe-client";
+const CLIENT_SECRET: &str = "vibe-secret";
+const REDIRECT_URI: &str = "http://localhost:4200/oauth/callback";
+
+/// Codes handed out with their PKCE challenge and the profile they sign in,
+/// and access tokens with the profile they read
+#[derive(Default)]
+struct MockProvider {
+    codes: Mutex<HashMap<String, (String, Value)>>,
+    tokens: Mutex<HashMap<String, Value>>,
+}
+
+impl MockProvider {
+    /// Does what the provider does when the user approves: remembers the
+    /// challenge from `authorize_url` and returns the code the browser brings back
+    fn approve(&self, authorize_url: &str, profile: Value) -> String {
+        let url = Url::parse(authorize_url).unwrap();
+        let challenge = url
+            .query_pairs()
+            .find(|(key, _)| key == "code_challenge")
+            .map(|(_, value)| value.into_owned())
+            .unwrap();
+        let code = format!("code-{}", Utc::now().timestamp_nanos_opt().unwrap());
+        self.codes
+            .lock()
+            .unwrap()
+            .insert(code.clone(), (challenge, profile));
+        code
+    }
+}
+
+async fn token(
+    State(mock): State<Arc<MockProvider>>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let basic = format!(
+        "Basic {}",
+        STANDARD.encode(format!("{CLIENT_ID}:{CLIENT_SECRET}"))
+    );
+    if headers.get(AUTHORIZATION).and_then(|v| v.to_str().ok()) != Some(basic.as_str()) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({"error": "invalid_client"})),
+        );
+    }
+    if form.get("grant_type").map(String::as_str) != Some("authorization_code")
+        || form.get("redirect_uri").map(String::as_str) != Some(REDIRECT_URI)
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "invalid_request"})),
+        );
+    }
+    let Some((challenge, profile)) = form
+        .get("code")
+        .and_then(|code| mock.codes.lock().unwrap().remove(code))
+    else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "invalid_grant"})),
+        );
+    };
+    let verifier = form.get("code_verifier").cloned().unwrap_or_default();
+    if URL_SAFE_NO_PAD.encode(digest(&SHA256, verifier.as_bytes())) != challenge {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "invalid_grant"})),
+        );
+    }
+
+    let access_token = format!("token-{}", Utc::now().timestamp_nanos_opt().unwrap());
+    mock.tokens
+        .lock()
+        .unwrap()
+        .insert(access_token.clone(), profile);
+    (
+        StatusCode::OK,
+        Json(json!({"access_token": access_token, "token_type": "Bearer"})),
+    )
+}
+
+async fn userinfo(State(mock): State<Arc<MockProvider>>, headers: HeaderMap) -> impl IntoResponse {
+    let profile = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .and_then(|token| mock.tokens.lock().unwrap().get(token).cloned());
+    match profile {
+        Some(profile) => (StatusCode::OK, Json(profile)),
+        None => (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({"error": "invalid_token"})),
+        ),
+    }
+}
+
+/// A mock provider on a free port, and a client for it named `name`
+async fn serve(name: &str, kind: OAuthProviderKind) -> (Arc<MockProvider>, OAuthProvider) {
+    common::auth_env("oauth-test-secret", false);
+    let mock = Arc::new(MockProvider::default());
+    let app = Router::new()
+        .route("/token", post(token))
+        .route("/userinfo", get(userinfo))
+        .with_state(Arc::clone(&mock));
+    let url = common::serve(app).await;
+
+    let provider = OAuthProvider::new(
+        OAuthProviderEnv {
+            name: name.to_string(),
+            kind,
+            client_id: CLIENT_ID.to_string(),
+            client_secret: CLIENT_SECRET.to_string(),
+            authorize_url: format!("{url}/authorize"),
+            token_url: format!("{url}/token"),
+            userinfo_url: format!("{url}/userinfo"),
+            scopes: "openid profile email".to_string(),
+        },
+        REDIRECT_URI.to_string(),
+    );
+    (mock, provider)
+}
+
+#[test]
+fn pkce_challenges_follow_rfc_7636() {
+    // Appendix B
+    assert_eq!(
+        oauth::code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+        "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+    );
+    let verifier = oauth::code_verifier("secret", "nonce");
+    assert_eq!(verifier.len(), 43);
+    assert_eq!(verifier, oauth::code_verifier("secret", "nonce"));
+    assert_ne!(verifier, oauth::code_verifier("other-secret", "nonce"));
+}
+
+#[tokio::test]
+async fn oidc_codes_are_exchanged_only_with_the_matching_verifier() {
+    let (mock, provider) = serve("oidc", OAuthProviderKind::Oidc).await;
+    let verifier = oauth::code_verifier("secret", "nonce");
+    let authorize_url = provider.authorize_url("the-state", &oauth::code_challenge(&verifier));
+
+    let url = Url::parse(&authorize_url).unwrap();
+    let query: HashMap<_, _> = url.query_pairs().into_owned().collect();
+    assert_eq!(query["response_type"], "code");
+    assert_eq!(query["client_id"], CLIENT_ID);
+    assert_eq!(query["redirect_uri"], REDIRECT_URI);
+    assert_eq!(query["state"], "the-state");
+    assert_eq!(query["code_challenge_method"], "S256");
+
+    let profile = json!({
+        "sub": "oidc-1",
+        "preferred_username": "Ally",
+        "name": "Ally Oop",
+        "email": "ally@example.com",
+        "email_verified": true,
+    });
+    let code = mock.approve(&authorize_url, profile.clone());
+    let stolen = mock.approve(&authorize_url, profile);
+
+    let signed_in = provider.exchange(&code, &verifier).await.unwrap();
+    assert_eq!(signed_in.subject, "oidc-1");
+    assert_eq!(signed_in.username.as_deref(), Some("Ally"));
+    assert_eq!(signed_in.display_name.as_deref(), Some("Ally Oop"));
+    assert!(signed_in.email_verified);
+    // Codes work once, and only for whoever holds the verifier
+    assert!(provider.exchange(&code, &verifier).await.is_err());
+    assert!(
+        provider
+            .exchange(&stolen, "not-the-verifier")
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn discord_users_map_to_the_same_profile() {
+    let (mock, provider) = serve("discord", OAuthProviderKind::Discord).await;
+    let verifier = oauth::code_verifier("secret", "nonce");
+    let authorize_url = provider.authorize_url("state", &oauth::code_challenge(&verifier));
+    let code = mock.approve(
+        &authorize_url,
+        json!({
+            "id": "80351110224678912",
+            "username": "nelly",
+            "global_name": "Nelly",
+            "avatar": "8342729096ea3675442027381ff50dfe",
+            "email": "nelly@example.com",
+            "verified": false,
+        }),
+    );
+
+    let profile = provider.exchange(&code, &verifier).await.unwrap();
+    assert_eq!(profile.subject, "80351110224678912");
+    assert_eq!(profile.username.as_deref(), Some("nelly"));
+    assert_eq!(profile.display_name.as_deref(), Some("Nelly"));
+    assert!(!profile.email_verified);
+    assert_eq!(
+        profile.avatar_url.as_deref(),
+        Some(
+            "https://cdn.discordapp.com/avatars/80351110224678912/8342729096ea3675442027381ff50dfe.png"
+        )
+    );
+}
+
+fn callback(
+    mock: &MockProvider,
+    authorize_url: &str,
+    state: String,
+    profile: Value,
+) -> OAuthCallbackModel {
+    OAuthCallbackModel {
+        code: mock.approve(authorize_url, profile),
+        state,
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn sign_up_log_in_link_and_unlink() {
+    let pool = common::test_pool();
+    let (oidc_mock, oidc) = serve("oidc", OAuthProviderKind::Oidc).await;
+    let (discord_mock, discord) = serve("discord", OAuthProviderKind::Discord).await;
+    let pool = Arc::new(pool);
+    let brawler_repository = Arc::new(BrawlerPostgres::new(Arc::clone(&pool)));
+    let use_case = OAuthUseCase::new(
+        Arc::clone(&brawler_repository),
+        Arc::new(BrawlerIdentityPostgres::new(Arc::clone(&pool))),
+        Arc::new(SessionPostgres::new(Arc::clone(&pool))),
+        Arc::new(TotpPostgres::new(Arc::clone(&pool))),
+        vec![Arc::new(oidc), Arc::new(discord)],
+    );
+    assert_eq!(use_case.providers(), ["oidc", "discord"]);
+
+    let tag = Utc::now().timestamp_nanos_opt().unwrap();
+    let profile = json!({
+        "sub": format!("oidc-{tag}"),
+        "preferred_username": "Shared Name!",
+        "name": "Shared Name",
+        "email": format!("{tag}@example.com"),
+        "email_verified": true,
+    });
+    let log_in = |profile: Value| {
+        let use_case = &use_case;
+        let oidc_mock = &oidc_mock;
+        async move {
+            let (started, nonce) = use_case.authorize("oidc", None).unwrap();
+            let model = callback(oidc_mock, &started.authorize_url, started.state, profile);
+            match use_case
+                .login(model, Some(nonce), SessionClient::default())
+                .await
+                .unwrap()
+            {
+                LoginOutcome::Passport(passport) => passport,
+                LoginOutcome::MfaRequired(_) => panic!("no two-factor was set up"),
+            }
+        }
+    };
+
+    // First sign-in creates the brawler from the profile
+    let passport = log_in(profile.clone()).await;
+    assert!(passport.refresh_token.is_some());
+    let brawler = brawler_repository.find_by_id(passport.id).await.unwrap();
+    assert!(brawler.username.starts_with("sharedname"));
+    assert_eq!(brawler.display_name, "Shared Name");
+    assert_eq!(brawler.contact_email, Some(format!("{tag}@example.com")));
+    assert_eq!(brawler.discord_id, None);
+
+    // The next one logs into the same brawler; another account gets its own
+    // brawler with a username of its own
+    assert_eq!(log_in(profile).await.id, passport.id);
+    let other = log_in(json!({
+        "sub": format!("oidc-other-{tag}"),
+        "preferred_username": brawler.username,
+    }))
+    .await;
+    assert_ne!(other.id, passport.id);
+    let other_brawler = brawler_repository.find_by_id(other.id).await.unwrap();
+    assert_ne!(other_brawler.username, brawler.username);
+    assert_eq!(other_brawler.contact_email, None);
+
+    // A state only signs in the browser that started it, so a callback link
+    // someone else started can't log a victim into their account
+    let (started, nonce) = use_case.authorize("oidc", None).unwrap();
+    let (_, foreign_nonce) = use_case.authorize("oidc", None).unwrap();
+    for browser_nonce in [None, Some(foreign_nonce)] {
+        let model = callback(
+            &oidc_mock,
+            &started.authorize_url,
+            started.state.clone(),
+            json!({"sub": "x"}),
+        );
+        assert!(matches!(
+            use_case
+                .login(model, browser_nonce, SessionClient::default())
+                .await,
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    // A login state can't link, nor a link state log in or link someone else
+    let model = callback(
+        &oidc_mock,
+        &started.authorize_url,
+        started.state,
+        json!({"sub": "x"}),
+    );
+    assert!(matches!(
+        use_case.link(passport.id, model, Some(nonce)).await,
+        Err(AppError::Validation(_))
+    ));
+    let (started, nonce) = use_case.authorize("discord", Some(passport.id)).unwrap();
+    let model = callback(
+        &discord_mock,
+        &started.authorize_url,
+        started.state.clone(),
+        json!({}),
+    );
+    assert!(matches!(
+        use_case.link(other.id, model, Some(nonce.clone())).await,
+        Err(AppError::Validation(_))
+    ));
+    let Err(AppError::NotFound(_)) = use_case.authorize("github", None) else {
+        panic!("started a sign-in with an unknown provider");
+    };
+
+    // Linking Discord fills in the verified id
+    let discord_user = json!({"id": format!("{tag}"), "username": "shared"});
+    let model = callback(
+        &discord_mock,
+        &started.authorize_url,
+        started.state,
+        discord_user.clone(),
+    );
+    let linked = use_case
+        .link(passport.id, model, Some(nonce))
+        .await
+        .unwrap();
+    assert_eq!(linked.provider, "discord");
+    let brawler = brawler_repository.find_by_id(passport.id).await.unwrap();
+    assert_eq!(brawler.discord_id, Some(tag.to_string()));
+    assert_eq!(use_case.identities(passport.id).await.unwrap().len(), 2);
+
+    // While it is linked, the profile can resend the verified id but not replace it
+    let profile_update = |discord_id: &str| UpdateBrawlerModel {
+        display_name: None,
+        bio: None,
+        discord_id: Some(discord_id.to_string()),
+        contact_email: None,
+        instagram: None,
+        facebook: None,
+    };
+    brawler_repository
+        .update_profile(passport.id, profile_update(&tag.to_string()))
+        .await
+        .unwrap();
+    let Err(AppError::Validation(_)) = brawler_repository
+        .update_profile(passport.id, profile_update("someone#0001"))
+        .await
+        .map_err(AppError::from)
+    else {
+        panic!("overwrote the linked Discord id");
+    };
+    let brawler = brawler_repository.find_by_id(passport.id).await.unwrap();
+    assert_eq!(brawler.discord_id, Some(tag.to_string()));
+
+    // The same Discord account can't be linked to someone else
+    let (started, nonce) = use_case.authorize("discord", Some(other.id)).unwrap();
+    let model = callback(
+        &discord_mock,
+        &started.authorize_url,
+        started.state,
+        discord_user,
+    );
+    assert!(matches!(
+        use_case.link(other.id, model, Some(nonce)).await,
+        Err(AppError::Conflict(_))
+    ));
+
+    let unlink = |password: Option<&str>| UnlinkIdentityModel {
+        password: password.map(str::to_string),
+    };
+    use_case
+        .unlink(passport.id, "discord".to_string(), unlink(None))
+        .await
+        .unwrap();
+    let brawler = brawler_repository.find_by_id(passport.id).await.unwrap();
+    assert_eq!(brawler.discord_id, None);
+    // Unlinked, it is free text again
+    let updated = brawler_repository
+        .update_profile(passport.id, profile_update("someone#0001"))
+        .await
+        .unwrap();
+    assert_eq!(updated.discord_id, Some("someone#0001".to_string()));
+    let Err(AppError::NotFound(_)) = use_case
+        .unlink(passport.id, "discord".to_string(), unlink(None))
+        .await
+    else {
+        panic!("unlinked a provider twice");
+    };
+
+    // The last provider only goes with the password
+    update(brawlers::table.find(passport.id))
+        .set(brawlers::password.eq(argon2::hash("pw".to_string()).unwrap()))
+        .execute(&mut pool.get().unwrap())
+        .unwrap();
+    let Err(AppError::Validation(_)) = use_case
+        .unlink(passport.id, "oidc".to_string(), unlink(None))
+        .await
+    else {
+        panic!("unlinked the last provider without a password");
+    };
+    let Err(AppError::Forbidden(_)) = use_case
+        .unlink(passport.id, "oidc".to_string(), unlink(Some("wrong")))
+        .await
+    else {
+        panic!("unlinked the last provider with a wrong password");
+    };
+    use_case
+        .unlink(passport.id, "oidc".to_string(), unlink(Some("pw")))
+        .await
+        .unwrap();
+    assert!(use_case.identities(passport.id).await.unwrap().is_empty());
+}
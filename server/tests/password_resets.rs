@@ -0,0 +1,130 @@
+//! Password reset tokens against the real tables.
+//!
+//! Needs a migrated database, so these are ignored unless run with `--ignored`;
+//! see `common`.
+
+mod common;
+
+use std::sync::Arc;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::{QueryDsl, RunQueryDsl};
+use server::{
+    domain::{
+        entities::password_resets::AddPasswordResetEntity,
+        repositories::password_resets::PasswordResetRepository,
+    },
+    infrastructure::{
+        database::{
+            postgresql_connection::PgPoolSquad,
+            repositories::password_resets::PasswordResetPostgres, schema::brawlers,
+        },
+        secure_token,
+    },
+};
+
+fn password_of(pool: &PgPoolSquad, brawler_id: i32) -> String {
+    let mut conn = pool.get().unwrap();
+    brawlers::table
+        .find(brawler_id)
+        .select(brawlers::password)
+        .first(&mut conn)
+        .unwrap()
+}
+
+/// Issues a reset token for the brawler and returns the raw token
+async fn issue(repo: &PasswordResetPostgres, brawler_id: i32, expires_at: NaiveDateTime) -> String {
+    let token = secure_token::generate().unwrap();
+    repo.issue(AddPasswordResetEntity {
+        brawler_id,
+        token_hash: secure_token::hash(&token),
+        expires_at,
+    })
+    .await
+    .unwrap();
+    token
+}
+
+fn in_an_hour() -> NaiveDateTime {
+    (Utc::now() + Duration::hours(1)).naive_utc()
+}
+
+fn unique(prefix: &str) -> String {
+    format!("{prefix}_{}", Utc::now().timestamp_nanos_opt().unwrap())
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn a_token_sets_the_password_once() {
+    let pool = common::test_pool();
+    let brawler_id = common::create_brawler(&pool, unique("reset_once"));
+    let pool = Arc::new(pool);
+    let repo = PasswordResetPostgres::new(Arc::clone(&pool));
+
+    let token = issue(&repo, brawler_id, in_an_hour()).await;
+
+    let consumed = repo
+        .consume(secure_token::hash(&token), "new-hash".to_string())
+        .await
+        .unwrap();
+    assert_eq!(consumed, Some(brawler_id));
+    assert_eq!(password_of(&pool, brawler_id), "new-hash");
+
+    let again = repo
+        .consume(secure_token::hash(&token), "other-hash".to_string())
+        .await
+        .unwrap();
+    assert_eq!(again, None);
+    assert_eq!(password_of(&pool, brawler_id), "new-hash");
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn expired_and_unknown_tokens_change_nothing() {
+    let pool = common::test_pool();
+    let brawler_id = common::create_brawler(&pool, unique("reset_expired"));
+    let pool = Arc::new(pool);
+    let repo = PasswordResetPostgres::new(Arc::clone(&pool));
+    let password = password_of(&pool, brawler_id);
+
+    let expired = issue(
+        &repo,
+        brawler_id,
+        (Utc::now() - Duration::minutes(1)).naive_utc(),
+    )
+    .await;
+
+    for token in [expired, "never-issued".to_string()] {
+        let consumed = repo
+            .consume(secure_token::hash(&token), "new-hash".to_string())
+            .await
+            .unwrap();
+        assert_eq!(consumed, None);
+    }
+    assert_eq!(password_of(&pool, brawler_id), password);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn a_newer_token_replaces_the_older_one() {
+    let pool = common::test_pool();
+    let brawler_id = common::create_brawler(&pool, unique("reset_newer"));
+    let pool = Arc::new(pool);
+    let repo = PasswordResetPostgres::new(Arc::clone(&pool));
+
+    let older = issue(&repo, brawler_id, in_an_hour()).await;
+    let newer = issue(&repo, brawler_id, in_an_hour()).await;
+
+    let consumed = repo
+        .consume(secure_token::hash(&older), "older-hash".to_string())
+        .await
+        .unwrap();
+    assert_eq!(consumed, None);
+
+    let consumed = repo
+        .consume(secure_token::hash(&newer), "newer-hash".to_string())
+        .await
+        .unwrap();
+    assert_eq!(consumed, Some(brawler_id));
+    assert_eq!(password_of(&pool, brawler_id), "newer-hash");
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use std::sync::Arc;
+
+use server::infrastructure::websocket::{
+    manager::ConnectionManager,
+    protocol::{AgentPresence, ServerEvent},
+};
+use tokio::sync::broadcast::error::TryRecvError;
+
+#[tokio::test]
+async fn only_first_and_last_session_reach_friends() {
+    let manager = Arc::new(ConnectionManager::new());
+    let presence = common::presence(&manager);
+    let mut friend = manager.subscribe_user(2).await;
+    let mut stranger = manager.subscribe_user(3).await;
+
+    // Two tabs open
+    presence.session_started(1).await.unwrap();
+    presence.session_started(1).await.unwrap();
+    assert_eq!(manager.session_count(1).await, 2);
+
+    assert_eq!(
+        friend.try_recv().unwrap().event,
+        ServerEvent::AgentOnline(AgentPresence {
+            user_id: 1,
+            last_seen_at: None,
+        })
+    );
+    assert_eq!(friend.try_recv(), Err(TryRecvError::Empty));
+
+    // Closing one tab changes nothing
+    presence.session_ended(1).await.unwrap();
+    assert_eq!(friend.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(manager.get_online_users().await, vec![1]);
+
+    presence.session_ended(1).await.unwrap();
+    match friend.try_recv().unwrap().event {
+        ServerEvent::AgentOffline(AgentPresence {
+            user_id: 1,
+            last_seen_at: Some(_),
+        }) => {}
+        other => panic!("expected agent_offline, got {other:?}"),
+    }
+    assert!(manager.get_online_users().await.is_empty());
+
+    assert_eq!(stranger.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[tokio::test]
+async fn online_list_only_has_friends() {
+    let manager = Arc::new(ConnectionManager::new());
+    let presence = common::presence(&manager);
+
+    presence.session_started(2).await.unwrap();
+    presence.session_started(3).await.unwrap();
+
+    let online = presence.online_friends(1).await.unwrap();
+    assert_eq!(online.iter().map(|b| b.id).collect::<Vec<_>>(), vec![2]);
+}
@@ -0,0 +1,220 @@
+//! Roles: carried in access tokens, guarded by `require_role`, changed by admins.
+//!
+//! The last test needs a migrated database and is ignored unless run with
+//! `--ignored`; see `common`.
+
+mod common;
+
+use std::sync::Arc;
+
+use axum::{Extension, Router, middleware, routing::get};
+use chrono::{Duration, Utc};
+use reqwest::{StatusCode, header::AUTHORIZATION};
+use server::{
+    application::use_cases::admin::AdminUseCase,
+    domain::{
+        entities::sessions::AddSessionEntity,
+        errors::AppError,
+        repositories::sessions::SessionRepository,
+        value_objects::brawler_role::{BrawlerRole, RoleFilter, SetRoleModel},
+    },
+    infrastructure::{
+        database::repositories::{brawlers::BrawlerPostgres, sessions::SessionPostgres},
+        http::middlewares::auth::{CurrentRole, SessionCheck, auth, require_role},
+        jwt::{generate_token, jwt_model::Passport},
+        secure_token,
+        websocket::manager::ConnectionManager,
+    },
+};
+
+const SECRET: &str = "roles-test-secret";
+
+fn token(role: BrawlerRole) -> String {
+    common::auth_env(SECRET, false);
+    Passport::new(
+        9,
+        "Role".to_string(),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        role,
+    )
+    .with_session(1)
+    .unwrap()
+    .token
+}
+
+async fn whoami(Extension(CurrentRole(role)): Extension<CurrentRole>) -> String {
+    role.to_string()
+}
+
+/// `/me` for anyone signed in, `/moderation` for moderators, `/admin` for admins
+async fn serve() -> String {
+    common::auth_env(SECRET, false);
+    let guarded = |role| {
+        Router::new()
+            .route("/", get(whoami))
+            .route_layer(middleware::from_fn_with_state(role, require_role))
+    };
+    let app = Router::new()
+        .route("/me", get(whoami))
+        .nest("/moderation", guarded(BrawlerRole::Moderator))
+        .nest("/admin", guarded(BrawlerRole::Admin))
+        .route_layer(middleware::from_fn(auth))
+        .layer(Extension(SessionCheck(Arc::new(common::ActiveSessions))));
+    common::serve(app).await
+}
+
+async fn get_as(url: &str, token: &str) -> (StatusCode, String) {
+    let response = reqwest::Client::new()
+        .get(url)
+        .header(AUTHORIZATION, format!("Bearer {token}"))
+        .send()
+        .await
+        .unwrap();
+    (response.status(), response.text().await.unwrap())
+}
+
+#[test]
+fn roles_rank_and_serialize_in_snake_case() {
+    assert!(BrawlerRole::User < BrawlerRole::Moderator);
+    assert!(BrawlerRole::Moderator < BrawlerRole::Admin);
+    assert!(!BrawlerRole::User.can_moderate());
+    assert!(BrawlerRole::Admin.can_moderate());
+
+    assert_eq!(
+        serde_json::to_string(&BrawlerRole::Moderator).unwrap(),
+        "\"moderator\""
+    );
+    assert_eq!("admin".parse::<BrawlerRole>().unwrap(), BrawlerRole::Admin);
+    assert!(matches!(
+        "root".parse::<BrawlerRole>(),
+        Err(AppError::Validation(_))
+    ));
+}
+
+#[tokio::test]
+async fn guards_let_through_the_role_and_those_above_it() {
+    let url = serve().await;
+    let user = token(BrawlerRole::User);
+    let moderator = token(BrawlerRole::Moderator);
+    let admin = token(BrawlerRole::Admin);
+
+    assert_eq!(
+        get_as(&format!("{url}/me"), &moderator).await,
+        (StatusCode::OK, "moderator".to_string())
+    );
+    assert_eq!(
+        get_as(&format!("{url}/moderation"), &user).await.0,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        get_as(&format!("{url}/moderation"), &moderator).await.0,
+        StatusCode::OK
+    );
+    assert_eq!(
+        get_as(&format!("{url}/moderation"), &admin).await.0,
+        StatusCode::OK
+    );
+    assert_eq!(
+        get_as(&format!("{url}/admin"), &moderator).await.0,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        get_as(&format!("{url}/admin"), &admin).await.0,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn tokens_without_a_role_count_as_a_plain_user() {
+    let url = serve().await;
+    let now = Utc::now();
+    let legacy = generate_token(
+        SECRET.to_string(),
+        &serde_json::json!({
+            "sub": "9",
+            "sid": 1,
+            "exp": (now + Duration::minutes(5)).timestamp(),
+            "iat": now.timestamp(),
+        }),
+    )
+    .unwrap();
+
+    assert_eq!(
+        get_as(&format!("{url}/me"), &legacy).await,
+        (StatusCode::OK, "user".to_string())
+    );
+    assert_eq!(
+        get_as(&format!("{url}/moderation"), &legacy).await.0,
+        StatusCode::FORBIDDEN
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn admins_promote_and_demotion_signs_out() {
+    let pool = common::test_pool();
+    let tag = Utc::now().timestamp_nanos_opt().unwrap();
+    let admin_id = common::create_brawler(&pool, format!("admin_{tag}"));
+    let brawler_id = common::create_brawler(&pool, format!("mod_{tag}"));
+    let pool = Arc::new(pool);
+    let sessions = Arc::new(SessionPostgres::new(Arc::clone(&pool)));
+    let admin = AdminUseCase::new(
+        Arc::new(BrawlerPostgres::new(Arc::clone(&pool))),
+        sessions.clone(),
+        Arc::new(ConnectionManager::new()),
+    );
+    let set = |role| SetRoleModel { role };
+
+    let Err(AppError::Forbidden(_)) = admin
+        .set_role(admin_id, admin_id, set(BrawlerRole::User))
+        .await
+    else {
+        panic!("an admin changed their own role");
+    };
+
+    let promoted = admin
+        .set_role(admin_id, brawler_id, set(BrawlerRole::Moderator))
+        .await
+        .unwrap();
+    assert_eq!(promoted.role, BrawlerRole::Moderator);
+    let moderators = admin
+        .list(RoleFilter {
+            role: Some(BrawlerRole::Moderator),
+        })
+        .await
+        .unwrap();
+    assert!(moderators.iter().any(|brawler| brawler.id == brawler_id));
+
+    let session_id = sessions
+        .start(
+            AddSessionEntity {
+                brawler_id,
+                user_agent: None,
+                ip: None,
+            },
+            secure_token::hash(&secure_token::generate().unwrap()),
+            (Utc::now() + Duration::days(1)).naive_utc(),
+        )
+        .await
+        .unwrap();
+    // Promotions wait for the next refresh; the session stays
+    admin
+        .set_role(admin_id, brawler_id, set(BrawlerRole::Admin))
+        .await
+        .unwrap();
+    assert!(sessions.is_active(session_id).await.unwrap());
+
+    let demoted = admin
+        .set_role(admin_id, brawler_id, set(BrawlerRole::User))
+        .await
+        .unwrap();
+    assert_eq!(demoted.role, BrawlerRole::User);
+    assert!(!sessions.is_active(session_id).await.unwrap());
+    let staff = admin.list(RoleFilter { role: None }).await.unwrap();
+    assert!(!staff.iter().any(|brawler| brawler.id == brawler_id));
+}
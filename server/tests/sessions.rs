@@ -0,0 +1,206 @@
+//! Login sessions and refresh token rotation against the real tables.
+//!
+//! Needs a migrated database, so these are ignored unless run with `--ignored`;
+//! see `common`.
+
+mod common;
+
+use std::sync::Arc;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use server::{
+    domain::{
+        entities::sessions::{AddSessionEntity, RefreshRotation},
+        errors::AppError,
+        repositories::sessions::SessionRepository,
+        value_objects::session_model::SessionClient,
+    },
+    infrastructure::{
+        database::{postgresql_connection::PgPoolSquad, repositories::sessions::SessionPostgres},
+        secure_token,
+    },
+};
+
+fn expiry() -> NaiveDateTime {
+    (Utc::now() + Duration::days(1)).naive_utc()
+}
+
+fn client(user_agent: &str) -> SessionClient {
+    SessionClient {
+        user_agent: Some(user_agent.to_string()),
+        ip: Some("203.0.113.7".to_string()),
+    }
+}
+
+/// Starts a login and returns its session id and refresh token
+async fn login(repo: &SessionPostgres, brawler_id: i32, user_agent: &str) -> (i32, String) {
+    let token = secure_token::generate().unwrap();
+    let SessionClient { user_agent, ip } = client(user_agent);
+    let session_id = repo
+        .start(
+            AddSessionEntity {
+                brawler_id,
+                user_agent,
+                ip,
+            },
+            secure_token::hash(&token),
+            expiry(),
+        )
+        .await
+        .unwrap();
+    (session_id, token)
+}
+
+async fn rotate(repo: &SessionPostgres, token: &str) -> (RefreshRotation, String) {
+    let next = secure_token::generate().unwrap();
+    let rotation = repo
+        .rotate(
+            secure_token::hash(token),
+            secure_token::hash(&next),
+            expiry(),
+            client("rotated-agent"),
+        )
+        .await
+        .unwrap();
+    (rotation, next)
+}
+
+fn setup() -> (Arc<PgPoolSquad>, SessionPostgres, i32) {
+    let pool = Arc::new(common::test_pool());
+    let tag = Utc::now().timestamp_nanos_opt().unwrap();
+    let brawler_id = common::create_brawler(&pool, format!("session_{tag}"));
+    let repo = SessionPostgres::new(Arc::clone(&pool));
+    (pool, repo, brawler_id)
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn reusing_a_rotated_token_revokes_its_session_only() {
+    let (_pool, repo, brawler_id) = setup();
+    let (laptop_id, laptop) = login(&repo, brawler_id, "laptop").await;
+    let (phone_id, phone) = login(&repo, brawler_id, "phone").await;
+
+    let (rotation, laptop_next) = rotate(&repo, &laptop).await;
+    assert_eq!(
+        rotation,
+        RefreshRotation::Rotated {
+            brawler_id,
+            session_id: laptop_id
+        }
+    );
+
+    // The old token comes back: whoever holds either copy is logged out
+    let (rotation, _) = rotate(&repo, &laptop).await;
+    assert_eq!(
+        rotation,
+        RefreshRotation::Reused {
+            brawler_id,
+            session_id: laptop_id
+        }
+    );
+    let (rotation, _) = rotate(&repo, &laptop_next).await;
+    assert_eq!(rotation, RefreshRotation::Rejected);
+    assert!(!repo.is_active(laptop_id).await.unwrap());
+
+    let (rotation, _) = rotate(&repo, &phone).await;
+    assert_eq!(
+        rotation,
+        RefreshRotation::Rotated {
+            brawler_id,
+            session_id: phone_id
+        }
+    );
+    assert!(repo.is_active(phone_id).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn a_token_rotates_once_under_concurrency() {
+    let (pool, _repo, brawler_id) = setup();
+    let repo = Arc::new(SessionPostgres::new(pool));
+    let (_, token) = login(&repo, brawler_id, "laptop").await;
+
+    let attempts = (0..6).map(|_| {
+        let repo = Arc::clone(&repo);
+        let token = token.clone();
+        tokio::spawn(async move { rotate(&repo, &token).await.0 })
+    });
+    let mut rotated = 0;
+    for attempt in attempts {
+        match attempt.await.unwrap() {
+            RefreshRotation::Rotated { .. } => rotated += 1,
+            RefreshRotation::Reused { .. } | RefreshRotation::Rejected => {}
+        }
+    }
+    assert_eq!(rotated, 1);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn logout_ends_one_session_and_logout_all_ends_every_session() {
+    let (_pool, repo, brawler_id) = setup();
+    let (laptop_id, laptop) = login(&repo, brawler_id, "laptop").await;
+    let (_, laptop) = rotate(&repo, &laptop).await;
+    let (phone_id, phone) = login(&repo, brawler_id, "phone").await;
+    let (tablet_id, tablet) = login(&repo, brawler_id, "tablet").await;
+
+    let revoked = repo
+        .revoke_by_token(secure_token::hash(&laptop))
+        .await
+        .unwrap();
+    assert_eq!(revoked, Some(laptop_id));
+    assert_eq!(rotate(&repo, &laptop).await.0, RefreshRotation::Rejected);
+    let (rotation, phone) = rotate(&repo, &phone).await;
+    assert!(matches!(rotation, RefreshRotation::Rotated { .. }));
+
+    // Unknown tokens and repeated logouts are a no-op
+    let revoked = repo
+        .revoke_by_token(secure_token::hash("never-issued"))
+        .await
+        .unwrap();
+    assert_eq!(revoked, None);
+    let revoked = repo
+        .revoke_by_token(secure_token::hash(&laptop))
+        .await
+        .unwrap();
+    assert_eq!(revoked, None);
+
+    let mut revoked = repo.revoke_all_for_brawler(brawler_id).await.unwrap();
+    revoked.sort();
+    assert_eq!(revoked, vec![phone_id, tablet_id]);
+    assert_eq!(rotate(&repo, &phone).await.0, RefreshRotation::Rejected);
+    assert_eq!(rotate(&repo, &tablet).await.0, RefreshRotation::Rejected);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn sessions_are_listed_by_last_use_and_revoked_by_their_owner_only() {
+    let (pool, repo, brawler_id) = setup();
+    let tag = Utc::now().timestamp_nanos_opt().unwrap();
+    let stranger_id = common::create_brawler(&pool, format!("session_stranger_{tag}"));
+
+    let (laptop_id, laptop) = login(&repo, brawler_id, "laptop").await;
+    let (phone_id, _) = login(&repo, brawler_id, "phone").await;
+    login(&repo, stranger_id, "stranger").await;
+
+    // Refreshing moves the laptop to the top and records where it came from
+    rotate(&repo, &laptop).await;
+    let sessions = repo.list_active(brawler_id).await.unwrap();
+    let ids = sessions.iter().map(|s| s.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![laptop_id, phone_id]);
+    assert_eq!(sessions[0].user_agent.as_deref(), Some("rotated-agent"));
+    assert_eq!(sessions[1].user_agent.as_deref(), Some("phone"));
+
+    let error = repo.revoke(stranger_id, phone_id).await.unwrap_err();
+    assert!(matches!(AppError::from(error), AppError::NotFound(_)));
+    assert!(repo.is_active(phone_id).await.unwrap());
+
+    repo.revoke(brawler_id, phone_id).await.unwrap();
+    assert!(!repo.is_active(phone_id).await.unwrap());
+    let sessions = repo.list_active(brawler_id).await.unwrap();
+    assert_eq!(sessions.len(), 1);
+
+    // Already revoked
+    let error = repo.revoke(brawler_id, phone_id).await.unwrap_err();
+    assert!(matches!(AppError::from(error), AppError::NotFound(_)));
+}
@@ -0,0 +1,199 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use axum::{Extension, Router, routing::get};
+use server::infrastructure::{
+    http::{
+        middlewares::auth::CurrentSession,
+        routers::events::{EventsRouterState, stream_events},
+    },
+    websocket::{
+        manager::ConnectionManager,
+        protocol::{AgentPresence, ServerEvent},
+    },
+};
+
+/// Serves `/api/events` for brawler 1 in session 10, without the auth middleware
+async fn serve(manager: &Arc<ConnectionManager>) -> String {
+    let state = Arc::new(EventsRouterState {
+        manager: Arc::clone(manager),
+        presence: common::presence(manager),
+    });
+    let app = Router::new()
+        .route("/api/events", get(stream_events))
+        .layer(Extension(1))
+        .layer(Extension(CurrentSession(10)))
+        .with_state(state);
+    format!("{}/api/events", common::serve(app).await)
+}
+
+/// `(id, frame)` of each event in the body
+async fn read_events(
+    response: &mut reqwest::Response,
+    count: usize,
+) -> Vec<(Option<String>, serde_json::Value)> {
+    let mut text = String::new();
+    let mut events = Vec::new();
+    while events.len() < count {
+        let chunk = tokio::time::timeout(Duration::from_secs(2), response.chunk())
+            .await
+            .expect("timed out waiting for an event")
+            .unwrap()
+            .expect("stream ended");
+        text.push_str(std::str::from_utf8(&chunk).unwrap());
+
+        while let Some(end) = text.find("\n\n") {
+            let block = text[..end].to_string();
+            text.drain(..end + 2);
+            let mut id = None;
+            let mut data = None;
+            for line in block.lines() {
+                if let Some(value) = line.strip_prefix("id: ") {
+                    id = Some(value.to_string());
+                } else if let Some(value) = line.strip_prefix("data: ") {
+                    data = Some(serde_json::from_str(value).unwrap());
+                }
+            }
+            // Keep-alive comments have no data
+            if let Some(data) = data {
+                events.push((id, data));
+            }
+        }
+    }
+    events
+}
+
+async fn wait_for_sessions(manager: &ConnectionManager, user_id: i32, count: usize) {
+    for _ in 0..100 {
+        if manager.session_count(user_id).await == count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("user {user_id} never had {count} sessions");
+}
+
+#[tokio::test]
+async fn streams_user_and_broadcast_events_with_seq_ids() {
+    let manager = Arc::new(ConnectionManager::new());
+    let url = serve(&manager).await;
+
+    let mut response = reqwest::get(&url).await.unwrap();
+    assert_eq!(
+        response.headers()["content-type"].to_str().unwrap(),
+        "text/event-stream"
+    );
+    wait_for_sessions(&manager, 1, 1).await;
+
+    let presence = ServerEvent::AgentOnline(AgentPresence {
+        user_id: 2,
+        last_seen_at: None,
+    });
+    manager.notify_user(1, presence.clone()).await;
+    manager.notify_user(2, presence.clone()).await;
+    manager.broadcast_all(presence.clone()).await;
+
+    let events = read_events(&mut response, 2).await;
+    for (id, frame) in &events {
+        assert_eq!(frame["type"], "agent_online");
+        assert_eq!(id.as_deref(), Some(frame["seq"].to_string().as_str()));
+    }
+    assert!(events[0].1["seq"].as_u64() < events[1].1["seq"].as_u64());
+
+    // Closing the stream ends the session
+    drop(response);
+    wait_for_sessions(&manager, 1, 0).await;
+}
+
+#[tokio::test]
+async fn last_event_id_replays_missed_events() {
+    let manager = Arc::new(ConnectionManager::new());
+    let url = serve(&manager).await;
+    let client = reqwest::Client::new();
+
+    let mut response = client.get(&url).send().await.unwrap();
+    wait_for_sessions(&manager, 1, 1).await;
+    let event = |user_id| {
+        ServerEvent::AgentOnline(AgentPresence {
+            user_id,
+            last_seen_at: None,
+        })
+    };
+    manager.notify_user(1, event(2)).await;
+    let (last_id, _) = read_events(&mut response, 1).await.remove(0);
+    drop(response);
+    wait_for_sessions(&manager, 1, 0).await;
+
+    // Missed while disconnected
+    manager.notify_user(1, event(3)).await;
+    manager.notify_user(1, event(4)).await;
+
+    let mut response = client
+        .get(&url)
+        .header("Last-Event-ID", last_id.unwrap())
+        .send()
+        .await
+        .unwrap();
+    let missed = read_events(&mut response, 2).await;
+    let users = missed
+        .iter()
+        .map(|(_, frame)| frame["data"]["user_id"].as_i64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(users, vec![3, 4]);
+
+    // A position from elsewhere can't be replayed
+    let mut response = client
+        .get(&url)
+        .header("Last-Event-ID", "5")
+        .send()
+        .await
+        .unwrap();
+    let (id, frame) = read_events(&mut response, 1).await.remove(0);
+    assert_eq!(frame["type"], "resync_required");
+    assert_eq!(
+        id.as_deref(),
+        Some(frame["data"]["latest_seq"].to_string().as_str())
+    );
+
+    let response = client
+        .get(&url)
+        .header("Last-Event-ID", "latest")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn revoking_the_session_ends_the_stream() {
+    let manager = Arc::new(ConnectionManager::new());
+    let url = serve(&manager).await;
+
+    let mut response = reqwest::get(&url).await.unwrap();
+    wait_for_sessions(&manager, 1, 1).await;
+
+    // Another login of the same brawler going away leaves this one alone
+    manager.revoke_session(11).await;
+    manager
+        .notify_user(
+            1,
+            ServerEvent::AgentOnline(AgentPresence {
+                user_id: 2,
+                last_seen_at: None,
+            }),
+        )
+        .await;
+    read_events(&mut response, 1).await;
+
+    manager.revoke_session(10).await;
+    wait_for_sessions(&manager, 1, 0).await;
+    let ended = tokio::time::timeout(Duration::from_secs(2), async {
+        while response.chunk().await.unwrap().is_some() {}
+    })
+    .await;
+    assert!(
+        ended.is_ok(),
+        "stream kept going after its session was revoked"
+    );
+}
@@ -0,0 +1,149 @@
+//! TOTP codes, recovery codes and the two-factor store.
+//!
+//! The code tests run anywhere. The store test needs a migrated database and is
+//! ignored unless run with `--ignored`; see `common`.
+
+mod common;
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use server::{
+    application::use_cases::mfa::redeem_code,
+    domain::{errors::AppError, repositories::totp::TotpRepository},
+    infrastructure::{
+        database::repositories::totp::TotpPostgres,
+        secure_token,
+        totp::{self, STEP_SECONDS},
+    },
+};
+
+/// The RFC 6238 appendix B SHA-1 secret, "12345678901234567890", in base32
+const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+#[test]
+fn codes_match_the_rfc_6238_vectors() {
+    // The RFC lists 8 digits; apps show the last 6
+    for (unix, code) in [
+        (59, "287082"),
+        (1_111_111_109, "081804"),
+        (1_234_567_890, "005924"),
+        (2_000_000_000, "279037"),
+    ] {
+        assert_eq!(
+            totp::code_at(RFC_SECRET, totp::step_at(unix)).unwrap(),
+            code
+        );
+    }
+}
+
+#[test]
+fn verify_allows_one_step_of_drift_and_reports_the_step() {
+    let now = 1_234_567_890;
+    let step = totp::step_at(now);
+    let code = |step| totp::code_at(RFC_SECRET, step).unwrap();
+
+    assert_eq!(
+        totp::verify(RFC_SECRET, &code(step), now).unwrap(),
+        Some(step)
+    );
+    assert_eq!(
+        totp::verify(RFC_SECRET, &code(step - 1), now).unwrap(),
+        Some(step - 1)
+    );
+    assert_eq!(
+        totp::verify(RFC_SECRET, &code(step + 1), now + STEP_SECONDS).unwrap(),
+        Some(step + 1)
+    );
+    assert_eq!(
+        totp::verify(RFC_SECRET, &code(step - 2), now).unwrap(),
+        None
+    );
+    // Spaces are how some apps group the digits
+    let spaced = format!("{} {}", &code(step)[..3], &code(step)[3..]);
+    assert_eq!(totp::verify(RFC_SECRET, &spaced, now).unwrap(), Some(step));
+    assert_eq!(totp::verify(RFC_SECRET, "12345", now).unwrap(), None);
+    assert_eq!(totp::verify(RFC_SECRET, "abcdef", now).unwrap(), None);
+}
+
+#[test]
+fn secrets_and_uris_are_what_authenticator_apps_take() {
+    let secret = totp::generate_secret().unwrap();
+    assert_eq!(secret.len(), 32);
+    assert!(totp::code_at(&secret, 1).is_ok());
+
+    assert_eq!(
+        totp::otpauth_uri("Vibe Assemble", "al ice", "ABC"),
+        "otpauth://totp/Vibe%20Assemble:al%20ice?secret=ABC&issuer=Vibe%20Assemble&algorithm=SHA1&digits=6&period=30"
+    );
+}
+
+#[test]
+fn recovery_codes_normalize_however_they_are_typed() {
+    let codes = totp::generate_recovery_codes(10).unwrap();
+    assert_eq!(codes.len(), 10);
+    let code = &codes[0];
+    assert_eq!(code.len(), 19);
+
+    let normalized = totp::normalize_recovery_code(code).unwrap();
+    assert_eq!(
+        totp::normalize_recovery_code(&code.to_uppercase().replace('-', " ")),
+        Some(normalized)
+    );
+    assert_eq!(totp::normalize_recovery_code("123456"), None);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn postgres_store_enrolls_confirms_and_uses_codes_once() {
+    let pool = common::test_pool();
+    let tag = Utc::now().timestamp_nanos_opt().unwrap();
+    let username = format!("totp_{tag}");
+    let brawler_id = common::create_brawler(&pool, username);
+    let repo = TotpPostgres::new(Arc::new(pool));
+
+    assert!(repo.find(brawler_id).await.unwrap().is_none());
+    let secret = totp::generate_secret().unwrap();
+    repo.begin_enrollment(brawler_id, secret.clone())
+        .await
+        .unwrap();
+    let pending = repo.find(brawler_id).await.unwrap().unwrap();
+    assert!(!pending.is_enabled());
+    // A pending enrollment is no use for logging in
+    let code = totp::code_at(&secret, totp::step_at(Utc::now().timestamp())).unwrap();
+    assert!(!redeem_code(&repo, &pending, &code).await.unwrap());
+
+    let codes = totp::generate_recovery_codes(2).unwrap();
+    let hashes = codes
+        .iter()
+        .map(|code| secure_token::hash(&totp::normalize_recovery_code(code).unwrap()))
+        .collect();
+    // Confirmed with the step before the current one, so the current code is unused
+    let step = totp::step_at(Utc::now().timestamp());
+    repo.confirm(brawler_id, step - 1, hashes).await.unwrap();
+    let enabled = repo.find(brawler_id).await.unwrap().unwrap();
+    assert!(enabled.is_enabled());
+    assert_eq!(repo.remaining_recovery_codes(brawler_id).await.unwrap(), 2);
+
+    let Err(e) = repo.begin_enrollment(brawler_id, secret.clone()).await else {
+        panic!("enrolled again over an enabled secret");
+    };
+    assert!(matches!(AppError::from(e), AppError::Conflict(_)));
+
+    // Each authenticator step and recovery code works once
+    let code = totp::code_at(&secret, step).unwrap();
+    assert!(redeem_code(&repo, &enabled, &code).await.unwrap());
+    assert!(!redeem_code(&repo, &enabled, &code).await.unwrap());
+    assert!(!repo.accept_step(brawler_id, step - 1).await.unwrap());
+    assert!(
+        redeem_code(&repo, &enabled, &codes[0].to_uppercase())
+            .await
+            .unwrap()
+    );
+    assert!(!redeem_code(&repo, &enabled, &codes[0]).await.unwrap());
+    assert_eq!(repo.remaining_recovery_codes(brawler_id).await.unwrap(), 1);
+
+    repo.disable(brawler_id).await.unwrap();
+    assert!(repo.find(brawler_id).await.unwrap().is_none());
+    assert_eq!(repo.remaining_recovery_codes(brawler_id).await.unwrap(), 0);
+}
@@ -0,0 +1,164 @@
+use chrono::Utc;
+use server::{
+    application::use_cases::mission_waitlist::promote_next,
+    domain::{
+        entities::{
+            crew_memberships::CrewMemberShips, mission_waitlist::MissionWaitlistEntity,
+            missions::MissionEntity, notifications::AddNotificationEntity,
+        },
+        errors::AppError,
+        repositories::transaction_provider::TransactionProvider,
+        value_objects::{join_policy::JoinPolicy, mission_statuses::MissionStatuses},
+    },
+    infrastructure::in_memory::transaction_provider::{InMemoryStore, InMemoryTransactionProvider},
+};
+
+fn store_with_mission() -> InMemoryStore {
+    let now = Utc::now().naive_utc();
+    InMemoryStore {
+        missions: vec![MissionEntity {
+            id: 1,
+            chief_id: 10,
+            name: "Raid".to_string(),
+            status: MissionStatuses::Open.to_string(),
+            description: None,
+            max_crew: 3,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            scheduled_at: None,
+            location: None,
+            category: "Test".to_string(),
+            join_policy: JoinPolicy::Open.to_string(),
+            is_private: false,
+        }],
+        crew_memberships: vec![
+            CrewMemberShips {
+                brawler_id: 20,
+                mission_id: 1,
+            },
+            CrewMemberShips {
+                brawler_id: 21,
+                mission_id: 1,
+            },
+        ],
+        ..Default::default()
+    }
+}
+
+fn notification(brawler_id: i32) -> AddNotificationEntity {
+    AddNotificationEntity {
+        brawler_id,
+        type_: "mission_started".to_string(),
+        content: "Mission 'Raid' has started!".to_string(),
+        related_id: Some(1),
+    }
+}
+
+#[tokio::test]
+async fn commits_every_operation_on_success() {
+    let provider = InMemoryTransactionProvider::new(store_with_mission());
+
+    provider
+        .transaction(|uow| {
+            uow.set_mission_status(1, 10, MissionStatuses::InProgress)?;
+            for brawler_id in uow.crew_ids(1)? {
+                uow.add_notification(notification(brawler_id))?;
+            }
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+    let store = provider.snapshot();
+    assert_eq!(store.missions[0].status, "InProgress");
+    assert_eq!(store.notifications.len(), 2);
+}
+
+#[tokio::test]
+async fn rolls_back_everything_on_error() {
+    let provider = InMemoryTransactionProvider::new(store_with_mission());
+
+    let result = provider
+        .transaction(|uow| {
+            uow.set_mission_status(1, 10, MissionStatuses::InProgress)?;
+            uow.add_notification(notification(20))?;
+            Err::<(), _>(AppError::Conflict("boom".to_string()))
+        })
+        .await;
+
+    assert!(matches!(result, Err(AppError::Conflict(_))));
+    let store = provider.snapshot();
+    assert_eq!(store.missions[0].status, "Open");
+    assert!(store.notifications.is_empty());
+}
+
+fn waitlisted(id: i32, brawler_id: i32) -> MissionWaitlistEntity {
+    MissionWaitlistEntity {
+        id,
+        mission_id: 1,
+        brawler_id,
+        created_at: Utc::now().naive_utc(),
+    }
+}
+
+#[tokio::test]
+async fn promotes_first_waitlisted_into_free_slot() {
+    let mut store = store_with_mission();
+    store.mission_waitlist = vec![waitlisted(7, 31), waitlisted(5, 30)];
+    let provider = InMemoryTransactionProvider::new(store);
+
+    let promoted = provider
+        .transaction(|uow| promote_next(uow, 1, "Raid"))
+        .await
+        .unwrap();
+    assert_eq!(promoted, Some(30));
+
+    // Mission is now full again, so the next one keeps waiting
+    let promoted = provider
+        .transaction(|uow| promote_next(uow, 1, "Raid"))
+        .await
+        .unwrap();
+    assert_eq!(promoted, None);
+
+    let store = provider.snapshot();
+    assert_eq!(store.crew_memberships.len(), 3);
+    assert_eq!(store.mission_waitlist.len(), 1);
+    assert_eq!(store.notifications.len(), 1);
+    assert_eq!(store.notifications[0].brawler_id, 30);
+    assert_eq!(store.notifications[0].type_, "waitlist_promoted");
+}
+
+#[tokio::test]
+async fn freed_slot_goes_to_the_waitlist_in_the_same_transaction() {
+    let mut store = store_with_mission();
+    store.missions[0].max_crew = 2;
+    store.mission_waitlist = vec![waitlisted(5, 30)];
+    let provider = InMemoryTransactionProvider::new(store);
+
+    let promoted = provider
+        .transaction(|uow| {
+            uow.remove_crew_member(1, 21)?;
+            promote_next(uow, 1, "Raid")
+        })
+        .await
+        .unwrap();
+    assert_eq!(promoted, Some(30));
+
+    // A failed promotion keeps the member, so the slot is never freed unfilled
+    let result = provider
+        .transaction(|uow| {
+            uow.remove_crew_member(1, 20)?;
+            Err::<(), _>(AppError::Conflict("boom".to_string()))
+        })
+        .await;
+    assert!(result.is_err());
+
+    let crew: Vec<i32> = provider
+        .snapshot()
+        .crew_memberships
+        .iter()
+        .map(|c| c.brawler_id)
+        .collect();
+    assert_eq!(crew, vec![20, 30]);
+}
@@ -0,0 +1,205 @@
+//! Fan-out across instances. The Postgres tests need a migrated database and
+//! are ignored unless run with `--ignored`; see `common`.
+
+mod common;
+
+use std::{
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use server::infrastructure::websocket::{
+    broadcast::{BroadcastBackend, Fanout},
+    heartbeat::HeartbeatConfig,
+    manager::{ConnectionManager, RoomCloseReason},
+    pg_broadcast::PgBroadcast,
+    protocol::{ClearChat, NewChatMessage, ServerEvent, Typing},
+};
+use tokio::{sync::broadcast::error::TryRecvError, time::timeout};
+
+#[derive(Default)]
+struct Recording(Mutex<Vec<Fanout>>);
+
+#[async_trait]
+impl BroadcastBackend for Recording {
+    async fn publish(&self, fanout: &Fanout) -> Result<()> {
+        self.0.lock().unwrap().push(fanout.clone());
+        Ok(())
+    }
+}
+
+fn clear_chat(mission_id: i32) -> ServerEvent {
+    ServerEvent::ClearChat(ClearChat { mission_id })
+}
+
+#[tokio::test]
+async fn local_sockets_are_served_before_relaying() {
+    let backend = Arc::new(Recording::default());
+    let manager = ConnectionManager::with_backend(HeartbeatConfig::default(), backend.clone());
+    let mut user = manager.subscribe_user(1).await;
+    let mut room = manager.subscribe(9).await;
+
+    manager.notify_user(1, clear_chat(1)).await;
+    manager.close_room(9, RoomCloseReason::MissionDeleted).await;
+
+    assert_eq!(user.try_recv().unwrap().event, clear_chat(1));
+    assert_eq!(room.closes.try_recv().unwrap().reason.code(), 4004);
+    assert_eq!(backend.0.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn delivered_fan_outs_are_not_relayed_again() {
+    let backend = Arc::new(Recording::default());
+    let manager = ConnectionManager::with_backend(HeartbeatConfig::default(), backend.clone());
+    let mut room = manager.subscribe(9).await;
+
+    manager
+        .deliver(&Fanout::Room {
+            mission_id: 9,
+            event: clear_chat(9),
+        })
+        .await;
+
+    assert_eq!(room.messages.try_recv().unwrap().event, clear_chat(9));
+    assert!(backend.0.lock().unwrap().is_empty());
+}
+
+/// Delivers straight into another manager, like a relay with nothing in between
+#[derive(Default)]
+struct Linked(OnceLock<ConnectionManager>);
+
+#[async_trait]
+impl BroadcastBackend for Linked {
+    async fn publish(&self, fanout: &Fanout) -> Result<()> {
+        if let Some(peer) = self.0.get() {
+            peer.deliver(fanout).await;
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn presence_counts_sockets_on_every_instance() {
+    let (to_second, to_first) = (Arc::new(Linked::default()), Arc::new(Linked::default()));
+    let first = ConnectionManager::with_backend(HeartbeatConfig::default(), to_second.clone());
+    let second = ConnectionManager::with_backend(HeartbeatConfig::default(), to_first.clone());
+    let _ = to_second.0.set(second.clone());
+    let _ = to_first.0.set(first.clone());
+
+    assert!(first.session_started(1).await);
+    // A second device on another replica doesn't bring them online again
+    assert!(!second.session_started(1).await);
+    assert_eq!(second.get_online_users().await, vec![1]);
+
+    // Nor does closing the first one take them offline
+    assert!(!first.session_ended(1).await);
+    assert_eq!(first.get_online_users().await, vec![1]);
+    assert!(second.session_ended(1).await);
+    assert!(first.get_online_users().await.is_empty());
+    assert!(second.get_online_users().await.is_empty());
+}
+
+/// Two managers on one database, standing in for two replicas
+fn replicas() -> (ConnectionManager, ConnectionManager) {
+    let url = common::database_url();
+
+    let mut managers = Vec::new();
+    for _ in 0..2 {
+        let backend = Arc::new(PgBroadcast::new(url.clone()).unwrap());
+        let manager = ConnectionManager::with_backend(HeartbeatConfig::default(), backend.clone());
+        backend.spawn_listener(manager.clone()).unwrap();
+        managers.push(manager);
+    }
+    let second = managers.pop().unwrap();
+    (managers.pop().unwrap(), second)
+}
+
+/// Give both listeners time to LISTEN before anything is published
+async fn settle() {
+    tokio::time::sleep(Duration::from_millis(500)).await;
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn postgres_relays_to_other_instances_once() {
+    let (first, second) = replicas();
+    // Users and missions far from anything the app creates
+    let mut local = first.subscribe_user(900_001).await;
+    let mut remote = second.subscribe_user(900_001).await;
+    let mut remote_room = second.subscribe(900_002).await;
+    settle().await;
+
+    first.notify_user(900_001, clear_chat(1)).await;
+    first
+        .close_room_member(900_002, 900_001, RoomCloseReason::Kicked)
+        .await;
+
+    let wait = Duration::from_secs(5);
+    assert_eq!(
+        timeout(wait, remote.recv()).await.unwrap().unwrap().event,
+        clear_chat(1)
+    );
+    let close = timeout(wait, remote_room.closes.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(close.applies_to(900_001));
+
+    // The publisher delivered locally and skipped its own notification
+    assert_eq!(local.try_recv().unwrap().event, clear_chat(1));
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(local.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn postgres_relays_fan_outs_too_big_for_notify() {
+    let (first, second) = replicas();
+    let mut remote = second.subscribe(900_003).await;
+    settle().await;
+
+    let event = ServerEvent::NewChatMessage(NewChatMessage {
+        mission_id: 900_003,
+        mission_name: "Overflow".to_string(),
+        sender_name: "tester".to_string(),
+        content: "x".repeat(20_000),
+    });
+    first.broadcast(900_003, event.clone()).await;
+
+    let received = timeout(Duration::from_secs(5), remote.messages.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(received.event, event);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_DATABASE_URL"]
+async fn postgres_relays_batched_chatter_in_order() {
+    let (first, second) = replicas();
+    let mut remote = second.subscribe(900_004).await;
+    settle().await;
+
+    let typing = Typing {
+        mission_id: 900_004,
+        brawler_id: 900_001,
+    };
+    let sent = [
+        ServerEvent::TypingStart(typing.clone()),
+        clear_chat(900_004),
+        ServerEvent::TypingStop(typing),
+    ];
+    for event in sent.clone() {
+        first.broadcast(900_004, event).await;
+    }
+
+    for event in sent {
+        let received = timeout(Duration::from_secs(5), remote.messages.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(received.event, event);
+    }
+}
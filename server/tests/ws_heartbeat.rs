@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use server::infrastructure::websocket::{
+    heartbeat::{self, HeartbeatConfig, Liveness},
+    manager::ConnectionManager,
+};
+
+const CONFIG: HeartbeatConfig = HeartbeatConfig {
+    interval: Duration::from_secs(10),
+    timeout: Duration::from_secs(25),
+};
+
+#[tokio::test(start_paused = true)]
+async fn silent_socket_expires_after_timeout() {
+    let liveness = Liveness::new();
+
+    let started = tokio::time::Instant::now();
+    heartbeat::expired(&liveness, CONFIG).await;
+
+    // Checked on the 10s cadence, so the first check past 25s is at 30s
+    assert_eq!(started.elapsed(), Duration::from_secs(30));
+}
+
+#[tokio::test(start_paused = true)]
+async fn any_frame_keeps_the_socket_alive() {
+    let liveness = Liveness::new();
+
+    let pong_every_8s = async {
+        loop {
+            tokio::time::sleep(Duration::from_secs(8)).await;
+            liveness.touch();
+        }
+    };
+
+    let reaped = tokio::time::timeout(Duration::from_secs(300), async {
+        tokio::select! {
+            _ = heartbeat::expired(&liveness, CONFIG) => true,
+            _ = pong_every_8s => false,
+        }
+    })
+    .await;
+    assert!(reaped.is_err(), "socket was reaped while still answering");
+}
+
+#[tokio::test]
+async fn metrics_count_reaped_connections() {
+    let manager = ConnectionManager::new();
+    manager.session_started(1).await;
+
+    manager.connection_opened();
+    manager.connection_opened();
+    manager.connection_closed(true);
+
+    let metrics = manager.metrics().await;
+    assert_eq!(metrics.opened_total, 2);
+    assert_eq!(metrics.active_connections, 1);
+    assert_eq!(metrics.reaped_total, 1);
+    assert_eq!(metrics.online_users, 1);
+}
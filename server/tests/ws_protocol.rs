@@ -0,0 +1,96 @@
+use serde_json::json;
+use server::infrastructure::websocket::protocol::{
+    ClientEvent, ClientFrame, CrewChange, FriendNotification, PROTOCOL_VERSION, ReadUpToRequest,
+    ServerEvent, ServerFrame, Typing, client_frame_schema, server_frame_schema,
+};
+
+#[test]
+fn server_events_keep_type_and_data_shape() {
+    let frame = ServerFrame::from(ServerEvent::KickedFromMission(CrewChange {
+        mission_id: 7,
+        mission_name: "Raid".to_string(),
+        brawler_id: 3,
+    }));
+
+    assert_eq!(
+        serde_json::to_value(&frame).unwrap(),
+        json!({
+            "version": PROTOCOL_VERSION,
+            "type": "kicked_from_mission",
+            "data": { "mission_id": 7, "mission_name": "Raid", "brawler_id": 3 },
+        })
+    );
+}
+
+#[test]
+fn friend_notifications_stay_nested_under_notification() {
+    let frame = ServerFrame::from(ServerEvent::Notification(
+        FriendNotification::FriendAccepted {
+            friend_id: 4,
+            content: "User Carol accepted your friend request".to_string(),
+        },
+    ));
+
+    assert_eq!(
+        serde_json::to_value(&frame).unwrap(),
+        json!({
+            "version": PROTOCOL_VERSION,
+            "type": "notification",
+            "data": {
+                "type": "friend_accepted",
+                "friend_id": 4,
+                "content": "User Carol accepted your friend request",
+            },
+        })
+    );
+}
+
+#[test]
+fn client_frames_parse_with_or_without_version() {
+    let frame: ClientFrame =
+        serde_json::from_str(r#"{"type":"read_up_to","data":{"comment_id":12}}"#).unwrap();
+    assert_eq!(frame.version, PROTOCOL_VERSION);
+    assert_eq!(
+        frame.event,
+        ClientEvent::ReadUpTo(ReadUpToRequest {
+            mission_id: None,
+            comment_id: 12,
+        })
+    );
+
+    let frame: ClientFrame =
+        serde_json::from_str(r#"{"version":1,"type":"typing_start"}"#).unwrap();
+    assert_eq!(frame.event, ClientEvent::TypingStart(None));
+
+    assert!(serde_json::from_str::<ClientFrame>(r#"{"type":"bogus","data":{}}"#).is_err());
+}
+
+#[test]
+fn checked_in_schemas_are_up_to_date() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/schemas");
+    for (name, schema) in [
+        ("ws-server-frame.schema.json", server_frame_schema()),
+        ("ws-client-frame.schema.json", client_frame_schema()),
+    ] {
+        let on_disk: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(format!("{dir}/{name}")).unwrap())
+                .unwrap();
+        assert_eq!(
+            on_disk,
+            serde_json::to_value(&schema).unwrap(),
+            "{name} is stale, run `cargo run --bin ws_schema`"
+        );
+    }
+}
+
+#[test]
+fn seq_is_only_sent_when_set() {
+    let mut frame = ServerFrame::from(ServerEvent::TypingStop(Typing {
+        mission_id: 1,
+        brawler_id: 2,
+    }));
+    assert!(serde_json::to_value(&frame).unwrap().get("seq").is_none());
+
+    frame.seq = Some(42);
+    assert_eq!(serde_json::to_value(&frame).unwrap()["seq"], json!(42));
+}
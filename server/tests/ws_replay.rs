@@ -0,0 +1,117 @@
+use server::infrastructure::websocket::{
+    event_log::{EVENT_LOG_CAPACITY, Replay, StreamKey},
+    manager::ConnectionManager,
+    protocol::{ClearChat, MissionRef, ResyncRequired, ServerEvent, Typing},
+};
+
+fn clear_chat(mission_id: i32) -> ServerEvent {
+    ServerEvent::ClearChat(ClearChat { mission_id })
+}
+
+fn seqs(replay: Replay) -> Vec<u64> {
+    match replay {
+        Replay::Events(frames) => frames.into_iter().filter_map(|f| f.seq).collect(),
+        Replay::ResyncRequired(resync) => panic!("unexpected {resync:?}"),
+    }
+}
+
+#[tokio::test]
+async fn missed_events_are_replayed_after_since() {
+    let manager = ConnectionManager::new();
+    let mut rx = manager.subscribe_user(1).await;
+
+    for mission_id in 1..=3 {
+        manager.notify_user(1, clear_chat(mission_id)).await;
+    }
+    let first = rx.try_recv().unwrap().seq.unwrap();
+    assert_eq!(rx.try_recv().unwrap().seq, Some(first + 1));
+
+    // The socket drops after the second event and comes back
+    drop(rx);
+    manager.unsubscribe_user(1).await;
+    let _rx = manager.subscribe_user(1).await;
+
+    match manager.replay(StreamKey::User(1), Some(first + 1)).await {
+        Replay::Events(frames) => {
+            assert_eq!(frames.len(), 1);
+            assert_eq!(frames[0].seq, Some(first + 2));
+            assert_eq!(frames[0].event, clear_chat(3));
+        }
+        other => panic!("expected a replay, got {other:?}"),
+    }
+
+    // Up to date: nothing to replay
+    assert!(seqs(manager.replay(StreamKey::User(1), Some(first + 2)).await).is_empty());
+}
+
+#[tokio::test]
+async fn too_large_a_gap_requires_a_resync() {
+    let manager = ConnectionManager::new();
+    let mut room = manager.subscribe(7).await;
+
+    manager.broadcast(7, clear_chat(7)).await;
+    let first = room.messages.try_recv().unwrap().seq.unwrap();
+    for _ in 0..EVENT_LOG_CAPACITY {
+        manager.broadcast(7, clear_chat(7)).await;
+    }
+    let latest_seq = first + EVENT_LOG_CAPACITY as u64;
+
+    // `first` itself has been evicted, but everything after it is still there
+    assert_eq!(
+        seqs(manager.replay(StreamKey::Room(7), Some(first)).await).len(),
+        EVENT_LOG_CAPACITY
+    );
+    assert_eq!(
+        manager.replay(StreamKey::Room(7), Some(first - 1)).await,
+        Replay::ResyncRequired(ResyncRequired { latest_seq })
+    );
+    // A position from another run or replica
+    assert_eq!(
+        manager
+            .replay(StreamKey::Room(7), Some(latest_seq + 50))
+            .await,
+        Replay::ResyncRequired(ResyncRequired { latest_seq })
+    );
+}
+
+#[tokio::test]
+async fn typing_is_not_numbered_or_replayed() {
+    let manager = ConnectionManager::new();
+    let mut room = manager.subscribe(7).await;
+
+    manager.broadcast(7, clear_chat(7)).await;
+    let typing = ServerEvent::TypingStart(Typing {
+        mission_id: 7,
+        brawler_id: 2,
+    });
+    manager.broadcast(7, typing.clone()).await;
+
+    let first = room.messages.try_recv().unwrap().seq.unwrap();
+    let live = room.messages.try_recv().unwrap();
+    assert_eq!((live.seq, live.event), (None, typing));
+    assert!(seqs(manager.replay(StreamKey::Room(7), Some(first)).await).is_empty());
+}
+
+#[tokio::test]
+async fn broadcast_all_reaches_users_between_sockets() {
+    let manager = ConnectionManager::new();
+    let rx = manager.subscribe_user(1).await;
+    drop(rx);
+    manager.unsubscribe_user(1).await;
+
+    let since = match manager.replay(StreamKey::User(1), None).await {
+        Replay::ResyncRequired(resync) => resync.latest_seq,
+        other => panic!("expected a resync without a position, got {other:?}"),
+    };
+
+    let started = ServerEvent::MissionDeleted(MissionRef {
+        mission_id: 3,
+        mission_name: "Raid".to_string(),
+    });
+    manager.broadcast_all(started.clone()).await;
+
+    match manager.replay(StreamKey::User(1), Some(since)).await {
+        Replay::Events(frames) => assert_eq!(frames[0].event, started),
+        other => panic!("expected a replay, got {other:?}"),
+    }
+}
@@ -0,0 +1,33 @@
+use server::infrastructure::websocket::manager::{ConnectionManager, RoomCloseReason};
+
+#[tokio::test]
+async fn member_close_only_applies_to_that_member() {
+    let manager = ConnectionManager::new();
+    let mut room = manager.subscribe(1).await;
+
+    manager
+        .close_room_member(1, 42, RoomCloseReason::Kicked)
+        .await;
+
+    let close = room.closes.recv().await.unwrap();
+    assert_eq!(close.reason.code(), 4003);
+    assert!(close.applies_to(42));
+    assert!(!close.applies_to(7));
+}
+
+#[tokio::test]
+async fn room_close_applies_to_everyone() {
+    let manager = ConnectionManager::new();
+    let mut first = manager.subscribe(1).await;
+    let mut second = manager.subscribe(1).await;
+    let mut other_room = manager.subscribe(2).await;
+
+    manager.close_room(1, RoomCloseReason::MissionDeleted).await;
+
+    for room in [&mut first, &mut second] {
+        let close = room.closes.recv().await.unwrap();
+        assert_eq!(close.reason.code(), 4004);
+        assert!(close.applies_to(7));
+    }
+    assert!(other_room.closes.try_recv().is_err());
+}
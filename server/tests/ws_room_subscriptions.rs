@@ -0,0 +1,133 @@
+use std::{sync::Arc, time::Duration};
+
+use server::infrastructure::websocket::{
+    manager::{ConnectionManager, RoomCloseReason},
+    protocol::{ClearChat, ServerEvent, ServerFrame, Subscribed, Unsubscribed},
+    room_subscriptions::RoomSubscriptions,
+};
+use tokio::{sync::mpsc, time::timeout};
+
+const USER: i32 = 5;
+
+fn clear_chat(mission_id: i32) -> ServerEvent {
+    ServerEvent::ClearChat(ClearChat { mission_id })
+}
+
+async fn next(out: &mut mpsc::UnboundedReceiver<ServerFrame>) -> ServerFrame {
+    timeout(Duration::from_secs(1), out.recv())
+        .await
+        .expect("a frame")
+        .expect("open channel")
+}
+
+fn setup() -> (
+    Arc<ConnectionManager>,
+    RoomSubscriptions,
+    mpsc::UnboundedReceiver<ServerFrame>,
+) {
+    let manager = Arc::new(ConnectionManager::new());
+    let (tx, rx) = mpsc::unbounded_channel();
+    let subscriptions = RoomSubscriptions::new(Arc::clone(&manager), USER, tx);
+    (manager, subscriptions, rx)
+}
+
+#[tokio::test]
+async fn one_socket_follows_several_rooms() {
+    let (manager, mut subscriptions, mut out) = setup();
+    subscriptions.subscribe(1, None).await;
+    subscriptions.subscribe(2, None).await;
+    assert_eq!(
+        next(&mut out).await.event,
+        ServerEvent::Subscribed(Subscribed { mission_id: 1 })
+    );
+    assert_eq!(
+        next(&mut out).await.event,
+        ServerEvent::Subscribed(Subscribed { mission_id: 2 })
+    );
+
+    manager.broadcast(2, clear_chat(2)).await;
+    manager.broadcast(1, clear_chat(1)).await;
+
+    // Each room forwards on its own, so only the order within a room is fixed
+    let mut frames = vec![next(&mut out).await, next(&mut out).await];
+    frames.sort_by_key(|frame| frame.room);
+    let received = frames
+        .into_iter()
+        .map(|frame| (frame.room, frame.event))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        received,
+        vec![(Some(1), clear_chat(1)), (Some(2), clear_chat(2))]
+    );
+    assert_eq!(subscriptions.len(), 2);
+}
+
+#[tokio::test]
+async fn kick_ends_only_that_room() {
+    let (manager, mut subscriptions, mut out) = setup();
+    subscriptions.subscribe(1, None).await;
+    subscriptions.subscribe(2, None).await;
+    next(&mut out).await;
+    next(&mut out).await;
+
+    // Someone else's kick changes nothing for this socket
+    manager
+        .close_room_member(1, 99, RoomCloseReason::Kicked)
+        .await;
+    manager
+        .close_room_member(1, USER, RoomCloseReason::Kicked)
+        .await;
+
+    assert_eq!(
+        next(&mut out).await.event,
+        ServerEvent::Unsubscribed(Unsubscribed {
+            mission_id: 1,
+            reason: Some("kicked".to_string()),
+        })
+    );
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!subscriptions.is_subscribed(1));
+    assert!(subscriptions.is_subscribed(2));
+}
+
+#[tokio::test]
+async fn unsubscribing_removes_the_room_channel() {
+    let (manager, mut subscriptions, mut out) = setup();
+    subscriptions.subscribe(1, None).await;
+    next(&mut out).await;
+    assert_eq!(manager.metrics().await.rooms, 1);
+
+    assert!(subscriptions.unsubscribe(1).await);
+    assert_eq!(
+        next(&mut out).await.event,
+        ServerEvent::Unsubscribed(Unsubscribed {
+            mission_id: 1,
+            reason: None,
+        })
+    );
+    assert_eq!(manager.metrics().await.rooms, 0);
+    assert!(!subscriptions.unsubscribe(1).await);
+}
+
+#[tokio::test]
+async fn subscribing_with_since_replays_the_room() {
+    let (manager, mut subscriptions, mut out) = setup();
+    subscriptions.subscribe(1, None).await;
+    next(&mut out).await;
+    manager.broadcast(1, clear_chat(1)).await;
+    let seen = next(&mut out).await.seq.unwrap();
+
+    subscriptions.unsubscribe(1).await;
+    next(&mut out).await;
+    // Kept alive by another socket while this one was away
+    let _other = manager.subscribe(1).await;
+    manager.broadcast(1, clear_chat(7)).await;
+
+    subscriptions.subscribe(1, Some(seen)).await;
+    next(&mut out).await;
+    let replayed = next(&mut out).await;
+    assert_eq!(
+        (replayed.seq, replayed.room, replayed.event),
+        (Some(seen + 1), Some(1), clear_chat(7))
+    );
+}